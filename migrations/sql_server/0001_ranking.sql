@@ -0,0 +1,171 @@
+-- Every object is only created when it is missing, so this is safe to run against a database that predates migrations.
+IF SCHEMA_ID(N'Ranking') IS NULL EXEC(N'CREATE SCHEMA [Ranking]');
+GO
+
+IF OBJECT_ID(N'[Ranking].[Server]', N'U') IS NULL
+CREATE TABLE [Ranking].[Server] (
+    id DECIMAL(20, 0) NOT NULL PRIMARY KEY,
+    timeout INT NOT NULL DEFAULT 60000,
+    ranking_disabled BIT NOT NULL DEFAULT 0
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[Level]', N'U') IS NULL
+CREATE TABLE [Ranking].[Level] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    level INT NOT NULL DEFAULT 0,
+    xp INT NOT NULL DEFAULT 0,
+    last_message DATETIME2 NULL,
+    PRIMARY KEY (server_id, [user_id])
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[Role]', N'U') IS NULL
+CREATE TABLE [Ranking].[Role] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    role_name NVARCHAR(100) NOT NULL,
+    role_id DECIMAL(20, 0) NULL,
+    min_level INT NOT NULL,
+    PRIMARY KEY (server_id, min_level)
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[DisabledChannel]', N'U') IS NULL
+CREATE TABLE [Ranking].[DisabledChannel] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    channel_id DECIMAL(20, 0) NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[CalculateLevel]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[CalculateLevel] @level INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    SELECT 5 * @level * @level + 50 * @level + 100;
+END');
+GO
+
+IF OBJECT_ID(N'[Ranking].[ProvideExp]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[ProvideExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @timeout INT, @level INT, @old_level INT, @xp INT, @last_message DATETIME2;
+    DECLARE @old_rank DECIMAL(20, 0), @new_rank DECIMAL(20, 0);
+    DECLARE @needed TABLE (xp INT);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+
+    SELECT @timeout = timeout FROM [Ranking].[Server] WHERE id = @serverid;
+    SELECT @level = level, @xp = xp, @last_message = last_message FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @level IS NULL
+    BEGIN
+        SELECT @level = 0, @xp = 0;
+        INSERT INTO [Ranking].[Level] (server_id, [user_id], level, xp) VALUES (@serverid, @userid, 0, 0);
+    END
+
+    IF @last_message IS NOT NULL AND DATEDIFF_BIG(MILLISECOND, @last_message, SYSUTCDATETIME()) < @timeout
+    BEGIN
+        SELECT -1, CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+        RETURN;
+    END
+
+    SELECT @old_level = @level, @xp = @xp + 20;
+    INSERT INTO @needed EXEC [Ranking].[CalculateLevel] @level = @level;
+    IF @xp >= (SELECT TOP 1 xp FROM @needed)
+    BEGIN
+        SET @xp = @xp - (SELECT TOP 1 xp FROM @needed);
+        SET @level = @level + 1;
+    END
+
+    UPDATE [Ranking].[Level] SET level = @level, xp = @xp, last_message = SYSUTCDATETIME() WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @level = @old_level
+    BEGIN
+        SELECT -1, CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+        RETURN;
+    END
+
+    SELECT TOP 1 @old_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @old_level ORDER BY min_level DESC;
+    SELECT TOP 1 @new_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @level ORDER BY min_level DESC;
+
+    IF @old_rank = @new_rank OR (@old_rank IS NULL AND @new_rank IS NULL)
+        SELECT @level, CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+    ELSE
+        SELECT @level, @old_rank, @new_rank;
+END');
+GO
+
+IF OBJECT_ID(N'[Ranking].[ToggleChannel]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[ToggleChannel] @serverid DECIMAL(20, 0), @channelid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    IF EXISTS (SELECT 1 FROM [Ranking].[DisabledChannel] WHERE server_id = @serverid AND channel_id = @channelid)
+    BEGIN
+        DELETE FROM [Ranking].[DisabledChannel] WHERE server_id = @serverid AND channel_id = @channelid;
+        SELECT CAST(0 AS BIT);
+    END
+    ELSE
+    BEGIN
+        INSERT INTO [Ranking].[DisabledChannel] (server_id, channel_id) VALUES (@serverid, @channelid);
+        SELECT CAST(1 AS BIT);
+    END
+END');
+GO
+
+IF OBJECT_ID(N'[Ranking].[AddRole]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[AddRole] @server_id DECIMAL(20, 0), @role_name NVARCHAR(100), @role_id DECIMAL(20, 0), @min_level DECIMAL(10, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    IF EXISTS (SELECT 1 FROM [Ranking].[Role] WHERE server_id = @server_id AND min_level = @min_level AND (role_id IS NULL OR role_id <> @role_id))
+    BEGIN
+        SELECT CAST(0 AS BIT);
+        RETURN;
+    END
+
+    DELETE FROM [Ranking].[Role] WHERE server_id = @server_id AND role_id = @role_id;
+    INSERT INTO [Ranking].[Role] (server_id, role_name, role_id, min_level) VALUES (@server_id, @role_name, @role_id, @min_level);
+    SELECT CAST(1 AS BIT);
+END');
+GO
+
+IF OBJECT_ID(N'[Ranking].[RemoveRole]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[RemoveRole] @serverid DECIMAL(20, 0), @roleid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DELETE FROM [Ranking].[Role] WHERE server_id = @serverid AND role_id = @roleid;
+    SELECT CAST(CASE WHEN @@ROWCOUNT > 0 THEN 1 ELSE 0 END AS BIT);
+END');
+GO
+
+IF OBJECT_ID(N'[Ranking].[SetServerTimeout]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[SetServerTimeout] @serverid DECIMAL(20, 0), @timeout DECIMAL(10, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    IF EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        UPDATE [Ranking].[Server] SET timeout = @timeout WHERE id = @serverid;
+    ELSE
+        INSERT INTO [Ranking].[Server] (id, timeout) VALUES (@serverid, @timeout);
+    SELECT CAST(1 AS BIT);
+END');
+GO
+
+IF OBJECT_ID(N'[Ranking].[GetAllUsers]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[GetAllUsers] @serverid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    SELECT l.[user_id], l.level, l.xp,
+        (SELECT TOP 1 r.role_id FROM [Ranking].[Role] r WHERE r.server_id = l.server_id AND r.min_level <= l.level ORDER BY r.min_level DESC)
+    FROM [Ranking].[Level] l
+    WHERE l.server_id = @serverid;
+END');
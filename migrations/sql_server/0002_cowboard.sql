@@ -0,0 +1,41 @@
+IF SCHEMA_ID(N'Cowboard') IS NULL EXEC(N'CREATE SCHEMA [Cowboard]');
+GO
+
+IF OBJECT_ID(N'[Cowboard].[Server]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Server] (
+    id DECIMAL(20, 0) NOT NULL PRIMARY KEY,
+    channel DECIMAL(20, 0) NULL,
+    add_threshold INT NOT NULL DEFAULT 5,
+    remove_threshold INT NOT NULL DEFAULT 4,
+    emote NVARCHAR(100) NOT NULL DEFAULT N'🐮',
+    webhook_id DECIMAL(20, 0) NULL,
+    webhook_token NVARCHAR(100) NULL
+);
+GO
+
+IF OBJECT_ID(N'[Cowboard].[Message]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Message] (
+    message_id DECIMAL(20, 0) NOT NULL,
+    message_channel_id DECIMAL(20, 0) NOT NULL,
+    post_id DECIMAL(20, 0) NOT NULL,
+    post_channel_id DECIMAL(20, 0) NOT NULL,
+    guild_id DECIMAL(20, 0) NOT NULL,
+    PRIMARY KEY (message_id, message_channel_id, guild_id)
+);
+GO
+
+IF OBJECT_ID(N'[Cowboard].[UpdateServer]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Cowboard].[UpdateServer] @id DECIMAL(20, 0), @channel DECIMAL(20, 0), @add_threshold INT, @remove_threshold INT,
+    @emote NVARCHAR(100), @webhook_id DECIMAL(20, 0), @webhook_token NVARCHAR(100)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    IF EXISTS (SELECT 1 FROM [Cowboard].[Server] WHERE id = @id)
+        UPDATE [Cowboard].[Server]
+        SET channel = @channel, add_threshold = @add_threshold, remove_threshold = @remove_threshold,
+            emote = @emote, webhook_id = @webhook_id, webhook_token = @webhook_token
+        WHERE id = @id;
+    ELSE
+        INSERT INTO [Cowboard].[Server] (id, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token)
+        VALUES (@id, @channel, @add_threshold, @remove_threshold, @emote, @webhook_id, @webhook_token);
+END');
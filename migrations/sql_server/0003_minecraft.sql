@@ -0,0 +1,9 @@
+IF SCHEMA_ID(N'Minecraft') IS NULL EXEC(N'CREATE SCHEMA [Minecraft]');
+GO
+
+IF OBJECT_ID(N'[Minecraft].[Feed]', N'U') IS NULL
+CREATE TABLE [Minecraft].[Feed] (
+    channel_id DECIMAL(20, 0) NOT NULL PRIMARY KEY,
+    host NVARCHAR(255) NOT NULL,
+    password NVARCHAR(255) NOT NULL
+);
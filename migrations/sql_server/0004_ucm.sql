@@ -0,0 +1,123 @@
+-- The UCM tables live in the scraper's own database. The scraper fills them; we only need them to exist.
+IF DB_ID(N'UniScraper') IS NULL CREATE DATABASE [UniScraper];
+GO
+
+USE [UniScraper];
+GO
+
+IF SCHEMA_ID(N'UCM') IS NULL EXEC(N'CREATE SCHEMA [UCM]');
+GO
+
+IF OBJECT_ID(N'[UCM].[class]', N'U') IS NULL
+CREATE TABLE [UCM].[class] (
+    id INT IDENTITY(1, 1) NOT NULL CONSTRAINT PK_class PRIMARY KEY,
+    term INT NOT NULL,
+    course_reference_number INT NOT NULL,
+    course_number NVARCHAR(32) NOT NULL,
+    campus_description NVARCHAR(64) NULL,
+    course_title NVARCHAR(256) NULL,
+    credit_hours TINYINT NOT NULL DEFAULT 0,
+    maximum_enrollment SMALLINT NOT NULL DEFAULT 0,
+    enrollment SMALLINT NOT NULL DEFAULT 0,
+    seats_available SMALLINT NOT NULL DEFAULT 0,
+    wait_capacity SMALLINT NOT NULL DEFAULT 0,
+    wait_available SMALLINT NOT NULL DEFAULT 0,
+    CONSTRAINT UQ_class_term_crn UNIQUE (term, course_reference_number)
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[professor]', N'U') IS NULL
+CREATE TABLE [UCM].[professor] (
+    id INT IDENTITY(1, 1) NOT NULL CONSTRAINT PK_professor PRIMARY KEY,
+    rmp_id INT NULL,
+    last_name NVARCHAR(64) NOT NULL,
+    first_name NVARCHAR(64) NOT NULL,
+    middle_name NVARCHAR(64) NULL,
+    full_name NVARCHAR(256) NOT NULL,
+    email NVARCHAR(256) NULL,
+    department NVARCHAR(128) NULL,
+    num_ratings INT NOT NULL DEFAULT 0,
+    rating REAL NOT NULL DEFAULT 0
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[faculty]', N'U') IS NULL
+CREATE TABLE [UCM].[faculty] (
+    professor_id INT NOT NULL REFERENCES [UCM].[professor] (id),
+    class_id INT NOT NULL REFERENCES [UCM].[class] (id),
+    PRIMARY KEY (professor_id, class_id)
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[meeting]', N'U') IS NULL
+CREATE TABLE [UCM].[meeting] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    class_id INT NOT NULL REFERENCES [UCM].[class] (id),
+    begin_time NCHAR(4) NULL,
+    end_time NCHAR(4) NULL,
+    begin_date NVARCHAR(16) NOT NULL,
+    end_date NVARCHAR(16) NOT NULL,
+    building NVARCHAR(16) NULL,
+    building_description NVARCHAR(128) NULL,
+    campus NVARCHAR(16) NULL,
+    campus_description NVARCHAR(64) NULL,
+    room NVARCHAR(16) NULL,
+    credit_hour_session REAL NOT NULL DEFAULT 0,
+    hours_per_week REAL NOT NULL DEFAULT 0,
+    in_session TINYINT NOT NULL DEFAULT 0,
+    meeting_type TINYINT NOT NULL DEFAULT 1
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[description]', N'U') IS NULL
+CREATE TABLE [UCM].[description] (
+    course_number NVARCHAR(32) NOT NULL PRIMARY KEY,
+    course_description NVARCHAR(MAX) NULL
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[stats]', N'U') IS NULL
+CREATE TABLE [UCM].[stats] (
+    table_name NVARCHAR(64) NOT NULL PRIMARY KEY,
+    last_update DATETIME NOT NULL
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[reminder]', N'U') IS NULL
+CREATE TABLE [UCM].[reminder] (
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    course_reference_number INT NOT NULL,
+    min_trigger INT NOT NULL DEFAULT 1,
+    for_waitlist BIT NOT NULL DEFAULT 0,
+    triggered BIT NOT NULL DEFAULT 0,
+    PRIMARY KEY ([user_id], course_reference_number)
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[TriggerReminders]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [UCM].[TriggerReminders]
+AS
+BEGIN
+    SET NOCOUNT ON;
+    UPDATE r SET triggered = 1
+    OUTPUT inserted.[user_id], inserted.course_reference_number, inserted.min_trigger
+    FROM [UCM].[reminder] r
+    INNER JOIN [UCM].[class] c ON c.course_reference_number = r.course_reference_number
+    WHERE r.triggered = 0 AND (CASE WHEN r.for_waitlist = 1 THEN c.wait_available ELSE c.seats_available END) >= r.min_trigger;
+END');
+GO
+
+-- Class and professor searches use CONTAINS, which needs full-text search installed.
+IF FULLTEXTSERVICEPROPERTY('IsFullTextInstalled') = 1 AND NOT EXISTS (SELECT 1 FROM sys.fulltext_catalogs WHERE name = N'UCMCatalog')
+    CREATE FULLTEXT CATALOG UCMCatalog;
+GO
+
+IF FULLTEXTSERVICEPROPERTY('IsFullTextInstalled') = 1 AND NOT EXISTS (SELECT 1 FROM sys.fulltext_indexes WHERE object_id = OBJECT_ID(N'[UCM].[class]'))
+    CREATE FULLTEXT INDEX ON [UCM].[class] (course_number, course_title) KEY INDEX PK_class ON UCMCatalog;
+GO
+
+IF FULLTEXTSERVICEPROPERTY('IsFullTextInstalled') = 1 AND NOT EXISTS (SELECT 1 FROM sys.fulltext_indexes WHERE object_id = OBJECT_ID(N'[UCM].[professor]'))
+    CREATE FULLTEXT INDEX ON [UCM].[professor] (full_name) KEY INDEX PK_professor ON UCMCatalog;
+GO
+
+USE [Cow];
@@ -0,0 +1,46 @@
+-- The defaults are the old hardcoded rules: 20 XP a message and 5L^2 + 50L + 100 per level.
+IF COL_LENGTH(N'[Ranking].[Server]', N'min_xp') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    min_xp INT NOT NULL DEFAULT 20,
+    max_xp INT NOT NULL DEFAULT 20,
+    xp_multiplier FLOAT NOT NULL DEFAULT 1.0,
+    level_curve TINYINT NOT NULL DEFAULT 1,
+    curve_base INT NOT NULL DEFAULT 100,
+    curve_step INT NOT NULL DEFAULT 50,
+    curve_growth INT NOT NULL DEFAULT 5,
+    curve_table NVARCHAR(MAX) NULL;
+GO
+
+-- The level curve lives in Rust now, so this only handles the cooldown and hands back what the user has.
+IF OBJECT_ID(N'[Ranking].[GrantExp]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[GrantExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0), @xp INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @timeout INT, @last_message DATETIME2;
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id], level, xp) VALUES (@serverid, @userid, 0, 0);
+
+    SELECT @timeout = timeout FROM [Ranking].[Server] WHERE id = @serverid;
+    SELECT @last_message = last_message FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @last_message IS NOT NULL AND DATEDIFF_BIG(MILLISECOND, @last_message, SYSUTCDATETIME()) < @timeout
+    BEGIN
+        SELECT CAST(0 AS BIT), level, xp FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+        RETURN;
+    END
+
+    UPDATE [Ranking].[Level] SET xp = xp + @xp, last_message = SYSUTCDATETIME()
+    OUTPUT CAST(1 AS BIT), inserted.level, inserted.xp
+    WHERE server_id = @serverid AND [user_id] = @userid;
+END');
+GO
+
+DROP PROCEDURE IF EXISTS [Ranking].[ProvideExp];
+GO
+
+DROP PROCEDURE IF EXISTS [Ranking].[CalculateLevel];
@@ -0,0 +1,9 @@
+-- Channel and role IDs never collide, so one table keyed by the target covers both.
+IF OBJECT_ID(N'[Ranking].[Multiplier]', N'U') IS NULL
+CREATE TABLE [Ranking].[Multiplier] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    target_id DECIMAL(20, 0) NOT NULL,
+    is_role BIT NOT NULL DEFAULT 0,
+    multiplier FLOAT NOT NULL,
+    PRIMARY KEY (server_id, target_id)
+);
@@ -0,0 +1,52 @@
+IF COL_LENGTH(N'[Ranking].[Server]', N'voice_xp') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    voice_xp INT NOT NULL DEFAULT 0,
+    voice_daily_cap INT NOT NULL DEFAULT 0;
+GO
+
+-- Only the XP from the current day is kept; it resets the first time someone earns voice XP on a new day.
+IF COL_LENGTH(N'[Ranking].[Level]', N'voice_day') IS NULL
+ALTER TABLE [Ranking].[Level] ADD
+    voice_day DATE NULL,
+    voice_xp INT NOT NULL DEFAULT 0;
+GO
+
+IF OBJECT_ID(N'[Ranking].[VoiceChannel]', N'U') IS NULL
+CREATE TABLE [Ranking].[VoiceChannel] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    channel_id DECIMAL(20, 0) NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
+GO
+
+-- Same shape as GrantExp, but capped per day instead of on a cooldown.
+IF OBJECT_ID(N'[Ranking].[GrantVoiceExp]', N'P') IS NULL EXEC(N'
+CREATE PROCEDURE [Ranking].[GrantVoiceExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0), @xp INT, @cap INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @today DATE = CAST(SYSUTCDATETIME() AS DATE);
+    DECLARE @earned INT;
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id], level, xp) VALUES (@serverid, @userid, 0, 0);
+
+    SELECT @earned = CASE WHEN voice_day = @today THEN voice_xp ELSE 0 END
+    FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @cap > 0 AND @earned + @xp > @cap
+        SET @xp = @cap - @earned;
+
+    IF @xp <= 0
+    BEGIN
+        SELECT CAST(0 AS BIT), level, xp FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+        RETURN;
+    END
+
+    UPDATE [Ranking].[Level] SET xp = xp + @xp, voice_day = @today, voice_xp = @earned + @xp
+    OUTPUT CAST(1 AS BIT), inserted.level, inserted.xp
+    WHERE server_id = @serverid AND [user_id] = @userid;
+END');
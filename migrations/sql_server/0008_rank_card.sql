@@ -0,0 +1,5 @@
+-- Servers can opt out of the rendered rank card and keep the plain embed.
+IF COL_LENGTH(N'[Ranking].[Server]', N'rank_embed') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    rank_embed BIT NOT NULL DEFAULT 0;
+GO
@@ -0,0 +1,95 @@
+-- Every chat and voice XP grant, so leaderboards can be cut down to a week, a month, or a season.
+IF OBJECT_ID(N'[Ranking].[XpLog]', N'U') IS NULL
+CREATE TABLE [Ranking].[XpLog] (
+    id BIGINT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    xp INT NOT NULL,
+    gained_at DATETIME2 NOT NULL DEFAULT SYSUTCDATETIME(),
+    INDEX IX_XpLog_ServerTime (server_id, gained_at)
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[Season]', N'U') IS NULL
+CREATE TABLE [Ranking].[Season] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    name NVARCHAR(100) NOT NULL,
+    started_at DATETIME2 NOT NULL DEFAULT SYSUTCDATETIME(),
+    ended_at DATETIME2 NULL,
+    CONSTRAINT UQ_Season_ServerName UNIQUE (server_id, name)
+);
+GO
+
+-- Final totals, saved when a season ends.
+IF OBJECT_ID(N'[Ranking].[SeasonStanding]', N'U') IS NULL
+CREATE TABLE [Ranking].[SeasonStanding] (
+    season_id INT NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    xp BIGINT NOT NULL,
+    PRIMARY KEY (season_id, [user_id])
+);
+GO
+
+-- Same as before, but the XP also goes into the log.
+ALTER PROCEDURE [Ranking].[GrantExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0), @xp INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @timeout INT, @last_message DATETIME2;
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id], level, xp) VALUES (@serverid, @userid, 0, 0);
+
+    SELECT @timeout = timeout FROM [Ranking].[Server] WHERE id = @serverid;
+    SELECT @last_message = last_message FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @last_message IS NOT NULL AND DATEDIFF_BIG(MILLISECOND, @last_message, SYSUTCDATETIME()) < @timeout
+    BEGIN
+        SELECT CAST(0 AS BIT), level, xp FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+        RETURN;
+    END
+
+    IF @xp > 0
+        INSERT INTO [Ranking].[XpLog] (server_id, [user_id], xp) VALUES (@serverid, @userid, @xp);
+
+    UPDATE [Ranking].[Level] SET xp = xp + @xp, last_message = SYSUTCDATETIME()
+    OUTPUT CAST(1 AS BIT), inserted.level, inserted.xp
+    WHERE server_id = @serverid AND [user_id] = @userid;
+END
+GO
+
+ALTER PROCEDURE [Ranking].[GrantVoiceExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0), @xp INT, @cap INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @today DATE = CAST(SYSUTCDATETIME() AS DATE);
+    DECLARE @earned INT;
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id], level, xp) VALUES (@serverid, @userid, 0, 0);
+
+    SELECT @earned = CASE WHEN voice_day = @today THEN voice_xp ELSE 0 END
+    FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @cap > 0 AND @earned + @xp > @cap
+        SET @xp = @cap - @earned;
+
+    IF @xp <= 0
+    BEGIN
+        SELECT CAST(0 AS BIT), level, xp FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+        RETURN;
+    END
+
+    INSERT INTO [Ranking].[XpLog] (server_id, [user_id], xp) VALUES (@serverid, @userid, @xp);
+
+    UPDATE [Ranking].[Level] SET xp = xp + @xp, voice_day = @today, voice_xp = @earned + @xp
+    OUTPUT CAST(1 AS BIT), inserted.level, inserted.xp
+    WHERE server_id = @serverid AND [user_id] = @userid;
+END
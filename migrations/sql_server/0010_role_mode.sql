@@ -0,0 +1,5 @@
+-- 0 replaces the old rank role on a rank up, 1 lets them stack.
+IF COL_LENGTH(N'[Ranking].[Server]', N'role_mode') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    role_mode TINYINT NOT NULL DEFAULT 0;
+GO
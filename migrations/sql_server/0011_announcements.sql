@@ -0,0 +1,9 @@
+-- Where level-ups get announced (see AnnounceTarget), and how often.
+IF COL_LENGTH(N'[Ranking].[Server]', N'announce_target') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    announce_target TINYINT NOT NULL DEFAULT 0,
+    announce_channel DECIMAL(20, 0) NULL,
+    announce_template NVARCHAR(1000) NULL,
+    announce_every INT NOT NULL DEFAULT 1,
+    announce_roles_only BIT NOT NULL DEFAULT 0;
+GO
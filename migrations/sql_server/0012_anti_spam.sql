@@ -0,0 +1,24 @@
+-- Anti-spam thresholds (see SpamSettings); zero turns a check off.
+IF COL_LENGTH(N'[Ranking].[Server]', N'spam_min_length') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    spam_min_length INT NOT NULL DEFAULT 0,
+    spam_block_repeats BIT NOT NULL DEFAULT 0,
+    spam_junk_ratio FLOAT NOT NULL DEFAULT 0,
+    spam_burst_count INT NOT NULL DEFAULT 0,
+    spam_burst_seconds INT NOT NULL DEFAULT 10,
+    spam_burst_multiplier FLOAT NOT NULL DEFAULT 0.5;
+GO
+
+-- Running totals of what the checks caught, per member.
+IF OBJECT_ID(N'[Ranking].[Spam]', N'U') IS NULL
+CREATE TABLE [Ranking].[Spam] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    short_count INT NOT NULL DEFAULT 0,
+    repeat_count INT NOT NULL DEFAULT 0,
+    junk_count INT NOT NULL DEFAULT 0,
+    burst_count INT NOT NULL DEFAULT 0,
+    last_flagged DATETIME2 NOT NULL,
+    PRIMARY KEY (server_id, [user_id])
+);
+GO
@@ -0,0 +1,20 @@
+-- XP decay (see DecaySettings); zero days turns it off.
+IF COL_LENGTH(N'[Ranking].[Server]', N'decay_after_days') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    decay_after_days INT NOT NULL DEFAULT 0,
+    decay_percent FLOAT NOT NULL DEFAULT 5,
+    decay_demote BIT NOT NULL DEFAULT 0,
+    decay_hide_departed BIT NOT NULL DEFAULT 0;
+GO
+
+-- When someone last lost XP to decay, and whether they've left the server.
+IF COL_LENGTH(N'[Ranking].[Level]', N'decayed_at') IS NULL
+ALTER TABLE [Ranking].[Level] ADD
+    decayed_at DATETIME2 NULL,
+    departed BIT NOT NULL DEFAULT 0;
+GO
+
+-- Finding someone's last gain shouldn't mean scanning the whole server's log.
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_XpLog_User' AND object_id = OBJECT_ID(N'[Ranking].[XpLog]'))
+CREATE INDEX IX_XpLog_User ON [Ranking].[XpLog] (server_id, [user_id], gained_at);
+GO
@@ -0,0 +1,10 @@
+-- Running `rankconfig fix` on a schedule (see AutoFixSettings); zero hours turns it off.
+IF COL_LENGTH(N'[Ranking].[Server]', N'autofix_hours') IS NULL
+ALTER TABLE [Ranking].[Server] ADD
+    autofix_hours INT NOT NULL DEFAULT 0,
+    autofix_channel DECIMAL(20, 0) NULL,
+    autofix_multiple BIT NOT NULL DEFAULT 0,
+    autofix_remove BIT NOT NULL DEFAULT 0,
+    autofix_demote BIT NOT NULL DEFAULT 0,
+    autofix_last_run DATETIME2 NULL;
+GO
@@ -0,0 +1,20 @@
+-- Every batch of role changes `rankconfig fix` (or the scheduled fix) made, so it can be undone.
+IF OBJECT_ID(N'[Ranking].[Fix]', N'U') IS NULL
+CREATE TABLE [Ranking].[Fix] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    created_at DATETIME2 NOT NULL DEFAULT SYSUTCDATETIME(),
+    reverted_at DATETIME2 NULL,
+    INDEX IX_Fix_ServerTime (server_id, created_at)
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[FixChange]', N'U') IS NULL
+CREATE TABLE [Ranking].[FixChange] (
+    fix_id INT NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    role_id DECIMAL(20, 0) NOT NULL,
+    added BIT NOT NULL,
+    PRIMARY KEY (fix_id, [user_id], role_id)
+);
+GO
@@ -0,0 +1,57 @@
+-- Several named boards per server, each with its own settings; the old single board becomes "cow".
+IF OBJECT_ID(N'[Cowboard].[Board]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Board] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    name NVARCHAR(100) NOT NULL,
+    channel DECIMAL(20, 0) NULL,
+    add_threshold INT NOT NULL DEFAULT 5,
+    remove_threshold INT NOT NULL DEFAULT 4,
+    emote NVARCHAR(100) NOT NULL DEFAULT N'🐮',
+    webhook_id DECIMAL(20, 0) NULL,
+    webhook_token NVARCHAR(100) NULL,
+    CONSTRAINT UQ_Board_ServerName UNIQUE (server_id, name)
+);
+GO
+
+IF OBJECT_ID(N'[Cowboard].[Server]', N'U') IS NOT NULL
+INSERT INTO [Cowboard].[Board] (server_id, name, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token)
+SELECT s.id, N'cow', s.channel, s.add_threshold, s.remove_threshold, s.emote, s.webhook_id, s.webhook_token FROM [Cowboard].[Server] s
+WHERE NOT EXISTS (SELECT 1 FROM [Cowboard].[Board] b WHERE b.server_id = s.id AND b.name = N'cow');
+GO
+
+-- Source channels a board only takes messages from (allowed = 1), or never does (allowed = 0).
+IF OBJECT_ID(N'[Cowboard].[BoardChannel]', N'U') IS NULL
+CREATE TABLE [Cowboard].[BoardChannel] (
+    board_id INT NOT NULL,
+    channel_id DECIMAL(20, 0) NOT NULL,
+    allowed BIT NOT NULL,
+    PRIMARY KEY (board_id, channel_id)
+);
+GO
+
+-- Same as [Cowboard].[Message], but a message can now be on more than one board.
+IF OBJECT_ID(N'[Cowboard].[Post]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Post] (
+    board_id INT NOT NULL,
+    message_id DECIMAL(20, 0) NOT NULL,
+    message_channel_id DECIMAL(20, 0) NOT NULL,
+    post_id DECIMAL(20, 0) NOT NULL,
+    post_channel_id DECIMAL(20, 0) NOT NULL,
+    guild_id DECIMAL(20, 0) NOT NULL,
+    PRIMARY KEY (board_id, message_id, message_channel_id),
+    INDEX IX_Post_Message (message_id, message_channel_id)
+);
+GO
+
+IF OBJECT_ID(N'[Cowboard].[Message]', N'U') IS NOT NULL
+INSERT INTO [Cowboard].[Post] (board_id, message_id, message_channel_id, post_id, post_channel_id, guild_id)
+SELECT b.id, m.message_id, m.message_channel_id, m.post_id, m.post_channel_id, m.guild_id
+FROM [Cowboard].[Message] m JOIN [Cowboard].[Board] b ON b.server_id = m.guild_id AND b.name = N'cow'
+WHERE NOT EXISTS (SELECT 1 FROM [Cowboard].[Post] p WHERE p.board_id = b.id AND p.message_id = m.message_id AND p.message_channel_id = m.message_channel_id);
+GO
+
+DROP PROCEDURE IF EXISTS [Cowboard].[UpdateServer];
+DROP TABLE IF EXISTS [Cowboard].[Message];
+DROP TABLE IF EXISTS [Cowboard].[Server];
+GO
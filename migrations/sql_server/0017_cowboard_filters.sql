@@ -0,0 +1,18 @@
+-- Server-wide rules for what any cowboard can take; boards are kept safe unless these say otherwise.
+IF OBJECT_ID(N'[Cowboard].[Filter]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Filter] (
+    server_id DECIMAL(20, 0) NOT NULL PRIMARY KEY,
+    allow_nsfw BIT NOT NULL DEFAULT 0,
+    allow_hidden BIT NOT NULL DEFAULT 0
+);
+GO
+
+-- Channels or categories no cowboard takes messages from (allowed = 0), or the only ones they do (allowed = 1).
+IF OBJECT_ID(N'[Cowboard].[FilterChannel]', N'U') IS NULL
+CREATE TABLE [Cowboard].[FilterChannel] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    channel_id DECIMAL(20, 0) NOT NULL,
+    allowed BIT NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
+GO
@@ -0,0 +1,10 @@
+-- For cowboard stats; posts from before this don't know their author, and count as no reactions until they next change.
+IF COL_LENGTH(N'[Cowboard].[Post]', N'author_id') IS NULL
+ALTER TABLE [Cowboard].[Post] ADD
+    author_id DECIMAL(20, 0) NULL,
+    reactions INT NOT NULL DEFAULT 0;
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_Post_Guild' AND object_id = OBJECT_ID(N'[Cowboard].[Post]'))
+CREATE INDEX IX_Post_Guild ON [Cowboard].[Post] (guild_id, reactions);
+GO
@@ -0,0 +1,24 @@
+-- Server-wide rules for whose reactions count towards any cowboard; 0 or NULL turns a rule off.
+IF OBJECT_ID(N'[Cowboard].[Rules]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Rules] (
+    server_id DECIMAL(20, 0) NOT NULL PRIMARY KEY,
+    min_account_days INT NOT NULL DEFAULT 0,
+    min_member_hours INT NOT NULL DEFAULT 0,
+    required_role DECIMAL(20, 0) NULL,
+    burst_count INT NOT NULL DEFAULT 0,
+    burst_minutes INT NOT NULL DEFAULT 10,
+    review_channel DECIMAL(20, 0) NULL
+);
+GO
+
+-- Messages held off a board for a moderator to look at, after too many reactions came in too fast.
+IF OBJECT_ID(N'[Cowboard].[Review]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Review] (
+    board_id INT NOT NULL,
+    message_id DECIMAL(20, 0) NOT NULL,
+    message_channel_id DECIMAL(20, 0) NOT NULL,
+    guild_id DECIMAL(20, 0) NOT NULL,
+    flagged_at DATETIME2 NOT NULL,
+    PRIMARY KEY (board_id, message_id, message_channel_id, guild_id)
+);
+GO
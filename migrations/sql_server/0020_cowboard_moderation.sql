@@ -0,0 +1,38 @@
+-- What moderators have decided about single messages: forced onto a board, or kept off it.
+IF OBJECT_ID(N'[Cowboard].[Override]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Override] (
+    board_id INT NOT NULL,
+    message_id DECIMAL(20, 0) NOT NULL,
+    message_channel_id DECIMAL(20, 0) NOT NULL,
+    guild_id DECIMAL(20, 0) NOT NULL,
+    forced BIT NOT NULL,
+    PRIMARY KEY (board_id, message_id, message_channel_id, guild_id)
+);
+GO
+
+-- Authors whose messages never go to a board; blocked channels are denied in [Cowboard].[BoardChannel].
+IF OBJECT_ID(N'[Cowboard].[BlockedUser]', N'U') IS NULL
+CREATE TABLE [Cowboard].[BlockedUser] (
+    board_id INT NOT NULL,
+    user_id DECIMAL(20, 0) NOT NULL,
+    PRIMARY KEY (board_id, user_id)
+);
+GO
+
+-- Everything moderators have done to the boards, newest last.
+IF OBJECT_ID(N'[Cowboard].[Action]', N'U') IS NULL
+CREATE TABLE [Cowboard].[Action] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    board_id INT NOT NULL,
+    action TINYINT NOT NULL,
+    target_id DECIMAL(20, 0) NOT NULL,
+    channel_id DECIMAL(20, 0) NULL,
+    moderator_id DECIMAL(20, 0) NOT NULL,
+    created_at DATETIME2 NOT NULL
+);
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_Action_Server' AND object_id = OBJECT_ID(N'[Cowboard].[Action]'))
+CREATE INDEX IX_Action_Server ON [Cowboard].[Action] (server_id, id);
+GO
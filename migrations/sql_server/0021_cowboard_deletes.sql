@@ -0,0 +1,4 @@
+-- Whether posts stay up, marked as deleted, after the original message is deleted; otherwise they're deleted too.
+IF COL_LENGTH(N'[Cowboard].[Filter]', N'keep_deleted') IS NULL
+ALTER TABLE [Cowboard].[Filter] ADD keep_deleted BIT NOT NULL DEFAULT 0;
+GO
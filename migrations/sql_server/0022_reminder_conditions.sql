@@ -0,0 +1,11 @@
+-- What a reminder watches for (see ReminderConditions), whether it re-arms, and where it's sent; no channel means a DM.
+-- Also what the class looked like at the last check, to tell when it changes; NULL until it's first been checked.
+IF COL_LENGTH(N'[UniScraper].[UCM].[reminder]', N'conditions') IS NULL
+ALTER TABLE [UniScraper].[UCM].[reminder] ADD
+    conditions TINYINT NOT NULL DEFAULT 1,
+    recurring BIT NOT NULL DEFAULT 0,
+    channel_id DECIMAL(20, 0) NULL,
+    last_available SMALLINT NULL,
+    last_instructors NVARCHAR(512) NULL,
+    last_schedule NVARCHAR(1024) NULL;
+GO
@@ -0,0 +1,6 @@
+-- Reasons a reminder went off that still need delivering (see ReminderConditions), and whether we gave up on them.
+IF COL_LENGTH(N'[UniScraper].[UCM].[reminder]', N'pending') IS NULL
+ALTER TABLE [UniScraper].[UCM].[reminder] ADD
+    pending TINYINT NOT NULL DEFAULT 0,
+    delivery_failed BIT NOT NULL DEFAULT 0;
+GO
@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS ranking_server (
+    id INTEGER PRIMARY KEY NOT NULL,
+    timeout INTEGER NOT NULL DEFAULT 60000,
+    ranking_disabled INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS ranking_level (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    level INTEGER NOT NULL DEFAULT 0,
+    xp INTEGER NOT NULL DEFAULT 0,
+    last_message INTEGER,
+    PRIMARY KEY (server_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS ranking_role (
+    server_id INTEGER NOT NULL,
+    role_name TEXT NOT NULL,
+    role_id INTEGER,
+    min_level INTEGER NOT NULL,
+    PRIMARY KEY (server_id, min_level)
+);
+
+CREATE TABLE IF NOT EXISTS ranking_disabled_channel (
+    server_id INTEGER NOT NULL,
+    channel_id INTEGER NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS cowboard_server (
+    id INTEGER PRIMARY KEY NOT NULL,
+    channel INTEGER,
+    add_threshold INTEGER NOT NULL DEFAULT 5,
+    remove_threshold INTEGER NOT NULL DEFAULT 4,
+    emote TEXT NOT NULL DEFAULT '🐮',
+    webhook_id INTEGER,
+    webhook_token TEXT
+);
+
+CREATE TABLE IF NOT EXISTS cowboard_message (
+    message_id INTEGER NOT NULL,
+    message_channel_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL,
+    post_channel_id INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL,
+    PRIMARY KEY (message_id, message_channel_id, guild_id)
+);
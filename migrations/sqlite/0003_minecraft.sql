@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS minecraft_feed (
+    channel_id INTEGER PRIMARY KEY NOT NULL,
+    host TEXT NOT NULL,
+    password TEXT NOT NULL
+);
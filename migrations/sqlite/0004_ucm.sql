@@ -0,0 +1,71 @@
+-- On SQL Server these live in the scraper's own database; locally they are just empty tables to fill by hand.
+CREATE TABLE IF NOT EXISTS ucm_class (
+    id INTEGER PRIMARY KEY NOT NULL,
+    term INTEGER NOT NULL,
+    course_reference_number INTEGER NOT NULL,
+    course_number TEXT NOT NULL,
+    campus_description TEXT,
+    course_title TEXT,
+    credit_hours INTEGER NOT NULL DEFAULT 0,
+    maximum_enrollment INTEGER NOT NULL DEFAULT 0,
+    enrollment INTEGER NOT NULL DEFAULT 0,
+    seats_available INTEGER NOT NULL DEFAULT 0,
+    wait_capacity INTEGER NOT NULL DEFAULT 0,
+    wait_available INTEGER NOT NULL DEFAULT 0,
+    UNIQUE (term, course_reference_number)
+);
+
+CREATE TABLE IF NOT EXISTS ucm_professor (
+    id INTEGER PRIMARY KEY NOT NULL,
+    rmp_id INTEGER,
+    last_name TEXT NOT NULL,
+    first_name TEXT NOT NULL,
+    middle_name TEXT,
+    full_name TEXT NOT NULL,
+    email TEXT,
+    department TEXT,
+    num_ratings INTEGER NOT NULL DEFAULT 0,
+    rating REAL NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS ucm_faculty (
+    professor_id INTEGER NOT NULL REFERENCES ucm_professor (id),
+    class_id INTEGER NOT NULL REFERENCES ucm_class (id),
+    PRIMARY KEY (professor_id, class_id)
+);
+
+CREATE TABLE IF NOT EXISTS ucm_meeting (
+    class_id INTEGER NOT NULL REFERENCES ucm_class (id),
+    begin_time TEXT,
+    end_time TEXT,
+    begin_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    building TEXT,
+    building_description TEXT,
+    campus TEXT,
+    campus_description TEXT,
+    room TEXT,
+    credit_hour_session REAL NOT NULL DEFAULT 0,
+    hours_per_week REAL NOT NULL DEFAULT 0,
+    in_session INTEGER NOT NULL DEFAULT 0,
+    meeting_type INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE IF NOT EXISTS ucm_description (
+    course_number TEXT PRIMARY KEY NOT NULL,
+    course_description TEXT
+);
+
+CREATE TABLE IF NOT EXISTS ucm_stats (
+    table_name TEXT PRIMARY KEY NOT NULL,
+    last_update TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS ucm_reminder (
+    user_id INTEGER NOT NULL,
+    course_reference_number INTEGER NOT NULL,
+    min_trigger INTEGER NOT NULL DEFAULT 1,
+    for_waitlist INTEGER NOT NULL DEFAULT 0,
+    triggered INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, course_reference_number)
+);
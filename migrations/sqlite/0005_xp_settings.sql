@@ -0,0 +1,9 @@
+-- The defaults are the old hardcoded rules: 20 XP a message and 5L^2 + 50L + 100 per level.
+ALTER TABLE ranking_server ADD COLUMN min_xp INTEGER NOT NULL DEFAULT 20;
+ALTER TABLE ranking_server ADD COLUMN max_xp INTEGER NOT NULL DEFAULT 20;
+ALTER TABLE ranking_server ADD COLUMN xp_multiplier REAL NOT NULL DEFAULT 1.0;
+ALTER TABLE ranking_server ADD COLUMN level_curve INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE ranking_server ADD COLUMN curve_base INTEGER NOT NULL DEFAULT 100;
+ALTER TABLE ranking_server ADD COLUMN curve_step INTEGER NOT NULL DEFAULT 50;
+ALTER TABLE ranking_server ADD COLUMN curve_growth INTEGER NOT NULL DEFAULT 5;
+ALTER TABLE ranking_server ADD COLUMN curve_table TEXT;
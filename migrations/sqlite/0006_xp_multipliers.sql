@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS ranking_multiplier (
+    server_id INTEGER NOT NULL,
+    target_id INTEGER NOT NULL,
+    is_role INTEGER NOT NULL DEFAULT 0,
+    multiplier REAL NOT NULL,
+    PRIMARY KEY (server_id, target_id)
+);
@@ -0,0 +1,12 @@
+ALTER TABLE ranking_server ADD COLUMN voice_xp INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN voice_daily_cap INTEGER NOT NULL DEFAULT 0;
+
+-- Only the XP from the current day is kept; it resets the first time someone earns voice XP on a new day.
+ALTER TABLE ranking_level ADD COLUMN voice_day TEXT;
+ALTER TABLE ranking_level ADD COLUMN voice_xp INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS ranking_voice_channel (
+    server_id INTEGER NOT NULL,
+    channel_id INTEGER NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
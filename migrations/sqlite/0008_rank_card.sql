@@ -0,0 +1,2 @@
+-- Servers can opt out of the rendered rank card and keep the plain embed.
+ALTER TABLE ranking_server ADD COLUMN rank_embed INTEGER NOT NULL DEFAULT 0;
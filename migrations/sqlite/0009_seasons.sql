@@ -0,0 +1,26 @@
+-- Every chat and voice XP grant, so leaderboards can be cut down to a week, a month, or a season.
+CREATE TABLE IF NOT EXISTS ranking_xp_log (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    xp INTEGER NOT NULL,
+    gained_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS ranking_xp_log_server_time ON ranking_xp_log (server_id, gained_at);
+
+CREATE TABLE IF NOT EXISTS ranking_season (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER,
+    UNIQUE (server_id, name)
+);
+
+-- Final totals, saved when a season ends.
+CREATE TABLE IF NOT EXISTS ranking_season_standing (
+    season_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    xp INTEGER NOT NULL,
+    PRIMARY KEY (season_id, user_id)
+);
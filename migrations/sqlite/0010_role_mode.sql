@@ -0,0 +1,2 @@
+-- 0 replaces the old rank role on a rank up, 1 lets them stack.
+ALTER TABLE ranking_server ADD COLUMN role_mode INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+-- Where level-ups get announced (see AnnounceTarget), and how often.
+ALTER TABLE ranking_server ADD COLUMN announce_target INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN announce_channel INTEGER;
+ALTER TABLE ranking_server ADD COLUMN announce_template TEXT;
+ALTER TABLE ranking_server ADD COLUMN announce_every INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE ranking_server ADD COLUMN announce_roles_only INTEGER NOT NULL DEFAULT 0;
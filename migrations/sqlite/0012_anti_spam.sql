@@ -0,0 +1,19 @@
+-- Anti-spam thresholds (see SpamSettings); zero turns a check off.
+ALTER TABLE ranking_server ADD COLUMN spam_min_length INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN spam_block_repeats INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN spam_junk_ratio REAL NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN spam_burst_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN spam_burst_seconds INTEGER NOT NULL DEFAULT 10;
+ALTER TABLE ranking_server ADD COLUMN spam_burst_multiplier REAL NOT NULL DEFAULT 0.5;
+
+-- Running totals of what the checks caught, per member; last_flagged is in milliseconds.
+CREATE TABLE IF NOT EXISTS ranking_spam (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    short_count INTEGER NOT NULL DEFAULT 0,
+    repeat_count INTEGER NOT NULL DEFAULT 0,
+    junk_count INTEGER NOT NULL DEFAULT 0,
+    burst_count INTEGER NOT NULL DEFAULT 0,
+    last_flagged INTEGER NOT NULL,
+    PRIMARY KEY (server_id, user_id)
+);
@@ -0,0 +1,12 @@
+-- XP decay (see DecaySettings); zero days turns it off.
+ALTER TABLE ranking_server ADD COLUMN decay_after_days INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN decay_percent REAL NOT NULL DEFAULT 5;
+ALTER TABLE ranking_server ADD COLUMN decay_demote INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN decay_hide_departed INTEGER NOT NULL DEFAULT 0;
+
+-- When someone last lost XP to decay (in milliseconds), and whether they've left the server.
+ALTER TABLE ranking_level ADD COLUMN decayed_at INTEGER;
+ALTER TABLE ranking_level ADD COLUMN departed INTEGER NOT NULL DEFAULT 0;
+
+-- Finding someone's last gain shouldn't mean scanning the whole server's log.
+CREATE INDEX IF NOT EXISTS ranking_xp_log_user ON ranking_xp_log (server_id, user_id, gained_at);
@@ -0,0 +1,7 @@
+-- Running `rankconfig fix` on a schedule (see AutoFixSettings); zero hours turns it off.
+ALTER TABLE ranking_server ADD COLUMN autofix_hours INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN autofix_channel INTEGER;
+ALTER TABLE ranking_server ADD COLUMN autofix_multiple INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN autofix_remove INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN autofix_demote INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ranking_server ADD COLUMN autofix_last_run INTEGER;
@@ -0,0 +1,17 @@
+-- Every batch of role changes `rankconfig fix` (or the scheduled fix) made, so it can be undone.
+CREATE TABLE IF NOT EXISTS ranking_fix (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    reverted_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS ranking_fix_server ON ranking_fix (server_id, created_at);
+
+CREATE TABLE IF NOT EXISTS ranking_fix_change (
+    fix_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    added INTEGER NOT NULL,
+    PRIMARY KEY (fix_id, user_id, role_id)
+);
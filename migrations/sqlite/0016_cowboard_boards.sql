@@ -0,0 +1,44 @@
+-- Several named boards per server, each with its own settings; the old single board becomes "cow".
+CREATE TABLE IF NOT EXISTS cowboard_board (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    channel INTEGER,
+    add_threshold INTEGER NOT NULL DEFAULT 5,
+    remove_threshold INTEGER NOT NULL DEFAULT 4,
+    emote TEXT NOT NULL DEFAULT '🐮',
+    webhook_id INTEGER,
+    webhook_token TEXT,
+    UNIQUE (server_id, name)
+);
+
+INSERT INTO cowboard_board (server_id, name, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token)
+SELECT id, 'cow', channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token FROM cowboard_server;
+
+-- Source channels a board only takes messages from (allowed = 1), or never does (allowed = 0).
+CREATE TABLE IF NOT EXISTS cowboard_board_channel (
+    board_id INTEGER NOT NULL,
+    channel_id INTEGER NOT NULL,
+    allowed INTEGER NOT NULL,
+    PRIMARY KEY (board_id, channel_id)
+);
+
+-- Same as cowboard_message, but a message can now be on more than one board.
+CREATE TABLE IF NOT EXISTS cowboard_post (
+    board_id INTEGER NOT NULL,
+    message_id INTEGER NOT NULL,
+    message_channel_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL,
+    post_channel_id INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL,
+    PRIMARY KEY (board_id, message_id, message_channel_id)
+);
+
+CREATE INDEX IF NOT EXISTS cowboard_post_message ON cowboard_post (message_id, message_channel_id);
+
+INSERT INTO cowboard_post (board_id, message_id, message_channel_id, post_id, post_channel_id, guild_id)
+SELECT b.id, m.message_id, m.message_channel_id, m.post_id, m.post_channel_id, m.guild_id
+FROM cowboard_message m JOIN cowboard_board b ON b.server_id = m.guild_id AND b.name = 'cow';
+
+DROP TABLE cowboard_message;
+DROP TABLE cowboard_server;
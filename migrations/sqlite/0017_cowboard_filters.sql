@@ -0,0 +1,14 @@
+-- Server-wide rules for what any cowboard can take; boards are kept safe unless these say otherwise.
+CREATE TABLE IF NOT EXISTS cowboard_filter (
+    server_id INTEGER PRIMARY KEY NOT NULL,
+    allow_nsfw INTEGER NOT NULL DEFAULT 0,
+    allow_hidden INTEGER NOT NULL DEFAULT 0
+);
+
+-- Channels or categories no cowboard takes messages from (allowed = 0), or the only ones they do (allowed = 1).
+CREATE TABLE IF NOT EXISTS cowboard_filter_channel (
+    server_id INTEGER NOT NULL,
+    channel_id INTEGER NOT NULL,
+    allowed INTEGER NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
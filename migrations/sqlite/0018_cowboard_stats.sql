@@ -0,0 +1,5 @@
+-- For cowboard stats; posts from before this don't know their author, and count as no reactions until they next change.
+ALTER TABLE cowboard_post ADD COLUMN author_id INTEGER;
+ALTER TABLE cowboard_post ADD COLUMN reactions INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS cowboard_post_guild ON cowboard_post (guild_id, reactions);
@@ -0,0 +1,20 @@
+-- Server-wide rules for whose reactions count towards any cowboard; 0 or NULL turns a rule off.
+CREATE TABLE IF NOT EXISTS cowboard_rules (
+    server_id INTEGER PRIMARY KEY NOT NULL,
+    min_account_days INTEGER NOT NULL DEFAULT 0,
+    min_member_hours INTEGER NOT NULL DEFAULT 0,
+    required_role INTEGER NULL,
+    burst_count INTEGER NOT NULL DEFAULT 0,
+    burst_minutes INTEGER NOT NULL DEFAULT 10,
+    review_channel INTEGER NULL
+);
+
+-- Messages held off a board for a moderator to look at, after too many reactions came in too fast.
+CREATE TABLE IF NOT EXISTS cowboard_review (
+    board_id INTEGER NOT NULL,
+    message_id INTEGER NOT NULL,
+    message_channel_id INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL,
+    flagged_at INTEGER NOT NULL,
+    PRIMARY KEY (board_id, message_id, message_channel_id, guild_id)
+);
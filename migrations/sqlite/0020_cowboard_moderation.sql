@@ -0,0 +1,30 @@
+-- What moderators have decided about single messages: forced onto a board, or kept off it.
+CREATE TABLE IF NOT EXISTS cowboard_override (
+    board_id INTEGER NOT NULL,
+    message_id INTEGER NOT NULL,
+    message_channel_id INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL,
+    forced INTEGER NOT NULL,
+    PRIMARY KEY (board_id, message_id, message_channel_id, guild_id)
+);
+
+-- Authors whose messages never go to a board; blocked channels are denied in cowboard_board_channel.
+CREATE TABLE IF NOT EXISTS cowboard_blocked_user (
+    board_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    PRIMARY KEY (board_id, user_id)
+);
+
+-- Everything moderators have done to the boards, newest last.
+CREATE TABLE IF NOT EXISTS cowboard_action (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    board_id INTEGER NOT NULL,
+    action INTEGER NOT NULL,
+    target_id INTEGER NOT NULL,
+    channel_id INTEGER NULL,
+    moderator_id INTEGER NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS cowboard_action_server ON cowboard_action (server_id, id);
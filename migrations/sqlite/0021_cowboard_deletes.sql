@@ -0,0 +1,2 @@
+-- Whether posts stay up, marked as deleted, after the original message is deleted; otherwise they're deleted too.
+ALTER TABLE cowboard_filter ADD COLUMN keep_deleted INTEGER NOT NULL DEFAULT 0;
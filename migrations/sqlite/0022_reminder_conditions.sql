@@ -0,0 +1,9 @@
+-- What a reminder watches for (see ReminderConditions), whether it re-arms, and where it's sent; no channel means a DM.
+ALTER TABLE ucm_reminder ADD COLUMN conditions INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE ucm_reminder ADD COLUMN recurring INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ucm_reminder ADD COLUMN channel_id INTEGER NULL;
+
+-- What the class looked like at the last check, to tell when it changes; NULL until it's first been checked.
+ALTER TABLE ucm_reminder ADD COLUMN last_available INTEGER NULL;
+ALTER TABLE ucm_reminder ADD COLUMN last_instructors TEXT NULL;
+ALTER TABLE ucm_reminder ADD COLUMN last_schedule TEXT NULL;
@@ -0,0 +1,3 @@
+-- Reasons a reminder went off that still need delivering (see ReminderConditions), and whether we gave up on them.
+ALTER TABLE ucm_reminder ADD COLUMN pending INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE ucm_reminder ADD COLUMN delivery_failed INTEGER NOT NULL DEFAULT 0;
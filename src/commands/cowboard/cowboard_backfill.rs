@@ -0,0 +1,240 @@
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serenity::model::channel::{Channel, ChannelType, Message};
+use serenity::model::id::{ChannelId, MessageId};
+use tokio::time;
+use tracing::error;
+use crate::{CowContext, cowdb, Error};
+use crate::{Database, db};
+use crate::commands::cowboard::cowboard_handler::{MooChange, channel_boards, has_board_emote, sync_moo};
+use crate::models::db_models::XpWindow;
+
+// Discord hands out history 100 messages at a time.
+const PAGE_SIZE: u64 = 100;
+// On top of serenity's own rate limiting, so a backfill doesn't crowd out everything else the bot's doing.
+const PAUSE: Duration = Duration::from_millis(500);
+const MAX_DAYS: i64 = 366;
+// Milliseconds between the Unix epoch and the first Discord snowflake.
+const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+// How often (in messages posted or checked) to update the progress message.
+const PROGRESS_EVERY: usize = 10;
+
+// The lowest message ID that could have been sent at this time, to page through history from.
+fn message_id_at(time: DateTime<Utc>) -> MessageId {
+    MessageId::from((time.timestamp_millis().max(0) as u64).saturating_sub(DISCORD_EPOCH) << 22)
+}
+
+#[derive(Default)]
+struct BackfillReport {
+    channels: usize,
+    scanned: usize,
+    // Messages with a board's emote on them, to check.
+    found: usize,
+    checked: usize,
+    posted: usize,
+    updated: usize,
+    removed: usize,
+    errors: usize
+}
+
+impl BackfillReport {
+    fn summary(&self) -> String {
+        format!("Looked through {} messages in {} channels; {} had reactions to check, and {} have been checked:\n\
+            - Posted: {}\n\
+            - Updated: {}\n\
+            - Taken down: {}\n\
+            - Errors: {}",
+            self.scanned, self.channels, self.found, self.checked, self.posted, self.updated, self.removed, self.errors)
+    }
+}
+
+// A failed edit isn't worth stopping halfway through posting for; the next one will catch up.
+async fn show_progress(ctx: CowContext<'_>, progress: &mut Message, description: String) {
+    if let Err(ex) = progress.edit(ctx.serenity_context(), |m| m
+        .embed(|e| e
+            .title("Cowboard Backfill")
+            .description(description)
+        )
+    ).await {
+        error!("Failed to update backfill progress: {}", ex);
+    }
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Look through older messages, and post or take down whatever has enough reactions now."),
+    required_permissions = "ADMINISTRATOR",
+    guild_cooldown = "900"
+)]
+pub async fn backfill(
+    ctx: CowContext<'_>,
+    #[description = "The first day to look through, like 2024-01-31"] from: String,
+    #[description = "The last day to look through; up to now if left out"] to: Option<String>,
+    #[description = "Only look through this channel; every channel if left out"] channel: Option<ChannelId>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    let guild = match ctx.guild() {
+        Some(guild) => guild,
+        None => {
+            ctx.say("This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let (start, end) = match XpWindow::parse_range(&from, to.as_deref()) {
+        Some(XpWindow::Between(start, end)) => (start, end.unwrap_or_else(Utc::now)),
+        _ => {
+            ctx.say("Dates must look like 2024-01-31, and the end can't be before the start.").await?;
+            return Ok(());
+        }
+    };
+    if end - start > chrono::Duration::days(MAX_DAYS) {
+        ctx.say(format!("A backfill can cover at most {MAX_DAYS} days at once.")).await?;
+        return Ok(());
+    }
+
+    let channels = match channel {
+        Some(channel) if guild.channels.contains_key(&channel) => vec![channel],
+        Some(_) => {
+            ctx.say("Could not find channel in this server!").await?;
+            return Ok(());
+        }
+        None => {
+            let mut channels = guild.channels.values()
+                .filter_map(|o| match o {
+                    Channel::Guild(channel) if channel.kind == ChannelType::Text || channel.kind == ChannelType::News => Some(channel),
+                    _ => None
+                })
+                .collect::<Vec<_>>();
+            channels.sort_by_key(|o| o.position);
+            channels.into_iter().map(|o| o.id).collect()
+        }
+    };
+
+    // Slash command replies can only be edited for 15 minutes, and a big backfill takes longer than that,
+    // so progress goes in a normal message instead.
+    ctx.say("Starting the backfill; progress will be posted in this channel.").await?;
+    let serenity = ctx.serenity_context();
+    let mut progress = ctx.channel_id().send_message(&serenity.http, |m| m
+        .embed(|e| e
+            .title("Cowboard Backfill")
+            .description("Now looking through old messages, please wait warmly...")
+        )
+    ).await?;
+
+    let (first, last) = (message_id_at(start), message_id_at(end));
+    let mut report = BackfillReport::default();
+    // Every message with a board's emote, and the IDs of the boards it might go on.
+    let mut candidates: Vec<(Message, Vec<i32>)> = Vec::new();
+
+    for channel_id in channels {
+        let boards = match channel_boards(serenity, guild.id, channel_id).await {
+            Ok(boards) => boards,
+            Err(ex) => {
+                error!("Failed to get the cowboards for {}: {}", channel_id, ex);
+                report.errors += 1;
+                continue;
+            }
+        };
+        if boards.is_empty() {
+            continue;
+        }
+        report.channels += 1;
+
+        // Discord only pages forwards from a message ID, so this walks from the start of the range.
+        let mut after = first;
+        'pages: loop {
+            let mut messages = match channel_id.messages(&serenity.http, |b| b.after(after).limit(PAGE_SIZE)).await {
+                Ok(messages) => messages,
+                Err(ex) => {
+                    // Most likely a channel we can't read; the others are still worth doing.
+                    error!("Failed to get message history for {}: {}", channel_id, ex);
+                    report.errors += 1;
+                    break;
+                }
+            };
+            let full_page = messages.len() as u64 == PAGE_SIZE;
+            messages.sort_by_key(|o| o.id);
+
+            for message in messages {
+                if message.id > last {
+                    break 'pages;
+                }
+                after = message.id;
+                report.scanned += 1;
+
+                let board_ids = boards.iter().filter(|o| has_board_emote(&message, o)).map(|o| o.id).collect::<Vec<_>>();
+                if !board_ids.is_empty() {
+                    candidates.push((message, board_ids));
+                }
+            }
+
+            if !full_page {
+                break;
+            }
+
+            report.found = candidates.len();
+            show_progress(ctx, &mut progress, format!("Looking through <#{channel_id}>...\n\n{}", report.summary())).await;
+            time::sleep(PAUSE).await;
+        }
+    }
+    report.found = candidates.len();
+
+    // Oldest first, across every channel, so the boards end up in the order things were said.
+    candidates.sort_by_key(|(message, _)| message.id);
+    let mut boards = db.get_cowboards(guild.id).await?;
+    let rules = db.get_cowboard_rules(guild.id).await?;
+    let mut countdown = PROGRESS_EVERY;
+
+    for (message, board_ids) in &candidates {
+        for config in boards.iter_mut().filter(|o| board_ids.contains(&o.id)) {
+            match sync_moo(serenity, guild.id, message, config, &rules).await {
+                Ok(MooChange::Posted) => report.posted += 1,
+                Ok(MooChange::Updated) => report.updated += 1,
+                Ok(MooChange::Removed) => report.removed += 1,
+                Ok(MooChange::Unchanged) => {}
+                Ok(MooChange::Failed) => report.errors += 1,
+                Err(ex) => {
+                    error!("Failed to backfill message {}: {}", message.id, ex);
+                    report.errors += 1;
+                }
+            }
+        }
+        report.checked += 1;
+
+        countdown -= 1;
+        if countdown == 0 {
+            countdown = PROGRESS_EVERY;
+            show_progress(ctx, &mut progress, format!("Posting in order...\n\n{}", report.summary())).await;
+        }
+        time::sleep(PAUSE).await;
+    }
+
+    show_progress(ctx, &mut progress, format!("Done!\n\n{}", report.summary())).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use super::*;
+
+    #[test]
+    fn message_ids_start_at_the_discord_epoch() {
+        assert_eq!(message_id_at(Utc.timestamp_opt(1_420_070_400, 0).unwrap()), MessageId(0));
+        assert_eq!(message_id_at(Utc.timestamp_opt(0, 0).unwrap()), MessageId(0));
+    }
+
+    #[test]
+    fn message_ids_match_their_timestamp() {
+        // The example snowflake from Discord's docs, sent at 2016-04-30 11:18:25.796 UTC.
+        let sent = Utc.timestamp_opt(1_462_015_105, 796_000_000).unwrap();
+        let id = message_id_at(sent);
+        assert_eq!(id.0 >> 22, 175928847299117063 >> 22);
+        assert!(id.0 <= 175928847299117063);
+    }
+}
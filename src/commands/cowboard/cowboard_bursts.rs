@@ -0,0 +1,40 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serenity::model::id::MessageId;
+use serenity::prelude::TypeMapKey;
+
+// Nothing gets held for longer than this after its last reaction, so the map doesn't grow forever.
+const FORGET_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+const PRUNE_EVERY: usize = 1000;
+
+// When each message's latest reactions came in, per board, to tell a sudden pile-on from a slow climb.
+// Nothing here is saved; a restart only means bursts that were under way start over.
+#[derive(Default)]
+pub struct ReactionBursts {
+    recent: HashMap<(i32, MessageId), VecDeque<Instant>>,
+    seen: usize
+}
+
+impl TypeMapKey for ReactionBursts {
+    type Value = Arc<Mutex<ReactionBursts>>;
+}
+
+impl ReactionBursts {
+    // Notes a reaction, and gives back how many the message has had on the board within `window`, counting this one.
+    pub fn record(&mut self, board_id: i32, message_id: MessageId, window: Duration, now: Instant) -> usize {
+        self.seen += 1;
+        if self.seen >= PRUNE_EVERY {
+            self.seen = 0;
+            self.recent.retain(|_, o| o.back().map(|last| now.duration_since(*last) < FORGET_AFTER).unwrap_or(false));
+        }
+
+        let sent = self.recent.entry((board_id, message_id)).or_default();
+        sent.push_back(now);
+        while sent.front().map(|o| now.duration_since(*o) > window).unwrap_or(false) {
+            sent.pop_front();
+        }
+
+        sent.len()
+    }
+}
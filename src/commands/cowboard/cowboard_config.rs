@@ -1,39 +1,160 @@
 use tracing::error;
 use crate::{CowContext, cowdb, Error};
-use serenity::model::channel::ReactionType;
-use serenity::model::id::ChannelId;
+use serenity::model::channel::{Message, ReactionType};
+use serenity::model::id::{ChannelId, GuildId, RoleId};
 use serenity::utils::MessageBuilder;
 use crate::{Database, db};
+use crate::commands::cowboard::cowboard_db_models::{Cowboard, CowboardRules, DEFAULT_BOARD};
+use crate::commands::cowboard::cowboard_handler::{MooChange, member_can_read, sync_moo};
+
+const MAX_NAME_LENGTH: usize = 32;
+
+fn format_channels(channels: &[u64]) -> String {
+    channels.iter().map(|o| format!("<#{o}>")).collect::<Vec<_>>().join(", ")
+}
+
+// None if it's not a setting; Some(None) clears the channel's filter.
+fn parse_filter(setting: &str) -> Option<Option<bool>> {
+    match setting.to_lowercase().as_str() {
+        "allow" => Some(Some(true)),
+        "deny" => Some(Some(false)),
+        "clear" => Some(None),
+        _ => None
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "Yes" } else { "No" }
+}
+
+fn describe_deletes(keep_deleted: bool) -> &'static str {
+    if keep_deleted { "Posts stay, marked [deleted]" } else { "Posts are deleted too" }
+}
+
+fn describe_bursts(rules: &CowboardRules) -> String {
+    match rules.review_channel {
+        Some(channel) if rules.burst_count > 0 => format!("{} reactions within {} minutes, reported in <#{channel}>", rules.burst_count, rules.burst_minutes),
+        _ => "Off".to_string()
+    }
+}
+
+// Which board a command means: the one named, else the server's only board, else the default one.
+// Only the default board can be set up before it's saved; any other name has to be made with create first.
+// Says what went wrong and gives back None when there's no telling.
+async fn find_board(ctx: CowContext<'_>, db: &Database, guild_id: GuildId, board: Option<String>) -> Result<Option<Cowboard>, Error> {
+    let name = match board {
+        Some(name) => name,
+        None => match db.get_cowboards(guild_id).await {
+            Ok(boards) if boards.len() == 1 => return Ok(boards.into_iter().next()),
+            Ok(boards) if boards.is_empty() || boards.iter().any(|o| o.name.eq_ignore_ascii_case(DEFAULT_BOARD)) => DEFAULT_BOARD.to_string(),
+            Ok(boards) => {
+                let names = boards.iter().map(|o| o.name.as_str()).collect::<Vec<_>>().join(", ");
+                ctx.say(format!("This server has more than one cowboard, so say which one: {names}.")).await?;
+                return Ok(None);
+            }
+            Err(ex) => {
+                ctx.say("We couldn't get the cowboard settings... try again later?").await?;
+                error!("Failed to get cowboards: {}", ex);
+                return Ok(None);
+            }
+        }
+    };
+
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH || name.contains(char::is_whitespace) {
+        ctx.say(format!("Cowboard names can't have spaces, and can be at most {MAX_NAME_LENGTH} characters long.")).await?;
+        return Ok(None);
+    }
+
+    match db.get_cowboard_config(guild_id, &name).await {
+        Ok(config) if config.id == 0 && !name.eq_ignore_ascii_case(DEFAULT_BOARD) => {
+            ctx.say(format!("There's no cowboard called {name}; make it first with `/cowboard create {name}`.")).await?;
+            Ok(None)
+        }
+        Ok(config) => Ok(Some(config)),
+        Err(ex) => {
+            ctx.say("We couldn't get the cowboard settings... try again later?").await?;
+            error!("Failed to get cowboard: {}", ex);
+            Ok(None)
+        }
+    }
+}
 
 #[poise::command(
     prefix_command,
     slash_command,
-    description_localized("en-US", "Get the current settings for the cowboard."),
+    description_localized("en-US", "Get the current settings for the cowboards, or just one of them."),
     guild_only,
     discard_spare_arguments
 )]
-pub async fn info(ctx: CowContext<'_>) -> Result<(), Error> {
-    info_code(ctx).await
+pub async fn info(
+    ctx: CowContext<'_>,
+    #[description = "The name of a cowboard; leave out to see them all."] board: Option<String>)
+-> Result<(), Error> {
+    info_code(ctx, board).await
 }
 
-pub async fn info_code(ctx: CowContext<'_>) -> Result<(), Error> {
+pub async fn info_code(ctx: CowContext<'_>, board: Option<String>) -> Result<(), Error> {
     let db = cowdb!(ctx);
 
     if let Some(guild_id) = ctx.guild_id() {
-        if let Ok(config) = db.get_cowboard_config(guild_id).await {
+        let boards = match &board {
+            Some(name) => db.get_cowboard_config(guild_id, name).await.map(|o| vec![o]),
+            None => db.get_cowboards(guild_id).await
+        };
+
+        if let (Ok(mut boards), Ok(filter), Ok(rules)) = (boards, db.get_cowboard_filter(guild_id).await, db.get_cowboard_rules(guild_id).await) {
+            // Unsaved boards are only worth showing while there's nothing else, as the defaults.
+            if let (Some(name), Some(config)) = (&board, boards.first()) {
+                if config.id == 0 && !name.eq_ignore_ascii_case(DEFAULT_BOARD) {
+                    ctx.say(format!("There's no cowboard called {name}.")).await?;
+                    return Ok(());
+                }
+            }
+            if boards.is_empty() {
+                boards.push(Cowboard::new(guild_id.0, DEFAULT_BOARD));
+            }
+
             ctx.send(|m| {
                 m.embeds.clear();
-                m.embed(|e|
+                // A message can only hold 10 embeds, and the server's filter takes one.
+                for config in boards.iter().take(9) {
+                    m.embed(|e| {
+                        e
+                            .title(format!("Cowboard Settings: {}", config.name))
+                            .description("If the emote doesn't display properly below, you probably want to use a different one!")
+                            .field("Emote", &config.emote, true)
+                            .field("Raw Emote", MessageBuilder::new().push_mono(&config.emote).build(), true)
+                            .field("Channel", config.channel.map(|o| format!("<#{o}>")).unwrap_or_else(|| "No Cowboard Channel".to_string()), true)
+                            .field("Add Threshold", MessageBuilder::new().push_mono(config.add_threshold).build(), true)
+                            .field("Remove Threshold", MessageBuilder::new().push_mono(config.remove_threshold).build(), true)
+                            .field("Webhook", if config.webhook_id.is_some() && config.webhook_token.is_some() { "Enabled" } else { "Disabled" }, true);
+                        if !config.allowed_channels.is_empty() {
+                            e.field("Only From", format_channels(&config.allowed_channels), false);
+                        }
+                        if !config.denied_channels.is_empty() {
+                            e.field("Never From", format_channels(&config.denied_channels), false);
+                        }
+                        e
+                    });
+                }
+                m.embed(|e| {
+                    e
+                        .title("Cowboard Server Filters")
+                        .field("NSFW To Any Board", yes_no(filter.allow_nsfw), true)
+                        .field("Hidden Channels To Any Board", yes_no(filter.allow_hidden), true)
+                        .field("Deleted Originals", describe_deletes(filter.keep_deleted), true)
+                        .field("Minimum Account Age", format!("{} days", rules.min_account_days), true)
+                        .field("Minimum Time In Server", format!("{} hours", rules.min_member_hours), true)
+                        .field("Required Role", rules.required_role.map(|o| format!("<@&{o}>")).unwrap_or_else(|| "None".to_string()), true)
+                        .field("Hold Bursts For Review", describe_bursts(&rules), false);
+                    if !filter.allowed_channels.is_empty() {
+                        e.field("Only From", format_channels(&filter.allowed_channels), false);
+                    }
+                    if !filter.denied_channels.is_empty() {
+                        e.field("Never From", format_channels(&filter.denied_channels), false);
+                    }
                     e
-                        .title("Cowboard Settings")
-                        .description("If the emote doesn't display properly below, you probably want to use a different one!")
-                        .field("Emote", &config.emote, true)
-                        .field("Raw Emote", MessageBuilder::new().push_mono(&config.emote).build(), true)
-                        .field("Channel", config.channel.map(|o| format!("<#{o}>")).unwrap_or_else(|| "No Cowboard Channel".to_string()), true)
-                        .field("Add Threshold", MessageBuilder::new().push_mono(config.add_threshold).build(), true)
-                        .field("Remove Threshold", MessageBuilder::new().push_mono(config.remove_threshold).build(), true)
-                        .field("Webhook", if config.webhook_id.is_some() && config.webhook_token.is_some() { "Enabled" } else { "Disabled" }, true)
-                )
+                })
             }).await?;
         } else {
             ctx.say("Failed to fetch Cowboard settings for this server...").await?;
@@ -48,30 +169,80 @@ pub async fn info_code(ctx: CowContext<'_>) -> Result<(), Error> {
 #[poise::command(
     prefix_command,
     slash_command,
-    description_localized("en-US", "Set the emote reaction to trigger a cowboard message."),
+    guild_only,
+    description_localized("en-US", "Make a new cowboard, to set up with the other cowboard commands."),
     required_permissions = "ADMINISTRATOR",
-    guild_only
+    discard_spare_arguments
 )]
-pub async fn emote(
+pub async fn create(
     ctx: CowContext<'_>,
-    #[description = "An emote on the server or a default Discord emoji."] emoji: ReactionType)
+    #[description = "A name for the cowboard, without spaces."] board: String,
+    #[description = "The channel to post messages to; can be set later."] channel: Option<ChannelId>)
 -> Result<(), Error> {
     let db = cowdb!(ctx);
 
     if let Some(guild_id) = ctx.guild_id() {
-        match db.get_cowboard_config(guild_id).await {
-            Ok(mut config) => {
-                config.emote = emoji.to_string();
-                if let Err(ex) = db.update_cowboard(&config).await {
-                    ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
-                    error!("Failed to update emote for cowboard: {}", ex);
-                } else {
-                    ctx.say("Successfully updated emote!").await?;
-                }
+        if board.is_empty() || board.len() > MAX_NAME_LENGTH || board.contains(char::is_whitespace) {
+            ctx.say(format!("Cowboard names can't have spaces, and can be at most {MAX_NAME_LENGTH} characters long.")).await?;
+            return Ok(());
+        }
+
+        if let Some(channel) = channel {
+            if !ctx.guild().map(|g| g.channels.contains_key(&channel)).unwrap_or(false) {
+                ctx.say("Could not find channel in this server!").await?;
+                return Ok(())
             }
+        }
+
+        let mut config = match db.get_cowboard_config(guild_id, &board).await {
+            Ok(config) if config.id != 0 => {
+                ctx.say(format!("There's already a cowboard called {}.", config.name)).await?;
+                return Ok(());
+            }
+            Ok(config) => config,
             Err(ex) => {
                 ctx.say("We couldn't get the cowboard settings... try again later?").await?;
                 error!("Failed to get cowboard: {}", ex);
+                return Ok(());
+            }
+        };
+        config.channel = channel.map(|o| o.0);
+
+        if let Err(ex) = db.update_cowboard(&config).await {
+            ctx.say("We couldn't create the cowboard, sorry... Try again later?").await?;
+            error!("Failed to create cowboard: {}", ex);
+        } else {
+            ctx.say(format!("Created the {board} cowboard! Pass its name to the other cowboard commands to set it up.")).await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized("en-US", "Set the emote reaction to trigger a cowboard message."),
+    required_permissions = "ADMINISTRATOR",
+    guild_only
+)]
+pub async fn emote(
+    ctx: CowContext<'_>,
+    #[description = "An emote on the server or a default Discord emoji."] emoji: ReactionType,
+    #[description = "Which cowboard; leave out if the server only has one."] board: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        if let Some(mut config) = find_board(ctx, &db, guild_id, board).await? {
+            config.emote = emoji.to_string();
+            if let Err(ex) = db.update_cowboard(&config).await {
+                ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+                error!("Failed to update emote for cowboard: {}", ex);
+            } else {
+                ctx.say("Successfully updated emote!").await?;
             }
         }
     } else {
@@ -90,7 +261,8 @@ pub async fn emote(
 )]
 pub async fn addthreshold(
     ctx: CowContext<'_>,
-    #[description = "A positive number, greater than the removal bound."] #[min = 1] add_threshold: i32)
+    #[description = "A positive number, greater than the removal bound."] #[min = 1] add_threshold: i32,
+    #[description = "Which cowboard; leave out if the server only has one."] board: Option<String>)
 -> Result<(), Error> {
     let db = cowdb!(ctx);
 
@@ -100,25 +272,19 @@ pub async fn addthreshold(
     }
 
     if let Some(guild_id) = ctx.guild_id() {
-        match db.get_cowboard_config(guild_id).await {
-            Ok(mut config) => {
-                if add_threshold < config.remove_threshold {
-                    ctx.say(format!("The minimum number of reactions required to add must be greater than or equal to the removal limit (currently set to {}).", config.remove_threshold)).await?;
-                    return Ok(())
-                }
+        if let Some(mut config) = find_board(ctx, &db, guild_id, board).await? {
+            if add_threshold < config.remove_threshold {
+                ctx.say(format!("The minimum number of reactions required to add must be greater than or equal to the removal limit (currently set to {}).", config.remove_threshold)).await?;
+                return Ok(())
+            }
 
-                config.add_threshold = add_threshold;
+            config.add_threshold = add_threshold;
 
-                if let Err(ex) = db.update_cowboard(&config).await {
-                    ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
-                    error!("Failed to update cowboard: {}", ex);
-                } else {
-                    ctx.say("Successfully updated minimum add threshold!").await?;
-                }
-            }
-            Err(ex) => {
-                ctx.say("We couldn't get the cowboard settings... try again later?").await?;
-                error!("Failed to get cowboard: {}", ex);
+            if let Err(ex) = db.update_cowboard(&config).await {
+                ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+                error!("Failed to update cowboard: {}", ex);
+            } else {
+                ctx.say("Successfully updated minimum add threshold!").await?;
             }
         }
     } else {
@@ -137,7 +303,8 @@ pub async fn addthreshold(
 )]
 pub async fn removethreshold(
     ctx: CowContext<'_>,
-    #[description = "A positive number, less than the addition bound."] #[min = 1] remove_threshold: i32)
+    #[description = "A positive number, less than the addition bound."] #[min = 1] remove_threshold: i32,
+    #[description = "Which cowboard; leave out if the server only has one."] board: Option<String>)
 -> Result<(), Error> {
     let db = cowdb!(ctx);
 
@@ -147,25 +314,19 @@ pub async fn removethreshold(
     }
 
     if let Some(guild_id) = ctx.guild_id() {
-        match db.get_cowboard_config(guild_id).await {
-            Ok(mut config) => {
-                if remove_threshold > config.add_threshold {
-                    ctx.say(format!("The maximum number of reactions required to remove must be less than or equal to the add limit (currently set to {}).", config.add_threshold)).await?;
-                    return Ok(())
-                }
+        if let Some(mut config) = find_board(ctx, &db, guild_id, board).await? {
+            if remove_threshold > config.add_threshold {
+                ctx.say(format!("The maximum number of reactions required to remove must be less than or equal to the add limit (currently set to {}).", config.add_threshold)).await?;
+                return Ok(())
+            }
 
-                config.remove_threshold = remove_threshold;
+            config.remove_threshold = remove_threshold;
 
-                if let Err(ex) = db.update_cowboard(&config).await {
-                    ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
-                    error!("Failed to update cowboard: {}", ex);
-                } else {
-                    ctx.say("Successfully updated maximum removal threshold!").await?;
-                }
-            }
-            Err(ex) => {
-                ctx.say("We couldn't get the cowboard settings... try again later?").await?;
-                error!("Failed to get cowboard: {}", ex);
+            if let Err(ex) = db.update_cowboard(&config).await {
+                ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+                error!("Failed to update cowboard: {}", ex);
+            } else {
+                ctx.say("Successfully updated maximum removal threshold!").await?;
             }
         }
     } else {
@@ -184,7 +345,8 @@ pub async fn removethreshold(
 )]
 pub async fn channel(
     ctx: CowContext<'_>,
-    #[description = "A channel to set the Cowboard channel to."] channel: Option<ChannelId>)
+    #[description = "A channel to set the Cowboard channel to."] channel: Option<ChannelId>,
+    #[description = "Which cowboard; leave out if the server only has one."] board: Option<String>)
 -> Result<(), Error> {
     let db = cowdb!(ctx);
 
@@ -196,22 +358,16 @@ pub async fn channel(
             return Ok(())
         }
 
-        match db.get_cowboard_config(guild_id).await {
-            Ok(mut config) => {
-                config.channel = Some(cowboard_channel.0);
-                config.webhook_id = None;
-                config.webhook_token = None;
-
-                if let Err(ex) = db.update_cowboard(&config).await {
-                    ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
-                    error!("Failed to update cowboard: {}", ex);
-                } else {
-                    ctx.say("Successfully updated channel! You may want to check webhooks; try using `.cowboard webhook` to enable it.").await?;
-                }
-            }
-            Err(ex) => {
-                ctx.say("We couldn't get the cowboard settings... try again later?").await?;
-                error!("Failed to get cowboard: {}", ex);
+        if let Some(mut config) = find_board(ctx, &db, guild_id, board).await? {
+            config.channel = Some(cowboard_channel.0);
+            config.webhook_id = None;
+            config.webhook_token = None;
+
+            if let Err(ex) = db.update_cowboard(&config).await {
+                ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+                error!("Failed to update cowboard: {}", ex);
+            } else {
+                ctx.say("Successfully updated channel! You may want to check webhooks; try using `.cowboard webhook` to enable it.").await?;
             }
         }
     } else {
@@ -229,62 +385,380 @@ pub async fn channel(
     required_permissions = "ADMINISTRATOR",
     discard_spare_arguments
 )]
-pub async fn webhook(ctx: CowContext<'_>) -> Result<(), Error> {
+pub async fn webhook(
+    ctx: CowContext<'_>,
+    #[description = "Which cowboard; leave out if the server only has one."] board: Option<String>)
+-> Result<(), Error> {
     let db = cowdb!(ctx);
 
     if let Some(guild) = ctx.guild() {
-        match db.get_cowboard_config(guild.id).await {
-            Ok(mut config) => {
-                if config.channel.is_none() {
-                    ctx.say("Cowboard channel is not set up!").await?;
-                    return Ok(());
-                }
+        if let Some(mut config) = find_board(ctx, &db, guild.id, board).await? {
+            if config.channel.is_none() {
+                ctx.say("Cowboard channel is not set up!").await?;
+                return Ok(());
+            }
 
-                let channel = ChannelId::from(config.channel.unwrap());
-                match guild.channels(&ctx).await {
-                    Ok(guild_channels) => {
-                        if let Some(guild_channel) = guild_channels.get(&channel)
-                        {
-                            if config.webhook_id.is_none() {
-                                match guild_channel.create_webhook(&ctx, "MooganCowboard").await {
-                                    Ok(webhook) => {
-                                        config.webhook_id = Some(webhook.id.0);
-                                        config.webhook_token = Some(webhook.token.unwrap())
-                                    }
-                                    Err(ex) => {
-                                        ctx.say(format!("Failed to add webhook; maybe I do not have permissions for the channel <#{guild_channel}>?")).await?;
-                                        error!("Failed to create webhook: {}", ex);
-                                        return Ok(())
-                                    }
-                                };
-                            } else {
-                                config.webhook_id = None;
-                                config.webhook_token = None;
-                            }
-
-                            if let Err(ex) = db.update_cowboard(&config).await {
-                                ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
-                                error!("Failed to update cowboard: {}", ex);
-                            } else if config.webhook_id.is_none() {
-                                ctx.say(format!("Disabled webhooks for <#{guild_channel}>.")).await?;
-                            } else {
-                                ctx.say(format!("Enabled webhooks for <#{guild_channel}>.")).await?;
-                            }
+            let channel = ChannelId::from(config.channel.unwrap());
+            match guild.channels(&ctx).await {
+                Ok(guild_channels) => {
+                    if let Some(guild_channel) = guild_channels.get(&channel)
+                    {
+                        if config.webhook_id.is_none() {
+                            match guild_channel.create_webhook(&ctx, "MooganCowboard").await {
+                                Ok(webhook) => {
+                                    config.webhook_id = Some(webhook.id.0);
+                                    config.webhook_token = Some(webhook.token.unwrap())
+                                }
+                                Err(ex) => {
+                                    ctx.say(format!("Failed to add webhook; maybe I do not have permissions for the channel <#{guild_channel}>?")).await?;
+                                    error!("Failed to create webhook: {}", ex);
+                                    return Ok(())
+                                }
+                            };
+                        } else {
+                            config.webhook_id = None;
+                            config.webhook_token = None;
                         }
-                        else
-                        {
-                            ctx.say(format!("We don't have access to <#{channel}>... maybe it's hidden for us?")).await?;
+
+                        if let Err(ex) = db.update_cowboard(&config).await {
+                            ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+                            error!("Failed to update cowboard: {}", ex);
+                        } else if config.webhook_id.is_none() {
+                            ctx.say(format!("Disabled webhooks for <#{guild_channel}>.")).await?;
+                        } else {
+                            ctx.say(format!("Enabled webhooks for <#{guild_channel}>.")).await?;
                         }
                     }
+                    else
+                    {
+                        ctx.say(format!("We don't have access to <#{channel}>... maybe it's hidden for us?")).await?;
+                    }
+                }
+                Err(ex) => {
+                    error!("Failed to get guild channels: {}", ex);
+                    ctx.say("We couldn't find the channels in this server, maybe we don't have permissions?").await?;
+                }
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Only take messages from some channels or categories for a cowboard, or never from others."),
+    required_permissions = "ADMINISTRATOR"
+)]
+pub async fn filter(
+    ctx: CowContext<'_>,
+    #[description = "The channel or category messages come from."] channel: ChannelId,
+    #[description = "\"allow\" to only take messages from allowed channels, \"deny\" to never take them, or \"clear\""] setting: String,
+    #[description = "Which cowboard; leave out if the server only has one."] board: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    let allowed = match parse_filter(&setting) {
+        Some(allowed) => allowed,
+        None => {
+            ctx.say("The setting must be \"allow\", \"deny\", or \"clear\".").await?;
+            return Ok(());
+        }
+    };
+
+    if let Some(guild_id) = ctx.guild_id() {
+        if !ctx.guild().map(|g| g.channels.contains_key(&channel)).unwrap_or(false) {
+            ctx.say("Could not find channel in this server!").await?;
+            return Ok(())
+        }
+
+        if let Some(mut config) = find_board(ctx, &db, guild_id, board).await? {
+            // The filters hang off the board, so it has to be saved first.
+            if config.id == 0 {
+                match db.update_cowboard(&config).await {
+                    Ok(id) => config.id = id,
                     Err(ex) => {
-                        error!("Failed to get guild channels: {}", ex);
-                        ctx.say("We couldn't find the channels in this server, maybe we don't have permissions?").await?;
+                        ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+                        error!("Failed to update cowboard: {}", ex);
+                        return Ok(());
                     }
                 }
             }
+
+            if let Err(ex) = db.set_cowboard_channel(config.id, channel, allowed).await {
+                ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+                error!("Failed to set cowboard channel filter: {}", ex);
+            } else {
+                let content = match allowed {
+                    Some(true) => format!("Messages from <#{channel}> can now go to the {} cowboard; channels that aren't allowed won't.", config.name),
+                    Some(false) => format!("Messages from <#{channel}> will never go to the {} cowboard.", config.name),
+                    None => format!("Cleared the filter for <#{channel}> on the {} cowboard.", config.name)
+                };
+                ctx.say(content).await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Delete a cowboard and forget everything posted to it."),
+    required_permissions = "ADMINISTRATOR",
+    discard_spare_arguments
+)]
+pub async fn delete(
+    ctx: CowContext<'_>,
+    #[description = "The name of the cowboard."] board: String)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        match db.remove_cowboard(guild_id, &board).await {
+            Ok(true) => { ctx.say(format!("Deleted the {board} cowboard. Anything already posted stays up.")).await?; }
+            Ok(false) => { ctx.say(format!("There's no cowboard called {board}.")).await?; }
             Err(ex) => {
-                error!("Failed to get cowboard: {}", ex);
-                ctx.say("We couldn't get the cowboard settings... try again later?").await?;
+                ctx.say("We couldn't delete the cowboard, sorry... Try again later?").await?;
+                error!("Failed to remove cowboard: {}", ex);
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Only take messages from some channels or categories for every cowboard, or never from others."),
+    required_permissions = "ADMINISTRATOR"
+)]
+pub async fn serverfilter(
+    ctx: CowContext<'_>,
+    #[description = "The channel or category messages come from."] channel: ChannelId,
+    #[description = "\"allow\" to only take messages from allowed channels, \"deny\" to never take them, or \"clear\""] setting: String)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    let allowed = match parse_filter(&setting) {
+        Some(allowed) => allowed,
+        None => {
+            ctx.say("The setting must be \"allow\", \"deny\", or \"clear\".").await?;
+            return Ok(());
+        }
+    };
+
+    if let Some(guild_id) = ctx.guild_id() {
+        if !ctx.guild().map(|g| g.channels.contains_key(&channel)).unwrap_or(false) {
+            ctx.say("Could not find channel in this server!").await?;
+            return Ok(())
+        }
+
+        if let Err(ex) = db.set_cowboard_filter_channel(guild_id, channel, allowed).await {
+            ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+            error!("Failed to set cowboard server filter: {}", ex);
+        } else {
+            let content = match allowed {
+                Some(true) => format!("Messages from <#{channel}> can now go to any cowboard; channels that aren't allowed won't."),
+                Some(false) => format!("Messages from <#{channel}> will never go to any cowboard."),
+                None => format!("Cleared the server filter for <#{channel}>.")
+            };
+            ctx.say(content).await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Choose whether NSFW or hidden channels' messages can go to cowboards that aren't; both are off by default."),
+    required_permissions = "ADMINISTRATOR"
+)]
+pub async fn safety(
+    ctx: CowContext<'_>,
+    #[description = "Let messages from NSFW channels go to cowboards that aren't NSFW"] nsfw: Option<bool>,
+    #[description = "Let messages go to cowboards that people who can't see the original channel can read"] hidden: Option<bool>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        let mut filter = db.get_cowboard_filter(guild_id).await?;
+        filter.allow_nsfw = nsfw.unwrap_or(filter.allow_nsfw);
+        filter.allow_hidden = hidden.unwrap_or(filter.allow_hidden);
+
+        if let Err(ex) = db.set_cowboard_filter(&filter).await {
+            ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+            error!("Failed to set cowboard safety settings: {}", ex);
+        } else {
+            ctx.say(format!("NSFW messages to any cowboard: {}\nHidden channels' messages to any cowboard: {}",
+                yes_no(filter.allow_nsfw), yes_no(filter.allow_hidden))).await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Choose whether deleting a message deletes its cowboard posts, or leaves them marked as deleted."),
+    required_permissions = "ADMINISTRATOR"
+)]
+pub async fn ondelete(
+    ctx: CowContext<'_>,
+    #[description = "\"delete\" to delete posts along with the original, or \"mark\" to keep them marked as deleted"] setting: String)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    let keep_deleted = match setting.to_lowercase().as_str() {
+        "delete" => false,
+        "mark" | "keep" => true,
+        _ => {
+            ctx.say("The setting must be \"delete\" or \"mark\".").await?;
+            return Ok(());
+        }
+    };
+
+    if let Some(guild_id) = ctx.guild_id() {
+        let mut filter = db.get_cowboard_filter(guild_id).await?;
+        filter.keep_deleted = keep_deleted;
+
+        if let Err(ex) = db.set_cowboard_filter(&filter).await {
+            ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+            error!("Failed to set cowboard delete setting: {}", ex);
+        } else {
+            ctx.say(format!("When an original message is deleted: {}.", describe_deletes(keep_deleted))).await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Choose whose reactions count towards every cowboard, and when a sudden pile-on gets held for review."),
+    required_permissions = "ADMINISTRATOR"
+)]
+pub async fn rules(
+    ctx: CowContext<'_>,
+    #[description = "Accounts younger than this many days don't count; 0 for any"] #[min = 0] account_days: Option<i32>,
+    #[description = "Members who joined less than this many hours ago don't count; 0 for anyone"] #[min = 0] member_hours: Option<i32>,
+    #[description = "Only members with this role count"] role: Option<RoleId>,
+    #[description = "Let everyone count again, whatever their roles"] clear_role: Option<bool>,
+    #[description = "Hold a message for review after this many reactions in a short time; 0 turns it off"] #[min = 0] burst_count: Option<i32>,
+    #[description = "How many minutes the burst has to come in within"] #[min = 1] burst_minutes: Option<i32>,
+    #[description = "Where held messages get pointed out to moderators"] review_channel: Option<ChannelId>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if account_days.unwrap_or(0) < 0 || member_hours.unwrap_or(0) < 0 || burst_count.unwrap_or(0) < 0 || burst_minutes.unwrap_or(1) < 1 {
+        ctx.say("The given numbers can't be negative, and bursts have to last at least a minute.").await?;
+        return Ok(());
+    }
+
+    if let Some(guild_id) = ctx.guild_id() {
+        if let Some(channel) = review_channel {
+            if !ctx.guild().map(|g| g.channels.contains_key(&channel)).unwrap_or(false) {
+                ctx.say("Could not find channel in this server!").await?;
+                return Ok(())
+            }
+        }
+
+        let mut rules = db.get_cowboard_rules(guild_id).await?;
+        rules.min_account_days = account_days.unwrap_or(rules.min_account_days);
+        rules.min_member_hours = member_hours.unwrap_or(rules.min_member_hours);
+        if clear_role.unwrap_or(false) {
+            rules.required_role = None;
+        } else if let Some(role) = role {
+            rules.required_role = Some(role.0);
+        }
+        rules.burst_count = burst_count.unwrap_or(rules.burst_count);
+        rules.burst_minutes = burst_minutes.unwrap_or(rules.burst_minutes);
+        rules.review_channel = review_channel.map(|o| o.0).or(rules.review_channel);
+
+        if let Err(ex) = db.set_cowboard_rules(&rules).await {
+            ctx.say("We couldn't update the cowboard, sorry... Try again later?").await?;
+            error!("Failed to set cowboard rules: {}", ex);
+        } else {
+            let mut content = format!("Reactions only count from accounts at least {} days old that have been in the server for {} hours{}.\nHolding bursts for review: {}",
+                rules.min_account_days, rules.min_member_hours,
+                rules.required_role.map(|o| format!(", with <@&{o}>")).unwrap_or_default(),
+                describe_bursts(&rules));
+            if rules.limits_reactions() {
+                content.push_str("\nBots' reactions don't count while any of these are set.");
+            }
+            if rules.burst_count > 0 && rules.review_channel.is_none() {
+                content.push_str("\nNothing will be held until there's a review channel to report it in.");
+            }
+            ctx.send(|m| m.content(content).allowed_mentions(|o| o.empty_parse())).await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Let a message held for review onto the cowboard, if it has enough reactions."),
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn approve(
+    ctx: CowContext<'_>,
+    #[description = "A link to the held message."] message: Message,
+    #[description = "Which cowboard; leave out if the server only has one."] board: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        // Held messages are only ever this server's, so anything else is someone pasting the wrong link.
+        if !member_can_read(ctx.serenity_context(), guild_id, message.channel_id, ctx.author().id).await {
+            ctx.say("That message isn't in a channel of this server that you can read.").await?;
+            return Ok(());
+        }
+
+        if let Some(mut config) = find_board(ctx, &db, guild_id, board).await? {
+            if !db.is_moo_flagged(config.id, message.id, message.channel_id, guild_id).await? {
+                ctx.say(format!("That message isn't being held for the {} cowboard.", config.name)).await?;
+                return Ok(());
+            }
+
+            db.unflag_moo(config.id, message.id, message.channel_id, guild_id).await?;
+            let rules = db.get_cowboard_rules(guild_id).await?;
+            match sync_moo(ctx.serenity_context(), guild_id, &message, &mut config, &rules).await {
+                Ok(MooChange::Posted) => { ctx.say(format!("Approved, and posted it to the {} cowboard.", config.name)).await?; }
+                Ok(MooChange::Failed) => { ctx.say("Approved, but we couldn't post it just yet; it'll go up with its next reaction.").await?; }
+                Ok(_) => { ctx.say("Approved; it'll be posted once it has enough reactions that count.").await?; }
+                Err(ex) => {
+                    ctx.say("Approved, but we couldn't post it just yet; it'll go up with its next reaction.").await?;
+                    error!("Failed to post approved cowboard message: {}", ex);
+                }
             }
         }
     } else {
@@ -292,4 +766,4 @@ pub async fn webhook(ctx: CowContext<'_>) -> Result<(), Error> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
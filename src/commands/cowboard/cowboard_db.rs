@@ -1,3 +1,5 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use serenity::{
     model::id::{
         GuildId,
@@ -9,115 +11,665 @@ use rust_decimal::{
     prelude::FromPrimitive
 };
 use rust_decimal::prelude::ToPrimitive;
-use serenity::model::id::MessageId;
+use serenity::model::id::{MessageId, UserId};
+use tiberius::Row;
 
-use crate::Database;
+use crate::Error;
+use crate::services::sql_server::SqlServerDatabase;
 use crate::commands::cowboard::cowboard_db_models::*;
 
+#[async_trait]
+pub trait CowboardStorage: Send + Sync {
+    // Every board on the server, oldest first.
+    async fn get_cowboards(&self, server_id: GuildId) -> Result<Vec<Cowboard>, Error>;
+
+    // The board with this name, or a fresh unsaved one if there isn't one yet.
+    async fn get_cowboard_config(&self, server_id: GuildId, name: &str) -> Result<Cowboard, Error>;
+
+    // Creates the board if there's none with its name yet; gives back its ID either way.
+    async fn update_cowboard(&self, config: &Cowboard) -> Result<i32, Error>;
+
+    async fn remove_cowboard(&self, server_id: GuildId, name: &str) -> Result<bool, Error>;
+
+    // Some(true) allows the channel, Some(false) denies it, and None takes it off both lists.
+    async fn set_cowboard_channel(&self, board_id: i32, channel: ChannelId, allowed: Option<bool>) -> Result<(), Error>;
+
+    // The server's filter, or the defaults if it's never been changed.
+    async fn get_cowboard_filter(&self, server_id: GuildId) -> Result<CowboardFilter, Error>;
+
+    // Only saves the flags; the channels go through set_cowboard_filter_channel.
+    async fn set_cowboard_filter(&self, filter: &CowboardFilter) -> Result<(), Error>;
+
+    // Same as set_cowboard_channel, but for every board on the server.
+    async fn set_cowboard_filter_channel(&self, server_id: GuildId, channel: ChannelId, allowed: Option<bool>) -> Result<(), Error>;
+
+    // The server's rules, or the defaults if they've never been changed.
+    async fn get_cowboard_rules(&self, server_id: GuildId) -> Result<CowboardRules, Error>;
+
+    async fn set_cowboard_rules(&self, rules: &CowboardRules) -> Result<(), Error>;
+
+    // Holds the message off the board for review; false if it already was.
+    async fn flag_moo(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, flagged_at: DateTime<Utc>) -> Result<bool, Error>;
+
+    async fn is_moo_flagged(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<bool, Error>;
+
+    async fn unflag_moo(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Error>;
+
+    // Some(true) if a moderator forced the message onto the board, Some(false) if they're keeping it off.
+    async fn get_moo_override(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<bool>, Error>;
+
+    // None forgets whatever was decided.
+    async fn set_moo_override(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, forced: Option<bool>) -> Result<(), Error>;
+
+    async fn is_cowboard_user_blocked(&self, board_id: i32, user: UserId) -> Result<bool, Error>;
+
+    async fn set_cowboard_user_blocked(&self, board_id: i32, user: UserId, blocked: bool) -> Result<(), Error>;
+
+    async fn log_cowboard_action(&self, action: &CowboardAction) -> Result<(), Error>;
+
+    // The latest `limit` actions on any of the server's boards, newest first.
+    async fn get_cowboard_actions(&self, server_id: GuildId, limit: i32) -> Result<Vec<CowboardAction>, Error>;
+
+    async fn get_cowboard_message(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, Error>;
+
+    // Wherever the message has been posted, on any board.
+    async fn get_cowboard_messages(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Vec<CowboardMessage>, Error>;
+
+    async fn moo_message(&self, post: &CowboardMessage) -> Result<(), Error>;
+
+    async fn set_moo_reactions(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, reactions: i32) -> Result<(), Error>;
+
+    async fn unmoo_message(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Error>;
+
+    // Totals and the top `limit` users and channels, on one board or (with None) all of them.
+    async fn get_cowboard_stats(&self, server_id: GuildId, board_id: Option<i32>, limit: i32) -> Result<CowboardStats, Error>;
+
+    async fn get_top_moos(&self, server_id: GuildId, board_id: Option<i32>, limit: i32) -> Result<Vec<CowboardMessage>, Error>;
+
+    // Any post, but always the same one for the same seed.
+    async fn get_random_moo(&self, server_id: GuildId, board_id: Option<i32>, seed: u64) -> Result<Option<CowboardMessage>, Error>;
+}
+
+const BOARD_COLUMNS: &str = "id, server_id, name, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token";
+const POST_COLUMNS: &str = "board_id, message_id, message_channel_id, post_id, post_channel_id, guild_id, author_id, reactions";
+
+fn board_from_row(row: &Row) -> Cowboard {
+    let server_id: Decimal = row.get(1).unwrap();
+    let name: &str = row.get(2).unwrap();
+    let channel_id: Option<Decimal> = row.get(3);
+    let emote_str: &str = row.get(6).unwrap();
+    let webhook_id: Option<Decimal> = row.get(7);
+    let webhook_token: Option<&str> = row.get(8);
+
+    Cowboard {
+        id: row.get(0).unwrap(),
+        server_id: server_id.to_u64().unwrap(),
+        name: name.to_string(),
+        channel: channel_id.and_then(|o| o.to_u64()),
+        add_threshold: row.get(4).unwrap(),
+        remove_threshold: row.get(5).unwrap(),
+        emote: emote_str.to_string(),
+        webhook_id: webhook_id.and_then(|o| o.to_u64()),
+        webhook_token: webhook_token.map(|o| o.to_string()),
+        allowed_channels: Vec::new(),
+        denied_channels: Vec::new()
+    }
+}
+
+// Sorts (board, channel, allowed) rows into each board's lists.
+fn fill_channels(boards: &mut [Cowboard], rows: &[Row]) {
+    for row in rows {
+        let board_id: i32 = row.get(0).unwrap();
+        let channel: Decimal = row.get(1).unwrap();
+        let allowed: bool = row.get(2).unwrap();
+        if let Some(board) = boards.iter_mut().find(|o| o.id == board_id) {
+            let list = if allowed { &mut board.allowed_channels } else { &mut board.denied_channels };
+            list.push(channel.to_u64().unwrap());
+        }
+    }
+}
+
+fn message_from_row(row: &Row) -> CowboardMessage {
+    let id = |i: usize| row.get(i).and_then(|u: Decimal| u.to_u64()).unwrap();
+    let author_id: Option<Decimal> = row.get(6);
+
+    CowboardMessage {
+        board_id: row.get(0).unwrap(),
+        message_id: id(1),
+        message_channel_id: id(2),
+        post_id: id(3),
+        post_channel_id: id(4),
+        guild_id: id(5),
+        author_id: author_id.and_then(|o| o.to_u64()),
+        reactions: row.get(7).unwrap()
+    }
+}
+
+fn ranking_from_row(row: &Row) -> CowboardRanking {
+    let id: Decimal = row.get(0).unwrap();
+
+    CowboardRanking {
+        id: id.to_u64().unwrap(),
+        posts: row.get(1).unwrap(),
+        reactions: row.get(2).unwrap()
+    }
+}
+
+fn action_from_row(row: &Row) -> Option<CowboardAction> {
+    let id = |i: usize| row.get(i).and_then(|u: Decimal| u.to_u64());
+    let action: u8 = row.get(2).unwrap();
+    let created_at: NaiveDateTime = row.get(6).unwrap();
+
+    Some(CowboardAction {
+        server_id: id(0).unwrap(),
+        board_id: row.get(1).unwrap(),
+        action: ModAction::try_from(action).ok()?,
+        target_id: id(3).unwrap(),
+        channel_id: id(4),
+        moderator_id: id(5).unwrap(),
+        created_at: Utc.from_utc_datetime(&created_at)
+    })
+}
+
 // Separating the database into different modules so it doesn't become a 2000 line file.
-impl Database {
-    pub async fn get_cowboard_config(&self, server_id: GuildId) -> Result<Cowboard, Box<dyn std::error::Error + Send + Sync>> {
+#[async_trait]
+impl CowboardStorage for SqlServerDatabase {
+    async fn get_cowboards(&self, server_id: GuildId) -> Result<Vec<Cowboard>, Error> {
         let mut conn = self.pool.get().await?;
         let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
         let res = conn.query(
-            "SELECT channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token FROM [Cowboard].[Server] WHERE id = @P1",
+            format!("SELECT {BOARD_COLUMNS} FROM [Cowboard].[Board] WHERE server_id = @P1 ORDER BY id; \
+            SELECT c.board_id, c.channel_id, c.allowed FROM [Cowboard].[BoardChannel] c \
+            JOIN [Cowboard].[Board] b ON b.id = c.board_id WHERE b.server_id = @P1"),
             &[&server])
             .await?
-            .into_row()
+            .into_results()
             .await?;
 
-        let mut out = Cowboard::new(server_id.0);
-
-        if let Some(item) = res {
-            let channel_id: Option<Decimal> = item.get(0);
-            let emote_str: &str = item.get(3).unwrap();
-            let webhook_id: Option<Decimal> = item.get(4);
-            let webhook_token: Option<&str> = item.get(5);
-            out = Cowboard {
-                id: server_id.0,
-                channel: channel_id.and_then(|o| o.to_u64()),
-                add_threshold: item.get(1).unwrap(),
-                remove_threshold: item.get(2).unwrap(),
-                emote: emote_str.to_string(),
-                webhook_id: webhook_id.and_then(|o| o.to_u64()),
-                webhook_token: webhook_token.map(|o| o.to_string())
-            };
-        }
+        let mut boards = res[0].iter().map(board_from_row).collect::<Vec<_>>();
+        fill_channels(&mut boards, &res[1]);
+
+        Ok(boards)
+    }
+
+    async fn get_cowboard_config(&self, server_id: GuildId, name: &str) -> Result<Cowboard, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            format!("SELECT {BOARD_COLUMNS} FROM [Cowboard].[Board] WHERE server_id = @P1 AND name = @P2; \
+            SELECT c.board_id, c.channel_id, c.allowed FROM [Cowboard].[BoardChannel] c \
+            JOIN [Cowboard].[Board] b ON b.id = c.board_id WHERE b.server_id = @P1 AND b.name = @P2"),
+            &[&server, &name])
+            .await?
+            .into_results()
+            .await?;
+
+        let mut boards = res[0].iter().map(board_from_row).collect::<Vec<_>>();
+        fill_channels(&mut boards, &res[1]);
 
-        Ok(out)
+        Ok(boards.pop().unwrap_or_else(|| Cowboard::new(server_id.0, name)))
     }
 
-    pub async fn update_cowboard(&self, config: &Cowboard) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn update_cowboard(&self, config: &Cowboard) -> Result<i32, Error> {
         let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(config.id).unwrap();
+        let server = Decimal::from_u64(config.server_id).unwrap();
         let channel = config.channel.map(|o| Decimal::from_u64(o).unwrap());
         let webhook_id = config.webhook_id.map(|o| Decimal::from_u64(o).unwrap());
 
-        conn.query(
-            "EXEC [Cowboard].[UpdateServer] @id = @P1, @channel = @P2, @add_threshold = @P3, @remove_threshold = @P4, @emote = @P5, @webhook_id = @P6, @webhook_token = @P7",
-            &[&server, &channel, &config.add_threshold, &config.remove_threshold, &config.emote, &webhook_id, &config.webhook_token])
+        let id = conn.query(
+            "IF EXISTS (SELECT 1 FROM [Cowboard].[Board] WHERE server_id = @P1 AND name = @P2) \
+                UPDATE [Cowboard].[Board] SET channel = @P3, add_threshold = @P4, remove_threshold = @P5, emote = @P6, webhook_id = @P7, webhook_token = @P8 \
+                OUTPUT inserted.id WHERE server_id = @P1 AND name = @P2 \
+            ELSE \
+                INSERT INTO [Cowboard].[Board] (server_id, name, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token) \
+                OUTPUT inserted.id VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8)",
+            &[&server, &config.name.as_str(), &channel, &config.add_threshold, &config.remove_threshold, &config.emote, &webhook_id, &config.webhook_token])
+            .await?
+            .into_row()
+            .await?
+            .and_then(|row| row.get(0))
+            .unwrap();
+
+        Ok(id)
+    }
+
+    async fn remove_cowboard(&self, server_id: GuildId, name: &str) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "DECLARE @removed TABLE (id INT); \
+            DELETE FROM [Cowboard].[Board] OUTPUT deleted.id INTO @removed WHERE server_id = @P1 AND name = @P2; \
+            DELETE FROM [Cowboard].[BoardChannel] WHERE board_id IN (SELECT id FROM @removed); \
+            DELETE FROM [Cowboard].[Post] WHERE board_id IN (SELECT id FROM @removed); \
+            DELETE FROM [Cowboard].[Review] WHERE board_id IN (SELECT id FROM @removed); \
+            DELETE FROM [Cowboard].[Override] WHERE board_id IN (SELECT id FROM @removed); \
+            DELETE FROM [Cowboard].[BlockedUser] WHERE board_id IN (SELECT id FROM @removed); \
+            SELECT COUNT(1) FROM @removed;",
+            &[&server, &name])
+            .await?
+            .into_row()
+            .await?;
+
+        let removed: i32 = res.and_then(|row| row.get(0)).unwrap_or(0);
+        Ok(removed > 0)
+    }
+
+    async fn set_cowboard_channel(&self, board_id: i32, channel: ChannelId, allowed: Option<bool>) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let channel = Decimal::from_u64(channel.0).unwrap();
+
+        match allowed {
+            Some(allowed) => conn.execute(
+                "IF EXISTS (SELECT 1 FROM [Cowboard].[BoardChannel] WHERE board_id = @P1 AND channel_id = @P2) \
+                    UPDATE [Cowboard].[BoardChannel] SET allowed = @P3 WHERE board_id = @P1 AND channel_id = @P2 \
+                ELSE \
+                    INSERT INTO [Cowboard].[BoardChannel] (board_id, channel_id, allowed) VALUES (@P1, @P2, @P3)",
+                &[&board_id, &channel, &allowed])
+                .await?,
+            None => conn.execute(
+                "DELETE FROM [Cowboard].[BoardChannel] WHERE board_id = @P1 AND channel_id = @P2",
+                &[&board_id, &channel])
+                .await?
+        };
+
+        Ok(())
+    }
+
+    async fn get_cowboard_filter(&self, server_id: GuildId) -> Result<CowboardFilter, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT allow_nsfw, allow_hidden, keep_deleted FROM [Cowboard].[Filter] WHERE server_id = @P1; \
+            SELECT channel_id, allowed FROM [Cowboard].[FilterChannel] WHERE server_id = @P1",
+            &[&server])
+            .await?
+            .into_results()
+            .await?;
+
+        let mut filter = CowboardFilter::new(server_id.0);
+        if let Some(row) = res[0].first() {
+            filter.allow_nsfw = row.get(0).unwrap();
+            filter.allow_hidden = row.get(1).unwrap();
+            filter.keep_deleted = row.get(2).unwrap();
+        }
+        for row in &res[1] {
+            let channel: Decimal = row.get(0).unwrap();
+            let allowed: bool = row.get(1).unwrap();
+            let list = if allowed { &mut filter.allowed_channels } else { &mut filter.denied_channels };
+            list.push(channel.to_u64().unwrap());
+        }
+
+        Ok(filter)
+    }
+
+    async fn set_cowboard_filter(&self, filter: &CowboardFilter) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(filter.server_id).unwrap();
+
+        conn.execute(
+            "IF EXISTS (SELECT 1 FROM [Cowboard].[Filter] WHERE server_id = @P1) \
+                UPDATE [Cowboard].[Filter] SET allow_nsfw = @P2, allow_hidden = @P3, keep_deleted = @P4 WHERE server_id = @P1 \
+            ELSE \
+                INSERT INTO [Cowboard].[Filter] (server_id, allow_nsfw, allow_hidden, keep_deleted) VALUES (@P1, @P2, @P3, @P4)",
+            &[&server, &filter.allow_nsfw, &filter.allow_hidden, &filter.keep_deleted])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_cowboard_filter_channel(&self, server_id: GuildId, channel: ChannelId, allowed: Option<bool>) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(server_id.0).unwrap();
+        let channel = Decimal::from_u64(channel.0).unwrap();
+
+        match allowed {
+            Some(allowed) => conn.execute(
+                "IF EXISTS (SELECT 1 FROM [Cowboard].[FilterChannel] WHERE server_id = @P1 AND channel_id = @P2) \
+                    UPDATE [Cowboard].[FilterChannel] SET allowed = @P3 WHERE server_id = @P1 AND channel_id = @P2 \
+                ELSE \
+                    INSERT INTO [Cowboard].[FilterChannel] (server_id, channel_id, allowed) VALUES (@P1, @P2, @P3)",
+                &[&server, &channel, &allowed])
+                .await?,
+            None => conn.execute(
+                "DELETE FROM [Cowboard].[FilterChannel] WHERE server_id = @P1 AND channel_id = @P2",
+                &[&server, &channel])
+                .await?
+        };
+
+        Ok(())
+    }
+
+    async fn get_cowboard_rules(&self, server_id: GuildId) -> Result<CowboardRules, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT min_account_days, min_member_hours, required_role, burst_count, burst_minutes, review_channel FROM [Cowboard].[Rules] WHERE server_id = @P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut rules = CowboardRules::new(server_id.0);
+        if let Some(row) = res {
+            let required_role: Option<Decimal> = row.get(2);
+            let review_channel: Option<Decimal> = row.get(5);
+            rules.min_account_days = row.get(0).unwrap();
+            rules.min_member_hours = row.get(1).unwrap();
+            rules.required_role = required_role.and_then(|o| o.to_u64());
+            rules.burst_count = row.get(3).unwrap();
+            rules.burst_minutes = row.get(4).unwrap();
+            rules.review_channel = review_channel.and_then(|o| o.to_u64());
+        }
+
+        Ok(rules)
+    }
+
+    async fn set_cowboard_rules(&self, rules: &CowboardRules) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(rules.server_id).unwrap();
+        let required_role = rules.required_role.map(|o| Decimal::from_u64(o).unwrap());
+        let review_channel = rules.review_channel.map(|o| Decimal::from_u64(o).unwrap());
+
+        conn.execute(
+            "IF EXISTS (SELECT 1 FROM [Cowboard].[Rules] WHERE server_id = @P1) \
+                UPDATE [Cowboard].[Rules] SET min_account_days = @P2, min_member_hours = @P3, required_role = @P4, burst_count = @P5, burst_minutes = @P6, review_channel = @P7 \
+                WHERE server_id = @P1 \
+            ELSE \
+                INSERT INTO [Cowboard].[Rules] (server_id, min_account_days, min_member_hours, required_role, burst_count, burst_minutes, review_channel) \
+                VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7)",
+            &[&server, &rules.min_account_days, &rules.min_member_hours, &required_role, &rules.burst_count, &rules.burst_minutes, &review_channel])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn flag_moo(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, flagged_at: DateTime<Utc>) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let message = Decimal::from_u64(message.0).unwrap();
+        let channel = Decimal::from_u64(channel.0).unwrap();
+        let server = Decimal::from_u64(guild.0).unwrap();
+        let flagged_at = flagged_at.naive_utc();
+
+        let res = conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Cowboard].[Review] WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4) \
+                INSERT INTO [Cowboard].[Review] (board_id, message_id, message_channel_id, guild_id, flagged_at) VALUES (@P1, @P2, @P3, @P4, @P5)",
+            &[&board_id, &message, &channel, &server, &flagged_at])
+            .await?;
+
+        Ok(res.total() > 0)
+    }
+
+    async fn is_moo_flagged(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let message = Decimal::from_u64(message.0).unwrap();
+        let channel = Decimal::from_u64(channel.0).unwrap();
+        let server = Decimal::from_u64(guild.0).unwrap();
+        let res = conn.query(
+            "SELECT 1 FROM [Cowboard].[Review] WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4",
+            &[&board_id, &message, &channel, &server])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.is_some())
+    }
+
+    async fn unflag_moo(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let message = Decimal::from_u64(message.0).unwrap();
+        let channel = Decimal::from_u64(channel.0).unwrap();
+        let server = Decimal::from_u64(guild.0).unwrap();
+
+        conn.execute(
+            "DELETE FROM [Cowboard].[Review] WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4",
+            &[&board_id, &message, &channel, &server])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_moo_override(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<bool>, Error> {
+        let mut conn = self.pool.get().await?;
+        let message = Decimal::from_u64(message.0).unwrap();
+        let channel = Decimal::from_u64(channel.0).unwrap();
+        let server = Decimal::from_u64(guild.0).unwrap();
+        let res = conn.query(
+            "SELECT forced FROM [Cowboard].[Override] WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4",
+            &[&board_id, &message, &channel, &server])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.and_then(|row| row.get(0)))
+    }
+
+    async fn set_moo_override(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, forced: Option<bool>) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let message = Decimal::from_u64(message.0).unwrap();
+        let channel = Decimal::from_u64(channel.0).unwrap();
+        let server = Decimal::from_u64(guild.0).unwrap();
+
+        match forced {
+            Some(forced) => conn.execute(
+                "IF EXISTS (SELECT 1 FROM [Cowboard].[Override] WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4) \
+                    UPDATE [Cowboard].[Override] SET forced = @P5 WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4 \
+                ELSE \
+                    INSERT INTO [Cowboard].[Override] (board_id, message_id, message_channel_id, guild_id, forced) VALUES (@P1, @P2, @P3, @P4, @P5)",
+                &[&board_id, &message, &channel, &server, &forced])
+                .await?,
+            None => conn.execute(
+                "DELETE FROM [Cowboard].[Override] WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4",
+                &[&board_id, &message, &channel, &server])
+                .await?
+        };
+
+        Ok(())
+    }
+
+    async fn is_cowboard_user_blocked(&self, board_id: i32, user: UserId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let user = Decimal::from_u64(user.0).unwrap();
+        let res = conn.query(
+            "SELECT 1 FROM [Cowboard].[BlockedUser] WHERE board_id = @P1 AND user_id = @P2",
+            &[&board_id, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.is_some())
+    }
+
+    async fn set_cowboard_user_blocked(&self, board_id: i32, user: UserId, blocked: bool) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let user = Decimal::from_u64(user.0).unwrap();
+
+        if blocked {
+            conn.execute(
+                "IF NOT EXISTS (SELECT 1 FROM [Cowboard].[BlockedUser] WHERE board_id = @P1 AND user_id = @P2) \
+                    INSERT INTO [Cowboard].[BlockedUser] (board_id, user_id) VALUES (@P1, @P2)",
+                &[&board_id, &user])
+                .await?;
+        } else {
+            conn.execute(
+                "DELETE FROM [Cowboard].[BlockedUser] WHERE board_id = @P1 AND user_id = @P2",
+                &[&board_id, &user])
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn log_cowboard_action(&self, action: &CowboardAction) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(action.server_id).unwrap();
+        let target = Decimal::from_u64(action.target_id).unwrap();
+        let channel = action.channel_id.map(|o| Decimal::from_u64(o).unwrap());
+        let moderator = Decimal::from_u64(action.moderator_id).unwrap();
+        let created_at = action.created_at.naive_utc();
+
+        conn.execute(
+            "INSERT INTO [Cowboard].[Action] (server_id, board_id, action, target_id, channel_id, moderator_id, created_at) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7)",
+            &[&server, &action.board_id, &(action.action as u8), &target, &channel, &moderator, &created_at])
             .await?;
 
         Ok(())
     }
 
-    pub async fn get_cowboard_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_cowboard_actions(&self, server_id: GuildId, limit: i32) -> Result<Vec<CowboardAction>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(server_id.0).unwrap();
+        let res = conn.query(
+            "SELECT TOP (@P2) server_id, board_id, action, target_id, channel_id, moderator_id, created_at FROM [Cowboard].[Action] \
+            WHERE server_id = @P1 ORDER BY id DESC",
+            &[&server, &limit])
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.iter().filter_map(action_from_row).collect())
+    }
+
+    async fn get_cowboard_message(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, Error> {
         let mut conn = self.pool.get().await?;
         let message_decimal = Decimal::from_u64(message.0).unwrap();
         let channel_decimal = Decimal::from_u64(channel.0).unwrap();
         let server_decimal = Decimal::from_u64(guild.0).unwrap();
         let res = conn.query(
-            "SELECT post_id, post_channel_id FROM [Cowboard].[Message] WHERE message_id = @P1 AND message_channel_id = @P2 AND guild_id = @P3",
-            &[&message_decimal, &channel_decimal, &server_decimal])
+            format!("SELECT {POST_COLUMNS} FROM [Cowboard].[Post] \
+            WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4"),
+            &[&board_id, &message_decimal, &channel_decimal, &server_decimal])
             .await?
             .into_row()
             .await?;
 
-        let mut out: Option<CowboardMessage> = None;
+        Ok(res.map(|row| message_from_row(&row)))
+    }
 
-        if let Some(item) = res {
-            let post_id = item.get(0).and_then(|u: Decimal| u.to_u64()).unwrap();
-            let post_channel_id = item.get(1).and_then(|u: Decimal| u.to_u64()).unwrap();
+    async fn get_cowboard_messages(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Vec<CowboardMessage>, Error> {
+        let mut conn = self.pool.get().await?;
+        let message_decimal = Decimal::from_u64(message.0).unwrap();
+        let channel_decimal = Decimal::from_u64(channel.0).unwrap();
+        let server_decimal = Decimal::from_u64(guild.0).unwrap();
+        let res = conn.query(
+            format!("SELECT {POST_COLUMNS} FROM [Cowboard].[Post] \
+            WHERE message_id = @P1 AND message_channel_id = @P2 AND guild_id = @P3"),
+            &[&message_decimal, &channel_decimal, &server_decimal])
+            .await?
+            .into_first_result()
+            .await?;
 
-            out = Some(CowboardMessage {
-                message_id: message.0,
-                message_channel_id: channel.0,
-                post_id,
-                post_channel_id,
-                guild_id: guild.0
-            });
-        }
+        Ok(res.iter().map(message_from_row).collect())
+    }
+
+    async fn moo_message(&self, post: &CowboardMessage) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let message = Decimal::from_u64(post.message_id).unwrap();
+        let channel = Decimal::from_u64(post.message_channel_id).unwrap();
+        let post_message = Decimal::from_u64(post.post_id).unwrap();
+        let post_channel = Decimal::from_u64(post.post_channel_id).unwrap();
+        let server = Decimal::from_u64(post.guild_id).unwrap();
+        let author = post.author_id.map(|o| Decimal::from_u64(o).unwrap());
+
+        conn.execute(
+            format!("INSERT INTO [Cowboard].[Post] ({POST_COLUMNS}) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8)"),
+            &[&post.board_id, &message, &channel, &post_message, &post_channel, &server, &author, &post.reactions])
+            .await?;
 
-        Ok(out)
+        Ok(())
     }
 
-    pub async fn moo_message(&self, message: MessageId, channel: ChannelId, post_message: MessageId, post_channel: ChannelId, guild: GuildId) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn set_moo_reactions(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, reactions: i32) -> Result<(), Error> {
         let mut conn = self.pool.get().await?;
         let message = Decimal::from_u64(message.0).unwrap();
         let channel = Decimal::from_u64(channel.0).unwrap();
-        let post_message = Decimal::from_u64(post_message.0).unwrap();
-        let post_channel = Decimal::from_u64(post_channel.0).unwrap();
         let server = Decimal::from_u64(guild.0).unwrap();
 
-        conn.query(
-            "INSERT INTO [Cowboard].[Message] (message_id, message_channel_id, post_id, post_channel_id, guild_id) VALUES (@P1, @P2, @P3, @P4, @P5)",
-            &[&message, &channel, &post_message, &post_channel, &server])
+        conn.execute(
+            "UPDATE [Cowboard].[Post] SET reactions = @P5 WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4",
+            &[&board_id, &message, &channel, &server, &reactions])
             .await?;
 
         Ok(())
     }
 
-    pub async fn unmoo_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn unmoo_message(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Error> {
         let mut conn = self.pool.get().await?;
         let message = Decimal::from_u64(message.0).unwrap();
         let channel = Decimal::from_u64(channel.0).unwrap();
         let server = Decimal::from_u64(guild.0).unwrap();
 
         conn.query(
-            "DELETE FROM [Cowboard].[Message] WHERE message_id = @P1 AND message_channel_id = @P2 AND guild_id = @P3",
-            &[&message, &channel, &server])
+            "DELETE FROM [Cowboard].[Post] WHERE board_id = @P1 AND message_id = @P2 AND message_channel_id = @P3 AND guild_id = @P4",
+            &[&board_id, &message, &channel, &server])
             .await?;
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn get_cowboard_stats(&self, server_id: GuildId, board_id: Option<i32>, limit: i32) -> Result<CowboardStats, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(server_id.0).unwrap();
+        let res = conn.query(
+            "SELECT COUNT_BIG(1), COALESCE(SUM(CAST(reactions AS BIGINT)), 0) FROM [Cowboard].[Post] \
+            WHERE guild_id = @P1 AND (@P2 IS NULL OR board_id = @P2); \
+            SELECT TOP (@P3) author_id, COUNT_BIG(1) AS posts, SUM(CAST(reactions AS BIGINT)) AS reactions FROM [Cowboard].[Post] \
+            WHERE guild_id = @P1 AND (@P2 IS NULL OR board_id = @P2) AND author_id IS NOT NULL \
+            GROUP BY author_id ORDER BY posts DESC, reactions DESC; \
+            SELECT TOP (@P3) message_channel_id, COUNT_BIG(1) AS posts, SUM(CAST(reactions AS BIGINT)) AS reactions FROM [Cowboard].[Post] \
+            WHERE guild_id = @P1 AND (@P2 IS NULL OR board_id = @P2) \
+            GROUP BY message_channel_id ORDER BY posts DESC, reactions DESC",
+            &[&server, &board_id, &limit])
+            .await?
+            .into_results()
+            .await?;
+
+        let totals = &res[0][0];
+        Ok(CowboardStats {
+            posts: totals.get(0).unwrap(),
+            reactions: totals.get(1).unwrap(),
+            users: res[1].iter().map(ranking_from_row).collect(),
+            channels: res[2].iter().map(ranking_from_row).collect()
+        })
+    }
+
+    async fn get_top_moos(&self, server_id: GuildId, board_id: Option<i32>, limit: i32) -> Result<Vec<CowboardMessage>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(server_id.0).unwrap();
+        let res = conn.query(
+            format!("SELECT TOP (@P3) {POST_COLUMNS} FROM [Cowboard].[Post] WHERE guild_id = @P1 AND (@P2 IS NULL OR board_id = @P2) \
+            ORDER BY reactions DESC, message_id"),
+            &[&server, &board_id, &limit])
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.iter().map(message_from_row).collect())
+    }
+
+    async fn get_random_moo(&self, server_id: GuildId, board_id: Option<i32>, seed: u64) -> Result<Option<CowboardMessage>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(server_id.0).unwrap();
+        let count: i64 = conn.query(
+            "SELECT COUNT_BIG(1) FROM [Cowboard].[Post] WHERE guild_id = @P1 AND (@P2 IS NULL OR board_id = @P2)",
+            &[&server, &board_id])
+            .await?
+            .into_row()
+            .await?
+            .and_then(|row| row.get(0))
+            .unwrap_or(0);
+        if count == 0 {
+            return Ok(None);
+        }
+
+        let offset = (seed % count as u64) as i64;
+        let res = conn.query(
+            format!("SELECT {POST_COLUMNS} FROM [Cowboard].[Post] WHERE guild_id = @P1 AND (@P2 IS NULL OR board_id = @P2) \
+            ORDER BY board_id, message_id OFFSET @P3 ROWS FETCH NEXT 1 ROWS ONLY"),
+            &[&server, &board_id, &offset])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.map(|row| message_from_row(&row)))
+    }
+}
@@ -1,31 +1,199 @@
+use std::fmt::{Display, Formatter};
+use chrono::{DateTime, Utc};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+
+// What a server's board is called when nobody's named one; the old single board became this.
+pub const DEFAULT_BOARD: &str = "cow";
+
+// `lineage` is the message's channel, then whatever it's nested under (see channel_lineage), so a category counts for everything in it.
+// If anything's allowed, only messages from under those count; anything denied never does.
+fn channel_allowed(allowed: &[u64], denied: &[u64], lineage: &[u64]) -> bool {
+    !lineage.iter().any(|o| denied.contains(o)) && (allowed.is_empty() || lineage.iter().any(|o| allowed.contains(o)))
+}
+
 pub struct Cowboard {
-    pub id: u64,
+    // Zero until it's been saved.
+    pub id: i32,
+    pub server_id: u64,
+    pub name: String,
     pub channel: Option<u64>,
     pub add_threshold: i32,
     pub remove_threshold: i32,
     pub emote: String,
     pub webhook_id: Option<u64>,
-    pub webhook_token: Option<String>
+    pub webhook_token: Option<String>,
+    // Channels or categories; see channel_allowed.
+    pub allowed_channels: Vec<u64>,
+    pub denied_channels: Vec<u64>
 }
 
 impl Cowboard {
-    pub fn new(id: u64) -> Self {
+    pub fn new(server_id: u64, name: &str) -> Self {
         Cowboard {
-            id,
+            id: 0,
+            server_id,
+            name: name.to_string(),
             channel: None,
             add_threshold: 5,
             remove_threshold: 4,
             emote: "🐮".to_string(),
             webhook_id: None,
-            webhook_token: None
+            webhook_token: None,
+            allowed_channels: Vec::new(),
+            denied_channels: Vec::new()
+        }
+    }
+
+    pub fn accepts(&self, lineage: &[u64]) -> bool {
+        channel_allowed(&self.allowed_channels, &self.denied_channels, lineage)
+    }
+}
+
+// Applies to every board on the server, before their own filters.
+pub struct CowboardFilter {
+    pub server_id: u64,
+    // Let messages from NSFW channels onto boards in channels that aren't.
+    pub allow_nsfw: bool,
+    // Let messages onto boards that people who can't see the original channel can read.
+    pub allow_hidden: bool,
+    // Leave posts up, marked as deleted, when the original message is deleted.
+    pub keep_deleted: bool,
+    pub allowed_channels: Vec<u64>,
+    pub denied_channels: Vec<u64>
+}
+
+impl CowboardFilter {
+    pub fn new(server_id: u64) -> Self {
+        CowboardFilter {
+            server_id,
+            allow_nsfw: false,
+            allow_hidden: false,
+            keep_deleted: false,
+            allowed_channels: Vec::new(),
+            denied_channels: Vec::new()
         }
     }
+
+    pub fn accepts(&self, lineage: &[u64]) -> bool {
+        channel_allowed(&self.allowed_channels, &self.denied_channels, lineage)
+    }
+}
+
+// Which reactions count towards any board on the server; the message's author and bots never do.
+pub struct CowboardRules {
+    pub server_id: u64,
+    // 0 for no limit on either.
+    pub min_account_days: i32,
+    pub min_member_hours: i32,
+    pub required_role: Option<u64>,
+    // This many reactions within burst_minutes holds the message back for a moderator instead; 0 turns it off.
+    pub burst_count: i32,
+    pub burst_minutes: i32,
+    // Where held messages get pointed out; nothing's held without one.
+    pub review_channel: Option<u64>
+}
+
+impl CowboardRules {
+    pub fn new(server_id: u64) -> Self {
+        CowboardRules {
+            server_id,
+            min_account_days: 0,
+            min_member_hours: 0,
+            required_role: None,
+            burst_count: 0,
+            burst_minutes: 10,
+            review_channel: None
+        }
+    }
+
+    // Whether any rule leaves reactions out; bots' only count while none do.
+    pub fn limits_reactions(&self) -> bool {
+        self.min_account_days > 0 || self.checks_members()
+    }
+
+    // Whether counting reactions means looking up each member, rather than just their account.
+    pub fn checks_members(&self) -> bool {
+        self.min_member_hours > 0 || self.required_role.is_some()
+    }
+
+    pub fn holds_bursts(&self) -> bool {
+        self.burst_count > 0 && self.review_channel.is_some()
+    }
+}
+
+// Something a moderator did to a board, saved as a number.
+#[derive(Copy, Clone, PartialEq, Eq, FromPrimitive)]
+pub enum ModAction {
+    ForcePost = 0,
+    Remove = 1,
+    BlockUser = 2,
+    UnblockUser = 3,
+    BlockChannel = 4,
+    UnblockChannel = 5
+}
+
+impl Display for ModAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModAction::ForcePost => write!(f, "Force posted"),
+            ModAction::Remove => write!(f, "Removed"),
+            ModAction::BlockUser => write!(f, "Blocked author"),
+            ModAction::UnblockUser => write!(f, "Unblocked author"),
+            ModAction::BlockChannel => write!(f, "Blocked channel"),
+            ModAction::UnblockChannel => write!(f, "Unblocked channel")
+        }
+    }
+}
+
+impl TryFrom<u8> for ModAction {
+    type Error = ();
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        FromPrimitive::from_u8(v).ok_or(())
+    }
+}
+
+pub struct CowboardAction {
+    pub server_id: u64,
+    pub board_id: i32,
+    pub action: ModAction,
+    // A message, user or channel, depending on the action.
+    pub target_id: u64,
+    // Where the message was, for the actions on messages.
+    pub channel_id: Option<u64>,
+    pub moderator_id: u64,
+    pub created_at: DateTime<Utc>
 }
 
 pub struct CowboardMessage {
+    pub board_id: i32,
     pub message_id: u64,
     pub message_channel_id: u64,
     pub post_id: u64,
     pub post_channel_id: u64,
-    pub guild_id: u64
-}
\ No newline at end of file
+    pub guild_id: u64,
+    // None for posts from before authors were kept.
+    pub author_id: Option<u64>,
+    // As of the last time the post was updated.
+    pub reactions: i32
+}
+
+impl CowboardMessage {
+    pub fn link(&self) -> String {
+        format!("https://discord.com/channels/{}/{}/{}", self.guild_id, self.message_channel_id, self.message_id)
+    }
+}
+
+// A user or channel, by how many of their messages made it onto a cowboard.
+pub struct CowboardRanking {
+    pub id: u64,
+    pub posts: i64,
+    pub reactions: i64
+}
+
+pub struct CowboardStats {
+    pub posts: i64,
+    pub reactions: i64,
+    pub users: Vec<CowboardRanking>,
+    pub channels: Vec<CowboardRanking>
+}
@@ -0,0 +1,557 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serenity::model::id::{GuildId, ChannelId, MessageId, UserId};
+
+use crate::Error;
+use crate::services::sqlite::{SqliteDatabase, to_sql_id, from_sql_id};
+use crate::commands::cowboard::cowboard_db::CowboardStorage;
+use crate::commands::cowboard::cowboard_db_models::*;
+
+const BOARD_COLUMNS: &str = "id, server_id, name, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token";
+const POST_COLUMNS: &str = "board_id, message_id, message_channel_id, post_id, post_channel_id, guild_id, author_id, reactions";
+
+fn board_from_row(row: &rusqlite::Row) -> Result<Cowboard, rusqlite::Error> {
+    let channel: Option<i64> = row.get(3)?;
+    let webhook_id: Option<i64> = row.get(7)?;
+
+    Ok(Cowboard {
+        id: row.get(0)?,
+        server_id: from_sql_id(row.get(1)?),
+        name: row.get(2)?,
+        channel: channel.map(from_sql_id),
+        add_threshold: row.get(4)?,
+        remove_threshold: row.get(5)?,
+        emote: row.get(6)?,
+        webhook_id: webhook_id.map(from_sql_id),
+        webhook_token: row.get(8)?,
+        allowed_channels: Vec::new(),
+        denied_channels: Vec::new()
+    })
+}
+
+fn message_from_row(row: &rusqlite::Row) -> Result<CowboardMessage, rusqlite::Error> {
+    let author_id: Option<i64> = row.get(6)?;
+
+    Ok(CowboardMessage {
+        board_id: row.get(0)?,
+        message_id: from_sql_id(row.get(1)?),
+        message_channel_id: from_sql_id(row.get(2)?),
+        post_id: from_sql_id(row.get(3)?),
+        post_channel_id: from_sql_id(row.get(4)?),
+        guild_id: from_sql_id(row.get(5)?),
+        author_id: author_id.map(from_sql_id),
+        reactions: row.get(7)?
+    })
+}
+
+fn ranking_from_row(row: &rusqlite::Row) -> Result<CowboardRanking, rusqlite::Error> {
+    Ok(CowboardRanking {
+        id: from_sql_id(row.get(0)?),
+        posts: row.get(1)?,
+        reactions: row.get(2)?
+    })
+}
+
+fn action_from_row(row: &rusqlite::Row) -> Result<Option<CowboardAction>, rusqlite::Error> {
+    let action: u8 = row.get(2)?;
+    let action = match ModAction::try_from(action) {
+        Ok(action) => action,
+        // Something newer wrote it; nothing to show.
+        Err(_) => return Ok(None)
+    };
+    let channel_id: Option<i64> = row.get(4)?;
+    let created_at: i64 = row.get(6)?;
+
+    Ok(Some(CowboardAction {
+        server_id: from_sql_id(row.get(0)?),
+        board_id: row.get(1)?,
+        action,
+        target_id: from_sql_id(row.get(3)?),
+        channel_id: channel_id.map(from_sql_id),
+        moderator_id: from_sql_id(row.get(5)?),
+        created_at: Utc.timestamp_millis_opt(created_at).unwrap()
+    }))
+}
+
+// Fills in each board's allow and deny lists.
+fn fill_channels(conn: &Connection, boards: &mut [Cowboard]) -> Result<(), rusqlite::Error> {
+    let mut statement = conn.prepare("SELECT channel_id, allowed FROM cowboard_board_channel WHERE board_id = ?1")?;
+    for board in boards {
+        let channels = statement
+            .query_map(params![board.id], |row| Ok((from_sql_id(row.get(0)?), row.get::<_, bool>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (channel, allowed) in channels {
+            if allowed { board.allowed_channels.push(channel) } else { board.denied_channels.push(channel) }
+        }
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl CowboardStorage for SqliteDatabase {
+    async fn get_cowboards(&self, server_id: GuildId) -> Result<Vec<Cowboard>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut boards = conn.prepare(&format!("SELECT {BOARD_COLUMNS} FROM cowboard_board WHERE server_id = ?1 ORDER BY id"))?
+                .query_map(params![server], board_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            fill_channels(conn, &mut boards)?;
+
+            Ok(boards)
+        }).await
+    }
+
+    async fn get_cowboard_config(&self, server_id: GuildId, name: &str) -> Result<Cowboard, Error> {
+        let server = to_sql_id(server_id.0);
+        let name = name.to_string();
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                &format!("SELECT {BOARD_COLUMNS} FROM cowboard_board WHERE server_id = ?1 AND name = ?2"),
+                params![server, name],
+                board_from_row)
+                .optional()?;
+
+            let mut boards = res.into_iter().collect::<Vec<_>>();
+            fill_channels(conn, &mut boards)?;
+
+            Ok(boards.pop().unwrap_or_else(|| Cowboard::new(server_id.0, &name)))
+        }).await
+    }
+
+    async fn update_cowboard(&self, config: &Cowboard) -> Result<i32, Error> {
+        let server = to_sql_id(config.server_id);
+        let name = config.name.clone();
+        let channel = config.channel.map(to_sql_id);
+        let add_threshold = config.add_threshold;
+        let remove_threshold = config.remove_threshold;
+        let emote = config.emote.clone();
+        let webhook_id = config.webhook_id.map(to_sql_id);
+        let webhook_token = config.webhook_token.clone();
+
+        self.call(move |conn| {
+            conn.query_row(
+                "INSERT INTO cowboard_board (server_id, name, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+                ON CONFLICT (server_id, name) DO UPDATE SET channel = excluded.channel, add_threshold = excluded.add_threshold, remove_threshold = excluded.remove_threshold, \
+                emote = excluded.emote, webhook_id = excluded.webhook_id, webhook_token = excluded.webhook_token \
+                RETURNING id",
+                params![server, name, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token],
+                |row| row.get(0))
+        }).await
+    }
+
+    async fn remove_cowboard(&self, server_id: GuildId, name: &str) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+        let name = name.to_string();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            let id: Option<i32> = tx.query_row(
+                "DELETE FROM cowboard_board WHERE server_id = ?1 AND name = ?2 RETURNING id",
+                params![server, name],
+                |row| row.get(0))
+                .optional()?;
+
+            if let Some(id) = id {
+                tx.execute("DELETE FROM cowboard_board_channel WHERE board_id = ?1", params![id])?;
+                tx.execute("DELETE FROM cowboard_post WHERE board_id = ?1", params![id])?;
+                tx.execute("DELETE FROM cowboard_review WHERE board_id = ?1", params![id])?;
+                tx.execute("DELETE FROM cowboard_override WHERE board_id = ?1", params![id])?;
+                tx.execute("DELETE FROM cowboard_blocked_user WHERE board_id = ?1", params![id])?;
+            }
+
+            tx.commit()?;
+            Ok(id.is_some())
+        }).await
+    }
+
+    async fn set_cowboard_channel(&self, board_id: i32, channel: ChannelId, allowed: Option<bool>) -> Result<(), Error> {
+        let channel = to_sql_id(channel.0);
+
+        self.call(move |conn| {
+            match allowed {
+                Some(allowed) => conn.execute(
+                    "INSERT INTO cowboard_board_channel (board_id, channel_id, allowed) VALUES (?1, ?2, ?3) \
+                    ON CONFLICT (board_id, channel_id) DO UPDATE SET allowed = excluded.allowed",
+                    params![board_id, channel, allowed])?,
+                None => conn.execute(
+                    "DELETE FROM cowboard_board_channel WHERE board_id = ?1 AND channel_id = ?2",
+                    params![board_id, channel])?
+            };
+            Ok(())
+        }).await
+    }
+
+    async fn get_cowboard_filter(&self, server_id: GuildId) -> Result<CowboardFilter, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut filter = CowboardFilter::new(server_id.0);
+            let flags: Option<(bool, bool, bool)> = conn.query_row(
+                "SELECT allow_nsfw, allow_hidden, keep_deleted FROM cowboard_filter WHERE server_id = ?1",
+                params![server],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .optional()?;
+            if let Some((allow_nsfw, allow_hidden, keep_deleted)) = flags {
+                filter.allow_nsfw = allow_nsfw;
+                filter.allow_hidden = allow_hidden;
+                filter.keep_deleted = keep_deleted;
+            }
+
+            let channels = conn.prepare("SELECT channel_id, allowed FROM cowboard_filter_channel WHERE server_id = ?1")?
+                .query_map(params![server], |row| Ok((from_sql_id(row.get(0)?), row.get::<_, bool>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (channel, allowed) in channels {
+                if allowed { filter.allowed_channels.push(channel) } else { filter.denied_channels.push(channel) }
+            }
+
+            Ok(filter)
+        }).await
+    }
+
+    async fn set_cowboard_filter(&self, filter: &CowboardFilter) -> Result<(), Error> {
+        let server = to_sql_id(filter.server_id);
+        let (allow_nsfw, allow_hidden, keep_deleted) = (filter.allow_nsfw, filter.allow_hidden, filter.keep_deleted);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO cowboard_filter (server_id, allow_nsfw, allow_hidden, keep_deleted) VALUES (?1, ?2, ?3, ?4) \
+                ON CONFLICT (server_id) DO UPDATE SET allow_nsfw = excluded.allow_nsfw, allow_hidden = excluded.allow_hidden, keep_deleted = excluded.keep_deleted",
+                params![server, allow_nsfw, allow_hidden, keep_deleted])?;
+            Ok(())
+        }).await
+    }
+
+    async fn set_cowboard_filter_channel(&self, server_id: GuildId, channel: ChannelId, allowed: Option<bool>) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+        let channel = to_sql_id(channel.0);
+
+        self.call(move |conn| {
+            match allowed {
+                Some(allowed) => conn.execute(
+                    "INSERT INTO cowboard_filter_channel (server_id, channel_id, allowed) VALUES (?1, ?2, ?3) \
+                    ON CONFLICT (server_id, channel_id) DO UPDATE SET allowed = excluded.allowed",
+                    params![server, channel, allowed])?,
+                None => conn.execute(
+                    "DELETE FROM cowboard_filter_channel WHERE server_id = ?1 AND channel_id = ?2",
+                    params![server, channel])?
+            };
+            Ok(())
+        }).await
+    }
+
+    async fn get_cowboard_rules(&self, server_id: GuildId) -> Result<CowboardRules, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                "SELECT min_account_days, min_member_hours, required_role, burst_count, burst_minutes, review_channel FROM cowboard_rules WHERE server_id = ?1",
+                params![server],
+                |row| {
+                    let required_role: Option<i64> = row.get(2)?;
+                    let review_channel: Option<i64> = row.get(5)?;
+                    Ok(CowboardRules {
+                        server_id: server_id.0,
+                        min_account_days: row.get(0)?,
+                        min_member_hours: row.get(1)?,
+                        required_role: required_role.map(from_sql_id),
+                        burst_count: row.get(3)?,
+                        burst_minutes: row.get(4)?,
+                        review_channel: review_channel.map(from_sql_id)
+                    })
+                })
+                .optional()
+                .map(|o| o.unwrap_or_else(|| CowboardRules::new(server_id.0)))
+        }).await
+    }
+
+    async fn set_cowboard_rules(&self, rules: &CowboardRules) -> Result<(), Error> {
+        let server = to_sql_id(rules.server_id);
+        let (min_account_days, min_member_hours, burst_count, burst_minutes) = (rules.min_account_days, rules.min_member_hours, rules.burst_count, rules.burst_minutes);
+        let required_role = rules.required_role.map(to_sql_id);
+        let review_channel = rules.review_channel.map(to_sql_id);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO cowboard_rules (server_id, min_account_days, min_member_hours, required_role, burst_count, burst_minutes, review_channel) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+                ON CONFLICT (server_id) DO UPDATE SET min_account_days = excluded.min_account_days, min_member_hours = excluded.min_member_hours, \
+                required_role = excluded.required_role, burst_count = excluded.burst_count, burst_minutes = excluded.burst_minutes, review_channel = excluded.review_channel",
+                params![server, min_account_days, min_member_hours, required_role, burst_count, burst_minutes, review_channel])?;
+            Ok(())
+        }).await
+    }
+
+    async fn flag_moo(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, flagged_at: DateTime<Utc>) -> Result<bool, Error> {
+        let message = to_sql_id(message.0);
+        let channel = to_sql_id(channel.0);
+        let server = to_sql_id(guild.0);
+        let flagged_at = flagged_at.timestamp_millis();
+
+        self.call(move |conn| {
+            let inserted = conn.execute(
+                "INSERT INTO cowboard_review (board_id, message_id, message_channel_id, guild_id, flagged_at) VALUES (?1, ?2, ?3, ?4, ?5) \
+                ON CONFLICT DO NOTHING",
+                params![board_id, message, channel, server, flagged_at])?;
+            Ok(inserted > 0)
+        }).await
+    }
+
+    async fn is_moo_flagged(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<bool, Error> {
+        let message = to_sql_id(message.0);
+        let channel = to_sql_id(channel.0);
+        let server = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            let flagged: Option<i32> = conn.query_row(
+                "SELECT 1 FROM cowboard_review WHERE board_id = ?1 AND message_id = ?2 AND message_channel_id = ?3 AND guild_id = ?4",
+                params![board_id, message, channel, server],
+                |row| row.get(0))
+                .optional()?;
+            Ok(flagged.is_some())
+        }).await
+    }
+
+    async fn unflag_moo(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Error> {
+        let message = to_sql_id(message.0);
+        let channel = to_sql_id(channel.0);
+        let server = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            conn.execute(
+                "DELETE FROM cowboard_review WHERE board_id = ?1 AND message_id = ?2 AND message_channel_id = ?3 AND guild_id = ?4",
+                params![board_id, message, channel, server])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_moo_override(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<bool>, Error> {
+        let message = to_sql_id(message.0);
+        let channel = to_sql_id(channel.0);
+        let server = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                "SELECT forced FROM cowboard_override WHERE board_id = ?1 AND message_id = ?2 AND message_channel_id = ?3 AND guild_id = ?4",
+                params![board_id, message, channel, server],
+                |row| row.get(0))
+                .optional()
+        }).await
+    }
+
+    async fn set_moo_override(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, forced: Option<bool>) -> Result<(), Error> {
+        let message = to_sql_id(message.0);
+        let channel = to_sql_id(channel.0);
+        let server = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            match forced {
+                Some(forced) => conn.execute(
+                    "INSERT INTO cowboard_override (board_id, message_id, message_channel_id, guild_id, forced) VALUES (?1, ?2, ?3, ?4, ?5) \
+                    ON CONFLICT (board_id, message_id, message_channel_id, guild_id) DO UPDATE SET forced = excluded.forced",
+                    params![board_id, message, channel, server, forced])?,
+                None => conn.execute(
+                    "DELETE FROM cowboard_override WHERE board_id = ?1 AND message_id = ?2 AND message_channel_id = ?3 AND guild_id = ?4",
+                    params![board_id, message, channel, server])?
+            };
+            Ok(())
+        }).await
+    }
+
+    async fn is_cowboard_user_blocked(&self, board_id: i32, user: UserId) -> Result<bool, Error> {
+        let user = to_sql_id(user.0);
+
+        self.call(move |conn| {
+            let blocked: Option<i32> = conn.query_row(
+                "SELECT 1 FROM cowboard_blocked_user WHERE board_id = ?1 AND user_id = ?2",
+                params![board_id, user],
+                |row| row.get(0))
+                .optional()?;
+            Ok(blocked.is_some())
+        }).await
+    }
+
+    async fn set_cowboard_user_blocked(&self, board_id: i32, user: UserId, blocked: bool) -> Result<(), Error> {
+        let user = to_sql_id(user.0);
+
+        self.call(move |conn| {
+            if blocked {
+                conn.execute("INSERT INTO cowboard_blocked_user (board_id, user_id) VALUES (?1, ?2) ON CONFLICT DO NOTHING", params![board_id, user])?;
+            } else {
+                conn.execute("DELETE FROM cowboard_blocked_user WHERE board_id = ?1 AND user_id = ?2", params![board_id, user])?;
+            }
+            Ok(())
+        }).await
+    }
+
+    async fn log_cowboard_action(&self, action: &CowboardAction) -> Result<(), Error> {
+        let server = to_sql_id(action.server_id);
+        let board_id = action.board_id;
+        let kind = action.action as u8;
+        let target = to_sql_id(action.target_id);
+        let channel = action.channel_id.map(to_sql_id);
+        let moderator = to_sql_id(action.moderator_id);
+        let created_at = action.created_at.timestamp_millis();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO cowboard_action (server_id, board_id, action, target_id, channel_id, moderator_id, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![server, board_id, kind, target, channel, moderator, created_at])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_cowboard_actions(&self, server_id: GuildId, limit: i32) -> Result<Vec<CowboardAction>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let actions = conn.prepare(
+                "SELECT server_id, board_id, action, target_id, channel_id, moderator_id, created_at FROM cowboard_action \
+                WHERE server_id = ?1 ORDER BY id DESC LIMIT ?2")?
+                .query_map(params![server, limit], action_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(actions.into_iter().flatten().collect())
+        }).await
+    }
+
+    async fn get_cowboard_message(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, Error> {
+        let message_id = to_sql_id(message.0);
+        let channel_id = to_sql_id(channel.0);
+        let guild_id = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                &format!("SELECT {POST_COLUMNS} FROM cowboard_post \
+                WHERE board_id = ?1 AND message_id = ?2 AND message_channel_id = ?3 AND guild_id = ?4"),
+                params![board_id, message_id, channel_id, guild_id],
+                message_from_row)
+                .optional()
+        }).await
+    }
+
+    async fn get_cowboard_messages(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Vec<CowboardMessage>, Error> {
+        let message_id = to_sql_id(message.0);
+        let channel_id = to_sql_id(channel.0);
+        let guild_id = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {POST_COLUMNS} FROM cowboard_post \
+                WHERE message_id = ?1 AND message_channel_id = ?2 AND guild_id = ?3"))?;
+            let messages = statement
+                .query_map(params![message_id, channel_id, guild_id], message_from_row)?
+                .collect::<Result<Vec<_>, _>>();
+            messages
+        }).await
+    }
+
+    async fn moo_message(&self, post: &CowboardMessage) -> Result<(), Error> {
+        let board_id = post.board_id;
+        let message = to_sql_id(post.message_id);
+        let channel = to_sql_id(post.message_channel_id);
+        let post_message = to_sql_id(post.post_id);
+        let post_channel = to_sql_id(post.post_channel_id);
+        let server = to_sql_id(post.guild_id);
+        let author = post.author_id.map(to_sql_id);
+        let reactions = post.reactions;
+
+        self.call(move |conn| {
+            conn.execute(
+                &format!("INSERT INTO cowboard_post ({POST_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"),
+                params![board_id, message, channel, post_message, post_channel, server, author, reactions])?;
+            Ok(())
+        }).await
+    }
+
+    async fn set_moo_reactions(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId, reactions: i32) -> Result<(), Error> {
+        let message = to_sql_id(message.0);
+        let channel = to_sql_id(channel.0);
+        let server = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            conn.execute(
+                "UPDATE cowboard_post SET reactions = ?5 WHERE board_id = ?1 AND message_id = ?2 AND message_channel_id = ?3 AND guild_id = ?4",
+                params![board_id, message, channel, server, reactions])?;
+            Ok(())
+        }).await
+    }
+
+    async fn unmoo_message(&self, board_id: i32, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Error> {
+        let message = to_sql_id(message.0);
+        let channel = to_sql_id(channel.0);
+        let server = to_sql_id(guild.0);
+
+        self.call(move |conn| {
+            conn.execute(
+                "DELETE FROM cowboard_post WHERE board_id = ?1 AND message_id = ?2 AND message_channel_id = ?3 AND guild_id = ?4",
+                params![board_id, message, channel, server])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_cowboard_stats(&self, server_id: GuildId, board_id: Option<i32>, limit: i32) -> Result<CowboardStats, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let (posts, reactions) = conn.query_row(
+                "SELECT COUNT(1), COALESCE(SUM(reactions), 0) FROM cowboard_post WHERE guild_id = ?1 AND (?2 IS NULL OR board_id = ?2)",
+                params![server, board_id],
+                |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+            let users = conn.prepare(
+                "SELECT author_id, COUNT(1) AS posts, SUM(reactions) AS reactions FROM cowboard_post \
+                WHERE guild_id = ?1 AND (?2 IS NULL OR board_id = ?2) AND author_id IS NOT NULL \
+                GROUP BY author_id ORDER BY posts DESC, reactions DESC LIMIT ?3")?
+                .query_map(params![server, board_id, limit], ranking_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let channels = conn.prepare(
+                "SELECT message_channel_id, COUNT(1) AS posts, SUM(reactions) AS reactions FROM cowboard_post \
+                WHERE guild_id = ?1 AND (?2 IS NULL OR board_id = ?2) \
+                GROUP BY message_channel_id ORDER BY posts DESC, reactions DESC LIMIT ?3")?
+                .query_map(params![server, board_id, limit], ranking_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(CowboardStats { posts, reactions, users, channels })
+        }).await
+    }
+
+    async fn get_top_moos(&self, server_id: GuildId, board_id: Option<i32>, limit: i32) -> Result<Vec<CowboardMessage>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {POST_COLUMNS} FROM cowboard_post WHERE guild_id = ?1 AND (?2 IS NULL OR board_id = ?2) \
+                ORDER BY reactions DESC, message_id LIMIT ?3"))?;
+            let messages = statement
+                .query_map(params![server, board_id, limit], message_from_row)?
+                .collect::<Result<Vec<_>, _>>();
+            messages
+        }).await
+    }
+
+    async fn get_random_moo(&self, server_id: GuildId, board_id: Option<i32>, seed: u64) -> Result<Option<CowboardMessage>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(1) FROM cowboard_post WHERE guild_id = ?1 AND (?2 IS NULL OR board_id = ?2)",
+                params![server, board_id],
+                |row| row.get(0))?;
+            if count == 0 {
+                return Ok(None);
+            }
+
+            conn.query_row(
+                &format!("SELECT {POST_COLUMNS} FROM cowboard_post WHERE guild_id = ?1 AND (?2 IS NULL OR board_id = ?2) \
+                ORDER BY board_id, message_id LIMIT 1 OFFSET ?3"),
+                params![server, board_id, (seed % count as u64) as i64],
+                message_from_row)
+                .optional()
+        }).await
+    }
+}
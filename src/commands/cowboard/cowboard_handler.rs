@@ -1,27 +1,278 @@
 use std::path::Path;
 use std::error;
+use std::time::{Duration, Instant};
+use chrono::Utc;
 use tokio::fs::File;
 use tracing::error;
 use serenity::client::Context;
-use serenity::model::channel::{Embed, Message, Reaction, ReactionType, AttachmentType};
-use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use serenity::model::channel::{Channel, Embed, GuildChannel, Message, Reaction, ReactionType, AttachmentType};
+use serenity::model::event::MessageUpdateEvent;
+use serenity::model::guild::{Guild, Role};
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, UserId};
+use serenity::model::user::User;
+use serenity::model::sticker::StickerFormatType;
+use serenity::builder::CreateEmbed;
 use tokio::io::AsyncWriteExt;
 use crate::{Database, db};
-use crate::commands::cowboard::cowboard_db_models::{Cowboard};
+use crate::commands::cowboard::cowboard_bursts::ReactionBursts;
+use crate::commands::cowboard::cowboard_db_models::{Cowboard, CowboardFilter, CowboardMessage, CowboardRules};
+use crate::services::message_handler::channel_lineage;
+
+// How much of a replied-to message is quoted.
+const REPLY_LENGTH: usize = 100;
+// Where the link to the original was, once it's been deleted.
+const DELETED_MARKER: &str = "[deleted]";
+
+// Discord hands out who reacted 100 people at a time.
+const REACTION_PAGE: u8 = 100;
+
+// Everyone who reacted with the board's emote and passes the server's rules.
+async fn count_reactions(ctx: &Context, guild_id: GuildId, message: &Message, config: &Cowboard, rules: &CowboardRules) -> Result<u64, Box<dyn error::Error + Send + Sync>> {
+    if !has_board_emote(message, config) {
+        return Ok(0);
+    }
 
-async fn count_reactions(ctx: &Context, message: &Message, config: &Cowboard) -> Result<u64, Box<dyn error::Error + Send + Sync>>{
     let config_emote = ReactionType::try_from(config.emote.as_str())?;
-    let matched_reaction = message.reactions.iter().find(|o|o.reaction_type.eq(&config_emote));
-    if let Some(reaction) = matched_reaction {
-        let count = reaction.count;
-        let people = message.reaction_users(&ctx.http, config_emote, None, UserId::from(message.author.id.0 - 2)).await?;
-        if people.iter().any(|o| o.id == message.author.id) {
-            return Ok(count - 1);
+    let now = Utc::now().timestamp();
+    let mut count = 0;
+    let mut after: Option<UserId> = None;
+    loop {
+        let people = message.reaction_users(&ctx.http, config_emote.clone(), Some(REACTION_PAGE), after).await?;
+        for person in &people {
+            if person.id != message.author.id && reaction_counts(ctx, guild_id, person, rules, now).await {
+                count += 1;
+            }
+        }
+
+        if people.len() < REACTION_PAGE as usize {
+            break;
+        }
+        after = people.last().map(|o| o.id);
+    }
+
+    Ok(count)
+}
+
+// Whether the server's rules let this person's reaction count; members that can't be found have left, so they don't.
+async fn reaction_counts(ctx: &Context, guild_id: GuildId, user: &User, rules: &CowboardRules, now: i64) -> bool {
+    if !rules.limits_reactions() {
+        return true;
+    }
+    // The rules are about who's really there, which bots never are.
+    if user.bot {
+        return false;
+    }
+    if rules.min_account_days > 0 && now - user.id.created_at().unix_timestamp() < rules.min_account_days as i64 * 24 * 60 * 60 {
+        return false;
+    }
+    if !rules.checks_members() {
+        return true;
+    }
+
+    let member = match ctx.cache.member(guild_id, user.id) {
+        Some(member) => member,
+        None => match guild_id.member(ctx, user.id).await {
+            Ok(member) => member,
+            Err(_) => return false
+        }
+    };
+    if rules.min_member_hours > 0 {
+        let joined = member.joined_at.map(|o| o.unix_timestamp()).unwrap_or(now);
+        if now - joined < rules.min_member_hours as i64 * 60 * 60 {
+            return false;
+        }
+    }
+
+    rules.required_role.map(|o| member.roles.contains(&RoleId::from(o))).unwrap_or(true)
+}
+
+// Custom emotes can be renamed, so only their IDs are compared.
+fn matches_emote(config: &Cowboard, emoji: &ReactionType) -> bool {
+    match (ReactionType::try_from(config.emote.as_str()), emoji) {
+        (Ok(ReactionType::Custom { id: a, .. }), ReactionType::Custom { id: b, .. }) => a == *b,
+        (Ok(ReactionType::Unicode(a)), ReactionType::Unicode(b)) => a == *b,
+        _ => false
+    }
+}
+
+// Threads aren't always cached, so Discord gets asked for the first step if the cache doesn't know it.
+async fn source_lineage(ctx: &Context, channel_id: ChannelId) -> Vec<ChannelId> {
+    let mut lineage = channel_lineage(&ctx.cache, channel_id);
+    if lineage.len() == 1 {
+        if let Ok(Channel::Guild(channel)) = channel_id.to_channel(ctx).await {
+            if let Some(parent) = channel.parent_id {
+                lineage.extend(channel_lineage(&ctx.cache, parent));
+            }
+        }
+    }
+
+    lineage
+}
+
+// Whether everyone who can see the board channel could also see the message where it was; checked role by role,
+// so overwrites for single members don't count.
+fn readable_from(guild: &Guild, lineage: &[ChannelId], board: ChannelId) -> bool {
+    // Threads aren't in the channel list, so this ends up being the channel they're in.
+    let source = lineage.iter().find_map(|o| guild.channels.get(o).cloned().and_then(|c| c.guild()));
+    let board = guild.channels.get(&board).cloned().and_then(|c| c.guild());
+    let (source, board) = match (source, board) {
+        (Some(source), Some(board)) => (source, board),
+        _ => return false
+    };
+
+    let sees = |channel: &GuildChannel, role: &Role| guild.role_permissions_in(channel, role).map(|o| o.view_channel()).unwrap_or(false);
+    guild.roles.values().all(|role| !sees(&board, role) || sees(&source, role))
+}
+
+// Keeps NSFW messages off boards that aren't, and private messages off boards more people can read, unless the server says otherwise.
+fn safe_to_post(ctx: &Context, guild: Option<&Guild>, filter: &CowboardFilter, lineage: &[ChannelId], board: ChannelId) -> bool {
+    let is_nsfw = |id: &ChannelId| id.to_channel_cached(&ctx.cache).map(|o| o.is_nsfw()).unwrap_or(false);
+    if !filter.allow_nsfw && lineage.iter().any(is_nsfw) && !is_nsfw(&board) {
+        return false;
+    }
+
+    filter.allow_hidden || guild.map(|o| readable_from(o, lineage, board)).unwrap_or(false)
+}
+
+// Whether someone can read the channel a message is in; threads go by the channel they're in. False for
+// channels that aren't in this server at all.
+pub async fn member_can_read(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, user_id: UserId) -> bool {
+    let lineage = source_lineage(ctx, channel_id).await;
+    let member = match guild_id.member(ctx, user_id).await {
+        Ok(member) => member,
+        Err(_) => return false
+    };
+    let guild = match ctx.cache.guild(guild_id) {
+        Some(guild) => guild,
+        None => return false
+    };
+
+    match lineage.iter().find_map(|o| guild.channels.get(o).cloned().and_then(|c| c.guild())) {
+        Some(channel) => guild.user_permissions_in(&channel, &member)
+            .map(|o| o.view_channel() && o.read_message_history())
+            .unwrap_or(false),
+        None => false
+    }
+}
+
+// Of `boards`, the ones that take messages from this channel, going by the server's filter and then their own.
+pub async fn boards_for_channel(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, boards: Vec<Cowboard>) -> Vec<Cowboard> {
+    let db = db!(ctx);
+    let filter = match db.get_cowboard_filter(guild_id).await {
+        Ok(filter) => filter,
+        Err(ex) => {
+            error!("Failed to get cowboard filter: {}", ex);
+            return Vec::new();
         }
-        return Ok(count);
+    };
+
+    let lineage = source_lineage(ctx, channel_id).await;
+    let ids = lineage.iter().map(|o| o.0).collect::<Vec<_>>();
+    if !filter.accepts(&ids) {
+        return Vec::new();
+    }
+
+    let guild = ctx.cache.guild(guild_id);
+    boards.into_iter()
+        .filter(|o| o.accepts(&ids) && safe_to_post(ctx, guild.as_ref(), &filter, &lineage, ChannelId::from(o.channel.unwrap())))
+        .collect()
+}
+
+// Every board on the server that this reaction could matter to.
+async fn matching_boards(ctx: &Context, guild_id: GuildId, reaction: &Reaction) -> Vec<Cowboard> {
+    let db = db!(ctx);
+    let boards = match db.get_cowboards(guild_id).await {
+        // No cowboard channel, why even check?
+        Ok(boards) => boards.into_iter()
+            .filter(|o| o.channel.is_some() && matches_emote(o, &reaction.emoji))
+            .collect::<Vec<_>>(),
+        Err(ex) => {
+            error!("Failed to get cowboard config: {}", ex);
+            return Vec::new();
+        }
+    };
+    if boards.is_empty() {
+        return boards;
+    }
+
+    boards_for_channel(ctx, guild_id, reaction.channel_id, boards).await
+}
+
+// Every board on the server that takes messages from this channel, whatever their emote; for backfilling.
+pub async fn channel_boards(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> Result<Vec<Cowboard>, Box<dyn error::Error + Send + Sync>> {
+    let db = db!(ctx);
+    let boards = db.get_cowboards(guild_id).await?.into_iter()
+        // Not the boards' own channels, or their posts would get posted again.
+        .filter(|o| o.channel.is_some() && o.channel != Some(channel_id.0))
+        .collect();
+
+    Ok(boards_for_channel(ctx, guild_id, channel_id, boards).await)
+}
+
+// Whether anyone's reacted to the message with the board's emote; saves counting when nobody has.
+pub fn has_board_emote(message: &Message, config: &Cowboard) -> bool {
+    message.reactions.iter().any(|o| matches_emote(config, &o.reaction_type))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MooChange {
+    Unchanged,
+    Posted,
+    Updated,
+    Removed,
+    // It should have gone up, but sending the post didn't work.
+    Failed
+}
+
+// What moderators have decided for the message on this board: Some(true) keeps it up whatever its reactions, Some(false) keeps it off.
+pub async fn moderated(ctx: &Context, guild_id: GuildId, message: &Message, config: &Cowboard) -> Result<Option<bool>, Box<dyn error::Error + Send + Sync>> {
+    let db = db!(ctx);
+    if let Some(forced) = db.get_moo_override(config.id, message.id, message.channel_id, guild_id).await? {
+        return Ok(Some(forced));
     }
+    if db.is_cowboard_user_blocked(config.id, message.author.id).await? {
+        return Ok(Some(false));
+    }
+
+    Ok(None)
+}
+
+// Posts, updates or takes down the message on the board to match how many reactions it has right now.
+// Messages held for review, or kept off by a moderator, stay off the board; forced ones stay up.
+pub async fn sync_moo(ctx: &Context, guild_id: GuildId, message: &Message, config: &mut Cowboard, rules: &CowboardRules) -> Result<MooChange, Box<dyn error::Error + Send + Sync>> {
+    let db = db!(ctx);
+    let forced = match moderated(ctx, guild_id, message, config).await? {
+        Some(false) => return Ok(MooChange::Unchanged),
+        verdict => verdict.is_some()
+    };
+    let count = count_reactions(ctx, guild_id, message, config, rules).await?;
+    let post = db.get_cowboard_message(config.id, message.id, message.channel_id, guild_id).await?;
+    let held = !forced && db.is_moo_flagged(config.id, message.id, message.channel_id, guild_id).await?;
+
+    let change = match post {
+        None if forced || (count >= config.add_threshold as u64 && !held) => {
+            if add_moo(ctx, guild_id, message, config, count).await { MooChange::Posted } else { MooChange::Failed }
+        }
+        None => MooChange::Unchanged,
+        Some(post) if !forced && count < config.remove_threshold as u64 => {
+            remove_moo(ctx, &post).await;
+            MooChange::Removed
+        }
+        Some(post) => match ctx.http.get_message(post.post_channel_id, post.post_id).await {
+            Ok(mut post_message) => {
+                update_moo(ctx, message, &mut post_message, config, count, None).await;
+                save_reactions(ctx, guild_id, message, config, count).await;
+                MooChange::Updated
+            }
+            Err(_) => {
+                // The old copy's gone, so forget it before making a new one.
+                db.unmoo_message(config.id, message.id, message.channel_id, guild_id).await?;
+                if add_moo(ctx, guild_id, message, config, count).await { MooChange::Posted } else { MooChange::Failed }
+            }
+        }
+    };
 
-    Ok(0)
+    Ok(change)
 }
 
 pub async fn add_reaction(ctx: &Context, added_reaction: &Reaction) {
@@ -29,116 +280,207 @@ pub async fn add_reaction(ctx: &Context, added_reaction: &Reaction) {
         return;
     }
     let guild_id = added_reaction.guild_id.unwrap();
+    let boards = matching_boards(ctx, guild_id, added_reaction).await;
+    if boards.is_empty() {
+        return;
+    }
+
+    let rules = match db!(ctx).get_cowboard_rules(guild_id).await {
+        Ok(rules) => rules,
+        Err(ex) => {
+            error!("Failed to get cowboard rules: {}", ex);
+            return;
+        }
+    };
+
+    match added_reaction.message(&ctx.http).await {
+        Ok(message) => {
+            for mut config in boards {
+                let forced = match moderated(ctx, guild_id, &message, &config).await {
+                    Ok(Some(false)) => continue,
+                    Ok(verdict) => verdict.is_some(),
+                    Err(ex) => {
+                        error!("Failed to check cowboard moderation: {}", ex);
+                        continue;
+                    }
+                };
+
+                if forced || !held_for_review(ctx, guild_id, &message, &config, &rules).await {
+                    add_to_board(ctx, guild_id, &message, &mut config, &rules, forced).await;
+                }
+            }
+        }
+        Err(ex) => {
+            error!("Failed to get reacted message: {}", ex);
+        }
+    }
+}
+
+// Whether the message is being kept off the board for a moderator, either from before or because of this reaction.
+async fn held_for_review(ctx: &Context, guild_id: GuildId, message: &Message, config: &Cowboard, rules: &CowboardRules) -> bool {
     let db = db!(ctx);
-    match db.get_cowboard_config(guild_id).await {
-        Ok(mut config) => {
-            if config.channel.is_none() {
-                // No cowboard, why even check?
-                return;
+    match db.is_moo_flagged(config.id, message.id, message.channel_id, guild_id).await {
+        Ok(true) => return true,
+        Ok(false) => {}
+        Err(ex) => {
+            error!("Failed to check cowboard review: {}", ex);
+            return false;
+        }
+    }
+    if !rules.holds_bursts() {
+        return false;
+    }
+
+    let bursts = {
+        let ctx_global = ctx.data.read().await;
+        ctx_global.get::<ReactionBursts>().expect("Couldn't find reaction bursts").clone()
+    };
+    let window = Duration::from_secs(rules.burst_minutes.max(1) as u64 * 60);
+    let recent = bursts.lock().expect("Reaction bursts were poisoned").record(config.id, message.id, window, Instant::now());
+    if recent < rules.burst_count as usize {
+        return false;
+    }
+
+    // Anything already up stays up; the burst only matters for getting on.
+    match db.get_cowboard_message(config.id, message.id, message.channel_id, guild_id).await {
+        Ok(None) => {}
+        Ok(Some(_)) => return false,
+        Err(ex) => {
+            error!("Failed to get message from database: {}", ex);
+            return false;
+        }
+    }
+
+    // Nor is there anything to hold back if it wouldn't be posted yet anyway.
+    match count_reactions(ctx, guild_id, message, config, rules).await {
+        Ok(count) if count >= config.add_threshold as u64 => {}
+        Ok(_) => return false,
+        Err(ex) => {
+            error!("Failed to count reactions: {}", ex);
+            return false;
+        }
+    }
+
+    match db.flag_moo(config.id, message.id, message.channel_id, guild_id, Utc::now()).await {
+        Ok(true) => {
+            let review_channel = ChannelId::from(rules.review_channel.unwrap());
+            let link = message.link_ensured(&ctx.http).await;
+            if let Err(ex) = review_channel.say(&ctx.http, format!("{recent} {} reactions came in on this message within {} minutes, so it's being held off the {} cowboard. \
+                Use `/cowboard approve` with its link to let it through.\n{link}", &config.emote, rules.burst_minutes, config.name)).await {
+                error!("Failed to send cowboard review notice: {}", ex);
             }
+        }
+        Ok(false) => {}
+        Err(ex) => {
+            error!("Failed to flag a message for cowboard review: {}", ex);
+        }
+    }
 
-            match added_reaction.message(&ctx.http).await {
-                Ok(message) => {
-                    match count_reactions(ctx, &message, &config).await {
-                        Ok(count) => {
-                            // Pray that the database's constraints work.
-                            if count >= config.add_threshold as u64 {
-                                let post_message = db.get_cowboard_message(message.id, message.channel_id, guild_id).await;
-                                if let Ok(Some(post)) = post_message {
-                                    match ctx.http.get_message(post.post_channel_id, post.post_id).await {
-                                        Ok(mut post) => {
-                                            update_moo(ctx, &message, &mut post, &mut config).await;
-                                        }
-                                        Err(ex) => {
-                                            error!("Failed to get old cowboard message: {}", ex);
-                                            // Create a new copy
-                                            add_moo(ctx, guild_id, added_reaction, &message, &mut config).await;
-                                        }
-                                    }
-                                } else if let Err(ex) = post_message {
-                                    error!("Failed to get message from database: {}", ex);
-                                } else {
-                                    // Moo that thing!
-                                    add_moo(ctx, guild_id, added_reaction, &message, &mut config).await;
-                                }
-                            }
+    true
+}
+
+async fn add_to_board(ctx: &Context, guild_id: GuildId, message: &Message, config: &mut Cowboard, rules: &CowboardRules, forced: bool) {
+    let db = db!(ctx);
+    match count_reactions(ctx, guild_id, message, config, rules).await {
+        Ok(count) => {
+            // Pray that the database's constraints work.
+            if forced || count >= config.add_threshold as u64 {
+                let post_message = db.get_cowboard_message(config.id, message.id, message.channel_id, guild_id).await;
+                if let Ok(Some(post)) = post_message {
+                    match ctx.http.get_message(post.post_channel_id, post.post_id).await {
+                        Ok(mut post) => {
+                            update_moo(ctx, message, &mut post, config, count, None).await;
+                            save_reactions(ctx, guild_id, message, config, count).await;
                         }
                         Err(ex) => {
-                            error!("Failed to count reactions: {}", ex);
+                            error!("Failed to get old cowboard message: {}", ex);
+                            // The old copy's gone, so forget it before making a new one.
+                            if let Err(ex) = db.unmoo_message(config.id, message.id, message.channel_id, guild_id).await {
+                                error!("Failed to unmoo a message in the database: {}", ex);
+                            }
+                            add_moo(ctx, guild_id, message, config, count).await;
                         }
                     }
-                }
-                Err(ex) => {
-                    error!("Failed to get reacted message: {}", ex);
+                } else if let Err(ex) = post_message {
+                    error!("Failed to get message from database: {}", ex);
+                } else {
+                    // Moo that thing!
+                    add_moo(ctx, guild_id, message, config, count).await;
                 }
             }
         }
         Err(ex) => {
-            error!("Failed to get cowboard config: {}", ex);
+            error!("Failed to count reactions: {}", ex);
         }
     }
 }
 
-async fn add_moo(ctx: &Context, guild_id: GuildId, reaction: &Reaction, message: &Message, config: &mut Cowboard) {
+// False if it couldn't be posted.
+async fn add_moo(ctx: &Context, guild_id: GuildId, message: &Message, config: &mut Cowboard, count: u64) -> bool {
     let db = db!(ctx);
 
     let message_result = if config.webhook_id.is_some() && config.webhook_token.is_some() {
-        send_webhook_message(ctx, message, config).await
+        send_webhook_message(ctx, message, config, count).await
     } else {
-        send_bot_message(ctx, message, config).await
+        send_bot_message(ctx, message, config, count).await
     };
 
     if let Err(ex) = message_result {
         error!("Failed to send cowboard message: {}", ex);
-        return;
+        return false;
     }
 
     let post_message = message_result.unwrap();
 
-    if let Err(ex) = db.moo_message(message.id, reaction.channel_id, post_message.id, post_message.channel_id, guild_id).await {
+    let post = CowboardMessage {
+        board_id: config.id,
+        message_id: message.id.0,
+        message_channel_id: message.channel_id.0,
+        post_id: post_message.id.0,
+        post_channel_id: post_message.channel_id.0,
+        guild_id: guild_id.0,
+        author_id: Some(message.author.id.0),
+        reactions: count as i32
+    };
+    if let Err(ex) = db.moo_message(&post).await {
         error!("Failed to moo a message in the database: {}", ex);
     }
+
+    true
+}
+
+// Kept for the stats; the post itself already shows the count.
+async fn save_reactions(ctx: &Context, guild_id: GuildId, message: &Message, config: &Cowboard, count: u64) {
+    let db = db!(ctx);
+
+    if let Err(ex) = db.set_moo_reactions(config.id, message.id, message.channel_id, guild_id, count as i32).await {
+        error!("Failed to save cowboard reactions: {}", ex);
+    }
 }
 
-async fn update_moo(ctx: &Context, message: &Message, post_message: &mut Message, config: &mut Cowboard) {
+// Only the count changes unless there's new content, from the original being edited.
+async fn update_moo(ctx: &Context, message: &Message, post_message: &mut Message, config: &mut Cowboard, count: u64, content: Option<&MooContent>) {
     if config.webhook_id.is_some() && config.webhook_token.is_some() {
-        update_webhook_message(ctx, message, post_message, config).await
+        update_webhook_message(ctx, message, post_message, config, count, content).await
     } else {
-        update_bot_message(ctx, message, post_message, config).await
+        update_bot_message(ctx, message, post_message, config, count, content).await
     };
 }
 
-async fn send_bot_message(ctx: &Context, message: &Message, config: &Cowboard) -> Result<Message, Box<dyn error::Error + Send + Sync>> {
+async fn send_bot_message(ctx: &Context, message: &Message, config: &Cowboard, reacts: u64) -> Result<Message, Box<dyn error::Error + Send + Sync>> {
     let channel = ChannelId::from(config.channel.unwrap());
-    let output_username = format_username(ctx, message).await;
-    let safe_content = message.content_safe(ctx);
-
-    let attachments = download_image_attachments(message).await;
+    let content = build_moo(ctx, message).await;
 
-    let reacts = count_reactions(ctx, message, config).await?;
     let link = message.link_ensured(&ctx.http).await;
 
     let message_output = channel.send_message(&ctx.http, |m|
         {
             let execution = m
                 .content(format!("{} {} | <#{}>\n{}", reacts, &config.emote, message.channel_id, link))
-                .embed(|e| {
-                    let temp = e
-                        .author(|a|
-                            a.name(&output_username).icon_url(message.author.face()))
-                        .description(&safe_content)
-                        .timestamp(message.timestamp)
-                        .footer(|f| f.text(format!("Message ID: {} / User ID: {}", message.id, message.author.id)));
-
-                    if !attachments.is_empty() {
-                        let (name, _) = &attachments[0];
-                        temp.attachment(name);
-                    }
-
-                    temp
-                });
+                .embed(|e| fill_embed(e, message, &content))
+                .add_embeds(content.embeds.iter().cloned().map(CreateEmbed::from).collect());
 
-            for (_, path) in &attachments {
+            for (_, path) in &content.files {
                 execution.add_file(AttachmentType::Path(Path::new(path)));
             }
 
@@ -146,7 +488,7 @@ async fn send_bot_message(ctx: &Context, message: &Message, config: &Cowboard) -
         }
     ).await;
 
-    delete_image_attachments(message).await;
+    delete_attachments(message).await;
     match message_output {
         Ok(message) => {
             Ok(message)
@@ -157,50 +499,30 @@ async fn send_bot_message(ctx: &Context, message: &Message, config: &Cowboard) -
     }
 }
 
-async fn update_bot_message(ctx: &Context, message: &Message, post_message: &mut Message, config: &mut Cowboard) {
-    match count_reactions(ctx, message, config).await {
-        Ok(reacts) => {
-            let link = message.link_ensured(&ctx.http).await;
-            if let Err(ex) = post_message.edit(&ctx.http,
-                                                  |m| m.content(format!("{} {} | <#{}>\n{}", reacts, &config.emote, message.channel_id, link))).await {
-                error!("Failed to edit post message??? {}", ex);
-            }
-        }
-        Err(ex) => {
-            error!("Failed to count reactions: {}", ex);
+async fn update_bot_message(ctx: &Context, message: &Message, post_message: &mut Message, config: &mut Cowboard, reacts: u64, content: Option<&MooContent>) {
+    let link = message.link_ensured(&ctx.http).await;
+    if let Err(ex) = post_message.edit(&ctx.http, |m| {
+        m.content(format!("{} {} | <#{}>\n{}", reacts, &config.emote, message.channel_id, link));
+        if let Some(content) = content {
+            m.set_embeds(moo_embeds(message, content));
         }
+        m
+    }).await {
+        error!("Failed to edit post message??? {}", ex);
     }
 }
 
-async fn send_webhook_message(ctx: &Context, message: &Message, config: &mut Cowboard) -> Result<Message, Box<dyn error::Error + Send + Sync>> {
+async fn send_webhook_message(ctx: &Context, message: &Message, config: &mut Cowboard, reacts: u64) -> Result<Message, Box<dyn error::Error + Send + Sync>> {
     let token = config.webhook_token.clone().unwrap();
     if let Ok(webhook) = ctx.http.get_webhook_with_token(config.webhook_id.unwrap(), &token).await {
-        let output_username = format_username(ctx, message).await;
-        let safe_content = message.content_safe(ctx);
-
-        let attachments = download_image_attachments(message).await;
-
-        let embeds = vec![
-            Embed::fake(|e|
-                {
-                    let temp = e
-                        .author(|a|
-                            a.name(&output_username).icon_url(message.author.face()))
-                        .description(&safe_content)
-                        .timestamp(message.timestamp)
-                        .footer(|f| f.text(format!("Message ID: {} / User ID: {}", message.id, message.author.id)));
-
-                    if !attachments.is_empty() {
-                        let (name, _) = &attachments[0];
-                        temp.attachment(name);
-                    }
+        let content = build_moo(ctx, message).await;
 
-                    temp
-                }
-            )
-        ];
+        let mut embeds = vec![Embed::fake(|e| fill_embed(e, message, &content))];
+        embeds.extend(content.embeds.iter().map(|o| Embed::fake(|e| {
+            *e = CreateEmbed::from(o.clone());
+            e
+        })));
 
-        let reacts = count_reactions(ctx, message, config).await?;
         let link = message.link_ensured(&ctx.http).await;
         if let Ok(Some(webhook_message)) = webhook.execute(&ctx.http, true, |m|
             {
@@ -208,71 +530,207 @@ async fn send_webhook_message(ctx: &Context, message: &Message, config: &mut Cow
                     .content(format!("{} {} | <#{}>\n{}", reacts, &config.emote, message.channel_id, link))
                     .embeds(embeds)
                     .avatar_url(message.author.face())
-                    .username(output_username);
+                    .username(&content.username);
 
-                for (_, path) in &attachments {
+                for (_, path) in &content.files {
                     execution.add_file(AttachmentType::Path(Path::new(path)));
                 }
 
                 execution
             }
         ).await {
-            delete_image_attachments(message).await;
+            delete_attachments(message).await;
             return Ok(webhook_message);
         }
     }
 
-    delete_image_attachments(message).await;
+    delete_attachments(message).await;
     disable_webhook(ctx, config).await;
-    send_bot_message(ctx, message, config).await
+    send_bot_message(ctx, message, config, reacts).await
+}
+
+// Everything from the original message that goes into a post, whichever way it's sent.
+struct MooContent {
+    username: String,
+    description: String,
+    // The name of an uploaded image to show in the main embed.
+    image: Option<String>,
+    // A sticker to show instead, if there's no image.
+    sticker: Option<String>,
+    // Downloaded to upload again, as (name, path).
+    files: Vec<(String, String)>,
+    // The original's link previews.
+    embeds: Vec<Embed>
+}
+
+async fn build_moo(ctx: &Context, message: &Message) -> MooContent {
+    let (files, links) = download_attachments(message).await;
+    describe_moo(ctx, message, files, links).await
+}
+
+// For an edited message, keeping whatever was uploaded with the post the first time; attachments can only be taken off an edit.
+async fn rebuild_moo(ctx: &Context, message: &Message, post_message: &Message) -> MooContent {
+    let files = post_message.attachments.iter()
+        .filter(|o| message.attachments.iter().any(|a| a.filename == o.filename))
+        .map(|o| (o.filename.clone(), o.url.clone()))
+        .collect::<Vec<_>>();
+    let links = message.attachments.iter()
+        .filter(|o| !files.iter().any(|(name, _)| *name == o.filename))
+        .map(|o| format!("[{}]({})", o.filename, o.url))
+        .collect();
+
+    describe_moo(ctx, message, files, links).await
+}
+
+async fn describe_moo(ctx: &Context, message: &Message, files: Vec<(String, String)>, mut links: Vec<String>) -> MooContent {
+    let mut description = String::new();
+
+    if let Some(reply) = &message.referenced_message {
+        let reply_content = reply.content_safe(ctx);
+        let snippet = if reply_content.chars().count() > REPLY_LENGTH {
+            format!("{}...", reply_content.chars().take(REPLY_LENGTH).collect::<String>())
+        } else if reply_content.is_empty() {
+            "*no text*".to_string()
+        } else {
+            reply_content
+        };
+        let reply_link = match message.guild_id {
+            Some(guild_id) => format!("https://discord.com/channels/{}/{}/{}", guild_id, reply.channel_id, reply.id),
+            None => reply.link()
+        };
+        description.push_str(&format!("> [Replying to {}]({}): {}\n", reply.author.name, reply_link, snippet.replace('\n', " ")));
+    }
+
+    description.push_str(&message.content_safe(ctx));
+
+    // Videos have dimensions too, but embeds can't play them; they're uploaded alongside as files instead.
+    let image = message.attachments.iter()
+        .find(|o| o.content_type.as_deref().map(|t| t.starts_with("image/")).unwrap_or(false)
+            && files.iter().any(|(name, _)| *name == o.filename))
+        .map(|o| o.filename.clone());
+
+    let mut sticker = None;
+    for item in &message.sticker_items {
+        // Lottie stickers are JSON, which embeds can't show.
+        match item.image_url() {
+            Some(url) if sticker.is_none() && matches!(item.format_type, StickerFormatType::Png | StickerFormatType::Apng) => sticker = Some(url),
+            _ => links.push(format!("Sticker: {}", item.name))
+        }
+    }
+
+    if !links.is_empty() {
+        description.push_str("\n\n");
+        description.push_str(&links.join("\n"));
+    }
+
+    // Embed descriptions can't be longer than this.
+    if description.chars().count() > 4096 {
+        description = format!("{}...", description.chars().take(4093).collect::<String>());
+    }
+
+    // Only previews with something to show; the rest would be rejected as empty. One embed is ours, and a message holds 10.
+    let embeds = message.embeds.iter()
+        .filter(|o| o.title.is_some() || o.description.is_some() || o.image.is_some() || o.thumbnail.is_some() || !o.fields.is_empty())
+        .take(9)
+        .cloned()
+        .collect();
+
+    MooContent {
+        username: format_username(ctx, message).await,
+        description,
+        image,
+        sticker,
+        files,
+        embeds
+    }
+}
+
+// Our embed, then the original's link previews.
+fn moo_embeds(message: &Message, content: &MooContent) -> Vec<CreateEmbed> {
+    let mut embed = CreateEmbed::default();
+    fill_embed(&mut embed, message, content);
+
+    let mut embeds = vec![embed];
+    embeds.extend(content.embeds.iter().cloned().map(CreateEmbed::from));
+    embeds
+}
+
+fn fill_embed<'a>(e: &'a mut CreateEmbed, message: &Message, content: &MooContent) -> &'a mut CreateEmbed {
+    e
+        .author(|a|
+            a.name(&content.username).icon_url(message.author.face()))
+        .description(&content.description)
+        .timestamp(message.timestamp)
+        .footer(|f| f.text(format!("Message ID: {} / User ID: {}", message.id, message.author.id)));
+
+    if let Some(name) = &content.image {
+        e.attachment(name);
+    } else if let Some(url) = &content.sticker {
+        e.image(url);
+    }
+
+    e
 }
 
-async fn download_image_attachments(message: &Message) -> Vec<(String, String)> {
+// Saves images, videos and audio to upload again, as (name, path); anything else, or anything too big, is linked instead.
+async fn download_attachments(message: &Message) -> (Vec<(String, String)>, Vec<String>) {
     let mut out: Vec<(String, String)> = Vec::new();
+    let mut links: Vec<String> = Vec::new();
 
     let directory = format!("cowboard/{}", message.id);
 
     if let Err(ex) = tokio::fs::create_dir_all(&directory).await {
         error!("Failed to create temporary directory: {}", ex);
-        return out;
+        links.extend(message.attachments.iter().map(|o| format!("[{}]({})", o.filename, o.url)));
+        return (out, links);
     }
 
     let mut size_limit: u64 = 8 * 1024 * 1024;
 
     for item in message.attachments.iter() {
-        if item.dimensions().is_some() && size_limit >= item.size {
-            // Is an image that we can upload!
-            let content = match item.download().await {
-                Ok(content) => content,
-                Err(ex) => {
-                    error!("Error downloading file: {}", ex);
-                    continue;
-                }
-            };
-
-            let file_path = format!("{}/{}", &directory, &item.filename);
-            let mut file = match File::create(&file_path).await {
-                Ok(file) => file,
-                Err(ex) => {
-                    error!("Error creating file: {}", ex);
-                    continue;
-                }
-            };
+        let media = item.dimensions().is_some() || item.content_type.as_deref()
+            .map(|o| o.starts_with("video/") || o.starts_with("audio/"))
+            .unwrap_or(false);
+
+        if !media || size_limit < item.size {
+            links.push(format!("[{}]({})", item.filename, item.url));
+            continue;
+        }
+
+        // Is something we can upload!
+        let content = match item.download().await {
+            Ok(content) => content,
+            Err(ex) => {
+                error!("Error downloading file: {}", ex);
+                links.push(format!("[{}]({})", item.filename, item.url));
+                continue;
+            }
+        };
 
-            if let Err(ex) = file.write_all(&content).await {
-                error!("Error saving image: {}", ex);
+        let file_path = format!("{}/{}", &directory, &item.filename);
+        let mut file = match File::create(&file_path).await {
+            Ok(file) => file,
+            Err(ex) => {
+                error!("Error creating file: {}", ex);
+                links.push(format!("[{}]({})", item.filename, item.url));
                 continue;
             }
+        };
 
-            size_limit -= item.size;
-            out.push((item.filename.clone(), file_path));
+        if let Err(ex) = file.write_all(&content).await {
+            error!("Error saving attachment: {}", ex);
+            links.push(format!("[{}]({})", item.filename, item.url));
+            continue;
         }
+
+        size_limit -= item.size;
+        out.push((item.filename.clone(), file_path));
     }
 
-    out
+    (out, links)
 }
 
-async fn delete_image_attachments(message: &Message) {
+async fn delete_attachments(message: &Message) {
     let directory = format!("cowboard/{}", message.id);
 
     if let Err(ex) = tokio::fs::remove_dir_all(&directory).await {
@@ -291,20 +749,21 @@ async fn format_username(ctx: &Context, message: &Message) -> String {
     }
 }
 
-async fn update_webhook_message(ctx: &Context, message: &Message, post_message: &Message, config: &mut Cowboard) {
+async fn update_webhook_message(ctx: &Context, message: &Message, post_message: &Message, config: &mut Cowboard, reacts: u64, content: Option<&MooContent>) {
     let token = config.webhook_token.clone().unwrap();
     if let Ok(webhook) = ctx.http.get_webhook_with_token(config.webhook_id.unwrap(), &token).await {
-        match count_reactions(ctx, message, config).await {
-            Ok(reacts) => {
-                let link = message.link_ensured(&ctx.http).await;
-                if let Err(ex) = webhook.edit_message(&ctx.http, post_message.id,
-                                                      |m| m.content(format!("{} {} | <#{}>\n{}", reacts, &config.emote, message.channel_id, link))).await {
-                    error!("Failed to edit post message??? {}", ex);
-                }
-            }
-            Err(ex) => {
-                error!("Failed to count reactions: {}", ex);
+        let link = message.link_ensured(&ctx.http).await;
+        if let Err(ex) = webhook.edit_message(&ctx.http, post_message.id, |m| {
+            m.content(format!("{} {} | <#{}>\n{}", reacts, &config.emote, message.channel_id, link));
+            if let Some(content) = content {
+                m.embeds(moo_embeds(message, content).into_iter().map(|o| Embed::fake(|e| {
+                    *e = o;
+                    e
+                })).collect());
             }
+            m
+        }).await {
+            error!("Failed to edit post message??? {}", ex);
         }
     } else {
         disable_webhook(ctx, config).await;
@@ -327,36 +786,60 @@ pub async fn remove_reaction(ctx: &Context, removed_reaction: &Reaction) {
     }
 
     let guild_id = removed_reaction.guild_id.unwrap();
-    let db = db!(ctx);
-    match db.get_cowboard_config(guild_id).await {
-        Ok(mut config) => {
-            match removed_reaction.message(&ctx.http).await {
-                Ok(message) => {
-                    match count_reactions(ctx, &message, &config).await {
-                        Ok(count) => {
-                            let post_message = db.get_cowboard_message(message.id, message.channel_id, guild_id).await;
-                            // Pray that the database's constraints work.
-                            if count < config.remove_threshold as u64 {
-                                // Unmoo that thing!
-                                remove_moo(ctx, guild_id, removed_reaction.channel_id, removed_reaction.message_id).await;
-                            } else if let Ok(Some(post)) = post_message {
-                                if let Ok(mut post) = ctx.http.get_message(post.post_channel_id, post.post_id).await {
-                                    update_moo(ctx, &message, &mut post, &mut config).await;
-                                }
-                            }
-                        }
-                        Err(ex) => {
-                            error!("Failed to count reactions: {}", ex);
-                        }
+    let boards = matching_boards(ctx, guild_id, removed_reaction).await;
+    if boards.is_empty() {
+        return;
+    }
+
+    let rules = match db!(ctx).get_cowboard_rules(guild_id).await {
+        Ok(rules) => rules,
+        Err(ex) => {
+            error!("Failed to get cowboard rules: {}", ex);
+            return;
+        }
+    };
+
+    match removed_reaction.message(&ctx.http).await {
+        Ok(message) => {
+            for mut config in boards {
+                let forced = match moderated(ctx, guild_id, &message, &config).await {
+                    Ok(Some(false)) => continue,
+                    Ok(verdict) => verdict.is_some(),
+                    Err(ex) => {
+                        error!("Failed to check cowboard moderation: {}", ex);
+                        continue;
                     }
+                };
+
+                remove_from_board(ctx, guild_id, &message, &mut config, &rules, forced).await;
+            }
+        }
+        Err(ex) => {
+            error!("Failed to get reacted message: {}", ex);
+        }
+    }
+}
+
+async fn remove_from_board(ctx: &Context, guild_id: GuildId, message: &Message, config: &mut Cowboard, rules: &CowboardRules, forced: bool) {
+    let db = db!(ctx);
+    match count_reactions(ctx, guild_id, message, config, rules).await {
+        Ok(count) => {
+            let post_message = db.get_cowboard_message(config.id, message.id, message.channel_id, guild_id).await;
+            // Pray that the database's constraints work.
+            if !forced && count < config.remove_threshold as u64 {
+                // Unmoo that thing!
+                if let Ok(Some(post)) = post_message {
+                    remove_moo(ctx, &post).await;
                 }
-                Err(ex) => {
-                    error!("Failed to get reacted message: {}", ex);
+            } else if let Ok(Some(post)) = post_message {
+                if let Ok(mut post) = ctx.http.get_message(post.post_channel_id, post.post_id).await {
+                    update_moo(ctx, message, &mut post, config, count, None).await;
+                    save_reactions(ctx, guild_id, message, config, count).await;
                 }
             }
         }
         Err(ex) => {
-            error!("Failed to get cowboard config: {}", ex);
+            error!("Failed to count reactions: {}", ex);
         }
     }
 }
@@ -364,27 +847,160 @@ pub async fn remove_reaction(ctx: &Context, removed_reaction: &Reaction) {
 pub async fn reaction_remove_all(ctx: &Context, channel_id: ChannelId, message: MessageId) {
     let guild_id = channel_id.message(&ctx.http, message).await.ok().and_then(|o| o.guild_id);
     if let Some(guild) = guild_id {
-        remove_moo(ctx, guild, channel_id, message).await;
+        let db = db!(ctx);
+        match db.get_cowboard_messages(message, channel_id, guild).await {
+            Ok(posts) => {
+                for post in posts {
+                    // Forced posts stay up with no reactions at all.
+                    match db.get_moo_override(post.board_id, message, channel_id, guild).await {
+                        Ok(Some(true)) => {}
+                        Ok(_) => remove_moo(ctx, &post).await,
+                        Err(ex) => error!("Failed to check cowboard moderation: {}", ex)
+                    }
+                }
+            }
+            Err(ex) => {
+                error!("Failed to query cowboard message: {}", ex);
+            }
+        }
     }
 }
 
-async fn remove_moo(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, message: MessageId) {
+// Brings every post of an edited message up to date with it.
+pub async fn message_update(ctx: &Context, event: &MessageUpdateEvent) {
+    let guild_id = match event.guild_id {
+        Some(guild_id) => guild_id,
+        None => return
+    };
     let db = db!(ctx);
+    let posts = match db.get_cowboard_messages(event.id, event.channel_id, guild_id).await {
+        Ok(posts) if posts.is_empty() => return,
+        Ok(posts) => posts,
+        Err(ex) => {
+            error!("Failed to query cowboard message: {}", ex);
+            return;
+        }
+    };
 
-    match db.get_cowboard_message(message, channel_id, guild_id).await {
-        Ok(message_info) => {
-            if let Some(cowboard_message) = message_info {
-                if let Err(ex) = ctx.http.delete_message(cowboard_message.post_channel_id, cowboard_message.post_id).await {
-                    error!("Failed to delete message: {} {} {}", ex, cowboard_message.post_channel_id, cowboard_message.post_id);
-                }
-            }
+    let (mut boards, rules) = match (db.get_cowboards(guild_id).await, db.get_cowboard_rules(guild_id).await) {
+        (Ok(boards), Ok(rules)) => (boards, rules),
+        (Err(ex), _) | (_, Err(ex)) => {
+            error!("Failed to get cowboard config: {}", ex);
+            return;
         }
+    };
+    // The event only has what changed, so the whole message is fetched again.
+    let message = match event.channel_id.message(&ctx.http, event.id).await {
+        Ok(message) => message,
+        Err(ex) => {
+            error!("Failed to get edited message: {}", ex);
+            return;
+        }
+    };
+
+    for post in posts {
+        let config = match boards.iter_mut().find(|o| o.id == post.board_id) {
+            Some(config) => config,
+            None => continue
+        };
+        let mut post_message = match ctx.http.get_message(post.post_channel_id, post.post_id).await {
+            Ok(post_message) => post_message,
+            Err(ex) => {
+                error!("Failed to get old cowboard message: {}", ex);
+                continue;
+            }
+        };
+
+        let count = match count_reactions(ctx, guild_id, &message, config, &rules).await {
+            Ok(count) => count,
+            Err(ex) => {
+                error!("Failed to count reactions: {}", ex);
+                post.reactions as u64
+            }
+        };
+        let content = rebuild_moo(ctx, &message, &post_message).await;
+        update_moo(ctx, &message, &mut post_message, config, count, Some(&content)).await;
+    }
+}
+
+// Deletes the original's posts too, or marks them as deleted, whichever the server wants.
+pub async fn message_delete(ctx: &Context, channel_id: ChannelId, message: MessageId, guild_id: Option<GuildId>) {
+    let guild_id = match guild_id {
+        Some(guild_id) => guild_id,
+        None => return
+    };
+    let db = db!(ctx);
+    let posts = match db.get_cowboard_messages(message, channel_id, guild_id).await {
+        Ok(posts) if posts.is_empty() => return,
+        Ok(posts) => posts,
         Err(ex) => {
             error!("Failed to query cowboard message: {}", ex);
+            return;
+        }
+    };
+
+    let keep_deleted = match db.get_cowboard_filter(guild_id).await {
+        Ok(filter) => filter.keep_deleted,
+        Err(ex) => {
+            error!("Failed to get cowboard filter: {}", ex);
+            return;
+        }
+    };
+    if !keep_deleted {
+        for post in posts {
+            remove_moo(ctx, &post).await;
+        }
+        return;
+    }
+
+    let mut boards = match db.get_cowboards(guild_id).await {
+        Ok(boards) => boards,
+        Err(ex) => {
+            error!("Failed to get cowboard config: {}", ex);
+            return;
+        }
+    };
+    for post in posts {
+        if let Some(config) = boards.iter_mut().find(|o| o.id == post.board_id) {
+            mark_deleted(ctx, &post, config).await;
         }
     }
+}
+
+// The post stays as it was, with the count line saying the original's gone; its link would lead nowhere anyway.
+async fn mark_deleted(ctx: &Context, post: &CowboardMessage, config: &mut Cowboard) {
+    let mut post_message = match ctx.http.get_message(post.post_channel_id, post.post_id).await {
+        Ok(post_message) => post_message,
+        Err(ex) => {
+            error!("Failed to get old cowboard message: {}", ex);
+            return;
+        }
+    };
+    let content = format!("{} {} | <#{}>\n{}", post.reactions, &config.emote, post.message_channel_id, DELETED_MARKER);
 
-    if let Err(ex) = db.unmoo_message(message, channel_id, guild_id).await {
+    if config.webhook_id.is_some() && config.webhook_token.is_some() {
+        let token = config.webhook_token.clone().unwrap();
+        if let Ok(webhook) = ctx.http.get_webhook_with_token(config.webhook_id.unwrap(), &token).await {
+            if let Err(ex) = webhook.edit_message(&ctx.http, post_message.id, |m| m.content(content)).await {
+                error!("Failed to edit post message??? {}", ex);
+            }
+        } else {
+            disable_webhook(ctx, config).await;
+        }
+    } else if let Err(ex) = post_message.edit(&ctx.http, |m| m.content(content)).await {
+        error!("Failed to edit post message??? {}", ex);
+    }
+}
+
+pub async fn remove_moo(ctx: &Context, cowboard_message: &CowboardMessage) {
+    let db = db!(ctx);
+
+    if let Err(ex) = ctx.http.delete_message(cowboard_message.post_channel_id, cowboard_message.post_id).await {
+        error!("Failed to delete message: {} {} {}", ex, cowboard_message.post_channel_id, cowboard_message.post_id);
+    }
+
+    let (message, channel, guild) = (MessageId::from(cowboard_message.message_id), ChannelId::from(cowboard_message.message_channel_id), GuildId::from(cowboard_message.guild_id));
+    if let Err(ex) = db.unmoo_message(cowboard_message.board_id, message, channel, guild).await {
         error!("Failed to unmoo a message in the database: {}", ex);
     }
-}
\ No newline at end of file
+}
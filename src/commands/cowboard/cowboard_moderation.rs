@@ -0,0 +1,452 @@
+use std::time::Duration;
+use chrono::Utc;
+use serenity::client::Context;
+use serenity::collector::CollectComponentInteraction;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::model::user::User;
+use tracing::error;
+use crate::{CowContext, cowdb, Error};
+use crate::{Database, db};
+use crate::commands::cowboard::cowboard_db_models::{Cowboard, CowboardAction, ModAction};
+use crate::commands::cowboard::cowboard_handler::{MooChange, boards_for_channel, channel_boards, member_can_read, remove_moo, sync_moo};
+
+// How long the context menu's buttons wait for a choice.
+const TIMEOUT: Duration = Duration::from_secs(60);
+const LOG_LENGTH: i32 = 15;
+
+// The boards an action is for: the one named, or every saved board on the server.
+// Says what went wrong and gives back nothing when there aren't any.
+async fn target_boards(ctx: CowContext<'_>, db: &Database, guild_id: GuildId, board: Option<String>) -> Result<Vec<Cowboard>, Error> {
+    let boards = match board {
+        Some(name) => {
+            let config = db.get_cowboard_config(guild_id, &name).await?;
+            if config.id == 0 {
+                ctx.say(format!("There's no cowboard called {name}.")).await?;
+                return Ok(Vec::new());
+            }
+            vec![config]
+        }
+        None => db.get_cowboards(guild_id).await?
+    };
+
+    if boards.is_empty() {
+        ctx.say("This server doesn't have a cowboard yet.").await?;
+    }
+
+    Ok(boards)
+}
+
+async fn log_action(db: &Database, guild_id: GuildId, config: &Cowboard, action: ModAction, target_id: u64, channel_id: Option<u64>, moderator: UserId) -> Result<(), Error> {
+    db.log_cowboard_action(&CowboardAction {
+        server_id: guild_id.0,
+        board_id: config.id,
+        action,
+        target_id,
+        channel_id,
+        moderator_id: moderator.0,
+        created_at: Utc::now()
+    }).await
+}
+
+// Does a moderator's action on one board and records it; says what happened there.
+async fn apply(ctx: &Context, guild_id: GuildId, moderator: UserId, config: &mut Cowboard, action: ModAction, message: &Message) -> Result<String, Error> {
+    let db = db!(ctx);
+    let (message_id, channel_id) = (message.id, message.channel_id);
+
+    let outcome = match action {
+        ModAction::ForcePost => {
+            if config.channel.is_none() {
+                return Ok(format!("The {} cowboard has no channel to post in.", config.name));
+            }
+
+            db.set_moo_override(config.id, message_id, channel_id, guild_id, Some(true)).await?;
+            db.unflag_moo(config.id, message_id, channel_id, guild_id).await?;
+            log_action(&db, guild_id, config, action, message_id.0, Some(channel_id.0), moderator).await?;
+
+            let rules = db.get_cowboard_rules(guild_id).await?;
+            match sync_moo(ctx, guild_id, message, config, &rules).await? {
+                MooChange::Posted => format!("Posted it to the {} cowboard, where it'll stay.", config.name),
+                MooChange::Updated | MooChange::Unchanged => format!("It'll stay up on the {} cowboard.", config.name),
+                MooChange::Removed => format!("It couldn't be kept up on the {} cowboard.", config.name),
+                MooChange::Failed => format!("We couldn't post it to the {} cowboard; maybe we can't send messages in its channel? It'll go up with its next reaction.", config.name)
+            }
+        }
+        ModAction::Remove => {
+            db.set_moo_override(config.id, message_id, channel_id, guild_id, Some(false)).await?;
+            log_action(&db, guild_id, config, action, message_id.0, Some(channel_id.0), moderator).await?;
+
+            match db.get_cowboard_message(config.id, message_id, channel_id, guild_id).await? {
+                Some(post) => {
+                    remove_moo(ctx, &post).await;
+                    format!("Took it down from the {} cowboard, and it'll stay off.", config.name)
+                }
+                None => format!("It'll stay off the {} cowboard.", config.name)
+            }
+        }
+        ModAction::BlockUser | ModAction::UnblockUser => {
+            return block_user(ctx, guild_id, moderator, config, message.author.id, action == ModAction::BlockUser).await;
+        }
+        ModAction::BlockChannel | ModAction::UnblockChannel => {
+            return block_channel(ctx, guild_id, moderator, config, channel_id, action == ModAction::BlockChannel).await;
+        }
+    };
+
+    Ok(outcome)
+}
+
+// Anything they've already had posted stays up.
+async fn block_user(ctx: &Context, guild_id: GuildId, moderator: UserId, config: &Cowboard, user: UserId, blocked: bool) -> Result<String, Error> {
+    let db = db!(ctx);
+
+    db.set_cowboard_user_blocked(config.id, user, blocked).await?;
+    let action = if blocked { ModAction::BlockUser } else { ModAction::UnblockUser };
+    log_action(&db, guild_id, config, action, user.0, None, moderator).await?;
+
+    Ok(if blocked {
+        format!("<@{user}>'s messages won't go to the {} cowboard.", config.name)
+    } else {
+        format!("<@{user}>'s messages can go to the {} cowboard again.", config.name)
+    })
+}
+
+// The same as denying the channel in the board's filter, so `filter` can undo it too.
+async fn block_channel(ctx: &Context, guild_id: GuildId, moderator: UserId, config: &Cowboard, channel: ChannelId, blocked: bool) -> Result<String, Error> {
+    let db = db!(ctx);
+
+    if blocked {
+        db.set_cowboard_channel(config.id, channel, Some(false)).await?;
+    } else if config.denied_channels.contains(&channel.0) {
+        // Only a deny gets cleared; an allow was never a block.
+        db.set_cowboard_channel(config.id, channel, None).await?;
+    } else {
+        return Ok(format!("<#{channel}> wasn't blocked from the {} cowboard.", config.name));
+    }
+    let action = if blocked { ModAction::BlockChannel } else { ModAction::UnblockChannel };
+    log_action(&db, guild_id, config, action, channel.0, None, moderator).await?;
+
+    Ok(if blocked {
+        format!("Messages from <#{channel}> won't go to the {} cowboard.", config.name)
+    } else {
+        format!("Messages from <#{channel}> can go to the {} cowboard again.", config.name)
+    })
+}
+
+// Does the action on every board given, one line each.
+async fn apply_all(ctx: &Context, guild_id: GuildId, moderator: UserId, boards: Vec<Cowboard>, action: ModAction, message: &Message) -> String {
+    let mut lines = Vec::new();
+    for mut config in boards {
+        match apply(ctx, guild_id, moderator, &mut config, action, message).await {
+            Ok(line) => lines.push(line),
+            Err(ex) => {
+                error!("Failed to moderate cowboard message: {}", ex);
+                lines.push(format!("We couldn't update the {} cowboard, sorry... Try again later?", config.name));
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+// Moderators can only act on messages from this server, in channels they can read.
+async fn check_message(ctx: CowContext<'_>, guild_id: GuildId, message: &Message) -> Result<bool, Error> {
+    let readable = member_can_read(ctx.serenity_context(), guild_id, message.channel_id, ctx.author().id).await;
+    if !readable {
+        ctx.say("That message isn't in a channel of this server that you can read.").await?;
+    }
+
+    Ok(readable)
+}
+
+// Forced posts only go to boards that'd take the message anyway; a named board still has to pass the filters,
+// so NSFW or hidden messages don't get pushed somewhere more people can see them.
+async fn force_boards(ctx: CowContext<'_>, db: &Database, guild_id: GuildId, channel: ChannelId, board: Option<String>) -> Result<Vec<Cowboard>, Error> {
+    let boards = match board {
+        Some(_) => {
+            let named = target_boards(ctx, db, guild_id, board).await?;
+            if named.is_empty() {
+                return Ok(named);
+            }
+            boards_for_channel(ctx.serenity_context(), guild_id, channel, named).await
+        }
+        None => channel_boards(ctx.serenity_context(), guild_id, channel).await?
+    };
+    if boards.is_empty() {
+        ctx.say("No cowboard takes messages from that channel.").await?;
+    }
+
+    Ok(boards)
+}
+
+async fn say_quietly(ctx: CowContext<'_>, content: String) -> Result<(), Error> {
+    ctx.send(|m| m.content(content).allowed_mentions(|o| o.empty_parse())).await?;
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Put a message on the cowboard and keep it there, whatever its reactions."),
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn forcepost(
+    ctx: CowContext<'_>,
+    #[description = "A link to the message."] message: Message,
+    #[description = "Which cowboard; leave out for every one that takes messages from its channel."] board: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        if !check_message(ctx, guild_id, &message).await? {
+            return Ok(());
+        }
+
+        let boards = force_boards(ctx, &db, guild_id, message.channel_id, board).await?;
+        if !boards.is_empty() {
+            let content = apply_all(ctx.serenity_context(), guild_id, ctx.author().id, boards, ModAction::ForcePost, &message).await;
+            say_quietly(ctx, content).await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Take a message down from the cowboard and keep it off."),
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn remove(
+    ctx: CowContext<'_>,
+    #[description = "A link to the message."] message: Message,
+    #[description = "Which cowboard; leave out for every one."] board: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        if !check_message(ctx, guild_id, &message).await? {
+            return Ok(());
+        }
+
+        let boards = target_boards(ctx, &db, guild_id, board).await?;
+        if !boards.is_empty() {
+            let content = apply_all(ctx.serenity_context(), guild_id, ctx.author().id, boards, ModAction::Remove, &message).await;
+            say_quietly(ctx, content).await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+async fn set_block(ctx: CowContext<'_>, user: Option<User>, channel: Option<ChannelId>, board: Option<String>, blocked: bool) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            ctx.say("This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+    if user.is_none() && channel.is_none() {
+        ctx.say("Give a user, a channel, or both.").await?;
+        return Ok(());
+    }
+    if let Some(channel) = channel {
+        if !ctx.guild().map(|g| g.channels.contains_key(&channel)).unwrap_or(false) {
+            ctx.say("Could not find channel in this server!").await?;
+            return Ok(())
+        }
+    }
+
+    let serenity = ctx.serenity_context();
+    let mut lines = Vec::new();
+    for config in target_boards(ctx, &db, guild_id, board).await? {
+        let mut results = Vec::new();
+        if let Some(user) = &user {
+            results.push(block_user(serenity, guild_id, ctx.author().id, &config, user.id, blocked).await);
+        }
+        if let Some(channel) = channel {
+            results.push(block_channel(serenity, guild_id, ctx.author().id, &config, channel, blocked).await);
+        }
+
+        for result in results {
+            match result {
+                Ok(line) => lines.push(line),
+                Err(ex) => {
+                    error!("Failed to update cowboard block: {}", ex);
+                    lines.push(format!("We couldn't update the {} cowboard, sorry... Try again later?", config.name));
+                }
+            }
+        }
+    }
+
+    if !lines.is_empty() {
+        say_quietly(ctx, lines.join("\n")).await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Keep an author's messages, or a channel's, off the cowboard."),
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn block(
+    ctx: CowContext<'_>,
+    #[description = "Someone whose messages shouldn't go to the cowboard."] user: Option<User>,
+    #[description = "A channel or category whose messages shouldn't go to the cowboard."] channel: Option<ChannelId>,
+    #[description = "Which cowboard; leave out for every one."] board: Option<String>)
+-> Result<(), Error> {
+    set_block(ctx, user, channel, board, true).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Let a blocked author's messages, or a blocked channel's, go to the cowboard again."),
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn unblock(
+    ctx: CowContext<'_>,
+    #[description = "Someone who was blocked."] user: Option<User>,
+    #[description = "A channel or category that was blocked."] channel: Option<ChannelId>,
+    #[description = "Which cowboard; leave out for every one."] board: Option<String>)
+-> Result<(), Error> {
+    set_block(ctx, user, channel, board, false).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "See what moderators have lately done to the cowboards."),
+    required_permissions = "MANAGE_MESSAGES",
+    discard_spare_arguments
+)]
+pub async fn modlog(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        let (actions, boards) = match (db.get_cowboard_actions(guild_id, LOG_LENGTH).await, db.get_cowboards(guild_id).await) {
+            (Ok(actions), Ok(boards)) => (actions, boards),
+            (Err(ex), _) | (_, Err(ex)) => {
+                ctx.say("We couldn't get the cowboard log... try again later?").await?;
+                error!("Failed to get cowboard actions: {}", ex);
+                return Ok(());
+            }
+        };
+
+        let description = if actions.is_empty() {
+            "Nothing's been done yet.".to_string()
+        } else {
+            actions.iter().map(|o| {
+                let board = boards.iter().find(|b| b.id == o.board_id).map(|b| b.name.as_str()).unwrap_or("(deleted)");
+                let target = match (o.action, o.channel_id) {
+                    (ModAction::ForcePost | ModAction::Remove, Some(channel)) => format!("https://discord.com/channels/{}/{}/{}", guild_id, channel, o.target_id),
+                    (ModAction::BlockUser | ModAction::UnblockUser, _) => format!("<@{}>", o.target_id),
+                    _ => format!("<#{}>", o.target_id)
+                };
+                format!("<t:{}:R> **{}** on {}: {} by <@{}>", o.created_at.timestamp(), o.action, board, target, o.moderator_id)
+            }).collect::<Vec<_>>().join("\n")
+        };
+
+        ctx.send(|m| m.embed(|e| e
+            .title("Cowboard Moderation Log")
+            .description(description)
+        )).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+// Right-clicking a message gives moderators the same actions as the commands, on every board at once.
+#[poise::command(
+    context_menu_command = "Cowboard Moderation",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn moderate(
+    ctx: CowContext<'_>,
+    #[description = "The message to moderate."] message: Message)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            ctx.say("This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+    if !check_message(ctx, guild_id, &message).await? {
+        return Ok(());
+    }
+
+    let reply = ctx.send(|m| m
+        .ephemeral(true)
+        .content(format!("What should happen to this message on the cowboards?\n{}", message.link()))
+        .components(|c| c.create_action_row(|r| r
+            .create_button(|b| b.custom_id("force").label("Force post").style(ButtonStyle::Success))
+            .create_button(|b| b.custom_id("remove").label("Remove").style(ButtonStyle::Danger))
+            .create_button(|b| b.custom_id("author").label("Block author").style(ButtonStyle::Secondary))
+            .create_button(|b| b.custom_id("channel").label("Block channel").style(ButtonStyle::Secondary))
+        ))
+    ).await?;
+    let reply_id = reply.message().await?.id;
+
+    let press = CollectComponentInteraction::new(ctx.serenity_context())
+        .message_id(reply_id)
+        .author_id(ctx.author().id)
+        .timeout(TIMEOUT)
+        .await;
+    let press = match press {
+        Some(press) => press,
+        None => {
+            reply.edit(ctx, |m| m.content("Never mind, then.").components(|c| c)).await?;
+            return Ok(());
+        }
+    };
+
+    let action = match press.data.custom_id.as_str() {
+        "force" => ModAction::ForcePost,
+        "remove" => ModAction::Remove,
+        "author" => ModAction::BlockUser,
+        _ => ModAction::BlockChannel
+    };
+    if let Err(ex) = press.create_interaction_response(ctx.serenity_context(), |r| r
+        .kind(InteractionResponseType::DeferredUpdateMessage)
+    ).await {
+        error!("Failed to respond to button press: {}", ex);
+    }
+
+    let boards = if action == ModAction::ForcePost {
+        channel_boards(ctx.serenity_context(), guild_id, message.channel_id).await?
+    } else {
+        db.get_cowboards(guild_id).await?
+    };
+    let content = if boards.is_empty() {
+        "No cowboard takes messages from that channel.".to_string()
+    } else {
+        apply_all(ctx.serenity_context(), guild_id, ctx.author().id, boards, action, &message).await
+    };
+
+    reply.edit(ctx, |m| m.content(content).components(|c| c)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::{CowContext, cowdb, Error};
+use crate::{Database, db};
+use crate::commands::cowboard::cowboard_db_models::{CowboardMessage, CowboardRanking};
+
+const TOP_MEMBERS: i32 = 5;
+const TOP_POSTS: i32 = 10;
+
+fn format_rankings(rankings: &[CowboardRanking], mention: fn(u64) -> String) -> String {
+    if rankings.is_empty() {
+        return "Nobody yet!".to_string();
+    }
+
+    rankings.iter().enumerate()
+        .map(|(i, o)| format!("{}. {}: {} posts ({} reactions)", i + 1, mention(o.id), o.posts, o.reactions))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_post(post: &CowboardMessage) -> String {
+    let author = post.author_id.map(|o| format!("<@{o}>")).unwrap_or_else(|| "someone".to_string());
+    format!("**{}** reactions, {} in <#{}> ([jump]({}))", post.reactions, author, post.message_channel_id, post.link())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "See who and where gets mooed the most, and the moo of the day."),
+    discard_spare_arguments
+)]
+pub async fn stats(
+    ctx: CowContext<'_>,
+    #[description = "The name of a cowboard; leave out to count them all."] board: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        let board_id = match &board {
+            Some(name) => match db.get_cowboard_config(guild_id, name).await?.id {
+                0 => {
+                    ctx.say(format!("There's no cowboard called {name}.")).await?;
+                    return Ok(());
+                }
+                id => Some(id)
+            },
+            None => None
+        };
+
+        let stats = db.get_cowboard_stats(guild_id, board_id, TOP_MEMBERS).await?;
+        if stats.posts == 0 {
+            ctx.say("Nothing's been mooed yet!").await?;
+            return Ok(());
+        }
+
+        // Changes once a day, but stays put for everyone who asks that day.
+        let day = (chrono::Utc::now().timestamp() / 86400) as u64;
+        let moo_of_the_day = db.get_random_moo(guild_id, board_id, day.wrapping_mul(2654435761)).await?;
+
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| {
+                e
+                    .title(match &board {
+                        Some(name) => format!("Cowboard Stats: {name}"),
+                        None => "Cowboard Stats".to_string()
+                    })
+                    .description(format!("{} posts, with {} reactions between them.", stats.posts, stats.reactions))
+                    .field("Most Mooed Members", format_rankings(&stats.users, |o| format!("<@{o}>")), false)
+                    .field("Most Mooed Channels", format_rankings(&stats.channels, |o| format!("<#{o}>")), false);
+                if let Some(post) = &moo_of_the_day {
+                    e.field("Moo of the Day", format_post(post), false);
+                }
+                e
+            })
+        }).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "See the most reacted posts of all time."),
+    discard_spare_arguments
+)]
+pub async fn top(
+    ctx: CowContext<'_>,
+    #[description = "The name of a cowboard; leave out to count them all."] board: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        let board_id = match &board {
+            Some(name) => match db.get_cowboard_config(guild_id, name).await?.id {
+                0 => {
+                    ctx.say(format!("There's no cowboard called {name}.")).await?;
+                    return Ok(());
+                }
+                id => Some(id)
+            },
+            None => None
+        };
+
+        let posts = db.get_top_moos(guild_id, board_id, TOP_POSTS).await?;
+        if posts.is_empty() {
+            ctx.say("Nothing's been mooed yet!").await?;
+            return Ok(());
+        }
+
+        let content = posts.iter().enumerate()
+            .map(|(i, o)| format!("`{}.` {}", i + 1, format_post(o)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| e
+                .title(match &board {
+                    Some(name) => format!("Top Cowboard Posts: {name}"),
+                    None => "Top Cowboard Posts".to_string()
+                })
+                .description(content)
+            )
+        }).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
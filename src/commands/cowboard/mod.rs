@@ -1,18 +1,28 @@
+mod cowboard_backfill;
+pub mod cowboard_bursts;
 mod cowboard_config;
-mod cowboard_db;
+pub mod cowboard_db;
+mod cowboard_db_sqlite;
 mod cowboard_db_models;
+mod cowboard_moderation;
+mod cowboard_stats;
 pub mod cowboard_handler;
 
+use cowboard_backfill::*;
 use cowboard_config::*;
+use cowboard_moderation::*;
+use cowboard_stats::*;
 use crate::{CowContext, Error};
 
+pub use cowboard_moderation::moderate;
+
 #[poise::command(prefix_command, slash_command,
-    subcommands("info", "emote", "addthreshold", "removethreshold", "channel", "webhook"),
+    subcommands("info", "create", "emote", "addthreshold", "removethreshold", "channel", "webhook", "filter", "serverfilter", "safety", "ondelete", "rules", "approve", "forcepost", "remove", "block", "unblock", "modlog", "delete", "stats", "top", "backfill"),
     discard_spare_arguments,
-    description_localized("en-US", "Commands for modifying how the cowboard (starboard) functions."),
+    description_localized("en-US", "Commands for modifying how the cowboards (starboards) function."),
     guild_only,
     identifying_name = "Cowboard"
 )]
 pub async fn cowboard(ctx: CowContext<'_>) -> Result<(), Error> {
-    info_code(ctx).await
+    info_code(ctx, None).await
 }
\ No newline at end of file
@@ -1,5 +1,11 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
 use serenity::{
+    builder::CreateEmbed,
     model::{
+        channel::AttachmentType,
         id::{
             UserId,
             GuildId
@@ -9,6 +15,9 @@ use serenity::{
     utils::MessageBuilder
 };
 use crate::{Database, db, cowdb, Error, CowContext};
+use crate::models::db_models::{XpWindow, XP_LOG_DAYS};
+use crate::util::paginator::{paginate, PageSource};
+use crate::util::rank_card::{RankCard, DEFAULT_COLOUR};
 use tracing::{error};
 
 // This prevents us from executing commands when the server has it disabled.
@@ -37,7 +46,7 @@ async fn rank_embed(ctx: &CowContext<'_>, server_id: &GuildId, user: &User) {
     let experience = db.get_xp(*server_id, user.id).await.unwrap();
     let xp = experience.xp;
     let level = experience.level;
-    let next_level_xp = db.calculate_level(level).await.unwrap();
+    let next_level_xp = db.get_xp_settings(*server_id).await.unwrap().xp_for_level(level);
 
     let current_role = db.get_highest_role(*server_id, level).await.unwrap();
     let mut current_role_str: String = String::from("No role");
@@ -77,6 +86,90 @@ async fn rank_embed(ctx: &CowContext<'_>, server_id: &GuildId, user: &User) {
     }
 }
 
+async fn fetch_avatar(url: &str) -> Option<Vec<u8>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .ok()?;
+
+    match client.get(url).send().await.and_then(|o| o.error_for_status()) {
+        Ok(response) => response.bytes().await.ok().map(|o| o.to_vec()),
+        Err(ex) => {
+            error!("Failed to download avatar: {}", ex);
+            None
+        }
+    }
+}
+
+async fn rank_card(ctx: &CowContext<'_>, server_id: &GuildId, user: &User) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    let cache = &ctx.serenity_context().cache;
+
+    let experience = db.get_xp(*server_id, user.id).await?;
+    let next_level_xp = db.get_xp_settings(*server_id).await?.xp_for_level(experience.level);
+    let rank = db.rank_within_members(*server_id, user.id).await?;
+    let rank_role = db.get_highest_role(*server_id, experience.level).await?
+        .and_then(|o| cache.role(*server_id, o));
+
+    // The rank role's colour if it has one, otherwise whatever colour their name shows up as.
+    let colour = rank_role.as_ref()
+        .map(|o| o.colour)
+        .filter(|o| o.0 != 0)
+        .or_else(|| cache.member(*server_id, user.id).and_then(|o| o.colour(cache)))
+        .map(|o| (o.r(), o.g(), o.b()))
+        .unwrap_or(DEFAULT_COLOUR);
+
+    // Discord serves the same avatar as a PNG, which saves us from decoding WebP.
+    let avatar_url = user.static_avatar_url()
+        .map(|o| o.replace(".webp?size=1024", ".png?size=256"))
+        .unwrap_or_else(|| user.default_avatar_url());
+
+    let card = RankCard {
+        name: user.name.clone(),
+        discriminator: user.discriminator,
+        level: experience.level,
+        xp: experience.xp,
+        next_level_xp,
+        rank,
+        role_name: rank_role.map(|o| o.name),
+        colour,
+        avatar: fetch_avatar(&avatar_url).await
+    };
+
+    // Drawing is all CPU, so keep it off the async threads.
+    let png = tokio::task::spawn_blocking(move || card.render()).await??;
+
+    ctx.send(|m| m
+        .attachment(AttachmentType::Bytes {
+            data: Cow::Owned(png),
+            filename: format!("rank-{}.png", user.id)
+        })
+    ).await?;
+
+    Ok(())
+}
+
+async fn show_rank(ctx: &CowContext<'_>, server_id: &GuildId, user: &User, embed: Option<bool>) {
+    let db = cowdb!(ctx);
+
+    let use_embed = match embed {
+        Some(embed) => embed,
+        None => db.get_rank_embed(*server_id).await.unwrap_or_else(|ex| {
+            error!("Failed to get rank card setting: {}", ex);
+            false
+        })
+    };
+
+    if !use_embed {
+        match rank_card(ctx, server_id, user).await {
+            Ok(_) => return,
+            Err(ex) => error!("Failed to send rank card, falling back to an embed: {}", ex)
+        }
+    }
+
+    rank_embed(ctx, server_id, user).await;
+}
+
 #[poise::command(
     prefix_command,
     slash_command,
@@ -86,7 +179,8 @@ async fn rank_embed(ctx: &CowContext<'_>, server_id: &GuildId, user: &User) {
 )]
 pub async fn rank(
     ctx: CowContext<'_>,
-    #[description = "A user to check their rank"] user: Option<UserId>)
+    #[description = "A user to check their rank"] user: Option<UserId>,
+    #[description = "Show a plain embed instead of an image card"] embed: Option<bool>)
 -> Result<(), Error> {
     if let Some(server_id) = ctx.guild_id() {
         if guild_disabled(&ctx, &server_id).await {
@@ -95,12 +189,12 @@ pub async fn rank(
 
         if let Some(other_id) = user {
             if let Ok(other_user) = other_id.to_user(&ctx).await {
-                rank_embed(&ctx, &server_id, &other_user).await;
+                show_rank(&ctx, &server_id, &other_user, embed).await;
             } else {
                 ctx.say("Could not find user...").await?;
             }
         } else {
-            rank_embed(&ctx, &server_id, ctx.author()).await;
+            show_rank(&ctx, &server_id, ctx.author(), embed).await;
         }
     } else {
         ctx.say("This command can only be run in a server.").await?;
@@ -109,6 +203,118 @@ pub async fn rank(
     Ok(())
 }
 
+// Matches the page size top_members uses.
+const LEVELS_PER_PAGE: usize = 10;
+
+struct Leaderboard {
+    db: Arc<Database>,
+    server_id: GuildId,
+    user_id: UserId,
+    // None is the lifetime leaderboard.
+    window: Option<(XpWindow, String)>
+}
+
+impl Leaderboard {
+    async fn lifetime(&self, page: usize, embed: &mut CreateEmbed) -> Result<usize, Error> {
+        let pagination = self.db.top_members(self.server_id, page as i32).await?;
+        let content = pagination.members.into_iter()
+            .enumerate()
+            .map(|o| {
+                let (index, member) = o;
+                format!("`#{}` <@{}> - Level {}, {} xp", index + LEVELS_PER_PAGE * page + 1, member.id, member.exp.level, member.exp.xp)
+            })
+            .reduce(|a, b| {format!("{a}\n{b}")})
+            .unwrap_or_else(|| "There is nothing on this page.".to_string());
+
+        embed
+            .title("Top Users")
+            .description(content)
+            .footer(|e| e.text(format!("Page {}/{}", page + 1, pagination.last_page.max(1))));
+
+        Ok(pagination.last_page.max(0) as usize)
+    }
+
+    async fn windowed(&self, window: XpWindow, title: &str, page: usize, embed: &mut CreateEmbed) -> Result<usize, Error> {
+        let pagination = self.db.top_members_in(self.server_id, window, page as i32).await?;
+        let content = pagination.members.into_iter()
+            .enumerate()
+            .map(|o| {
+                let (index, member) = o;
+                format!("`#{}` <@{}> - {} xp", index + LEVELS_PER_PAGE * page + 1, member.id, member.xp)
+            })
+            .reduce(|a, b| {format!("{a}\n{b}")})
+            .unwrap_or_else(|| "Nobody has earned any XP here yet.".to_string());
+
+        embed
+            .title(format!("Top Users ({title})"))
+            .description(content)
+            .footer(|e| e.text(format!("Page {}/{}", page + 1, pagination.last_page.max(1))));
+
+        Ok(pagination.last_page.max(0) as usize)
+    }
+}
+
+#[async_trait]
+impl PageSource for Leaderboard {
+    async fn render(&self, page: usize, embed: &mut CreateEmbed) -> Result<usize, Error> {
+        match &self.window {
+            Some((window, title)) => self.windowed(*window, title, page, embed).await,
+            None => self.lifetime(page, embed).await
+        }
+    }
+
+    async fn author_page(&self) -> Result<Option<usize>, Error> {
+        let rank = match &self.window {
+            Some((window, _)) => self.db.rank_within_window(self.server_id, self.user_id, *window).await?,
+            None => self.db.rank_within_members(self.server_id, self.user_id).await?
+        };
+        Ok(rank.map(|o| (o.max(1) as usize - 1) / LEVELS_PER_PAGE))
+    }
+}
+
+// Turns the period/from/to options into a window and a title; Err is the message to show the user.
+async fn pick_window(db: &Database, server_id: GuildId, period: Option<String>, from: Option<String>, to: Option<String>) -> Result<Option<(XpWindow, String)>, String> {
+    if let Some(from) = from {
+        let window = XpWindow::parse_range(&from, to.as_deref())
+            .ok_or_else(|| "Dates must look like 2024-01-31, and the end can't be before the start.".to_string())?;
+        if matches!(window, XpWindow::Between(start, _) if start < XpWindow::kept_since()) {
+            return Err(format!("Only the last {XP_LOG_DAYS} days of XP are kept, so a range can't start before then."));
+        }
+        let title = match to {
+            Some(to) => format!("{} to {}", from.trim(), to.trim()),
+            None => format!("since {}", from.trim())
+        };
+        return Ok(Some((window, title)));
+    }
+
+    let period = match period {
+        Some(period) => period.trim().to_string(),
+        None => return Ok(None)
+    };
+
+    match period.to_lowercase().as_str() {
+        "" | "all" | "lifetime" => Ok(None),
+        "week" | "weekly" => Ok(Some((XpWindow::this_week(), "this week".to_string()))),
+        "month" | "monthly" => Ok(Some((XpWindow::this_month(), "this month".to_string()))),
+        name => {
+            let seasons = db.get_seasons(server_id).await.map_err(|ex| {
+                error!("Failed to get seasons: {}", ex);
+                "Failed to get the seasons.".to_string()
+            })?;
+
+            let season = if name == "season" {
+                seasons.into_iter().find(|o| o.ended_at.is_none())
+                    .ok_or_else(|| "There is no season running right now.".to_string())?
+            } else {
+                seasons.into_iter().find(|o| o.name.to_lowercase() == name)
+                    .ok_or_else(|| "The period must be \"all\", \"week\", \"month\", \"season\", or the name of a season.".to_string())?
+            };
+
+            Ok(Some((season.window(), season.name)))
+        }
+    }
+}
+
 #[poise::command(
     prefix_command,
     slash_command,
@@ -117,7 +323,10 @@ pub async fn rank(
 )]
 pub async fn levels(
     ctx: CowContext<'_>,
-    #[description = "The page of rankings to fetch"] #[min = 1] page: Option<i32>)
+    #[description = "The page of rankings to start on"] #[min = 1] page: Option<i32>,
+    #[description = "\"all\", \"week\", \"month\", \"season\", or the name of a season"] period: Option<String>,
+    #[description = "Only count XP from this day on (YYYY-MM-DD, UTC)"] from: Option<String>,
+    #[description = "Only count XP up to this day (YYYY-MM-DD, UTC)"] to: Option<String>)
 -> Result<(), Error> {
     let db = cowdb!(ctx);
     if let Some(server_id) = ctx.guild_id() {
@@ -125,30 +334,19 @@ pub async fn levels(
             return Ok(());
         }
 
-        let level_page = page.unwrap_or(1).max(1);
-        match db.top_members(server_id, level_page - 1).await {
-            Ok(pagination) => {
-                let content = pagination.members.into_iter()
-                    .enumerate()
-                    .map(|o| {
-                        let (index, member) = o;
-                        format!("`#{}` <@{}> - Level {}, {} xp", (index as i32) + 10 * (level_page - 1) + 1, member.id, member.exp.level, member.exp.xp)
-                    })
-                    .reduce(|a, b| {format!("{a}\n{b}")})
-                    .unwrap_or_else(|| "There is nothing on this page.".to_string());
-                ctx.send(|m| {
-                    m.embeds.clear();
-                    m.embed(|e|
-                        e
-                            .title("Top Users")
-                            .description(content)
-                            .footer(|e| e.text(format!("Page {}/{}", level_page, pagination.last_page)))
-                    )}).await?;
-            },
-            Err(ex) => {
-                ctx.say("Failed to get rankings.".to_string()).await?;
-                error!("Failed to get rankings: {}", ex);
+        let window = match pick_window(&db, server_id, period, from, to).await {
+            Ok(window) => window,
+            Err(message) => {
+                ctx.say(message).await?;
+                return Ok(());
             }
+        };
+
+        let level_page = page.unwrap_or(1).max(1);
+        let leaderboard = Leaderboard { db, server_id, user_id: ctx.author().id, window };
+        if let Err(ex) = paginate(ctx, leaderboard, (level_page - 1) as usize).await {
+            ctx.say("Failed to get rankings.".to_string()).await?;
+            error!("Failed to get rankings: {}", ex);
         }
     } else {
         ctx.say("This command can only be run in a server.").await?;
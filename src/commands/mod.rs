@@ -21,7 +21,7 @@ use crate::commands::general::*;
 use crate::commands::rank_config::rankconfig;
 use crate::commands::timeout::timeout;
 use crate::commands::ucm::ucm;
-use crate::commands::cowboard::cowboard;
+use crate::commands::cowboard::{cowboard, moderate};
 use crate::commands::music::music;
 use crate::commands::minecraft::*;
 
@@ -43,6 +43,7 @@ pub async fn get_framework(pref: &str, _app_id: UserId, owners: HashSet<UserId>)
             timeout(),
             ucm(),
             cowboard(),
+            moderate(),
             music(),
             reimu(),
             marisa(),
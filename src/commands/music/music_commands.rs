@@ -1,9 +1,12 @@
+use async_trait::async_trait;
 use lavalink_rs::model::{TrackQueue};
 use tracing::error;
 use regex::Regex;
+use serenity::builder::CreateEmbed;
 use serenity::utils::MessageBuilder;
 use crate::{Error, Lavalink};
 use crate::commands::music::spotify;
+use crate::util::paginator::{paginate, PageSource};
 use crate::CowContext;
 
 #[poise::command(
@@ -407,6 +410,36 @@ fn generate_queue(queue: &[TrackQueue]) -> Vec<String> {
     output
 }
 
+// A snapshot of the queue when the command was run, so paging doesn't jump around as songs finish.
+struct QueuePages {
+    pages: Vec<String>,
+    now_playing: Option<String>,
+    server_name: Option<String>
+}
+
+#[async_trait]
+impl PageSource for QueuePages {
+    async fn render(&self, page: usize, embed: &mut CreateEmbed) -> Result<usize, Error> {
+        let page_num = page.min(self.pages.len() - 1);
+
+        embed
+            .author(|a| {
+                if let Some(server) = &self.server_name {
+                    a.name(format!("Player Queue | Page {}/{} | Playing in {}", page_num + 1, self.pages.len(), server));
+                } else {
+                    a.name(format!("Player Queue | Page {}/{}", page_num + 1, self.pages.len()));
+                }
+
+                a
+            })
+            .title("Now Playing")
+            .description(self.now_playing.as_deref().unwrap_or("Nothing is playing."))
+            .field("Queued", &self.pages[page_num], false);
+
+        Ok(self.pages.len())
+    }
+}
+
 #[poise::command(
     prefix_command,
     slash_command,
@@ -416,59 +449,24 @@ fn generate_queue(queue: &[TrackQueue]) -> Vec<String> {
 )]
 pub async fn queue(
     ctx: CowContext<'_>,
-    #[description = "The page of the queue to display"] #[min = 1] page: Option<usize>)
+    #[description = "The page of the queue to start on"] #[min = 1] page: Option<usize>)
 -> Result<(), Error> {
     let lava_client = {
         let data = ctx.serenity_context().data.read().await;
         data.get::<Lavalink>().unwrap().clone()
     };
 
-    let mut page_num = if let Some(arg_page) = page {
-        arg_page
-    } else {
-        1
-    };
+    let page_num = page.unwrap_or(1).max(1);
 
     let guild_id = ctx.guild_id().unwrap();
-    if let Some(node) = lava_client.nodes().await.get(&guild_id.0) {
-        let queue = &node.queue;
-        let pages = generate_queue(queue);
-
-        if page_num > pages.len() {
-            page_num = pages.len();
-        } else if page_num == 0 {
-            page_num = 1;
-        }
-
-        let page = &pages[page_num - 1];
-        let server_name = guild_id.name(ctx.serenity_context());
-
-        ctx.send(|m| {
-            m.embeds.clear();
-            m.embed(|e| {
-                e
-                    .author(|a| {
-                        if let Some(server) = server_name {
-                            a.name(format!("Player Queue | Page {}/{} | Playing in {}", page_num, pages.len(), server));
-                        } else {
-                            a.name(format!("Player Queue | Page {}/{}", page_num, pages.len()));
-                        }
-
-                        a
-                    })
-                    .title("Now Playing")
-                    .field("Queued", page, false);
-
-                if let Some(now_playing) = &node.now_playing {
-                    e.description(generate_line(now_playing));
-                } else {
-                    e.description("Nothing is playing.");
-                }
-
-                e
-            })
-        }).await?;
-
+    let queue_pages = lava_client.nodes().await.get(&guild_id.0).map(|node| QueuePages {
+        pages: generate_queue(&node.queue),
+        now_playing: node.now_playing.as_ref().map(generate_line),
+        server_name: guild_id.name(ctx.serenity_context())
+    });
+
+    if let Some(queue_pages) = queue_pages {
+        paginate(ctx, queue_pages, page_num - 1).await?;
     } else {
         ctx.say("Nothing is playing at the moment.").await?;
     }
@@ -0,0 +1,204 @@
+use serenity::model::id::ChannelId;
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+use crate::models::db_models::{AnnounceSettings, AnnounceTarget, DEFAULT_ANNOUNCE_TEMPLATE};
+
+// Parameters: rankconfig announce target [here | channel | dm | off] [channel]
+
+// Leaves room in the embed for the rank and role-failure lines.
+const MAX_TEMPLATE_LENGTH: usize = 1000;
+const MAX_EVERY: i32 = 1000;
+
+fn describe_target(settings: &AnnounceSettings) -> String {
+    match (settings.target, settings.channel_id) {
+        (AnnounceTarget::Here, _) => "Where they levelled up".to_string(),
+        (AnnounceTarget::Channel, Some(channel_id)) => format!("<#{channel_id}>"),
+        (AnnounceTarget::Channel, None) => "Where they levelled up (no channel set)".to_string(),
+        (AnnounceTarget::Dm, _) => "Their DMs".to_string(),
+        (AnnounceTarget::Off, _) => "Nowhere".to_string()
+    }
+}
+
+fn describe_when(settings: &AnnounceSettings) -> String {
+    match (settings.roles_only, settings.every) {
+        (true, _) => "Only on a new rank".to_string(),
+        (false, every) if every <= 1 => "Every level".to_string(),
+        (false, every) => format!("Every {every} levels, and on a new rank")
+    }
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("announce_show", "announce_target", "announce_template", "announce_when"),
+    description_localized("en-US", "Configure where and how often level-ups are announced."),
+    discard_spare_arguments
+)]
+pub async fn announce(ctx: CowContext<'_>) -> Result<(), Error> {
+    announce_show_code(ctx).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "show",
+    description_localized("en-US", "Show the level-up announcement settings for this server."),
+    discard_spare_arguments
+)]
+pub async fn announce_show(ctx: CowContext<'_>) -> Result<(), Error> {
+    announce_show_code(ctx).await
+}
+
+pub async fn announce_show_code(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let settings = db.get_announce_settings(guild_id).await?;
+
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| e
+                .title("Level-up Announcements")
+                .field("Sent to", describe_target(&settings), false)
+                .field("When", describe_when(&settings), false)
+                .field("Template", format!("`{}`", settings.template.as_deref().unwrap_or(DEFAULT_ANNOUNCE_TEMPLATE)), false)
+                .footer(|f| f.text("Templates can use {user}, {old_level}, {level} and {role}."))
+            )
+        }).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "target",
+    description_localized("en-US", "Send level-ups where they happened, to a channel, by DM, or nowhere.")
+)]
+pub async fn announce_target(
+    ctx: CowContext<'_>,
+    #[description = "\"here\", \"channel\", \"dm\", or \"off\""] target: String,
+    #[description = "The channel to announce in, for \"channel\""] channel: Option<ChannelId>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let target = match AnnounceTarget::parse(&target) {
+            Some(target) => target,
+            None => {
+                ctx.say("The target must be \"here\", \"channel\", \"dm\", or \"off\".").await?;
+                return Ok(());
+            }
+        };
+
+        if target == AnnounceTarget::Channel && channel.is_none() {
+            ctx.say("Give a channel to announce level-ups in.").await?;
+            return Ok(());
+        }
+
+        let mut settings = db.get_announce_settings(guild_id).await?;
+        settings.target = target;
+        if target == AnnounceTarget::Channel {
+            settings.channel_id = channel;
+        }
+
+        match db.set_announce_settings(&settings).await {
+            Ok(_) => { ctx.say(format!("Level-ups now go to: {}.", describe_target(&settings).to_lowercase())).await?; }
+            Err(ex) => {
+                error!("Failed to set announcement target: {}", ex);
+                ctx.say("Failed to set where level-ups are announced.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "template",
+    description_localized("en-US", "Set the level-up message; leave it empty to go back to the default.")
+)]
+pub async fn announce_template(
+    ctx: CowContext<'_>,
+    #[description = "The message, using {user}, {old_level}, {level} and {role}"] #[rest] template: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let template = template.map(|o| o.trim().to_string()).filter(|o| !o.is_empty());
+        if template.as_ref().map(|o| o.chars().count() > MAX_TEMPLATE_LENGTH).unwrap_or(false) {
+            ctx.say(format!("The template can be at most {MAX_TEMPLATE_LENGTH} characters.")).await?;
+            return Ok(());
+        }
+
+        let mut settings = db.get_announce_settings(guild_id).await?;
+        settings.template = template;
+
+        match db.set_announce_settings(&settings).await {
+            Ok(_) => {
+                match &settings.template {
+                    Some(template) => { ctx.say(format!("Level-ups will now say: `{template}`")).await?; }
+                    None => { ctx.say("Level-ups are back to the default message.").await?; }
+                }
+            }
+            Err(ex) => {
+                error!("Failed to set announcement template: {}", ex);
+                ctx.say("Failed to set the level-up message.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "when",
+    description_localized("en-US", "Announce every level, every Nth level, or only when someone gets a new rank.")
+)]
+pub async fn announce_when(
+    ctx: CowContext<'_>,
+    #[description = "Announce every this many levels (1 is every level)"] every: i32,
+    #[description = "Only announce new ranks, ignoring the above"] roles_only: Option<bool>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        if !(1..=MAX_EVERY).contains(&every) {
+            ctx.say(format!("The number of levels must be between 1 and {MAX_EVERY}.")).await?;
+            return Ok(());
+        }
+
+        let mut settings = db.get_announce_settings(guild_id).await?;
+        settings.every = every;
+        settings.roles_only = roles_only.unwrap_or(false);
+
+        match db.set_announce_settings(&settings).await {
+            Ok(_) => { ctx.say(format!("Level-ups are now announced: {}.", describe_when(&settings).to_lowercase())).await?; }
+            Err(ex) => {
+                error!("Failed to set announcement frequency: {}", ex);
+                ctx.say("Failed to set how often level-ups are announced.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
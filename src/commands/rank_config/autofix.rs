@@ -0,0 +1,127 @@
+use serenity::model::id::ChannelId;
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+use crate::models::db_models::AutoFixSettings;
+
+// Parameters: rankconfig autofix set [hours] [channel] [multiple] [remove] [demote]
+
+const MAX_HOURS: i32 = 24 * 30;
+
+fn yes_no(value: bool) -> String {
+    if value { "Yes".to_string() } else { "No".to_string() }
+}
+
+fn describe_settings(settings: &AutoFixSettings) -> Vec<(&'static str, String)> {
+    vec![
+        ("Runs", match settings.every_hours {
+            0 => "Off".to_string(),
+            1 => "Every hour".to_string(),
+            hours => format!("Every {hours} hours")
+        }),
+        ("Reports to", settings.log_channel.map(|o| format!("<#{o}>")).unwrap_or_else(|| "Nowhere".to_string())),
+        ("Fix multiple roles", yes_no(settings.options.multiple)),
+        ("Revoke ranks", yes_no(settings.options.remove)),
+        ("Demote", yes_no(settings.options.demote))
+    ]
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("autofix_show", "autofix_set"),
+    description_localized("en-US", "Run the rank role fix on a schedule, so hand-edited roles don't drift."),
+    discard_spare_arguments
+)]
+pub async fn autofix(ctx: CowContext<'_>) -> Result<(), Error> {
+    autofix_show_code(ctx).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "show",
+    description_localized("en-US", "Show the scheduled role fix settings for this server."),
+    discard_spare_arguments
+)]
+pub async fn autofix_show(ctx: CowContext<'_>) -> Result<(), Error> {
+    autofix_show_code(ctx).await
+}
+
+pub async fn autofix_show_code(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let settings = db.get_auto_fix_settings(guild_id).await?;
+
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| {
+                e.title("Scheduled Role Fix");
+                for (name, description) in describe_settings(&settings) {
+                    e.field(name, description, false);
+                }
+                if let Some(last_run) = settings.last_run {
+                    e.timestamp(last_run).footer(|f| f.text("Last run"));
+                }
+                e
+            })
+        }).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "set",
+    description_localized("en-US", "Change the scheduled role fix; anything left out stays the same, and 0 hours turns it off.")
+)]
+pub async fn autofix_set(
+    ctx: CowContext<'_>,
+    #[description = "How many hours between each run"] hours: Option<i32>,
+    #[description = "The channel to post a summary in when something was wrong"] channel: Option<ChannelId>,
+    #[description = "Fix users with multiple valid ranks"] multiple: Option<bool>,
+    #[description = "Remove ranks from people who shouldn't have a rank"] remove: Option<bool>,
+    #[description = "Demote users who have a higher rank than they should"] demote: Option<bool>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let mut settings = db.get_auto_fix_settings(guild_id).await?;
+        settings.every_hours = hours.unwrap_or(settings.every_hours);
+        settings.log_channel = channel.or(settings.log_channel);
+        settings.options.multiple = multiple.unwrap_or(settings.options.multiple);
+        settings.options.remove = remove.unwrap_or(settings.options.remove);
+        settings.options.demote = demote.unwrap_or(settings.options.demote);
+
+        if !(0..=MAX_HOURS).contains(&settings.every_hours) {
+            ctx.say(format!("The number of hours must be between 0 and {MAX_HOURS}.")).await?;
+            return Ok(());
+        }
+
+        match db.set_auto_fix_settings(&settings).await {
+            Ok(_) => {
+                let content = describe_settings(&settings).into_iter()
+                    .map(|(name, description)| format!("{name}: {description}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ctx.say(format!("Updated the scheduled role fix.\n{content}")).await?;
+            }
+            Err(ex) => {
+                error!("Failed to set scheduled role fix settings: {}", ex);
+                ctx.say("Failed to update the scheduled role fix.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
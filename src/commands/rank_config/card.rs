@@ -0,0 +1,28 @@
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Switch the rank command between an image card and a plain embed."),
+    discard_spare_arguments
+)]
+pub async fn card(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        match db.toggle_rank_embed(guild_id).await {
+            Ok(true) => { ctx.say("`rank` now shows a plain embed. Members can still ask for a card with the `embed` option.").await?; }
+            Ok(false) => { ctx.say("`rank` now shows an image card. Members can still ask for the plain embed with the `embed` option.").await?; }
+            Err(ex) => {
+                error!("Failed to toggle rank card: {}", ex);
+                ctx.say("Failed to toggle the rank card.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
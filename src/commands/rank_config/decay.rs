@@ -0,0 +1,130 @@
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+use crate::models::db_models::DecaySettings;
+use crate::services::decay_handler::reconcile_departed;
+
+// Parameters: rankconfig decay set [after_days] [percent] [demote] [hide_departed]
+
+const MAX_AFTER_DAYS: i32 = 3650;
+
+fn describe_settings(settings: &DecaySettings) -> Vec<(&'static str, String)> {
+    let after = if settings.enabled() {
+        format!("After {} days without earning XP, lose {}% a week", settings.after_days, settings.percent)
+    } else {
+        "Off".to_string()
+    };
+
+    vec![
+        ("Decay", after),
+        ("Take away ranks", if settings.demote { "Yes".to_string() } else { "No".to_string() }),
+        ("Hide members who left", if settings.hide_departed { "Yes".to_string() } else { "No".to_string() })
+    ]
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("decay_show", "decay_set"),
+    description_localized("en-US", "Slowly take XP away from members who've stopped talking."),
+    discard_spare_arguments
+)]
+pub async fn decay(ctx: CowContext<'_>) -> Result<(), Error> {
+    decay_show_code(ctx).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "show",
+    description_localized("en-US", "Show the XP decay settings for this server."),
+    discard_spare_arguments
+)]
+pub async fn decay_show(ctx: CowContext<'_>) -> Result<(), Error> {
+    decay_show_code(ctx).await
+}
+
+pub async fn decay_show_code(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let settings = db.get_decay_settings(guild_id).await?;
+
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| {
+                e.title("XP Decay Settings");
+                for (name, description) in describe_settings(&settings) {
+                    e.field(name, description, false);
+                }
+                e
+            })
+        }).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "set",
+    description_localized("en-US", "Change how XP decays; anything left out stays the same, and 0 days turns it off.")
+)]
+pub async fn decay_set(
+    ctx: CowContext<'_>,
+    #[description = "Days without earning XP before someone starts losing it"] after_days: Option<i32>,
+    #[description = "Percentage of their XP lost each week after that"] percent: Option<f64>,
+    #[description = "Whether to take away rank roles they drop below"] demote: Option<bool>,
+    #[description = "Whether to leave members who left off the leaderboards"] hide_departed: Option<bool>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let mut settings = db.get_decay_settings(guild_id).await?;
+        let was_hiding = settings.hide_departed;
+        settings.after_days = after_days.unwrap_or(settings.after_days);
+        settings.percent = percent.unwrap_or(settings.percent);
+        settings.demote = demote.unwrap_or(settings.demote);
+        settings.hide_departed = hide_departed.unwrap_or(settings.hide_departed);
+
+        if !(0..=MAX_AFTER_DAYS).contains(&settings.after_days) {
+            ctx.say(format!("The number of days must be between 0 and {MAX_AFTER_DAYS}.")).await?;
+            return Ok(());
+        }
+
+        if !(0.0..=100.0).contains(&settings.percent) {
+            ctx.say("The percentage must be between 0 and 100.").await?;
+            return Ok(());
+        }
+
+        match db.set_decay_settings(&settings).await {
+            Ok(_) => {
+                let content = describe_settings(&settings).into_iter()
+                    .map(|(name, description)| format!("{name}: {description}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ctx.say(format!("Updated the XP decay settings.\n{content}")).await?;
+
+                // Only people leaving from now on get noticed as it happens, so catch up on everyone who already has.
+                if settings.hide_departed && !was_hiding {
+                    let http = ctx.serenity_context().http.clone();
+                    let _ = tokio::task::spawn(async move { reconcile_departed(&http, &db, guild_id).await });
+                }
+            }
+            Err(ex) => {
+                error!("Failed to set decay settings: {}", ex);
+                ctx.say("Failed to update the XP decay settings.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
@@ -1,15 +1,11 @@
-use std::collections::{HashMap, HashSet};
-use tracing::error;
 use crate::{CowContext, cowdb, Error};
-use serenity::{
-    model::{
-        id::{
-            RoleId
-        }
-    },
-    utils::MessageBuilder
-};
+use serenity::utils::MessageBuilder;
 use crate::{Database, db};
+use crate::models::db_models::FixOptions;
+use crate::services::auto_fix_handler::{FIX_RETENTION_DAYS, FixRun, fix_roles, undo_fix};
+
+// Embed descriptions max out at 4096 characters, and the summary goes on top.
+const MAX_DIFF_LENGTH: usize = 3500;
 
 #[poise::command(
     prefix_command,
@@ -33,35 +29,38 @@ pub async fn scan(ctx: CowContext<'_>) -> Result<(), Error> {
             )
         }).await?;
 
-        let roles = db.get_roles(guild_id).await?;
-        let role_set = roles.into_iter().filter_map(|r| r.role_id).collect::<HashSet<_>>();
+        let ranks = db.get_roles(guild_id).await?;
+        let mode = db.get_role_mode(guild_id).await?;
         let users = db.get_users(guild_id).await?;
         for u in users {
             if let Ok(member) = guild_id.member(&ctx, u.user).await {
-                let member_role_set: HashSet<RoleId> = HashSet::from_iter(member.roles.iter().cloned());
-                let intersection = role_set.intersection(&member_role_set).collect::<HashSet<_>>();
-                if let Some(expected_role) = u.role_id {
-                    if intersection.contains(&expected_role) && intersection.len() == 1 {
-                        continue; // Correct: one role and it's the expected one
-                    }
-                    // Either doesn't have the role, wrong role, or too many roles
-                    message.push("<@").push(u.user).push("> should have ").role(expected_role);
-                    if intersection.is_empty() {
-                        message.push(" but doesn't");
-                    } else {
-                        message.push(" but has: ");
-                        intersection.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
-                    }
-                    message.push("\n");
-                } else {
-                    if intersection.is_empty() {
-                        continue; // Correct: no roles
-                    }
+                let expected = mode.earned_roles(&ranks, u.exp.level);
+                let (missing, excess) = mode.role_changes(&ranks, u.exp.level, &member.roles);
+                if missing.is_empty() && excess.is_empty() {
+                    continue; // Correct: exactly the roles the mode says they've earned
+                }
+
+                if expected.is_empty() {
                     // Has a role, when they shouldn't
                     message.push("<@").push(u.user).push("> has excess roles: ");
-                    intersection.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
+                    excess.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
                     message.push("\n");
+                    continue;
                 }
+
+                // Either doesn't have the roles, wrong roles, or too many roles
+                message.push("<@").push(u.user).push("> should have ");
+                expected.iter().for_each(|r| { message.role(r).push(" "); });
+                let missing_any = !missing.is_empty();
+                if missing_any {
+                    message.push("but is missing: ");
+                    missing.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
+                }
+                if !excess.is_empty() {
+                    message.push(if missing_any { "and has: " } else { "but has: " });
+                    excess.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
+                }
+                message.push("\n");
             }
         }
 
@@ -99,22 +98,36 @@ pub async fn fix(
     #[description = "Remove ranks from people who shouldn't have a rank"] option_remove: Option<bool>,
     #[description = "Demote users who have a higher rank than they should"] option_demote: Option<bool>
 ) -> Result<(), Error> {
-    let db = cowdb!(ctx);
-    if let Some(guild_id) = ctx.guild_id() {
-        /*
-            There are several invalid cases we have to worry about:
-            - The user shouldn't have the role, and yet they do have conflicting roles (non-trivial) -> remove
-            - The user should have the role, and:
-              - they *do not* have any conflicting roles (trivial)
-              - they have one conflicting role
-                - and they should be higher up (trivial)
-                - and they should be lower down (non-trivial) -> demote
-              - they have multiple conflicting roles (non-trivial) -> multiple
+    fix_code(ctx, option_multiple, option_remove, option_demote, FixRun::Now).await
+}
 
-             The trivial cases will be done by default, and the non-trivial cases can be done by options.
-         */
+// No cooldown here, since nothing changes; previewing shouldn't lock anyone out of the real fix.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "List what fix would change, without changing anything."),
+    required_permissions = "ADMINISTRATOR",
+    discard_spare_arguments
+)]
+pub async fn previewfix(
+    ctx: CowContext<'_>,
+    #[description = "Fix users with multiple valid ranks"] option_multiple: Option<bool>,
+    #[description = "Remove ranks from people who shouldn't have a rank"] option_remove: Option<bool>,
+    #[description = "Demote users who have a higher rank than they should"] option_demote: Option<bool>
+) -> Result<(), Error> {
+    fix_code(ctx, option_multiple, option_remove, option_demote, FixRun::DryRun).await
+}
 
-        let (mut count_trivial, mut count_multiple, mut count_remove, mut count_demote, mut count_error, mut total_error, mut total) = (0, 0, 0, 0, 0, 0, 0);
+async fn fix_code(ctx: CowContext<'_>, option_multiple: Option<bool>, option_remove: Option<bool>, option_demote: Option<bool>, run: FixRun) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        // The trivial cases (see RoleFix) will be done by default, and the non-trivial cases can be done by options.
+        let options = FixOptions {
+            multiple: option_multiple.unwrap_or(false),
+            remove: option_remove.unwrap_or(false),
+            demote: option_demote.unwrap_or(false)
+        };
 
         let discord_message = ctx.send(|m| {
             m.embeds.clear();
@@ -123,100 +136,22 @@ pub async fn fix(
                 .description("Now fixing roles, please wait warmly...")
             )
         }).await?;
-        
-        let roles = db.get_roles(guild_id).await?;
-        let role_map = roles.into_iter().filter(|r| r.role_id.is_some()).map(|r| (r.role_id.unwrap(), r.min_level)).collect::<HashMap<_, _>>();
-        let role_set: HashSet<RoleId> = role_map.keys().cloned().collect(); // Mildly disgusting.
-        let users = db.get_users(guild_id).await?;
-        for u in users {
-            if let Ok(mut member) = guild_id.member(&ctx, u.user).await {
-                total += 1;
-
-                let member_role_set: HashSet<RoleId> = HashSet::from_iter(member.roles.iter().cloned());
-                let intersection = role_set.intersection(&member_role_set).collect::<HashSet<_>>();
-                if let Some(expected_role) = u.role_id {
-                    if intersection.contains(&expected_role) && intersection.len() == 1 {
-                        continue; // Correct: one role and it's the expected one
-                    }
-                    total_error += 1;
-
-                    if intersection.is_empty() { // They do not have the role, and need it
-                        if let Err(ex) = member.add_role(&ctx, expected_role).await {
-                            error!("Failed to add role: {}", ex);
-                            count_error += 1;
-                        } else {
-                            count_trivial += 1;
-                        }
-                    } else if intersection.len() == 1 { // They have another role in place
-                        let existing_role = intersection.into_iter().next().unwrap();
-                        let promote = role_map[existing_role] < role_map[&expected_role];
-                        if promote || option_demote.unwrap_or(false) { // Promote them
-                            if let Err(ex) = member.remove_role(&ctx, existing_role).await {
-                                error!("Failed to remove role for demoting: {}", ex);
-                                count_error += 1;
-                            }
-
-                            if let Err(ex) = member.add_role(&ctx, expected_role).await {
-                                error!("Failed to add role for promoting/demoting: {}", ex);
-                                count_error += 1;
-                            } else if promote {
-                                count_trivial += 1;
-                            } else {
-                                count_demote += 1;
-                            }
-                        }
-                    } else if option_multiple.unwrap_or(false) { // We have multiple to deal with
-                        for r in intersection {
-                            if *r == expected_role {
-                                continue;
-                            }
-
-                            if let Err(ex) = member.remove_role(&ctx, r).await {
-                                error!("Failed to remove excess roles: {}", ex);
-                                count_error += 1;
-                            }
-                        }
-
-                        if !member.roles.contains(&expected_role) {
-                            if let Err(ex) = member.add_role(&ctx, expected_role).await {
-                                error!("Failed to add role: {}", ex);
-                                count_error += 1;
-                            }
-                        }
-
-                        count_multiple += 1;
-                    }
-                } else {
-                    if intersection.is_empty() {
-                        continue; // Correct: no roles
-                    }
-
-                    total_error += 1;
-
-                    if option_remove.unwrap_or(false) {
-                        for r in intersection {
-                            if let Err(ex) = member.remove_role(&ctx, r).await {
-                                error!("Failed to remove role: {}", ex);
-                                count_error += 1;
-                            } else {
-                                count_remove += 1;
-                            }
-                        }
-                    }
-                }
-            }
+
+        let dry_run = run == FixRun::DryRun;
+        let report = fix_roles(ctx.serenity_context(), &db, guild_id, options, run).await?;
+
+        let mut content = report.summary();
+        if dry_run {
+            content = format!("Nothing has been changed yet; this is what would happen.\n\n{content}\n\n{}", report.diff(MAX_DIFF_LENGTH));
+        } else if let Some(fix_id) = report.fix_id {
+            content = format!("{content}\n\nUndo this with `rankconfig undofix {fix_id}` within {FIX_RETENTION_DAYS} days.");
         }
 
         discord_message.edit(ctx, |m| {
             m.embeds.clear();
             m.embed(|e| e
-                .title("Role Auto-fix")
-                .description(format!("Processed {total} members in the database with {total_error} errors found:\n\
-            - Trivial fixes: {count_trivial}\n\
-            - Fixes for multiple roles: {count_multiple}\n\
-            - Members with their roles fully revoked: {count_remove}\n\
-            - Members demoted: {count_demote}\n\
-            - Errors adding/removing roles: {count_error}"))
+                .title(if dry_run { "Role Auto-fix (Dry Run)" } else { "Role Auto-fix" })
+                .description(content)
             )
         }).await?;
     } else {
@@ -224,4 +159,54 @@ pub async fn fix(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Undo the role changes from a recent fix."),
+    required_permissions = "ADMINISTRATOR",
+    guild_cooldown = "900",
+    discard_spare_arguments
+)]
+pub async fn undofix(
+    ctx: CowContext<'_>,
+    #[description = "The fix to undo; the latest one if left out"] fix_id: Option<i32>
+) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let since = chrono::Utc::now() - chrono::Duration::days(FIX_RETENTION_DAYS);
+        let fix = match db.get_fix(guild_id, fix_id, since).await? {
+            Some(fix) => fix,
+            None => {
+                ctx.say(format!("There's no fix from the last {FIX_RETENTION_DAYS} days to undo.")).await?;
+                return Ok(());
+            }
+        };
+
+        let discord_message = ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| e
+                .title("Undo Role Auto-fix")
+                .description("Now putting roles back, please wait warmly...")
+            )
+        }).await?;
+
+        let (reverted, errors) = undo_fix(ctx.serenity_context(), &db, guild_id, &fix).await?;
+
+        discord_message.edit(ctx, |m| {
+            m.embeds.clear();
+            m.embed(|e| e
+                .title("Undo Role Auto-fix")
+                .description(format!("Undid fix {} from <t:{}:f>:\n\
+            - Members put back: {reverted}\n\
+            - Errors adding/removing roles: {errors}", fix.id, fix.created_at.timestamp()))
+            )
+        }).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
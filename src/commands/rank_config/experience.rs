@@ -0,0 +1,409 @@
+use std::borrow::Cow;
+use serde::Serialize;
+use serde_json::Value;
+use serenity::model::{
+    channel::{Attachment, AttachmentType},
+    id::{GuildId, UserId}
+};
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+use crate::models::db_models::{Experience, Member, XpSettings};
+use crate::services::message_handler::sync_rank_roles;
+
+// Parameters: rankconfig give/take/set [user] [amount] [xp | levels]
+
+// Keeps a typo from making the bot spin through millions of level ups.
+const MAX_XP_CHANGE: i32 = 1_000_000;
+const MAX_LEVEL_CHANGE: i32 = 10_000;
+
+#[derive(Serialize)]
+struct LevelRecord {
+    user_id: String,
+    level: i32,
+    xp: i32
+}
+
+enum Adjustment {
+    Give,
+    Take,
+    Set
+}
+
+fn parse_levels_unit(unit: &Option<String>) -> Option<bool> {
+    match unit.as_deref().map(|o| o.trim().to_lowercase()) {
+        None => Some(false),
+        Some(unit) => match unit.as_str() {
+            "xp" | "exp" => Some(false),
+            "level" | "levels" | "lvl" => Some(true),
+            _ => None
+        }
+    }
+}
+
+async fn adjust(ctx: CowContext<'_>, user: UserId, amount: i32, unit: Option<String>, adjustment: Adjustment) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            ctx.say("This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let levels = match parse_levels_unit(&unit) {
+        Some(levels) => levels,
+        None => {
+            ctx.say("The unit must be \"xp\" or \"levels\".").await?;
+            return Ok(());
+        }
+    };
+
+    let limit = if levels { MAX_LEVEL_CHANGE } else { MAX_XP_CHANGE };
+    if amount < 0 || amount > limit {
+        ctx.say(format!("The amount must be between 0 and {limit}.")).await?;
+        return Ok(());
+    }
+
+    let settings = db.get_xp_settings(guild_id).await?;
+    let old = db.get_xp(guild_id, user).await?;
+
+    let (level, xp) = match (adjustment, levels) {
+        (Adjustment::Give, false) => settings.normalize(old.level, old.xp as i64 + amount as i64),
+        (Adjustment::Take, false) => settings.normalize(old.level, old.xp as i64 - amount as i64),
+        (Adjustment::Set, false) => settings.normalize(old.level, amount as i64),
+        // Moving levels keeps whatever progress they had, as long as it still fits.
+        (Adjustment::Give, true) => settings.normalize(old.level.saturating_add(amount), old.xp as i64),
+        (Adjustment::Take, true) => settings.normalize((old.level - amount).max(0), old.xp as i64),
+        (Adjustment::Set, true) => settings.normalize(amount, 0)
+    };
+
+    if let Err(ex) = db.set_xp(guild_id, user, level, xp).await {
+        error!("Failed to set xp for user: {}", ex);
+        ctx.say("Failed to update their XP.").await?;
+        return Ok(());
+    }
+
+    let mut content = format!("<@{}> went from level {} ({} XP) to level {} ({} XP).", user, old.level, old.xp, level, xp);
+
+    // Same as levelling up normally, so the server's role mode decides what they keep.
+    let old_rank = db.get_highest_role(guild_id, old.level).await?;
+    let new_rank = db.get_highest_role(guild_id, level).await?;
+    if old_rank != new_rank {
+        if let Some(new_rank) = new_rank {
+            content += &format!("\nThey are now a <@&{new_rank}>.");
+        } else {
+            content += "\nThey no longer have a rank.";
+        }
+
+        if !sync_rank_roles(&ctx.serenity_context().http, &db, guild_id, user, level).await {
+            content += "\n(We failed to update their roles; maybe we don't have permission, or they left?)";
+        }
+    }
+
+    ctx.send(|m| {
+        m.embeds.clear();
+        m.embed(|e| e
+            .title("XP Updated")
+            .description(content)
+        )}).await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Give a member XP or levels.")
+)]
+pub async fn give(
+    ctx: CowContext<'_>,
+    #[description = "The member to give to"] user: UserId,
+    #[description = "How much to give"] #[min = 0] amount: i32,
+    #[description = "\"xp\" (default) or \"levels\""] unit: Option<String>)
+-> Result<(), Error> {
+    adjust(ctx, user, amount, unit, Adjustment::Give).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Take XP or levels away from a member.")
+)]
+pub async fn take(
+    ctx: CowContext<'_>,
+    #[description = "The member to take from"] user: UserId,
+    #[description = "How much to take"] #[min = 0] amount: i32,
+    #[description = "\"xp\" (default) or \"levels\""] unit: Option<String>)
+-> Result<(), Error> {
+    adjust(ctx, user, amount, unit, Adjustment::Take).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "set",
+    description_localized("en-US", "Set a member's XP (towards their next level) or level.")
+)]
+pub async fn set_exp(
+    ctx: CowContext<'_>,
+    #[description = "The member to change"] user: UserId,
+    #[description = "What to set it to"] #[min = 0] amount: i32,
+    #[description = "\"xp\" (default) or \"levels\""] unit: Option<String>)
+-> Result<(), Error> {
+    adjust(ctx, user, amount, unit, Adjustment::Set).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Export every member's level and XP on this server."),
+    guild_cooldown = "60"
+)]
+pub async fn export(
+    ctx: CowContext<'_>,
+    #[description = "\"csv\" (default) or \"json\""] format: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let format = format.unwrap_or_else(|| "csv".to_string()).trim().to_lowercase();
+        if format != "csv" && format != "json" {
+            ctx.say("The format must be \"csv\" or \"json\".").await?;
+            return Ok(());
+        }
+
+        let mut users = db.get_users(guild_id).await?;
+        users.sort_by(|a, b| b.exp.level.cmp(&a.exp.level).then(b.exp.xp.cmp(&a.exp.xp)));
+
+        let data = if format == "json" {
+            let records = users.iter()
+                .map(|o| LevelRecord {
+                    user_id: o.user.to_string(),
+                    level: o.exp.level,
+                    xp: o.exp.xp
+                })
+                .collect::<Vec<_>>();
+            serde_json::to_vec_pretty(&records)?
+        } else {
+            let mut csv = String::from("user_id,level,xp\n");
+            for user in &users {
+                csv += &format!("{},{},{}\n", user.user, user.exp.level, user.exp.xp);
+            }
+            csv.into_bytes()
+        };
+
+        ctx.send(|m| m
+            .content(format!("Exported {} member(s).", users.len()))
+            .attachment(AttachmentType::Bytes {
+                data: Cow::Owned(data),
+                filename: format!("levels-{guild_id}.{format}")
+            })
+        ).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+fn json_id(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(id) => id.trim().parse().ok(),
+        Value::Number(id) => id.as_u64(),
+        _ => None
+    }
+}
+
+fn json_i64(value: Option<&Value>) -> Option<i64> {
+    match value? {
+        Value::String(number) => number.trim().parse().ok(),
+        Value::Number(number) => number.as_i64(),
+        _ => None
+    }
+}
+
+// Takes our own export, or MEE6-style dumps ({"players": [{"id", "level", "xp", "detailed_xp"}]}).
+// Entries without a level have their XP treated as a lifetime total.
+fn parse_json(data: &[u8], settings: &XpSettings, total: bool) -> Option<Vec<Member>> {
+    let value: Value = serde_json::from_slice(data).ok()?;
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        Value::Object(object) => object.get("players").or_else(|| object.get("levels"))?.as_array()?,
+        _ => return None
+    };
+
+    entries.iter()
+        .map(|entry| {
+            let id = entry.get("user_id").or_else(|| entry.get("id")).and_then(json_id)?;
+            let level = json_i64(entry.get("level"));
+            // MEE6's "xp" is their lifetime total; the first entry of "detailed_xp" is the progress into the current level.
+            let progress = entry.get("detailed_xp").and_then(|o| json_i64(o.get(0)));
+            let xp = progress.or_else(|| json_i64(entry.get("xp")))?;
+
+            Some(to_member(id, level, xp, settings, total || (progress.is_none() && level.is_none())))
+        })
+        .collect()
+}
+
+// Takes our own export: user_id,level,xp, with or without the header. Two columns means user_id,total_xp.
+fn parse_csv(data: &[u8], settings: &XpSettings, total: bool) -> Option<Vec<Member>> {
+    let text = std::str::from_utf8(data).ok()?;
+    let mut out = Vec::new();
+
+    for line in text.lines().map(|o| o.trim()).filter(|o| !o.is_empty()) {
+        let fields = line.split(',').map(|o| o.trim().trim_matches('"')).collect::<Vec<_>>();
+        let id = match fields[0].parse::<u64>() {
+            Ok(id) => id,
+            // Probably the header.
+            Err(_) => continue
+        };
+
+        let member = match &fields[1..] {
+            [xp] => to_member(id, None, xp.parse().ok()?, settings, true),
+            [level, xp, ..] => to_member(id, Some(level.parse().ok()?), xp.parse().ok()?, settings, total),
+            _ => return None
+        };
+
+        out.push(member);
+    }
+
+    Some(out)
+}
+
+fn to_member(id: u64, level: Option<i64>, xp: i64, settings: &XpSettings, total: bool) -> Member {
+    let level = if total { 0 } else { level.unwrap_or(0).clamp(0, i32::MAX as i64) as i32 };
+    let (level, xp) = settings.normalize(level, xp);
+
+    Member {
+        id: UserId::from(id),
+        exp: Experience { level, xp }
+    }
+}
+
+async fn download(attachment: &Attachment) -> Result<Vec<u8>, Error> {
+    // Big enough for a few hundred thousand members.
+    if attachment.size > 8 * 1024 * 1024 {
+        return Err("The file is too big.".into());
+    }
+
+    Ok(attachment.download().await?)
+}
+
+async fn import_code(ctx: CowContext<'_>, guild_id: GuildId, file: Attachment, total: bool) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    let data = match download(&file).await {
+        Ok(data) => data,
+        Err(ex) => {
+            ctx.say(format!("Couldn't download the file: {ex}")).await?;
+            return Ok(());
+        }
+    };
+
+    let settings = db.get_xp_settings(guild_id).await?;
+    let is_json = file.filename.to_lowercase().ends_with(".json") || data.iter().find(|o| !o.is_ascii_whitespace()).map(|o| *o == b'[' || *o == b'{').unwrap_or(false);
+    let members = if is_json {
+        parse_json(&data, &settings, total)
+    } else {
+        parse_csv(&data, &settings, total)
+    };
+
+    match members {
+        Some(members) if !members.is_empty() => {
+            match db.import_levels(guild_id, &members).await {
+                Ok(count) => {
+                    ctx.say(format!("Imported {count} member(s). Roles haven't been touched; run `rankconfig scan` and `rankconfig fix` to sync them.")).await?;
+                }
+                Err(ex) => {
+                    error!("Failed to import levels: {}", ex);
+                    ctx.say("Failed to import; nothing was changed.").await?;
+                }
+            }
+        }
+        Some(_) => { ctx.say("There was nobody in that file to import.").await?; }
+        None => { ctx.say("Couldn't read that file; it should look like what `rankconfig export` gives you.").await?; }
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Import levels and XP from an export (CSV or JSON) or a MEE6-style dump. Overwrites existing members."),
+    guild_cooldown = "60"
+)]
+pub async fn import(
+    ctx: CowContext<'_>,
+    #[description = "The CSV or JSON file"] file: Attachment,
+    #[description = "Treat XP as a lifetime total, and recalculate levels with this server's curve"] total: Option<bool>)
+-> Result<(), Error> {
+    if let Some(guild_id) = ctx.guild_id() {
+        import_code(ctx, guild_id, file, total.unwrap_or(false)).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(members: &[Member]) -> Vec<(u64, i32, i32)> {
+        members.iter().map(|o| (o.id.0, o.exp.level, o.exp.xp)).collect()
+    }
+
+    #[test]
+    fn csv_takes_our_export() {
+        let settings = XpSettings::new(0);
+        let members = parse_csv(b"user_id,level,xp\n5,3,10\n6,0,254\n", &settings, false).unwrap();
+        assert_eq!(levels(&members), vec![(5, 3, 10), (6, 1, 154)]);
+    }
+
+    #[test]
+    fn csv_totals_keep_big_values() {
+        let settings = XpSettings::new(0);
+        let members = parse_csv(b"7,1900000\n8,3000000000\n", &settings, false).unwrap();
+        for (member, total) in members.iter().zip([1_900_000, 3_000_000_000]) {
+            assert_eq!(settings.total_xp(member.exp.level, member.exp.xp), total);
+        }
+        assert!(members[0].exp.level >= 99);
+    }
+
+    #[test]
+    fn csv_rejects_bad_rows() {
+        let settings = XpSettings::new(0);
+        assert!(parse_csv(b"5,three,10\n", &settings, false).is_none());
+        assert!(parse_csv(b"5\n", &settings, false).is_none());
+    }
+
+    #[test]
+    fn json_takes_mee6_dumps() {
+        let settings = XpSettings::new(0);
+        let data = br#"{"players": [
+            {"id": "9", "level": 5, "xp": 99999, "detailed_xp": [10, 320, 99999]},
+            {"id": 10, "xp": 1900000}
+        ]}"#;
+        let members = parse_json(data, &settings, false).unwrap();
+        assert_eq!((members[0].id.0, members[0].exp.level, members[0].exp.xp), (9, 5, 10));
+        assert_eq!(settings.total_xp(members[1].exp.level, members[1].exp.xp), 1_900_000);
+    }
+
+    #[test]
+    fn json_takes_our_export() {
+        let settings = XpSettings::new(0);
+        let data = br#"[{"user_id": "11", "level": 2, "xp": 15}]"#;
+        assert_eq!(levels(&parse_json(data, &settings, false).unwrap()), vec![(11, 2, 15)]);
+        assert!(parse_json(b"{}", &settings, false).is_none());
+    }
+}
@@ -1,12 +1,30 @@
 mod roles;
 mod diagnostics;
+mod xp;
+mod multipliers;
+mod experience;
+mod card;
+mod seasons;
+mod announce;
+mod spam;
+mod decay;
+mod autofix;
 
 use roles::*;
 use diagnostics::*;
+use xp::*;
+use multipliers::*;
+use experience::*;
+use card::*;
+use seasons::*;
+use announce::*;
+use spam::*;
+use decay::*;
+use autofix::*;
 use crate::{CowContext, Error};
 
 #[poise::command(prefix_command, slash_command,
-    subcommands("list", "add", "remove", "scan", "fix"),
+    subcommands("list", "add", "remove", "scan", "fix", "previewfix", "undofix", "xp", "multipliers", "give", "take", "set_exp", "export", "import", "card", "season", "rolemode", "announce", "spam", "decay", "autofix"),
     discard_spare_arguments,
     description_localized("en-US", "Configuration to manage ranks and levelling on the server."),
     aliases("rc"),
@@ -0,0 +1,151 @@
+use serenity::model::id::{ChannelId, RoleId};
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+use crate::models::db_models::MultiplierTarget;
+
+// Parameters: rankconfig multipliers set [multiplier] [channel | role]
+
+fn pick_target(channel: Option<ChannelId>, role: Option<RoleId>) -> Option<MultiplierTarget> {
+    match (channel, role) {
+        (Some(channel), None) => Some(MultiplierTarget::Channel(channel)),
+        (None, Some(role)) => Some(MultiplierTarget::Role(role)),
+        _ => None
+    }
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("multiplier_set", "multiplier_list", "multiplier_clear"),
+    description_localized("en-US", "Give channels (or categories) and roles more or less XP."),
+    discard_spare_arguments
+)]
+pub async fn multipliers(ctx: CowContext<'_>) -> Result<(), Error> {
+    multiplier_list_code(ctx).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "set",
+    description_localized("en-US", "Set the XP multiplier for a channel, category, or role.")
+)]
+pub async fn multiplier_set(
+    ctx: CowContext<'_>,
+    #[description = "The multiplier, like 2 for double or 0.5 for half XP"] multiplier: f64,
+    #[description = "The channel or category to apply it to"] channel: Option<ChannelId>,
+    #[description = "The role to apply it to"] role: Option<RoleId>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        if !multiplier.is_finite() || multiplier < 0.0 {
+            ctx.say("The multiplier can't be negative.").await?;
+            return Ok(());
+        }
+
+        if let Some(target) = pick_target(channel, role) {
+            match db.set_multiplier(guild_id, target, multiplier).await {
+                Ok(_) => { ctx.say(format!("XP for {target} is now multiplied by {multiplier}x.")).await?; }
+                Err(ex) => {
+                    error!("Failed to set xp multiplier: {}", ex);
+                    ctx.say("Failed to set the multiplier.").await?;
+                }
+            }
+        } else {
+            ctx.say("Give either a channel or a role (but not both).").await?;
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "list",
+    description_localized("en-US", "List the XP multipliers on this server."),
+    discard_spare_arguments
+)]
+pub async fn multiplier_list(ctx: CowContext<'_>) -> Result<(), Error> {
+    multiplier_list_code(ctx).await
+}
+
+pub async fn multiplier_list_code(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        match db.get_multipliers(guild_id).await {
+            Ok(items) => {
+                let content = items.into_iter()
+                    .map(|o| format!("{}: {}x", o.target, o.multiplier))
+                    .reduce(|a, b| {format!("{a}\n{b}")})
+                    .unwrap_or_else(|| "No multipliers are set on this server.".to_string());
+
+                ctx.send(|m| {
+                    m.embeds.clear();
+                    m.embed(|e| e
+                        .title("XP Multipliers")
+                        .description(content)
+                        .footer(|f| f.text("A channel's multiplier beats its category's; role multipliers stack on top."))
+                    )}).await?;
+            }
+            Err(ex) => {
+                error!("Failed to get xp multipliers: {}", ex);
+                ctx.say("Failed to get the multipliers.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "clear",
+    description_localized("en-US", "Clear the XP multiplier for a channel or role, or every multiplier if neither is given.")
+)]
+pub async fn multiplier_clear(
+    ctx: CowContext<'_>,
+    #[description = "The channel or category to clear"] channel: Option<ChannelId>,
+    #[description = "The role to clear"] role: Option<RoleId>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let target = pick_target(channel, role);
+        if target.is_none() && channel.is_some() {
+            ctx.say("Give either a channel or a role (but not both).").await?;
+            return Ok(());
+        }
+
+        match db.clear_multipliers(guild_id, target).await {
+            Ok(0) => { ctx.say("There was no multiplier to clear.").await?; }
+            Ok(count) => {
+                if let Some(target) = target {
+                    ctx.say(format!("Cleared the multiplier for {target}.")).await?;
+                } else {
+                    ctx.say(format!("Cleared {count} multiplier(s).")).await?;
+                }
+            }
+            Err(ex) => {
+                error!("Failed to clear xp multipliers: {}", ex);
+                ctx.say("Failed to clear the multipliers.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
@@ -9,6 +9,7 @@ use serenity::{
 use crate::{Database, db};
 use tracing::{error};
 use serenity::model::guild::Role;
+use crate::models::db_models::RoleMode;
 
 // Parameters: rankconfig add [min_level] [rank]
 
@@ -81,6 +82,58 @@ pub async fn remove(
     Ok(())
 }
 
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    description_localized("en-US", "Choose whether members keep every rank role they earn, or only the highest one."),
+    required_permissions = "ADMINISTRATOR",
+    discard_spare_arguments
+)]
+pub async fn rolemode(
+    ctx: CowContext<'_>,
+    #[description = "\"replace\" to keep only the highest rank, \"stack\" to keep them all"] mode: Option<String>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let mode = match mode {
+            Some(mode) => mode,
+            None => {
+                let current = db.get_role_mode(guild_id).await?;
+                ctx.say(format!("The role mode is currently {}.", current.to_string().to_lowercase())).await?;
+                return Ok(());
+            }
+        };
+
+        let mode = match RoleMode::parse(&mode) {
+            Some(mode) => mode,
+            None => {
+                ctx.say("The role mode must be \"replace\" or \"stack\".").await?;
+                return Ok(());
+            }
+        };
+
+        match db.set_role_mode(guild_id, mode).await {
+            Ok(_) => {
+                let content = match mode {
+                    RoleMode::Replace => "Members now keep only the highest rank they've earned.",
+                    RoleMode::Stack => "Members now keep every rank they've earned."
+                };
+                // Existing members only change as they level up, so point at fix for the rest.
+                ctx.say(format!("{content} Run `rankconfig scan` and `rankconfig fix` to bring everyone else in line.")).await?;
+            }
+            Err(ex) => {
+                error!("Failed to set role mode: {}", ex);
+                ctx.say("Failed to set the role mode.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
 #[poise::command(
     prefix_command,
     slash_command,
@@ -0,0 +1,136 @@
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+
+// Keeps names usable as a `levels` period, and short enough for an embed title.
+const MAX_NAME_LENGTH: usize = 100;
+const RESERVED_NAMES: [&str; 7] = ["all", "lifetime", "week", "weekly", "month", "monthly", "season"];
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("season_start", "season_end", "season_list"),
+    description_localized("en-US", "Run named seasons with their own leaderboards, without touching anyone's level."),
+    discard_spare_arguments
+)]
+pub async fn season(ctx: CowContext<'_>) -> Result<(), Error> {
+    season_list_code(ctx).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "start",
+    description_localized("en-US", "Start a new season; only XP earned from now on counts towards it.")
+)]
+pub async fn season_start(
+    ctx: CowContext<'_>,
+    #[description = "The name of the season, like \"Fall 2024\""] #[rest] name: String)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH || RESERVED_NAMES.contains(&name.to_lowercase().as_str()) {
+            ctx.say(format!("The name must be 1-{MAX_NAME_LENGTH} characters, and can't be one of {}.", RESERVED_NAMES.join(", "))).await?;
+            return Ok(());
+        }
+
+        let seasons = db.get_seasons(guild_id).await?;
+        if let Some(current) = seasons.iter().find(|o| o.ended_at.is_none()) {
+            ctx.say(format!("\"{}\" is still running; end it with `rankconfig season end` first.", current.name)).await?;
+            return Ok(());
+        }
+
+        if seasons.iter().any(|o| o.name.to_lowercase() == name.to_lowercase()) {
+            ctx.say("There's already a season with that name.").await?;
+            return Ok(());
+        }
+
+        match db.start_season(guild_id, name).await {
+            Ok(season) => { ctx.say(format!("Started \"{}\". See the standings with `/levels period:season`.", season.name)).await?; }
+            Err(ex) => {
+                error!("Failed to start season: {}", ex);
+                ctx.say("Failed to start the season.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "end",
+    description_localized("en-US", "End the current season and archive its final standings."),
+    discard_spare_arguments
+)]
+pub async fn season_end(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        match db.end_season(guild_id).await {
+            Ok(Some(season)) => { ctx.say(format!("Ended \"{0}\". Its final standings are under `/levels period:{0}`.", season.name)).await?; }
+            Ok(None) => { ctx.say("There is no season running right now.").await?; }
+            Err(ex) => {
+                error!("Failed to end season: {}", ex);
+                ctx.say("Failed to end the season.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "list",
+    description_localized("en-US", "List this server's seasons."),
+    discard_spare_arguments
+)]
+pub async fn season_list(ctx: CowContext<'_>) -> Result<(), Error> {
+    season_list_code(ctx).await
+}
+
+pub async fn season_list_code(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        match db.get_seasons(guild_id).await {
+            Ok(seasons) => {
+                let content = seasons.into_iter()
+                    .map(|o| match o.ended_at {
+                        Some(ended_at) => format!("**{}**: <t:{}:d> to <t:{}:d>", o.name, o.started_at.timestamp(), ended_at.timestamp()),
+                        None => format!("**{}**: since <t:{}:d> (running)", o.name, o.started_at.timestamp())
+                    })
+                    .reduce(|a, b| {format!("{a}\n{b}")})
+                    .unwrap_or_else(|| "No seasons yet; start one with `rankconfig season start`.".to_string());
+
+                ctx.send(|m| {
+                    m.embeds.clear();
+                    m.embed(|e| e
+                        .title("Seasons")
+                        .description(content)
+                    )}).await?;
+            }
+            Err(ex) => {
+                error!("Failed to get seasons: {}", ex);
+                ctx.say("Failed to get the seasons.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use serenity::model::id::UserId;
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+use crate::models::db_models::{SpamReason, SpamSettings};
+
+// Parameters: rankconfig spam set [min_length] [repeats] [junk_ratio] [burst_count] [burst_seconds] [burst_multiplier]
+
+const MAX_MIN_LENGTH: i32 = 200;
+const MAX_BURST_COUNT: i32 = 100;
+const MAX_BURST_SECONDS: i32 = 3600;
+
+fn describe_settings(settings: &SpamSettings) -> Vec<(SpamReason, String)> {
+    vec![
+        (SpamReason::Short, match settings.min_length {
+            0 => "Off".to_string(),
+            length => format!("Under {length} characters get nothing")
+        }),
+        (SpamReason::Repeat, if settings.block_repeats { "Repeats get nothing".to_string() } else { "Off".to_string() }),
+        (SpamReason::Junk, if settings.max_junk_ratio > 0.0 {
+            format!("{:.0}% or more emoji/links get nothing", settings.max_junk_ratio * 100.0)
+        } else {
+            "Off".to_string()
+        }),
+        (SpamReason::Burst, match settings.burst_count {
+            0 => "Off".to_string(),
+            count => format!("Past {count} messages in {}s get {}x", settings.burst_seconds, settings.burst_multiplier)
+        })
+    ]
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("spam_show", "spam_set", "spam_user"),
+    description_localized("en-US", "Ignore or discount XP for short, repeated, emoji-only, or rapid-fire messages."),
+    discard_spare_arguments
+)]
+pub async fn spam(ctx: CowContext<'_>) -> Result<(), Error> {
+    spam_show_code(ctx).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "show",
+    description_localized("en-US", "Show the anti-spam settings for this server."),
+    discard_spare_arguments
+)]
+pub async fn spam_show(ctx: CowContext<'_>) -> Result<(), Error> {
+    spam_show_code(ctx).await
+}
+
+pub async fn spam_show_code(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let settings = db.get_spam_settings(guild_id).await?;
+
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| {
+                e.title("Anti-spam Settings");
+                for (reason, description) in describe_settings(&settings) {
+                    e.field(reason, description, false);
+                }
+                e
+            })
+        }).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "set",
+    description_localized("en-US", "Change the anti-spam thresholds; anything left out stays the same, and 0 turns a check off.")
+)]
+pub async fn spam_set(
+    ctx: CowContext<'_>,
+    #[description = "Messages shorter than this (ignoring emoji and links) get no XP"] min_length: Option<i32>,
+    #[description = "Whether saying the same thing twice in a row gets no XP"] repeats: Option<bool>,
+    #[description = "Messages at least this fraction emoji or links get no XP, like 0.8"] junk_ratio: Option<f64>,
+    #[description = "Messages past this many in a burst get discounted"] burst_count: Option<i32>,
+    #[description = "How many seconds count as one burst"] burst_seconds: Option<i32>,
+    #[description = "XP multiplier for messages in a burst, like 0.5 for half"] burst_multiplier: Option<f64>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let mut settings = db.get_spam_settings(guild_id).await?;
+        settings.min_length = min_length.unwrap_or(settings.min_length);
+        settings.block_repeats = repeats.unwrap_or(settings.block_repeats);
+        settings.max_junk_ratio = junk_ratio.unwrap_or(settings.max_junk_ratio);
+        settings.burst_count = burst_count.unwrap_or(settings.burst_count);
+        settings.burst_seconds = burst_seconds.unwrap_or(settings.burst_seconds);
+        settings.burst_multiplier = burst_multiplier.unwrap_or(settings.burst_multiplier);
+
+        if !(0..=MAX_MIN_LENGTH).contains(&settings.min_length)
+            || !(0..=MAX_BURST_COUNT).contains(&settings.burst_count)
+            || !(1..=MAX_BURST_SECONDS).contains(&settings.burst_seconds) {
+            ctx.say(format!("The minimum length must be 0-{MAX_MIN_LENGTH}, the burst count 0-{MAX_BURST_COUNT}, and the burst length 1-{MAX_BURST_SECONDS} seconds.")).await?;
+            return Ok(());
+        }
+
+        if !(0.0..=1.0).contains(&settings.max_junk_ratio) || !(0.0..=1.0).contains(&settings.burst_multiplier) {
+            ctx.say("The emoji/link ratio and the burst multiplier must be between 0 and 1.").await?;
+            return Ok(());
+        }
+
+        match db.set_spam_settings(&settings).await {
+            Ok(_) => {
+                let content = describe_settings(&settings).into_iter()
+                    .map(|(reason, description)| format!("{reason}: {description}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ctx.say(format!("Updated the anti-spam settings.\n{content}")).await?;
+            }
+            Err(ex) => {
+                error!("Failed to set anti-spam settings: {}", ex);
+                ctx.say("Failed to update the anti-spam settings.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    rename = "user",
+    description_localized("en-US", "See how many of someone's messages the anti-spam checks caught, and why.")
+)]
+pub async fn spam_user(
+    ctx: CowContext<'_>,
+    #[description = "The member to look up"] user: UserId)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        match db.get_spam_stats(guild_id, user).await {
+            Ok(stats) => {
+                if stats.total() == 0 {
+                    ctx.say(format!("None of <@{user}>'s messages have been caught.")).await?;
+                    return Ok(());
+                }
+
+                ctx.send(|m| {
+                    m.embeds.clear();
+                    m.embed(|e| {
+                        e.title("Anti-spam Record")
+                            .description(format!("<@{user}> has had {} messages ignored or discounted.", stats.total()))
+                            .field(SpamReason::Short, stats.short, true)
+                            .field(SpamReason::Repeat, stats.repeat, true)
+                            .field(SpamReason::Junk, stats.junk, true)
+                            .field(SpamReason::Burst, format!("{} (discounted)", stats.burst), true);
+                        if let Some(last_flagged) = stats.last_flagged {
+                            e.timestamp(last_flagged).footer(|f| f.text("Last caught"));
+                        }
+                        e
+                    })
+                }).await?;
+            }
+            Err(ex) => {
+                error!("Failed to get spam stats: {}", ex);
+                ctx.say("Failed to get their anti-spam record.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
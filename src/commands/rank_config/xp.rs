@@ -0,0 +1,276 @@
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+use crate::models::db_models::{LevelCurve, XpSettings};
+
+// Most levels we'll list out at once, so the embed doesn't blow past Discord's limits.
+const MAX_PREVIEW_LEVELS: i32 = 50;
+
+fn describe_curve(settings: &XpSettings) -> String {
+    match settings.curve {
+        LevelCurve::Linear => format!("{} + {}L", settings.curve_base, settings.curve_step),
+        LevelCurve::Quadratic => format!("{}L² + {}L + {}", settings.curve_growth, settings.curve_step, settings.curve_base),
+        LevelCurve::Custom => {
+            if settings.curve_table.is_empty() {
+                "Empty table".to_string()
+            } else {
+                format!("Table: {} (the last entry repeats)", settings.table_to_string().replace(',', ", "))
+            }
+        }
+    }
+}
+
+fn describe_gain(settings: &XpSettings) -> String {
+    if settings.max_xp > settings.min_xp {
+        format!("{}-{} XP per message", settings.min_xp, settings.max_xp)
+    } else {
+        format!("{} XP per message", settings.min_xp)
+    }
+}
+
+async fn update_settings(ctx: &CowContext<'_>, settings: &XpSettings, success: String) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+
+    match db.set_xp_settings(settings).await {
+        Ok(_) => { ctx.say(success).await?; }
+        Err(ex) => {
+            error!("Failed to update xp settings: {}", ex);
+            ctx.say("Failed to update the xp settings.").await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("show", "gain", "curve", "table", "multiplier", "preview"),
+    description_localized("en-US", "Configure how much XP is given out and how much each level needs."),
+    discard_spare_arguments
+)]
+pub async fn xp(ctx: CowContext<'_>) -> Result<(), Error> {
+    show_code(ctx).await
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Show the current XP settings for this server."),
+    discard_spare_arguments
+)]
+pub async fn show(ctx: CowContext<'_>) -> Result<(), Error> {
+    show_code(ctx).await
+}
+
+pub async fn show_code(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        match db.get_xp_settings(guild_id).await {
+            Ok(settings) => {
+                ctx.send(|m| {
+                    m.embeds.clear();
+                    m.embed(|e| e
+                        .title("XP Settings")
+                        .field("Gain", describe_gain(&settings), true)
+                        .field("Multiplier", format!("{}x", settings.multiplier), true)
+                        .field(format!("Curve ({})", settings.curve), describe_curve(&settings), false)
+                    )}).await?;
+            }
+            Err(ex) => {
+                error!("Failed to get xp settings: {}", ex);
+                ctx.say("Failed to get the xp settings.").await?;
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Set how much XP a message gives; give a maximum to make it random.")
+)]
+pub async fn gain(
+    ctx: CowContext<'_>,
+    #[description = "XP per message, or the lowest roll if a maximum is given"] #[min = 0] min: i32,
+    #[description = "The highest roll for XP per message"] #[min = 0] max: Option<i32>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let max = max.unwrap_or(min);
+        if min < 0 || max < min {
+            ctx.say("The XP per message can't be negative, and the maximum has to be at least the minimum.").await?;
+            return Ok(());
+        }
+
+        let mut settings = db.get_xp_settings(guild_id).await?;
+        settings.min_xp = min;
+        settings.max_xp = max;
+        let content = format!("Members now get {}.", describe_gain(&settings));
+        update_settings(&ctx, &settings, content).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Set the level curve: base + step * L (+ growth * L² if quadratic).")
+)]
+pub async fn curve(
+    ctx: CowContext<'_>,
+    #[description = "\"linear\", \"quadratic\", or \"custom\" (set the table first)"] kind: String,
+    #[description = "XP needed for the first level"] base: Option<i32>,
+    #[description = "Extra XP needed per level"] step: Option<i32>,
+    #[description = "Extra XP needed per level squared (quadratic only)"] growth: Option<i32>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let kind = match LevelCurve::parse(&kind) {
+            Some(kind) => kind,
+            None => {
+                ctx.say("The curve must be \"linear\", \"quadratic\", or \"custom\".").await?;
+                return Ok(());
+            }
+        };
+
+        let mut settings = db.get_xp_settings(guild_id).await?;
+
+        if kind == LevelCurve::Custom && settings.curve_table.is_empty() {
+            ctx.say("There is no custom table yet; set one with `rankconfig xp table`.").await?;
+            return Ok(());
+        }
+
+        settings.curve = kind;
+        settings.curve_base = base.unwrap_or(settings.curve_base);
+        settings.curve_step = step.unwrap_or(settings.curve_step);
+        settings.curve_growth = growth.unwrap_or(settings.curve_growth);
+
+        if settings.curve_base <= 0 || settings.curve_step < 0 || settings.curve_growth < 0 {
+            ctx.say("The base must be positive, and the step and growth can't be negative.").await?;
+            return Ok(());
+        }
+
+        let content = format!("The level curve is now {} ({}).", settings.curve.to_string().to_lowercase(), describe_curve(&settings));
+        update_settings(&ctx, &settings, content).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Use a custom table of XP needed per level, starting from level 0.")
+)]
+pub async fn table(
+    ctx: CowContext<'_>,
+    #[description = "XP needed for each level, separated by commas; the last one repeats"] #[rest] values: String)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let table = XpSettings::table_from_string(&values);
+        let parts = values.split([',', ' ']).filter(|o| !o.trim().is_empty()).count();
+
+        if table.is_empty() || table.len() != parts || table.iter().any(|o| *o <= 0) {
+            ctx.say("The table must be a list of positive numbers, like \"100, 250, 500\".").await?;
+            return Ok(());
+        }
+
+        let mut settings = db.get_xp_settings(guild_id).await?;
+        settings.curve = LevelCurve::Custom;
+        settings.curve_table = table;
+        let content = format!("The level curve is now a custom table ({}).", describe_curve(&settings));
+        update_settings(&ctx, &settings, content).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Multiply all XP given out on this server.")
+)]
+pub async fn multiplier(
+    ctx: CowContext<'_>,
+    #[description = "The multiplier, like 1.5 for 50% more XP"] value: f64)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        if !value.is_finite() || value < 0.0 {
+            ctx.say("The multiplier can't be negative.").await?;
+            return Ok(());
+        }
+
+        let mut settings = db.get_xp_settings(guild_id).await?;
+        settings.multiplier = value;
+        update_settings(&ctx, &settings, format!("XP is now multiplied by {value}x.")).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Preview how much XP it takes to reach each level.")
+)]
+pub async fn preview(
+    ctx: CowContext<'_>,
+    #[description = "How many levels to show"] #[min = 1] #[max = 50] levels: Option<i32>)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(guild_id) = ctx.guild_id() {
+        let levels = levels.unwrap_or(10).clamp(1, MAX_PREVIEW_LEVELS);
+        let settings = db.get_xp_settings(guild_id).await?;
+
+        let mut total: i64 = 0;
+        let content = (1..=levels)
+            .map(|level| {
+                let needed = settings.xp_for_level(level - 1);
+                total += needed as i64;
+                format!("Level {level}: {needed} XP ({total} total)")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embed(|e| e
+                .title("Level Curve Preview")
+                .description(content)
+                .footer(|f| f.text(describe_gain(&settings)))
+            )}).await?;
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
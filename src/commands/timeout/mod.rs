@@ -1,12 +1,14 @@
 mod timeout_config;
+mod voice_config;
 
 use timeout_config::*;
+use voice_config::*;
 use crate::{CowContext, Error};
 
 #[poise::command(prefix_command, slash_command,
-    subcommands("get", "set"),
+    subcommands("get", "set", "voice"),
     discard_spare_arguments,
-    description_localized("en-US", "Commands for viewing and settinge the cooldown for chat xp."),
+    description_localized("en-US", "Commands for viewing and setting the cooldown for chat xp, and voice xp."),
     identifying_name = "Leveling Timeout"
 )]
 pub async fn timeout(ctx: CowContext<'_>) -> Result<(), Error> {
@@ -0,0 +1,162 @@
+use serenity::model::id::ChannelId;
+use tracing::error;
+use crate::{CowContext, Database, db, cowdb, Error};
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("rate", "cap", "channel"),
+    description_localized("en-US", "Shows the settings for XP earned in voice channels."),
+    discard_spare_arguments
+)]
+pub async fn voice(ctx: CowContext<'_>) -> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(server_id) = ctx.guild_id() {
+        match db.get_voice_settings(server_id).await {
+            Ok(voice) => {
+                let rate = if voice.xp_per_minute > 0 {
+                    format!("{} XP per minute", voice.xp_per_minute)
+                } else {
+                    "Disabled".to_string()
+                };
+
+                let cap = if voice.daily_cap > 0 {
+                    format!("{} XP per day", voice.daily_cap)
+                } else {
+                    "None".to_string()
+                };
+
+                let channels = voice.channels.iter()
+                    .map(|o| format!("<#{o}>"))
+                    .reduce(|a, b| format!("{a}, {b}"))
+                    .unwrap_or_else(|| "Every voice channel except AFK".to_string());
+
+                ctx.send(|m| {
+                    m.embeds.clear();
+                    m.embed(|e| e
+                        .title("Voice XP")
+                        .field("Rate", rate, true)
+                        .field("Daily Cap", cap, true)
+                        .field("Channels", channels, false)
+                        .footer(|f| f.text("Only counts while someone else is in the channel, and not while deafened."))
+                    )}).await?;
+            }
+            Err(err) => {
+                ctx.say("Could not get voice settings.").await?;
+                error!("Could not get voice settings: {}", err);
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Sets how much XP a minute in a voice channel gives; 0 turns it off."),
+)]
+pub async fn rate(
+    ctx: CowContext<'_>,
+    #[description = "XP per minute"] #[min = 0] xp: i32)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(server_id) = ctx.guild_id() {
+        if xp < 0 {
+            ctx.say("The rate can't be negative.").await?;
+            return Ok(());
+        }
+
+        let voice = db.get_voice_settings(server_id).await?;
+        match db.set_voice_settings(server_id, xp, voice.daily_cap).await {
+            Ok(_) => {
+                if xp > 0 {
+                    ctx.say(format!("Voice channels now give {xp} XP per minute.")).await?;
+                } else {
+                    ctx.say("Voice channels no longer give XP.").await?;
+                }
+            }
+            Err(err) => {
+                ctx.say("Could not set the voice rate.").await?;
+                error!("Could not set voice rate: {}", err);
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Sets the most voice XP someone can earn in a day (UTC); 0 removes the cap."),
+)]
+pub async fn cap(
+    ctx: CowContext<'_>,
+    #[description = "XP per day"] #[min = 0] xp: i32)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(server_id) = ctx.guild_id() {
+        if xp < 0 {
+            ctx.say("The cap can't be negative.").await?;
+            return Ok(());
+        }
+
+        let voice = db.get_voice_settings(server_id).await?;
+        match db.set_voice_settings(server_id, voice.xp_per_minute, xp).await {
+            Ok(_) => {
+                if xp > 0 {
+                    ctx.say(format!("Voice XP is now capped at {xp} per day.")).await?;
+                } else {
+                    ctx.say("Voice XP is no longer capped.").await?;
+                }
+            }
+            Err(err) => {
+                ctx.say("Could not set the voice cap.").await?;
+                error!("Could not set voice cap: {}", err);
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Toggles whether a voice channel (or category) counts; if none are set, they all do."),
+)]
+pub async fn channel(
+    ctx: CowContext<'_>,
+    #[description = "The voice channel or category"] channel: ChannelId)
+-> Result<(), Error> {
+    let db = cowdb!(ctx);
+    if let Some(server_id) = ctx.guild_id() {
+        match db.toggle_voice_channel(server_id, channel).await {
+            Ok(true) => { ctx.say(format!("<#{channel}> now counts for voice XP.")).await?; }
+            Ok(false) => { ctx.say(format!("<#{channel}> no longer counts for voice XP.")).await?; }
+            Err(err) => {
+                ctx.say("Could not toggle the voice channel.").await?;
+                error!("Could not toggle voice channel: {}", err);
+            }
+        }
+    } else {
+        ctx.say("This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use async_trait::async_trait;
 use std::collections::HashMap;
 use chrono::NaiveDateTime;
 use num_traits::ToPrimitive;
@@ -11,49 +12,193 @@ use rust_decimal::{
     prelude::FromPrimitive
 };
 
-use crate::Database;
+use crate::Error;
+use crate::services::sql_server::SqlServerDatabase;
 use crate::commands::ucm::courses_db_models::*;
 
-impl Database {
-    pub async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, Box<dyn std::error::Error + Send + Sync>> {
+#[async_trait]
+pub trait CourseStorage: Send + Sync {
+    async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, Error>;
+
+    async fn add_reminder(&self, reminder: &Reminder) -> Result<(), Error>;
+
+    async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, Error>;
+
+    // Every reminder that could still go off; see Reminder::check.
+    async fn get_armed_reminders(&self) -> Result<Vec<Reminder>, Error>;
+
+    // Saves whether it's triggered and what was last seen.
+    async fn save_reminder_state(&self, reminder: &Reminder) -> Result<(), Error>;
+
+    async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, Error>;
+
+    // Every class an armed reminder is on, with its instructors and meetings, by CRN. Done in bulk so the
+    // reminder check doesn't go back and forth for each one.
+    async fn get_watched_classes(&self) -> Result<HashMap<i32, ClassWatch>, Error>;
+
+    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
+    async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, Error>;
+
+    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
+    async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, Error>;
+
+    async fn get_description_for_course(&self, course_number: &str) -> Result<Option<String>, Error>;
+
+    // Course number is like CSE-031.
+    async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, Error>;
+
+    // Course name is like Computer Organization and Assembly.
+    async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, Error>;
+
+    async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, Error>;
+
+    async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, Error>;
+
+    async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, Error>;
+}
+
+const REMINDER_COLUMNS: &str = "user_id, course_reference_number, min_trigger, for_waitlist, triggered, conditions, recurring, channel_id, last_available, last_instructors, last_schedule, pending, delivery_failed";
+
+// Not gone off yet, going off again, or still waiting to be delivered.
+const REMINDER_ARMED: &str = "triggered = 0 OR recurring = 1 OR pending <> 0";
+
+const CLASS_COLUMNS: &str = "class.id, term, course_number, campus_description, course_title, credit_hours, maximum_enrollment, enrollment, seats_available, wait_capacity, wait_available";
+
+const PROFESSOR_COLUMNS: &str = "professor.id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name";
+
+const MEETING_COLUMNS: &str = "begin_time, end_time, begin_date, end_date, building, building_description, campus, campus_description, room, credit_hour_session, hours_per_week, in_session, meeting_type";
+
+fn reminder_from_row(row: &tiberius::Row) -> Reminder {
+    let user_id: Decimal = row.get(0).unwrap();
+    let conditions: u8 = row.get(5).unwrap();
+    let channel_id: Option<Decimal> = row.get(7);
+    let last_available: Option<i16> = row.get(8);
+    let last_instructors: Option<&str> = row.get(9);
+    let last_schedule: Option<&str> = row.get(10);
+    let pending: u8 = row.get(11).unwrap();
+
+    Reminder {
+        user_id: user_id.to_u64().unwrap(),
+        course_reference_number: row.get(1).unwrap(),
+        min_trigger: row.get(2).unwrap(),
+        for_waitlist: row.get(3).unwrap(),
+        triggered: row.get(4).unwrap(),
+        conditions: ReminderConditions::from_bits_truncate(conditions),
+        recurring: row.get(6).unwrap(),
+        channel_id: channel_id.and_then(|o| o.to_u64()),
+        last_available: last_available.map(|o| o as i32),
+        last_instructors: last_instructors.map(|o| o.to_string()),
+        last_schedule: last_schedule.map(|o| o.to_string()),
+        pending: ReminderConditions::from_bits_truncate(pending),
+        delivery_failed: row.get(12).unwrap()
+    }
+}
+
+fn class_from_row(row: &tiberius::Row, course_reference_number: i32) -> Class {
+    let course_number: &str = row.get(2).unwrap();
+    let campus_description: Option<&str> = row.get(3);
+    let course_title: Option<&str> = row.get(4);
+
+    Class {
+        id: row.get(0).unwrap(),
+        term: row.get(1).unwrap(),
+        course_reference_number,
+        course_number: course_number.to_string(),
+        campus_description: campus_description.map(|o| o.to_string()),
+        course_title: course_title.map(|o| o.to_string()),
+        credit_hours: row.get(5).unwrap(),
+        maximum_enrollment: row.get(6).unwrap(),
+        enrollment: row.get(7).unwrap(),
+        seats_available: row.get(8).unwrap(),
+        wait_capacity: row.get(9).unwrap(),
+        wait_available: row.get(10).unwrap()
+    }
+}
+
+fn professor_from_row(row: &tiberius::Row) -> Professor {
+    let last_name: &str = row.get(2).unwrap();
+    let first_name: &str = row.get(3).unwrap();
+    let middle_name: Option<&str> = row.get(4);
+    let email: Option<&str> = row.get(5);
+    let department: Option<&str> = row.get(6);
+    let full_name: &str = row.get(9).unwrap();
+
+    Professor {
+        id: row.get(0).unwrap(),
+        rmp_id: row.get(1),
+        last_name: last_name.to_string(),
+        first_name: first_name.to_string(),
+        middle_name: middle_name.map(|o| o.to_string()),
+        email: email.map(|o| o.to_string()),
+        department: department.map(|o| o.to_string()),
+        num_ratings: row.get(7).unwrap(),
+        rating: row.get(8).unwrap(),
+        full_name: full_name.to_string()
+    }
+}
+
+fn meeting_from_row(row: &tiberius::Row, class_id: i32) -> Meeting {
+    let begin_time: Option<&str> = row.get(0);
+    let end_time: Option<&str> = row.get(1);
+    let begin_date: &str = row.get(2).unwrap();
+    let end_date: &str = row.get(3).unwrap();
+    let building: Option<&str> = row.get(4);
+    let building_description: Option<&str> = row.get(5);
+    let campus: Option<&str> = row.get(6);
+    let campus_description: Option<&str> = row.get(7);
+    let room: Option<&str> = row.get(8);
+    let meeting_type: u8 = row.get(12).unwrap();
+
+    Meeting {
+        class_id,
+        begin_time: begin_time.map(|o| o.to_string()),
+        end_time: end_time.map(|o| o.to_string()),
+        begin_date: begin_date.to_string(),
+        end_date: end_date.to_string(),
+        building: building.map(|o| o.to_string()),
+        building_description: building_description.map(|o| o.to_string()),
+        campus: campus.map(|o| o.to_string()),
+        campus_description: campus_description.map(|o| o.to_string()),
+        room: room.map(|o| o.to_string()),
+        credit_hour_session: row.get(9).unwrap(),
+        hours_per_week: row.get(10).unwrap(),
+        in_session: Days::from_bits(row.get(11).unwrap()).unwrap(),
+        meeting_type: MeetingType::try_from(meeting_type).unwrap()
+    }
+}
+
+#[async_trait]
+impl CourseStorage for SqlServerDatabase {
+    async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, Error> {
         let mut conn = self.pool.get().await?;
         let user_decimal = Decimal::from_u64(user_id.0).unwrap();
         let res = conn.query(
-            "SELECT course_reference_number, min_trigger, for_waitlist, triggered FROM [UniScraper].[UCM].[reminder] WHERE user_id = @P1",
+            format!("SELECT {REMINDER_COLUMNS} FROM [UniScraper].[UCM].[reminder] WHERE user_id = @P1"),
             &[&user_decimal])
             .await?
             .into_first_result()
             .await?;
 
-        let mut out: Vec<Reminder> = Vec::new();
-
-        for reminder in res {
-            out.push(Reminder {
-                user_id: user_id.0,
-                course_reference_number: reminder.get(0).unwrap(),
-                min_trigger: reminder.get(1).unwrap(),
-                for_waitlist: reminder.get(2).unwrap(),
-                triggered: reminder.get(3).unwrap()
-            });
-        }
-
-        Ok(out)
+        Ok(res.iter().map(reminder_from_row).collect())
     }
 
-    pub async fn add_reminder(&self, reminder: &Reminder) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn add_reminder(&self, reminder: &Reminder) -> Result<(), Error> {
         let mut conn = self.pool.get().await?;
         let user_decimal = Decimal::from_u64(reminder.user_id).unwrap();
+        let channel = reminder.channel_id.map(|o| Decimal::from_u64(o).unwrap());
 
         // Will panic if there is a duplicate, since I have uniqueness set.
         conn.execute(
-            "INSERT INTO [UniScraper].[UCM].[reminder] (user_id, course_reference_number, min_trigger, for_waitlist, triggered) VALUES (@P1, @P2, @P3, @P4, @P5)",
-            &[&user_decimal, &reminder.course_reference_number, &reminder.min_trigger, &reminder.for_waitlist, &reminder.triggered])
+            "INSERT INTO [UniScraper].[UCM].[reminder] (user_id, course_reference_number, min_trigger, for_waitlist, triggered, conditions, recurring, channel_id) \
+            VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8)",
+            &[&user_decimal, &reminder.course_reference_number, &reminder.min_trigger, &reminder.for_waitlist, &reminder.triggered,
+                &reminder.conditions.bits(), &reminder.recurring, &channel])
             .await?;
 
         Ok(())
     }
 
-    pub async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, Error> {
         let mut conn = self.pool.get().await?;
         let user_decimal = Decimal::from_u64(user_id.0).unwrap();
 
@@ -65,144 +210,125 @@ impl Database {
         Ok(total > 0)
     }
 
-    pub async fn trigger_reminders(&self) -> Result<Vec<Trigger>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_armed_reminders(&self) -> Result<Vec<Reminder>, Error> {
         let mut conn = self.pool.get().await?;
-
         let res = conn.simple_query(
-            "EXEC [UniScraper].[UCM].[TriggerReminders]")
+            format!("SELECT {REMINDER_COLUMNS} FROM [UniScraper].[UCM].[reminder] WHERE {REMINDER_ARMED}"))
             .await?
             .into_first_result()
             .await?;
 
-        let mut out: Vec<Trigger> = Vec::new();
+        Ok(res.iter().map(reminder_from_row).collect())
+    }
 
-        for reminder in res {
-            let user_id: Decimal = reminder.get(0).unwrap();
-            out.push(Trigger {
-                user_id: user_id.to_u64().unwrap(),
-                course_reference_number: reminder.get(1).unwrap(),
-                min_trigger: reminder.get(2).unwrap()
-            });
-        }
+    async fn save_reminder_state(&self, reminder: &Reminder) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let user_decimal = Decimal::from_u64(reminder.user_id).unwrap();
+        let last_available = reminder.last_available.map(|o| o as i16);
 
-        Ok(out)
+        conn.execute(
+            "UPDATE [UniScraper].[UCM].[reminder] SET triggered = @P3, last_available = @P4, last_instructors = @P5, last_schedule = @P6, \
+            pending = @P7, delivery_failed = @P8 WHERE user_id = @P1 AND course_reference_number = @P2",
+            &[&user_decimal, &reminder.course_reference_number, &reminder.triggered, &last_available, &reminder.last_instructors, &reminder.last_schedule,
+                &reminder.pending.bits(), &reminder.delivery_failed])
+            .await?;
+
+        Ok(())
     }
 
-    pub async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, Error> {
         let mut conn = self.pool.get().await?;
         let res = conn.query(
-            "SELECT id, term, course_number, campus_description, course_title, credit_hours, maximum_enrollment, enrollment, seats_available, wait_capacity, wait_available FROM [UniScraper].[UCM].[class] WHERE course_reference_number = @P1",
+            format!("SELECT {CLASS_COLUMNS} FROM [UniScraper].[UCM].[class] WHERE course_reference_number = @P1"),
             &[&course_reference_number])
             .await?
             .into_row()
             .await?;
 
-        let mut out: Option<Class> = None;
+        Ok(res.map(|o| class_from_row(&o, course_reference_number)))
+    }
 
-        if let Some(class) = res {
-            let course_number: &str = class.get(2).unwrap();
-            let campus_description: Option<&str> = class.get(3);
-            let course_title: Option<&str> = class.get(4);
-            out = Some(Class {
-                id: class.get(0).unwrap(),
-                term: class.get(1).unwrap(),
-                course_reference_number,
-                course_number: course_number.to_string(),
-                campus_description: campus_description.map(|o| o.to_string()),
-                course_title: course_title.map(|o| o.to_string()),
-                credit_hours: class.get(5).unwrap(),
-                maximum_enrollment: class.get(6).unwrap(),
-                enrollment: class.get(7).unwrap(),
-                seats_available: class.get(8).unwrap(),
-                wait_capacity: class.get(9).unwrap(),
-                wait_available: class.get(10).unwrap()
+    async fn get_watched_classes(&self) -> Result<HashMap<i32, ClassWatch>, Error> {
+        let mut conn = self.pool.get().await?;
+        let watched = format!("SELECT course_reference_number FROM [UniScraper].[UCM].[reminder] WHERE {REMINDER_ARMED}");
+
+        // Oldest term first, so the newest section with a CRN is the one that sticks.
+        let classes = conn.simple_query(
+            format!("SELECT {CLASS_COLUMNS}, course_reference_number FROM [UniScraper].[UCM].[class] \
+            WHERE course_reference_number IN ({watched}) ORDER BY term"))
+            .await?
+            .into_first_result()
+            .await?;
+
+        let mut out: HashMap<i32, ClassWatch> = HashMap::new();
+        for row in classes {
+            let course_reference_number: i32 = row.get(11).unwrap();
+            out.insert(course_reference_number, ClassWatch {
+                class: class_from_row(&row, course_reference_number),
+                instructors: Vec::new(),
+                meetings: Vec::new()
             });
         }
 
-        Ok(out)
-    }
+        let by_id: HashMap<i32, i32> = out.values().map(|o| (o.class.id, o.class.course_reference_number)).collect();
+        let watched_ids = format!("SELECT id FROM [UniScraper].[UCM].[class] WHERE course_reference_number IN ({watched})");
 
-    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
-    pub async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let res = conn.query(
-            "SELECT professor.id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name FROM [UniScraper].[UCM].[professor] INNER JOIN [UniScraper].[UCM].[faculty] ON professor.id = faculty.professor_id WHERE class_id = @P1;",
-            &[&class_id])
+        let professors = conn.simple_query(
+            format!("SELECT {PROFESSOR_COLUMNS}, faculty.class_id FROM [UniScraper].[UCM].[professor] \
+            INNER JOIN [UniScraper].[UCM].[faculty] ON professor.id = faculty.professor_id \
+            WHERE faculty.class_id IN ({watched_ids})"))
             .await?
             .into_first_result()
             .await?;
 
-        let mut out: Vec<Professor> = Vec::new();
-
-        for professor in res {
-            let last_name: &str = professor.get(2).unwrap();
-            let first_name: &str = professor.get(3).unwrap();
-            let middle_name: Option<&str> = professor.get(4);
-            let email: Option<&str> = professor.get(5);
-            let department: Option<&str> = professor.get(6);
-            let full_name: &str = professor.get(9).unwrap();
-            out.push(Professor {
-                id: professor.get(0).unwrap(),
-                rmp_id: professor.get(1),
-                last_name: last_name.to_string(),
-                first_name: first_name.to_string(),
-                middle_name: middle_name.map(|o| o.to_string()),
-                email: email.map(|o| o.to_string()),
-                department: department.map(|o| o.to_string()),
-                num_ratings: professor.get(7).unwrap(),
-                rating: professor.get(8).unwrap(),
-                full_name: full_name.to_string()
-            });
+        for row in professors {
+            let class_id: i32 = row.get(10).unwrap();
+            if let Some(watch) = by_id.get(&class_id).and_then(|o| out.get_mut(o)) {
+                watch.instructors.push(professor_from_row(&row));
+            }
+        }
+
+        let meetings = conn.simple_query(
+            format!("SELECT {MEETING_COLUMNS}, class_id FROM [UniScraper].[UCM].[meeting] WHERE class_id IN ({watched_ids})"))
+            .await?
+            .into_first_result()
+            .await?;
+
+        for row in meetings {
+            let class_id: i32 = row.get(13).unwrap();
+            if let Some(watch) = by_id.get(&class_id).and_then(|o| out.get_mut(o)) {
+                watch.meetings.push(meeting_from_row(&row, class_id));
+            }
         }
 
         Ok(out)
     }
 
-    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
-    pub async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, Error> {
         let mut conn = self.pool.get().await?;
         let res = conn.query(
-            "SELECT begin_time, end_time, begin_date, end_date, building, building_description, campus, campus_description, room, credit_hour_session, hours_per_week, in_session, meeting_type FROM [UniScraper].[UCM].[meeting] WHERE class_id = @P1;",
+            format!("SELECT {PROFESSOR_COLUMNS} FROM [UniScraper].[UCM].[professor] INNER JOIN [UniScraper].[UCM].[faculty] ON professor.id = faculty.professor_id WHERE class_id = @P1;"),
             &[&class_id])
             .await?
             .into_first_result()
             .await?;
 
-        let mut out: Vec<Meeting> = Vec::new();
+        Ok(res.iter().map(professor_from_row).collect())
+    }
 
-        for meeting in res {
-            let begin_time: Option<&str> = meeting.get(0);
-            let end_time: Option<&str> = meeting.get(1);
-            let begin_date: &str = meeting.get(2).unwrap();
-            let end_date: &str = meeting.get(3).unwrap();
-            let building: Option<&str> = meeting.get(4);
-            let building_description: Option<&str> = meeting.get(5);
-            let campus: Option<&str> = meeting.get(6);
-            let campus_description: Option<&str> = meeting.get(7);
-            let room: Option<&str> = meeting.get(8);
-            let meeting_type: u8 = meeting.get(12).unwrap();
-            out.push(Meeting {
-                class_id,
-                begin_time: begin_time.map(|o| o.to_string()),
-                end_time: end_time.map(|o| o.to_string()),
-                begin_date: begin_date.to_string(),
-                end_date: end_date.to_string(),
-                building: building.map(|o| o.to_string()),
-                building_description: building_description.map(|o| o.to_string()),
-                campus: campus.map(|o| o.to_string()),
-                campus_description: campus_description.map(|o| o.to_string()),
-                room: room.map(|o| o.to_string()),
-                credit_hour_session: meeting.get(9).unwrap(),
-                hours_per_week: meeting.get(10).unwrap(),
-                in_session: Days::from_bits(meeting.get(11).unwrap()).unwrap(),
-                meeting_type: MeetingType::try_from(meeting_type).unwrap()
-            });
-        }
+    async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, Error> {
+        let mut conn = self.pool.get().await?;
+        let res = conn.query(
+            format!("SELECT {MEETING_COLUMNS} FROM [UniScraper].[UCM].[meeting] WHERE class_id = @P1;"),
+            &[&class_id])
+            .await?
+            .into_first_result()
+            .await?;
 
-        Ok(out)
+        Ok(res.iter().map(|o| meeting_from_row(o, class_id)).collect())
     }
 
-    pub async fn get_description_for_course(&self, course_number: &str) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_description_for_course(&self, course_number: &str) -> Result<Option<String>, Error> {
         let mut conn = self.pool.get().await?;
         let res = conn.query(
             "SELECT course_description FROM [UniScraper].[UCM].[description] WHERE @P1 LIKE course_number + '%';",
@@ -219,16 +345,14 @@ impl Database {
         Ok(None)
     }
 
-    // Course number is like CSE-031.
-    pub async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, Error> {
         self.general_class_search(course_number, term,
                                   "SELECT id, course_reference_number, course_number, course_title \
                                   FROM UniScraper.UCM.class \
                                   WHERE term = @P1 AND CONTAINS(course_number, @P2);").await
     }
 
-    // Course name is like Computer Organization and Assembly.
-    pub async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, Error> {
         self.general_class_search(course_name, term,
           "SELECT id, course_reference_number, course_number, course_title FROM \
                     (SELECT id, course_reference_number, course_number, course_title, term, ROW_NUMBER() \
@@ -237,22 +361,26 @@ impl Database {
                     WHERE mukyu.RowNumber = 1;").await
     }
 
-    fn create_full_text_query(&self, search_query: &str) -> String {
-        search_query
-            .trim()
-            .split(' ')
-            .map(|o| o.replace(['(', ')', '\"', '\''], "")) // *unqueries your query*
-            .map(|o| format!("\"*{o}*\"")) // Wildcards
-            .reduce(|a, b| format!("{a} AND {b}"))
-            .unwrap()
-    }
-
-    async fn general_class_search(&self, search_query: &str, term: i32, sql: &str) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, Error> {
         let mut conn = self.pool.get().await?;
 
         let input = self.create_full_text_query(search_query);
 
-        let res = conn.query(sql, &[&term, &input])
+        let res = conn.query("SELECT id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name FROM [UniScraper].[UCM].[professor] WHERE CONTAINS(full_name, @P1);", &[&input])
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.iter().map(professor_from_row).collect())
+    }
+
+    async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, Error> {
+        let mut conn = self.pool.get().await?;
+
+        let res = conn.query("SELECT class.id, class.course_reference_number, class.course_number, class.course_title FROM [UniScraper].[UCM].[professor] \
+            INNER JOIN [UniScraper].[UCM].[faculty] ON professor.id = faculty.professor_id \
+            INNER JOIN [UniScraper].[UCM].[class] ON class.id = faculty.class_id \
+            WHERE class.term = @P1 AND professor.id = @P2", &[&term, &professor_id])
             .await?
             .into_first_result()
             .await?;
@@ -270,60 +398,49 @@ impl Database {
                 course_title: course_title.map(|o| o.to_string())
             };
 
-            if search_query == course_number || course_title.map(|o| o == search_query).unwrap_or(false) {
-                // Return early with one item
-                return Ok(vec![item]);
-            } else {
-                out.push(item);
-            }
+            out.push(item);
         }
 
         Ok(out)
     }
 
-    pub async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, Error> {
         let mut conn = self.pool.get().await?;
-
-        let input = self.create_full_text_query(search_query);
-
-        let res = conn.query("SELECT id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name FROM [UniScraper].[UCM].[professor] WHERE CONTAINS(full_name, @P1);", &[&input])
+        let res = conn.simple_query(
+            "SELECT table_name, last_update FROM [UniScraper].[UCM].[stats];")
             .await?
             .into_first_result()
             .await?;
 
-        let mut out: Vec<Professor> = Vec::new();
-
-        for professor in res {
-            let last_name: &str = professor.get(2).unwrap();
-            let first_name: &str = professor.get(3).unwrap();
-            let middle_name: Option<&str> = professor.get(4);
-            let email: Option<&str> = professor.get(5);
-            let department: Option<&str> = professor.get(6);
-            let full_name: &str = professor.get(9).unwrap();
-            out.push(Professor {
-                id: professor.get(0).unwrap(),
-                rmp_id: professor.get(1),
-                last_name: last_name.to_string(),
-                first_name: first_name.to_string(),
-                middle_name: middle_name.map(|o| o.to_string()),
-                email: email.map(|o| o.to_string()),
-                department: department.map(|o| o.to_string()),
-                num_ratings: professor.get(7).unwrap(),
-                rating: professor.get(8).unwrap(),
-                full_name: full_name.to_string()
-            });
+        let mut out = HashMap::new();
+
+        for meeting in res {
+            let table_name: &str = meeting.get(0).unwrap();
+            let last_update: NaiveDateTime = meeting.get(1).unwrap();
+            out.insert(table_name.to_string(), last_update);
         }
 
         Ok(out)
     }
+}
+
+impl SqlServerDatabase {
+    fn create_full_text_query(&self, search_query: &str) -> String {
+        search_query
+            .trim()
+            .split(' ')
+            .map(|o| o.replace(['(', ')', '\"', '\''], "")) // *unqueries your query*
+            .map(|o| format!("\"*{o}*\"")) // Wildcards
+            .reduce(|a, b| format!("{a} AND {b}"))
+            .unwrap()
+    }
 
-    pub async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn general_class_search(&self, search_query: &str, term: i32, sql: &str) -> Result<Vec<PartialClass>, Error> {
         let mut conn = self.pool.get().await?;
 
-        let res = conn.query("SELECT class.id, class.course_reference_number, class.course_number, class.course_title FROM [UniScraper].[UCM].[professor] \
-            INNER JOIN [UniScraper].[UCM].[faculty] ON professor.id = faculty.professor_id \
-            INNER JOIN [UniScraper].[UCM].[class] ON class.id = faculty.class_id \
-            WHERE class.term = @P1 AND professor.id = @P2", &[&term, &professor_id])
+        let input = self.create_full_text_query(search_query);
+
+        let res = conn.query(sql, &[&term, &input])
             .await?
             .into_first_result()
             .await?;
@@ -341,28 +458,14 @@ impl Database {
                 course_title: course_title.map(|o| o.to_string())
             };
 
-            out.push(item);
-        }
-
-        Ok(out)
-    }
-
-    pub async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let res = conn.simple_query(
-            "SELECT table_name, last_update FROM [UniScraper].[UCM].[stats];")
-            .await?
-            .into_first_result()
-            .await?;
-
-        let mut out = HashMap::new();
-
-        for meeting in res {
-            let table_name: &str = meeting.get(0).unwrap();
-            let last_update: NaiveDateTime = meeting.get(1).unwrap();
-            out.insert(table_name.to_string(), last_update);
+            if search_query == course_number || course_title.map(|o| o == search_query).unwrap_or(false) {
+                // Return early with one item
+                return Ok(vec![item]);
+            } else {
+                out.push(item);
+            }
         }
 
         Ok(out)
     }
-}
\ No newline at end of file
+}
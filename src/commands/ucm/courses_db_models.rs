@@ -3,18 +3,194 @@ use bitflags::bitflags;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 
+bitflags! {
+    // What a reminder watches a class for.
+    pub struct ReminderConditions: u8 {
+        // Seats (or waitlist spots) reaching min_trigger.
+        const SEATS = 1;
+        // Going from none available to some, and back.
+        const OPENS = 2;
+        const CLOSES = 4;
+        const INSTRUCTOR = 8;
+        // Meeting times or rooms.
+        const SCHEDULE = 16;
+    }
+}
+
+impl Display for ReminderConditions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut names = Vec::new();
+
+        if self.contains(ReminderConditions::SEATS) { names.push("Seats"); }
+        if self.contains(ReminderConditions::OPENS) { names.push("Opens"); }
+        if self.contains(ReminderConditions::CLOSES) { names.push("Closes"); }
+        if self.contains(ReminderConditions::INSTRUCTOR) { names.push("Instructor"); }
+        if self.contains(ReminderConditions::SCHEDULE) { names.push("Time or room"); }
+
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub struct Reminder {
     pub user_id: u64,
     pub course_reference_number: i32,
     pub min_trigger: i32,
     pub for_waitlist: bool,
-    pub triggered: bool
+    // Once a reminder that doesn't recur has gone off, it's done. For recurring ones, only that the seats were
+    // reported and haven't filled up again yet.
+    pub triggered: bool,
+    pub conditions: ReminderConditions,
+    pub recurring: bool,
+    // A channel to mention them in instead of a DM, for people who keep those closed.
+    pub channel_id: Option<u64>,
+    // What the class looked like at the last check; None until it's been checked.
+    pub last_available: Option<i32>,
+    pub last_instructors: Option<String>,
+    pub last_schedule: Option<String>,
+    // What went off but hasn't been delivered yet, by the condition behind it.
+    pub pending: ReminderConditions,
+    // Set when we gave up on delivering it, so it shows up in the list.
+    pub delivery_failed: bool
 }
 
-pub struct Trigger {
-    pub user_id: u64,
-    pub course_reference_number: i32,
-    pub min_trigger: i32
+impl Reminder {
+    pub fn new(user_id: u64, course_reference_number: i32) -> Self {
+        Reminder {
+            user_id,
+            course_reference_number,
+            min_trigger: 1,
+            for_waitlist: false,
+            triggered: false,
+            conditions: ReminderConditions::SEATS,
+            recurring: false,
+            channel_id: None,
+            last_available: None,
+            last_instructors: None,
+            last_schedule: None,
+            pending: ReminderConditions::empty(),
+            delivery_failed: false
+        }
+    }
+
+    // Compares the class to how it was at the last check, and gives back why the reminder goes off, if it does.
+    // What was seen gets updated either way, so changes are only reported once.
+    pub fn check(&mut self, watch: &ClassWatch) -> Vec<TriggerReason> {
+        let mut reasons = Vec::new();
+        if self.triggered && !self.recurring {
+            return reasons;
+        }
+
+        let available = watch.available(self.for_waitlist);
+        let instructors = watch.instructors();
+        let schedule = watch.schedule();
+        let changed = |last: &Option<String>, now: &String| last.as_ref().map(|o| o != now).unwrap_or(false);
+
+        if self.conditions.contains(ReminderConditions::SEATS) {
+            if !self.triggered && available >= self.min_trigger {
+                reasons.push(TriggerReason::Seats);
+            } else if self.triggered && available < self.min_trigger {
+                // Filled up again, so the next opening is worth hearing about.
+                self.triggered = false;
+            }
+        }
+        if let Some(last) = self.last_available {
+            if self.conditions.contains(ReminderConditions::OPENS) && last <= 0 && available > 0 {
+                reasons.push(TriggerReason::Opened);
+            }
+            if self.conditions.contains(ReminderConditions::CLOSES) && last > 0 && available <= 0 {
+                reasons.push(TriggerReason::Closed);
+            }
+        }
+        if self.conditions.contains(ReminderConditions::INSTRUCTOR) && changed(&self.last_instructors, &instructors) {
+            reasons.push(TriggerReason::Instructor);
+        }
+        if self.conditions.contains(ReminderConditions::SCHEDULE) && changed(&self.last_schedule, &schedule) {
+            reasons.push(TriggerReason::Schedule);
+        }
+
+        self.last_available = Some(available);
+        self.last_instructors = Some(instructors);
+        self.last_schedule = Some(schedule);
+        if reasons.contains(&TriggerReason::Seats) || (!self.recurring && !reasons.is_empty()) {
+            self.triggered = true;
+        }
+
+        reasons
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum TriggerReason {
+    Seats,
+    Opened,
+    Closed,
+    Instructor,
+    Schedule
+}
+
+impl TriggerReason {
+    const ALL: [TriggerReason; 5] = [TriggerReason::Seats, TriggerReason::Opened, TriggerReason::Closed, TriggerReason::Instructor, TriggerReason::Schedule];
+
+    // Each reason has exactly one condition behind it, which is also how it's kept while waiting to be delivered.
+    pub fn condition(&self) -> ReminderConditions {
+        match self {
+            TriggerReason::Seats => ReminderConditions::SEATS,
+            TriggerReason::Opened => ReminderConditions::OPENS,
+            TriggerReason::Closed => ReminderConditions::CLOSES,
+            TriggerReason::Instructor => ReminderConditions::INSTRUCTOR,
+            TriggerReason::Schedule => ReminderConditions::SCHEDULE
+        }
+    }
+
+    pub fn from_conditions(conditions: ReminderConditions) -> Vec<TriggerReason> {
+        TriggerReason::ALL.into_iter().filter(|o| conditions.contains(o.condition())).collect()
+    }
+}
+
+impl Display for TriggerReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerReason::Seats => write!(f, "Enough seats opened up"),
+            TriggerReason::Opened => write!(f, "The section opened"),
+            TriggerReason::Closed => write!(f, "The section closed"),
+            TriggerReason::Instructor => write!(f, "The instructor changed"),
+            TriggerReason::Schedule => write!(f, "The meeting time or room changed")
+        }
+    }
+}
+
+// A class as reminders see it, along with who teaches it and when.
+pub struct ClassWatch {
+    pub class: Class,
+    pub instructors: Vec<Professor>,
+    pub meetings: Vec<Meeting>
+}
+
+impl ClassWatch {
+    pub fn available(&self, for_waitlist: bool) -> i32 {
+        if for_waitlist { self.class.wait_available as i32 } else { self.class.seats_available as i32 }
+    }
+
+    // Sorted, so the same people listed in another order aren't a change.
+    pub fn instructors(&self) -> String {
+        let mut names = self.instructors.iter().map(|o| o.full_name.clone()).collect::<Vec<_>>();
+        names.sort();
+        names.join(", ")
+    }
+
+    pub fn schedule(&self) -> String {
+        let mut meetings = self.meetings.iter().map(|o| format!("{}: {} {}-{} in {} {}",
+            o.meeting_type,
+            o.in_session,
+            o.begin_time.as_deref().unwrap_or("?"),
+            o.end_time.as_deref().unwrap_or("?"),
+            o.building.as_deref().unwrap_or("?"),
+            o.room.as_deref().unwrap_or("?")
+        )).collect::<Vec<_>>();
+        meetings.sort();
+        meetings.join("\n")
+    }
 }
 
 pub struct Class {
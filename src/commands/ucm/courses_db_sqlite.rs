@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use rusqlite::{OptionalExtension, Row, params};
+use serenity::model::id::UserId;
+
+use crate::Error;
+use crate::services::sqlite::{SqliteDatabase, to_sql_id, from_sql_id};
+use crate::commands::ucm::courses_db::CourseStorage;
+use crate::commands::ucm::courses_db_models::*;
+
+// SQLite has no full-text CONTAINS, so we do the same "every word is somewhere in there" check by hand.
+fn matches_query(search_query: &str, haystack: &str) -> bool {
+    let haystack = haystack.to_lowercase();
+    search_query
+        .trim()
+        .split(' ')
+        .map(|o| o.replace(['(', ')', '\"', '\''], "").to_lowercase())
+        .all(|o| haystack.contains(&o))
+}
+
+fn to_partial_class(row: &Row) -> Result<PartialClass, rusqlite::Error> {
+    Ok(PartialClass {
+        id: row.get(0)?,
+        course_reference_number: row.get(1)?,
+        course_number: row.get(2)?,
+        course_title: row.get(3)?
+    })
+}
+
+fn to_professor(row: &Row) -> Result<Professor, rusqlite::Error> {
+    Ok(Professor {
+        id: row.get(0)?,
+        rmp_id: row.get(1)?,
+        last_name: row.get(2)?,
+        first_name: row.get(3)?,
+        middle_name: row.get(4)?,
+        email: row.get(5)?,
+        department: row.get(6)?,
+        num_ratings: row.get(7)?,
+        rating: row.get(8)?,
+        full_name: row.get(9)?
+    })
+}
+
+const REMINDER_COLUMNS: &str = "user_id, course_reference_number, min_trigger, for_waitlist, triggered, conditions, recurring, channel_id, last_available, last_instructors, last_schedule, pending, delivery_failed";
+
+// Not gone off yet, going off again, or still waiting to be delivered.
+const REMINDER_ARMED: &str = "triggered = 0 OR recurring = 1 OR pending <> 0";
+
+const CLASS_COLUMNS: &str = "id, term, course_reference_number, course_number, campus_description, course_title, credit_hours, maximum_enrollment, enrollment, seats_available, wait_capacity, wait_available";
+
+const MEETING_COLUMNS: &str = "class_id, begin_time, end_time, begin_date, end_date, building, building_description, campus, campus_description, room, credit_hour_session, hours_per_week, in_session, meeting_type";
+
+fn to_reminder(row: &Row) -> Result<Reminder, rusqlite::Error> {
+    let channel_id: Option<i64> = row.get(7)?;
+    Ok(Reminder {
+        user_id: from_sql_id(row.get(0)?),
+        course_reference_number: row.get(1)?,
+        min_trigger: row.get(2)?,
+        for_waitlist: row.get(3)?,
+        triggered: row.get(4)?,
+        conditions: ReminderConditions::from_bits_truncate(row.get(5)?),
+        recurring: row.get(6)?,
+        channel_id: channel_id.map(from_sql_id),
+        last_available: row.get(8)?,
+        last_instructors: row.get(9)?,
+        last_schedule: row.get(10)?,
+        pending: ReminderConditions::from_bits_truncate(row.get(11)?),
+        delivery_failed: row.get(12)?
+    })
+}
+
+fn to_class(row: &Row) -> Result<Class, rusqlite::Error> {
+    Ok(Class {
+        id: row.get(0)?,
+        term: row.get(1)?,
+        course_reference_number: row.get(2)?,
+        course_number: row.get(3)?,
+        campus_description: row.get(4)?,
+        course_title: row.get(5)?,
+        credit_hours: row.get(6)?,
+        maximum_enrollment: row.get(7)?,
+        enrollment: row.get(8)?,
+        seats_available: row.get(9)?,
+        wait_capacity: row.get(10)?,
+        wait_available: row.get(11)?
+    })
+}
+
+fn to_meeting(row: &Row) -> Result<Meeting, rusqlite::Error> {
+    let meeting_type: u8 = row.get(13)?;
+    Ok(Meeting {
+        class_id: row.get(0)?,
+        begin_time: row.get(1)?,
+        end_time: row.get(2)?,
+        begin_date: row.get(3)?,
+        end_date: row.get(4)?,
+        building: row.get(5)?,
+        building_description: row.get(6)?,
+        campus: row.get(7)?,
+        campus_description: row.get(8)?,
+        room: row.get(9)?,
+        credit_hour_session: row.get(10)?,
+        hours_per_week: row.get(11)?,
+        in_session: Days::from_bits(row.get(12)?).unwrap(),
+        meeting_type: MeetingType::try_from(meeting_type).unwrap()
+    })
+}
+
+impl SqliteDatabase {
+    async fn general_class_search(&self, search_query: &str, term: i32, by_name: bool) -> Result<Vec<PartialClass>, Error> {
+        let search_query = search_query.to_string();
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, course_reference_number, course_number, course_title FROM ucm_class WHERE term = ?1 ORDER BY course_reference_number")?;
+            let classes = statement
+                .query_map(params![term], to_partial_class)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut out: Vec<PartialClass> = Vec::new();
+            // Searching by name only gives back one section per course title.
+            let mut seen_titles: HashSet<Option<String>> = HashSet::new();
+
+            for item in classes {
+                let searched = if by_name { item.course_title.clone().unwrap_or_default() } else { item.course_number.clone() };
+                if !matches_query(&search_query, &searched) {
+                    continue;
+                }
+
+                if search_query == item.course_number || item.course_title.as_ref().map(|o| *o == search_query).unwrap_or(false) {
+                    // Return early with one item
+                    return Ok(vec![item]);
+                }
+
+                if by_name && !seen_titles.insert(item.course_title.clone()) {
+                    continue;
+                }
+
+                out.push(item);
+            }
+
+            Ok(out)
+        }).await
+    }
+}
+
+#[async_trait]
+impl CourseStorage for SqliteDatabase {
+    async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, Error> {
+        let user = to_sql_id(user_id.0);
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare(&format!("SELECT {REMINDER_COLUMNS} FROM ucm_reminder WHERE user_id = ?1"))?;
+            let reminders = statement
+                .query_map(params![user], to_reminder)?
+                .collect::<Result<Vec<_>, _>>();
+            reminders
+        }).await
+    }
+
+    async fn add_reminder(&self, reminder: &Reminder) -> Result<(), Error> {
+        let user = to_sql_id(reminder.user_id);
+        let course_reference_number = reminder.course_reference_number;
+        let min_trigger = reminder.min_trigger;
+        let for_waitlist = reminder.for_waitlist;
+        let triggered = reminder.triggered;
+        let conditions = reminder.conditions.bits();
+        let recurring = reminder.recurring;
+        let channel = reminder.channel_id.map(to_sql_id);
+
+        // Will fail if there is a duplicate, same as SQL Server.
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ucm_reminder (user_id, course_reference_number, min_trigger, for_waitlist, triggered, conditions, recurring, channel_id) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![user, course_reference_number, min_trigger, for_waitlist, triggered, conditions, recurring, channel])?;
+            Ok(())
+        }).await
+    }
+
+    async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, Error> {
+        let user = to_sql_id(user_id.0);
+
+        self.call(move |conn| {
+            let total = conn.execute(
+                "DELETE FROM ucm_reminder WHERE user_id = ?1 AND course_reference_number = ?2",
+                params![user, course_reference_number])?;
+            Ok(total > 0)
+        }).await
+    }
+
+    async fn get_armed_reminders(&self) -> Result<Vec<Reminder>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare(&format!("SELECT {REMINDER_COLUMNS} FROM ucm_reminder WHERE {REMINDER_ARMED}"))?;
+            let reminders = statement
+                .query_map([], to_reminder)?
+                .collect::<Result<Vec<_>, _>>();
+            reminders
+        }).await
+    }
+
+    async fn save_reminder_state(&self, reminder: &Reminder) -> Result<(), Error> {
+        let user = to_sql_id(reminder.user_id);
+        let course_reference_number = reminder.course_reference_number;
+        let triggered = reminder.triggered;
+        let last_available = reminder.last_available;
+        let last_instructors = reminder.last_instructors.clone();
+        let last_schedule = reminder.last_schedule.clone();
+        let pending = reminder.pending.bits();
+        let delivery_failed = reminder.delivery_failed;
+
+        self.call(move |conn| {
+            conn.execute(
+                "UPDATE ucm_reminder SET triggered = ?3, last_available = ?4, last_instructors = ?5, last_schedule = ?6, pending = ?7, delivery_failed = ?8 \
+                WHERE user_id = ?1 AND course_reference_number = ?2",
+                params![user, course_reference_number, triggered, last_available, last_instructors, last_schedule, pending, delivery_failed])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, Error> {
+        self.call(move |conn| {
+            conn.query_row(
+                &format!("SELECT {CLASS_COLUMNS} FROM ucm_class WHERE course_reference_number = ?1 ORDER BY term DESC LIMIT 1"),
+                params![course_reference_number],
+                to_class)
+                .optional()
+        }).await
+    }
+
+    async fn get_watched_classes(&self) -> Result<HashMap<i32, ClassWatch>, Error> {
+        self.call(move |conn| {
+            let watched = format!("SELECT course_reference_number FROM ucm_reminder WHERE {REMINDER_ARMED}");
+            let watched_ids = format!("SELECT id FROM ucm_class WHERE course_reference_number IN ({watched})");
+
+            // Oldest term first, so the newest section with a CRN is the one that sticks, same as get_class.
+            let mut statement = conn.prepare(
+                &format!("SELECT {CLASS_COLUMNS} FROM ucm_class WHERE course_reference_number IN ({watched}) ORDER BY term"))?;
+            let mut out: HashMap<i32, ClassWatch> = HashMap::new();
+            for class in statement.query_map([], to_class)? {
+                let class = class?;
+                out.insert(class.course_reference_number, ClassWatch { class, instructors: Vec::new(), meetings: Vec::new() });
+            }
+
+            let by_id: HashMap<i32, i32> = out.values().map(|o| (o.class.id, o.class.course_reference_number)).collect();
+
+            let mut statement = conn.prepare(
+                &format!("SELECT ucm_professor.id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name, class_id \
+                FROM ucm_professor INNER JOIN ucm_faculty ON ucm_professor.id = ucm_faculty.professor_id WHERE class_id IN ({watched_ids})"))?;
+            let professors = statement
+                .query_map([], |row| Ok((row.get::<_, i32>(10)?, to_professor(row)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (class_id, professor) in professors {
+                if let Some(watch) = by_id.get(&class_id).and_then(|o| out.get_mut(o)) {
+                    watch.instructors.push(professor);
+                }
+            }
+
+            let mut statement = conn.prepare(&format!("SELECT {MEETING_COLUMNS} FROM ucm_meeting WHERE class_id IN ({watched_ids})"))?;
+            let meetings = statement
+                .query_map([], to_meeting)?
+                .collect::<Result<Vec<_>, _>>()?;
+            for meeting in meetings {
+                if let Some(watch) = by_id.get(&meeting.class_id).and_then(|o| out.get_mut(o)) {
+                    watch.meetings.push(meeting);
+                }
+            }
+
+            Ok(out)
+        }).await
+    }
+
+    async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT ucm_professor.id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name \
+                FROM ucm_professor INNER JOIN ucm_faculty ON ucm_professor.id = ucm_faculty.professor_id WHERE class_id = ?1")?;
+            let professors = statement
+                .query_map(params![class_id], to_professor)?
+                .collect::<Result<Vec<_>, _>>();
+            professors
+        }).await
+    }
+
+    async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare(&format!("SELECT {MEETING_COLUMNS} FROM ucm_meeting WHERE class_id = ?1"))?;
+            let meetings = statement
+                .query_map(params![class_id], to_meeting)?
+                .collect::<Result<Vec<_>, _>>();
+            meetings
+        }).await
+    }
+
+    async fn get_description_for_course(&self, course_number: &str) -> Result<Option<String>, Error> {
+        let course_number = course_number.to_string();
+
+        self.call(move |conn| {
+            let description: Option<Option<String>> = conn.query_row(
+                "SELECT course_description FROM ucm_description WHERE ?1 LIKE course_number || '%'",
+                params![course_number],
+                |row| row.get(0))
+                .optional()?;
+            Ok(description.flatten())
+        }).await
+    }
+
+    async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, Error> {
+        self.general_class_search(course_number, term, false).await
+    }
+
+    async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, Error> {
+        self.general_class_search(course_name, term, true).await
+    }
+
+    async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, Error> {
+        let search_query = search_query.to_string();
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name FROM ucm_professor")?;
+            let professors = statement
+                .query_map([], to_professor)?
+                .filter(|o| o.as_ref().map(|p| matches_query(&search_query, &p.full_name)).unwrap_or(true))
+                .collect::<Result<Vec<_>, _>>();
+            professors
+        }).await
+    }
+
+    async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT ucm_class.id, ucm_class.course_reference_number, ucm_class.course_number, ucm_class.course_title FROM ucm_professor \
+                INNER JOIN ucm_faculty ON ucm_professor.id = ucm_faculty.professor_id \
+                INNER JOIN ucm_class ON ucm_class.id = ucm_faculty.class_id \
+                WHERE ucm_class.term = ?1 AND ucm_professor.id = ?2")?;
+            let classes = statement
+                .query_map(params![term, professor_id], to_partial_class)?
+                .collect::<Result<Vec<_>, _>>();
+            classes
+        }).await
+    }
+
+    async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare("SELECT table_name, last_update FROM ucm_stats")?;
+            let stats = statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<HashMap<String, NaiveDateTime>, _>>();
+            stats
+        }).await
+    }
+}
@@ -7,7 +7,8 @@ mod course_models;
 mod pavilion;
 mod pav_models;
 pub mod reminders;
-mod courses_db;
+pub mod courses_db;
+mod courses_db_sqlite;
 mod courses_db_models;
 mod foodtrucks;
 mod calendar;
@@ -1,8 +1,52 @@
+use async_trait::async_trait;
+use serenity::builder::CreateEmbed;
+use serenity::model::id::ChannelId;
+use serenity::model::permissions::Permissions;
 use tracing::error;
 use crate::{CowContext, cowdb, Error};
 
 use crate::{db, Database};
-use crate::commands::ucm::courses_db_models::Reminder;
+use crate::commands::ucm::courses_db_models::{Reminder, ReminderConditions};
+use crate::util::paginator::{paginate, PageSource};
+
+// Well under Discord's 25 field limit.
+const REMINDERS_PER_PAGE: usize = 10;
+
+struct ReminderPages {
+    reminders: Vec<Reminder>
+}
+
+#[async_trait]
+impl PageSource for ReminderPages {
+    async fn render(&self, page: usize, embed: &mut CreateEmbed) -> Result<usize, Error> {
+        let pages = self.reminders.chunks(REMINDERS_PER_PAGE).len();
+        embed.title("Your Course Reminders");
+
+        if self.reminders.is_empty() {
+            embed.description("You do not have any reminders set. Add some using `reminders add`.");
+        } else {
+            let page = page.min(pages - 1);
+            for reminder in self.reminders.chunks(REMINDERS_PER_PAGE).nth(page).unwrap_or_default() {
+                let delivery = reminder.channel_id.map(|o| format!("<#{o}>")).unwrap_or_else(|| "DM".to_string());
+                let status = if !reminder.pending.is_empty() {
+                    "\n**Delivery pending**, still trying to send it."
+                } else if reminder.delivery_failed {
+                    "\n**Delivery failed**, check that your DMs are open or that I can talk in that channel."
+                } else {
+                    ""
+                };
+                embed.field(format!("CRN {}", reminder.course_reference_number),
+                            format!("Watching: `{}`\nMinimum Trigger: `{}`\nFor Waitlist: `{}`\nRecurring: `{}`\nTriggered: `{}`\nSent To: {}{}",
+                                    reminder.conditions, reminder.min_trigger, reminder.for_waitlist, reminder.recurring, reminder.triggered, delivery, status),
+                            false);
+            }
+
+            embed.footer(|f| f.text(format!("Page {}/{}", page + 1, pages)));
+        }
+
+        Ok(pages.max(1))
+    }
+}
 
 #[poise::command(
     prefix_command,
@@ -19,24 +63,7 @@ pub async fn list_code(ctx: CowContext<'_>) -> Result<(), Error> {
 
     match db.get_user_reminders(ctx.author().id).await {
         Ok(reminders) => {
-            ctx.send(|m| {
-                m.embeds.clear();
-                m.embed(|e| {
-                    e.title("Your Course Reminders");
-
-                    if reminders.is_empty() {
-                        e.description("You do not have any reminders set. Add some using `reminders add`.");
-                    } else {
-                        for reminder in reminders {
-                            e.field(format!("CRN {}", reminder.course_reference_number),
-                                    format!("Minimum Trigger: `{}`\nFor Waitlist: `{}`\nTriggered: `{}`", reminder.min_trigger, reminder.for_waitlist, reminder.triggered),
-                                    false);
-                        }
-                    }
-
-                    e
-                })
-            }).await?;
+            paginate(ctx, ReminderPages { reminders }, 0).await?;
         }
         Err(ex) => {
             error!("Failed to get reminders for user: {}", ex);
@@ -47,16 +74,53 @@ pub async fn list_code(ctx: CowContext<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+// Whether the channel's in this server, and both the author and the bot can see it and send messages there;
+// otherwise anyone could have mentions posted in staff-only or read-only channels.
+async fn can_remind_in(ctx: CowContext<'_>, channel_id: ChannelId) -> bool {
+    let guild = match ctx.guild() {
+        Some(guild) => guild,
+        None => return false
+    };
+    let channel = match guild.channels.get(&channel_id).cloned().and_then(|o| o.guild()) {
+        Some(channel) => channel,
+        None => return false
+    };
+
+    let serenity = ctx.serenity_context();
+    let bot_id = serenity.cache.current_user_id();
+    for (user_id, needed) in [
+        (ctx.author().id, Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES),
+        (bot_id, Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS)
+    ] {
+        let allowed = match guild.member(serenity, user_id).await {
+            Ok(member) => guild.user_permissions_in(&channel, &member).map(|o| o.contains(needed)).unwrap_or(false),
+            Err(_) => false
+        };
+        if !allowed {
+            return false;
+        }
+    }
+
+    true
+}
+
 #[poise::command(
     prefix_command,
     slash_command,
-    description_localized("en-US", "Control reminders for class seats.")
+    description_localized("en-US", "Control reminders for class seats, openings, instructors and meeting times.")
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn add(
     ctx: CowContext<'_>,
     #[description = "The CRN of the class to get reminders for"] #[min = 10000] course_reference_number: i32,
     #[description = "The minimum amount of seats to trigger at, 1 minimum"]  #[min = 1] min_seats: Option<i32>,
-    #[description = "If the reminder is for a waitlist spot"] for_waitlist: Option<bool>)
+    #[description = "If the reminder is for a waitlist spot"] for_waitlist: Option<bool>,
+    #[description = "Remind when the section opens up"] on_open: Option<bool>,
+    #[description = "Remind when the section closes"] on_close: Option<bool>,
+    #[description = "Remind when the instructor changes"] on_instructor: Option<bool>,
+    #[description = "Remind when the meeting time or room changes"] on_schedule: Option<bool>,
+    #[description = "Keep reminding each time seats open up again, instead of only once"] recurring: Option<bool>,
+    #[description = "Mention you in this channel instead of sending a DM"] channel: Option<ChannelId>)
 -> Result<(), Error> {
 
     let min_trigger = if let Some(seats) = min_seats {
@@ -70,14 +134,34 @@ pub async fn add(
         1
     };
     
-    let reminder = Reminder {
-        user_id: ctx.author().id.0,
-        course_reference_number,
-        min_trigger,
-        for_waitlist: for_waitlist.unwrap_or(false),
-        triggered: false
+    let channel_id = if let Some(channel) = channel {
+        if !can_remind_in(ctx, channel).await {
+            ctx.say("Reminders can only be sent to a channel in this server that both of us can talk in.").await?;
+            return Ok(());
+        }
+
+        Some(channel.0)
+    } else {
+        None
     };
 
+    let mut conditions = ReminderConditions::empty();
+    conditions.set(ReminderConditions::OPENS, on_open.unwrap_or(false));
+    conditions.set(ReminderConditions::CLOSES, on_close.unwrap_or(false));
+    conditions.set(ReminderConditions::INSTRUCTOR, on_instructor.unwrap_or(false));
+    conditions.set(ReminderConditions::SCHEDULE, on_schedule.unwrap_or(false));
+    // Seats are what it's always been about, so keep watching them unless only something else was asked for.
+    if min_seats.is_some() || conditions.is_empty() {
+        conditions.insert(ReminderConditions::SEATS);
+    }
+
+    let mut reminder = Reminder::new(ctx.author().id.0, course_reference_number);
+    reminder.min_trigger = min_trigger;
+    reminder.for_waitlist = for_waitlist.unwrap_or(false);
+    reminder.conditions = conditions;
+    reminder.recurring = recurring.unwrap_or(false);
+    reminder.channel_id = channel_id;
+
     let db = cowdb!(ctx);
 
     if let Ok(Some(class)) = db.get_class(course_reference_number).await {
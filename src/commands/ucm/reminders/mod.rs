@@ -1,15 +1,19 @@
 mod course_reminders;
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::error;
 use serenity::{
     CacheAndHttp,
+    builder::CreateEmbed,
+    model::{id::{ChannelId, UserId}, mention::Mention},
     prelude::TypeMap
 };
 use tokio::sync::RwLock;
 use tokio::time;
 use crate::{CowContext, Database, Error};
+use crate::commands::ucm::courses_db_models::{Class, Reminder, ReminderConditions, TriggerReason};
 use course_reminders::*;
 
 #[poise::command(
@@ -25,38 +29,161 @@ pub async fn reminders(ctx: CowContext<'_>) -> Result<(), Error> {
     list_code(ctx).await
 }
 
+// How many times in a row delivery can fail before we give up and flag it in the list instead.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+// Doubles after each failure: 1, 2, 4, then 8 minutes.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+// Where a batch of reminders goes: the user, and the channel to mention them in, if not their DMs.
+type Destination = (u64, Option<u64>);
+
+struct Backoff {
+    attempts: u32,
+    retry_at: Instant
+}
+
 pub async fn check_reminders(data: Arc<RwLock<TypeMap>>, ctx: Arc<CacheAndHttp>) {
     let mut interval_min = time::interval(Duration::from_secs(60));
+    // Kept in memory; after a restart, anything still pending just gets tried again right away.
+    let mut backoff: HashMap<Destination, Backoff> = HashMap::new();
     loop {
         interval_min.tick().await;
-        let ctx_global = data.read().await;
-        let db = ctx_global.get::<Database>().expect("Couldn't find database").clone();
-        match db.trigger_reminders().await {
-            Ok(triggers) => {
-                for trigger in triggers {
-                    if let Ok(user) = ctx.http.get_user(trigger.user_id).await {
-                        if let Ok(Some(class)) = db.get_class(trigger.course_reference_number).await {
-                            if let Err(ex) = user.direct_message(&ctx.http, |m| {
-                                m.embed(|e| e
-                                    .title("Reminder Triggered~")
-                                    .description(class.course_title.unwrap_or_else(|| "<unknown class name>".to_string()))
-                                    .field("Course Number", class.course_number, true)
-                                    .field("Course Reference Number", class.course_reference_number, true)
-                                    .field("Seats Available/Total", format!("{}/{}", class.seats_available, class.maximum_enrollment), true)
-                                    .field("Waitlist Available/Total", format!("{}/{}", class.wait_available, class.wait_capacity), true)
-                                )
-                            }).await {
-                                error!("Failed to send DM to user: {}", ex);
-                            }
-                        }
+        let db = {
+            let ctx_global = data.read().await;
+            ctx_global.get::<Database>().expect("Couldn't find database").clone()
+        };
+
+        let mut reminders = match db.get_armed_reminders().await {
+            Ok(reminders) => reminders,
+            Err(ex) => {
+                error!("Failed to query reminders: {}", ex);
+                continue;
+            }
+        };
+        let watches = match db.get_watched_classes().await {
+            Ok(watches) => watches,
+            Err(ex) => {
+                error!("Failed to get classes for reminders: {}", ex);
+                continue;
+            }
+        };
+        let before = reminders.clone();
+
+        // Everything going to the same place gets sent together.
+        let mut deliveries: HashMap<Destination, Vec<usize>> = HashMap::new();
+        for (i, reminder) in reminders.iter_mut().enumerate() {
+            if let Some(watch) = watches.get(&reminder.course_reference_number) {
+                for reason in reminder.check(watch) {
+                    reminder.pending.insert(reason.condition());
+                }
+            }
+
+            if !reminder.pending.is_empty() {
+                deliveries.entry((reminder.user_id, reminder.channel_id)).or_default().push(i);
+            }
+        }
+
+        // Nothing left to send there, whether it went through or they removed the reminder.
+        backoff.retain(|o, _| deliveries.contains_key(o));
+
+        let now = Instant::now();
+        for (destination, indices) in deliveries {
+            if backoff.get(&destination).map(|o| o.retry_at > now).unwrap_or(false) {
+                continue;
+            }
+
+            let result = {
+                let entries = indices.iter()
+                    .map(|o| &reminders[*o])
+                    .map(|o| (o, watches.get(&o.course_reference_number).map(|w| &w.class)))
+                    .collect::<Vec<_>>();
+                deliver(&ctx, destination, &entries).await
+            };
+
+            // Some(failed) once we're done with these, None if they stay pending for another try.
+            let done = match result {
+                Ok(_) => {
+                    backoff.remove(&destination);
+                    Some(false)
+                }
+                Err(ex) => {
+                    let attempts = backoff.get(&destination).map(|o| o.attempts).unwrap_or(0) + 1;
+                    if attempts >= MAX_DELIVERY_ATTEMPTS {
+                        error!("Giving up on delivering reminders to user {} after {} attempts: {}", destination.0, attempts, ex);
+                        backoff.remove(&destination);
+                        Some(true)
                     } else {
-                        error!("Failed to get user");
+                        error!("Failed to deliver reminders to user {}, trying again later: {}", destination.0, ex);
+                        backoff.insert(destination, Backoff { attempts, retry_at: now + RETRY_DELAY * 2u32.pow(attempts - 1) });
+                        None
                     }
                 }
-            },
-            Err(ex) => {
-                error!("Failed to query reminders: {}", ex);
+            };
+
+            if let Some(failed) = done {
+                for i in indices {
+                    reminders[i].pending = ReminderConditions::empty();
+                    reminders[i].delivery_failed = failed;
+                }
+            }
+        }
+
+        for (reminder, before) in reminders.iter().zip(before.iter()) {
+            if reminder == before {
+                continue;
+            }
+
+            if let Err(ex) = db.save_reminder_state(reminder).await {
+                error!("Failed to save reminder state: {}", ex);
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// A field for each class; Discord caps embeds at 25 of those.
+fn reminder_embeds(entries: &[(&Reminder, Option<&Class>)]) -> Vec<CreateEmbed> {
+    entries.chunks(25).map(|chunk| {
+        let mut embed = CreateEmbed::default();
+        embed.title("Reminder Triggered~");
+        for (reminder, class) in chunk {
+            let reasons = TriggerReason::from_conditions(reminder.pending)
+                .iter()
+                .map(|o| o.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            match class {
+                Some(class) => embed.field(
+                    format!("{} (CRN {})", class.course_number, class.course_reference_number),
+                    format!("{}\n{}\nSeats: {}/{}, Waitlist: {}/{}",
+                            class.course_title.as_deref().unwrap_or("<unknown class name>"),
+                            reasons,
+                            class.seats_available, class.maximum_enrollment,
+                            class.wait_available, class.wait_capacity),
+                    false),
+                None => embed.field(format!("CRN {}", reminder.course_reference_number), reasons, false)
+            };
+        }
+        embed
+    }).collect()
+}
+
+// Everything for one destination goes out as a single message.
+async fn deliver(ctx: &Arc<CacheAndHttp>, destination: Destination, entries: &[(&Reminder, Option<&Class>)]) -> Result<(), Error> {
+    let embeds = reminder_embeds(entries);
+    let (user_id, channel_id) = destination;
+
+    if let Some(channel) = channel_id {
+        let user = UserId(user_id);
+        ChannelId(channel).send_message(&ctx.http, |m| m
+            .content(Mention::from(user))
+            .allowed_mentions(|o| o.users(vec![user]))
+            .set_embeds(embeds)
+        ).await?;
+    } else {
+        let user = ctx.http.get_user(user_id).await?;
+        user.direct_message(&ctx.http, |m| m.set_embeds(embeds)).await?;
+    }
+
+    Ok(())
+}
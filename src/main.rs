@@ -6,16 +6,17 @@ mod util;
 use std::collections::{HashSet};
 use commands::{get_framework};
 use models::config::Config;
-use services::{*, database::Database};
+use services::{*, database::Database, spam_filter::SpamFilter};
+use commands::cowboard::cowboard_bursts::ReactionBursts;
 use std::fs;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::env;
 use std::error;
 use lavalink_rs::{LavalinkClient, gateway::LavalinkEventHandler};
 use serenity::{
     async_trait,
     client::{Context, EventHandler},
-    model::{channel::{Reaction}, gateway::{Ready, GatewayIntents}, id::{UserId, ChannelId, MessageId}, guild::Member},
+    model::{channel::{Message, Reaction}, event::MessageUpdateEvent, gateway::{Ready, GatewayIntents}, id::{UserId, ChannelId, GuildId, MessageId}, guild::Member, user::User},
     http::Http,
     prelude::TypeMapKey
 };
@@ -47,6 +48,10 @@ impl EventHandler for Handler {
         message_handler::on_join(&ctx, &new_member).await;
     }
 
+    async fn guild_member_removal(&self, ctx: Context, guild_id: GuildId, user: User, _member_data_if_available: Option<Member>) {
+        message_handler::on_leave(&ctx, guild_id, &user).await;
+    }
+
     async fn reaction_add(&self, ctx: Context, added_reaction: Reaction) {
         commands::cowboard::cowboard_handler::add_reaction(&ctx, &added_reaction).await;
     }
@@ -59,6 +64,20 @@ impl EventHandler for Handler {
         commands::cowboard::cowboard_handler::reaction_remove_all(&ctx, channel_id, removed_from_message_id).await;
     }
 
+    async fn message_update(&self, ctx: Context, _old_if_available: Option<Message>, _new: Option<Message>, event: MessageUpdateEvent) {
+        commands::cowboard::cowboard_handler::message_update(&ctx, &event).await;
+    }
+
+    async fn message_delete(&self, ctx: Context, channel_id: ChannelId, deleted_message_id: MessageId, guild_id: Option<GuildId>) {
+        commands::cowboard::cowboard_handler::message_delete(&ctx, channel_id, deleted_message_id, guild_id).await;
+    }
+
+    async fn message_delete_bulk(&self, ctx: Context, channel_id: ChannelId, multiple_deleted_messages_ids: Vec<MessageId>, guild_id: Option<GuildId>) {
+        for message_id in multiple_deleted_messages_ids {
+            commands::cowboard::cowboard_handler::message_delete(&ctx, channel_id, message_id, guild_id).await;
+        }
+    }
+
     async fn ready(&self, ctx: Context, ready: Ready) {
         bot_init::ready(&ctx, &ready).await;
     }
@@ -120,10 +139,10 @@ async fn main() -> Result<(), Box<dyn error::Error>>  {
     let config_json = fs::read_to_string("config.json").expect("config.json not found");
     let config : Config = serde_json::from_str(&config_json).expect("config.json is malformed");
 
-    let token = config.token;
+    let token = config.token.clone();
     let (app_id, owners) = fetch_bot_info(&token).await;
     let framework = get_framework(&config.cmd_prefix, app_id, owners).await;
-    let database = Arc::new(Database::new(&config.sql_server_ip, config.sql_server_port, &config.sql_server_username, &config.sql_server_password).await.unwrap());
+    let database = Arc::new(Database::new(&config).await.unwrap());
 
     let event_handler = Handler;
 
@@ -172,12 +191,26 @@ async fn main() -> Result<(), Box<dyn error::Error>>  {
         {
             let mut data = serenity.data.write().await;
             data.insert::<Database>(database.clone());
+            data.insert::<SpamFilter>(Arc::new(Mutex::new(SpamFilter::default())));
+            data.insert::<ReactionBursts>(Arc::new(Mutex::new(ReactionBursts::default())));
         }
 
         // Start our reminder task and forget about it. Tokio allows us to start without await.
         #[allow(clippy::let_underscore_future)]
         let _ = tokio::task::spawn(commands::ucm::reminders::check_reminders(serenity.data.clone(), serenity.cache_and_http.clone()));
 
+        // Same deal for voice XP, which ticks once a minute.
+        #[allow(clippy::let_underscore_future)]
+        let _ = tokio::task::spawn(voice_handler::check_voice(serenity.data.clone(), serenity.cache_and_http.clone()));
+
+        // And XP decay, which looks for newly inactive members every hour.
+        #[allow(clippy::let_underscore_future)]
+        let _ = tokio::task::spawn(decay_handler::check_decay(serenity.data.clone(), serenity.cache_and_http.clone()));
+
+        // Scheduled rank role fixes, for servers that turned them on.
+        #[allow(clippy::let_underscore_future)]
+        let _ = tokio::task::spawn(auto_fix_handler::check_auto_fix(serenity.data.clone(), serenity.cache_and_http.clone()));
+
         let commands = &poise.options().commands;
         let command_builders = poise::builtins::create_application_commands(commands);
         let try_create_commands = Command::set_global_application_commands(&serenity.cache_and_http.http, |commands| {
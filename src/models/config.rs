@@ -3,13 +3,33 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub token: String,
+    #[serde(default)]
+    pub database: DatabaseBackend,
+    #[serde(default)]
     pub sql_server_ip: String,
+    #[serde(default)]
     pub sql_server_port: u16,
+    #[serde(default)]
     pub sql_server_username: String,
+    #[serde(default)]
     pub sql_server_password: String,
+    #[serde(default = "default_sqlite_path")]
+    pub sqlite_path: String,
     pub cmd_prefix: String,
     pub lavalink_ip: String,
     pub lavalink_password: String,
     pub danbooru_login: String,
     pub danbooru_api_key: String
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseBackend {
+    #[default]
+    SqlServer,
+    Sqlite
+}
+
+fn default_sqlite_path() -> String {
+    "cow.db".to_string()
+}
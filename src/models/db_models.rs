@@ -1,7 +1,13 @@
-use serenity::model::id::{RoleId, UserId};
+use std::fmt::{Display, Formatter};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use rand::Rng;
+use serenity::model::id::{ChannelId, RoleId, UserId};
 
 pub struct LevelUp {
     pub level: i32,
+    pub old_level: i32,
     pub old_rank: Option<u64>,
     pub new_rank: Option<u64>
 }
@@ -10,6 +16,7 @@ impl LevelUp {
     pub fn new() -> Self {
         LevelUp {
             level: 0,
+            old_level: 0,
             old_rank: None,
             new_rank: None
         }
@@ -56,4 +63,870 @@ pub struct MemberPagination {
     pub members: Vec<Member>,
     pub current_page: i32,
     pub last_page: i32
-}
\ No newline at end of file
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, FromPrimitive)]
+pub enum LevelCurve {
+    Linear = 0,
+    Quadratic = 1,
+    Custom = 2
+}
+
+impl Display for LevelCurve {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelCurve::Linear => write!(f, "Linear"),
+            LevelCurve::Quadratic => write!(f, "Quadratic"),
+            LevelCurve::Custom => write!(f, "Custom")
+        }
+    }
+}
+
+impl TryFrom<u8> for LevelCurve {
+    type Error = ();
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        FromPrimitive::from_u8(v).ok_or(())
+    }
+}
+
+impl LevelCurve {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "linear" => Some(LevelCurve::Linear),
+            "quadratic" => Some(LevelCurve::Quadratic),
+            "custom" | "table" => Some(LevelCurve::Custom),
+            _ => None
+        }
+    }
+}
+
+// Whether members keep every rank role they've earned, or only the highest one.
+#[derive(Copy, Clone, PartialEq, Eq, FromPrimitive)]
+pub enum RoleMode {
+    Replace = 0,
+    Stack = 1
+}
+
+impl Display for RoleMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoleMode::Replace => write!(f, "Replace"),
+            RoleMode::Stack => write!(f, "Stack")
+        }
+    }
+}
+
+impl TryFrom<u8> for RoleMode {
+    type Error = ();
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        FromPrimitive::from_u8(v).ok_or(())
+    }
+}
+
+impl RoleMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "replace" | "highest" => Some(RoleMode::Replace),
+            "stack" | "all" => Some(RoleMode::Stack),
+            _ => None
+        }
+    }
+
+    // The rank roles someone at this level should have; ranks are expected in get_roles order (lowest first).
+    pub fn earned_roles(&self, ranks: &[Rank], level: i32) -> Vec<RoleId> {
+        let mut earned = ranks.iter().filter(|o| o.min_level <= level);
+
+        match self {
+            RoleMode::Replace => earned.next_back().and_then(|o| o.role_id).into_iter().collect(),
+            RoleMode::Stack => earned.filter_map(|o| o.role_id).collect()
+        }
+    }
+
+    // The rank roles someone holding `held` is missing, and the ones they shouldn't have.
+    pub fn role_changes(&self, ranks: &[Rank], level: i32, held: &[RoleId]) -> (Vec<RoleId>, Vec<RoleId>) {
+        let expected = self.earned_roles(ranks, level);
+        let excess = ranks.iter()
+            .filter_map(|o| o.role_id)
+            .filter(|o| held.contains(o) && !expected.contains(o))
+            .collect();
+        let missing = expected.into_iter().filter(|o| !held.contains(o)).collect();
+
+        (missing, excess)
+    }
+
+    // Which sort of fix someone holding `held` needs, or None if their rank roles are already right.
+    pub fn classify(&self, ranks: &[Rank], level: i32, held: &[RoleId]) -> Option<RoleFix> {
+        let (missing, excess) = self.role_changes(ranks, level, held);
+        if missing.is_empty() && excess.is_empty() {
+            return None;
+        }
+
+        let min_level = |role: &RoleId| ranks.iter().find(|o| o.role_id == Some(*role)).map(|o| o.min_level).unwrap_or(i32::MIN);
+        let expected = self.earned_roles(ranks, level);
+        // Ranks come lowest first, so the last one expected is the highest they've earned.
+        let top = expected.last().map(min_level).unwrap_or(i32::MIN);
+        let held_ranks = held.iter().filter(|o| ranks.iter().any(|r| r.role_id == Some(**o))).count();
+
+        Some(if expected.is_empty() {
+            RoleFix::Remove
+        } else if excess.iter().any(|o| min_level(o) > top) {
+            RoleFix::Demote
+        } else if *self == RoleMode::Replace && held_ranks > 1 {
+            RoleFix::Multiple
+        } else {
+            RoleFix::Trivial
+        })
+    }
+}
+
+/*
+    The ways someone's rank roles can be wrong, going by the role mode:
+    - They shouldn't have any rank, and yet they do have rank roles -> Remove
+    - They should have some ranks, and:
+      - they're only missing some of them -> Trivial
+      - they have a rank above the ones they've earned -> Demote
+      - in replace mode, they have more than one rank role -> Multiple
+      - in replace mode, they have one lower rank and should be higher up -> Trivial
+
+    In stack mode, holding every rank up to their level is correct, so there's nothing "multiple" to fix.
+ */
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum RoleFix {
+    Trivial,
+    Multiple,
+    Remove,
+    Demote
+}
+
+// Which of the non-trivial fixes to go ahead with; trivial ones always are.
+#[derive(Copy, Clone, Default)]
+pub struct FixOptions {
+    pub multiple: bool,
+    pub remove: bool,
+    pub demote: bool
+}
+
+impl FixOptions {
+    pub fn allows(&self, fix: RoleFix) -> bool {
+        match fix {
+            RoleFix::Trivial => true,
+            RoleFix::Multiple => self.multiple,
+            RoleFix::Remove => self.remove,
+            RoleFix::Demote => self.demote
+        }
+    }
+}
+
+// One role someone gained or lost to a fix.
+#[derive(Clone)]
+pub struct RoleChange {
+    pub user_id: UserId,
+    pub role_id: RoleId,
+    pub added: bool
+}
+
+// Everything one run of `rankconfig fix` changed, kept around for a while so it can be undone.
+pub struct FixChangeSet {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub changes: Vec<RoleChange>
+}
+
+// Running `rankconfig fix` on a schedule; zero hours turns it off.
+#[derive(Clone)]
+pub struct AutoFixSettings {
+    pub server_id: u64,
+    pub every_hours: i32,
+    // Where the summary goes after each run, if anywhere.
+    pub log_channel: Option<ChannelId>,
+    pub options: FixOptions,
+    pub last_run: Option<DateTime<Utc>>
+}
+
+impl AutoFixSettings {
+    pub fn new(server_id: u64) -> Self {
+        AutoFixSettings {
+            server_id,
+            every_hours: 0,
+            log_channel: None,
+            options: FixOptions::default(),
+            last_run: None
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.every_hours > 0 && self.last_run.map(|o| now - o >= Duration::hours(self.every_hours as i64)).unwrap_or(true)
+    }
+}
+
+// Per-server XP rules; the defaults match what the old CalculateLevel/ProvideExp procedures did.
+#[derive(Clone)]
+pub struct XpSettings {
+    pub server_id: u64,
+    pub min_xp: i32,
+    pub max_xp: i32,
+    pub multiplier: f64,
+    pub curve: LevelCurve,
+    pub curve_base: i32,
+    pub curve_step: i32,
+    pub curve_growth: i32,
+    pub curve_table: Vec<i32>
+}
+
+impl XpSettings {
+    pub fn new(server_id: u64) -> Self {
+        XpSettings {
+            server_id,
+            min_xp: 20,
+            max_xp: 20,
+            multiplier: 1.0,
+            curve: LevelCurve::Quadratic,
+            curve_base: 100,
+            curve_step: 50,
+            curve_growth: 5,
+            curve_table: Vec::new()
+        }
+    }
+
+    // XP handed out for one message, before any channel/role bonuses.
+    pub fn roll_xp(&self) -> i32 {
+        let xp = if self.max_xp > self.min_xp {
+            rand::thread_rng().gen_range(self.min_xp..=self.max_xp)
+        } else {
+            self.min_xp
+        };
+
+        (xp as f64 * self.multiplier).round().max(0.0) as i32
+    }
+
+    // XP needed to go from this level to the next one.
+    pub fn xp_for_level(&self, level: i32) -> i32 {
+        let needed = match self.curve {
+            // Past the end of the table, every level costs the same as the last entry.
+            LevelCurve::Custom => self.curve_table.get(level.max(0) as usize).or_else(|| self.curve_table.last()).copied().unwrap_or(100) as i128,
+            _ => self.curve_xp(level.max(0) as i128)
+        };
+
+        // Anything under 1 would let someone level up forever off a single message.
+        needed.clamp(1, i32::MAX as i128) as i32
+    }
+
+    // The linear or quadratic curve before it's capped. The settings are checked when they're set, so the clamps
+    // only guard against odd rows; they keep it at least 1 and never going down, which xp_to_reach relies on.
+    fn curve_xp(&self, level: i128) -> i128 {
+        let (base, step, growth) = self.curve_terms();
+        base + step * level + growth * level * level
+    }
+
+    fn curve_terms(&self) -> (i128, i128, i128) {
+        let growth = if self.curve == LevelCurve::Quadratic { self.curve_growth.max(0) as i128 } else { 0 };
+        (self.curve_base.max(1) as i128, self.curve_step.max(0) as i128, growth)
+    }
+
+    // Everything it takes to get from level 0 to this level, worked out directly rather than level by level,
+    // since a cheap curve and a big XP change could otherwise mean millions of levels to step through.
+    fn xp_to_reach(&self, level: i128) -> i128 {
+        if self.curve == LevelCurve::Custom {
+            let covered = level.min(self.curve_table.len() as i128);
+            let spent = self.curve_table.iter().take(covered as usize).map(|o| (*o).max(1) as i128).sum::<i128>();
+            let rest = self.curve_table.last().map(|o| (*o).max(1) as i128).unwrap_or(100);
+            return spent + (level - covered) * rest;
+        }
+
+        // The first level that costs more than i32::MAX, after which every level is capped to that.
+        let (mut low, mut high) = (0, i32::MAX as i128 + 1);
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.curve_xp(mid) > i32::MAX as i128 { high = mid } else { low = mid + 1 }
+        }
+
+        let n = level.min(low);
+        let (base, step, growth) = self.curve_terms();
+        let uncapped = base * n + step * n * (n - 1) / 2 + growth * (n - 1) * n * (2 * n - 1) / 6;
+        uncapped + (level - n) * i32::MAX as i128
+    }
+
+    // The level a lifetime total works out to, and the XP left over towards the next one.
+    fn from_total(&self, total: i128) -> (i32, i32) {
+        if total <= 0 {
+            return (0, 0);
+        }
+
+        let (mut low, mut high) = (0, i32::MAX as i128);
+        while low < high {
+            let mid = low + (high - low + 1) / 2;
+            if self.xp_to_reach(mid) <= total { low = mid } else { high = mid - 1 }
+        }
+
+        (low as i32, (total - self.xp_to_reach(low)).min(i32::MAX as i128) as i32)
+    }
+
+    // Spends XP on as many levels as it covers, giving back the new level and the leftover XP.
+    pub fn level_up(&self, level: i32, xp: i32) -> (i32, i32) {
+        self.normalize(level, xp as i64)
+    }
+
+    // Everything spent on levels so far, plus the leftover.
+    pub fn total_xp(&self, level: i32, xp: i32) -> i64 {
+        (self.xp_to_reach(level.max(0) as i128) + xp as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    // Takes a percentage of someone's total XP away, dropping levels as needed.
+    pub fn decay(&self, level: i32, xp: i32, percent: f64) -> (i32, i32) {
+        let lost = (self.total_xp(level, xp) as f64 * percent.clamp(0.0, 100.0) / 100.0).round() as i64;
+        self.normalize(level, xp as i64 - lost)
+    }
+
+    // Like level_up, but also handles XP going negative by dropping levels (never below level 0, 0 XP).
+    pub fn normalize(&self, level: i32, xp: i64) -> (i32, i32) {
+        self.from_total(self.xp_to_reach(level.max(0) as i128) + xp as i128)
+    }
+
+    pub fn table_to_string(&self) -> String {
+        self.curve_table.iter().map(|o| o.to_string()).collect::<Vec<_>>().join(",")
+    }
+
+    pub fn table_from_string(table: &str) -> Vec<i32> {
+        table.split([',', ' ']).filter_map(|o| o.trim().parse().ok()).collect()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MultiplierTarget {
+    Channel(ChannelId),
+    Role(RoleId)
+}
+
+impl MultiplierTarget {
+    pub fn id(&self) -> u64 {
+        match self {
+            MultiplierTarget::Channel(id) => id.0,
+            MultiplierTarget::Role(id) => id.0
+        }
+    }
+
+    pub fn is_role(&self) -> bool {
+        matches!(self, MultiplierTarget::Role(_))
+    }
+
+    pub fn from_id(id: u64, is_role: bool) -> Self {
+        if is_role {
+            MultiplierTarget::Role(RoleId::from(id))
+        } else {
+            MultiplierTarget::Channel(ChannelId::from(id))
+        }
+    }
+}
+
+impl Display for MultiplierTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultiplierTarget::Channel(id) => write!(f, "<#{id}>"),
+            MultiplierTarget::Role(id) => write!(f, "<@&{id}>")
+        }
+    }
+}
+
+pub struct XpMultiplier {
+    pub target: MultiplierTarget,
+    pub multiplier: f64
+}
+
+impl XpMultiplier {
+    // The most specific channel wins (a channel over its category), and every role the member has stacks on top.
+    pub fn combine(multipliers: &[XpMultiplier], channels: &[ChannelId], roles: &[RoleId]) -> f64 {
+        let channel = channels.iter()
+            .find_map(|channel| multipliers.iter().find(|o| o.target == MultiplierTarget::Channel(*channel)))
+            .map(|o| o.multiplier)
+            .unwrap_or(1.0);
+
+        multipliers.iter()
+            .filter(|o| matches!(o.target, MultiplierTarget::Role(role) if roles.contains(&role)))
+            .fold(channel, |total, o| total * o.multiplier)
+    }
+}
+
+// Per-server XP decay; zero days turns it off.
+#[derive(Clone)]
+pub struct DecaySettings {
+    pub server_id: u64,
+    // How long someone has to go without earning XP before they start losing it.
+    pub after_days: i32,
+    // Share of their total XP lost each week after that.
+    pub percent: f64,
+    // Whether decay takes rank roles away too, like `rankconfig fix` with option_demote.
+    pub demote: bool,
+    // Leave members who left the server off the leaderboards.
+    pub hide_departed: bool
+}
+
+impl DecaySettings {
+    pub fn new(server_id: u64) -> Self {
+        DecaySettings {
+            server_id,
+            after_days: 0,
+            percent: 5.0,
+            demote: false,
+            hide_departed: false
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.after_days > 0 && self.percent > 0.0
+    }
+}
+
+// Where level-up messages go.
+#[derive(Copy, Clone, PartialEq, Eq, FromPrimitive)]
+pub enum AnnounceTarget {
+    // Wherever the message (or voice chat) that levelled them up was.
+    Here = 0,
+    Channel = 1,
+    Dm = 2,
+    Off = 3
+}
+
+impl Display for AnnounceTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnnounceTarget::Here => write!(f, "Here"),
+            AnnounceTarget::Channel => write!(f, "Channel"),
+            AnnounceTarget::Dm => write!(f, "DM"),
+            AnnounceTarget::Off => write!(f, "Off")
+        }
+    }
+}
+
+impl TryFrom<u8> for AnnounceTarget {
+    type Error = ();
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        FromPrimitive::from_u8(v).ok_or(())
+    }
+}
+
+impl AnnounceTarget {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "here" | "same" => Some(AnnounceTarget::Here),
+            "channel" => Some(AnnounceTarget::Channel),
+            "dm" | "dms" => Some(AnnounceTarget::Dm),
+            "off" | "none" => Some(AnnounceTarget::Off),
+            _ => None
+        }
+    }
+}
+
+pub const DEFAULT_ANNOUNCE_TEMPLATE: &str = "{user} leveled up from {old_level} to {level}.";
+
+#[derive(Clone)]
+pub struct AnnounceSettings {
+    pub server_id: u64,
+    pub target: AnnounceTarget,
+    // Only used with AnnounceTarget::Channel; falls back to Here if it's missing.
+    pub channel_id: Option<ChannelId>,
+    // None keeps the default message (with a line about the new rank, if there is one).
+    pub template: Option<String>,
+    // Announce every Nth level; 1 is every level.
+    pub every: i32,
+    // Only announce when someone gets a new rank.
+    pub roles_only: bool
+}
+
+impl AnnounceSettings {
+    pub fn new(server_id: u64) -> Self {
+        AnnounceSettings {
+            server_id,
+            target: AnnounceTarget::Here,
+            channel_id: None,
+            template: None,
+            every: 1,
+            roles_only: false
+        }
+    }
+
+    // New ranks are always worth announcing; otherwise it's whether they passed a multiple of `every`.
+    pub fn should_announce(&self, data: &LevelUp) -> bool {
+        if self.target == AnnounceTarget::Off {
+            return false;
+        }
+
+        if data.new_rank.is_some() {
+            return true;
+        }
+
+        let every = self.every.max(1);
+        !self.roles_only && data.level.div_euclid(every) > data.old_level.div_euclid(every)
+    }
+
+    // Fills in {user}, {old_level}, {level} and {role}.
+    pub fn render(&self, user_id: UserId, data: &LevelUp) -> String {
+        let template = match &self.template {
+            Some(template) => template.clone(),
+            None if data.new_rank.is_some() => format!("{DEFAULT_ANNOUNCE_TEMPLATE}\nYou are now a {{role}}."),
+            None => DEFAULT_ANNOUNCE_TEMPLATE.to_string()
+        };
+        let role = data.new_rank.map(|o| format!("<@&{o}>")).unwrap_or_else(|| "no new rank".to_string());
+
+        template
+            .replace("{user}", &format!("<@{user_id}>"))
+            .replace("{old_level}", &data.old_level.to_string())
+            .replace("{level}", &data.level.to_string())
+            .replace("{role}", &role)
+    }
+}
+
+// Per-server anti-spam thresholds, on top of the cooldown; zero turns a check off.
+#[derive(Clone)]
+pub struct SpamSettings {
+    pub server_id: u64,
+    // Messages with fewer characters than this (not counting emoji and links) get nothing.
+    pub min_length: i32,
+    // Saying the same thing twice in a row gets nothing the second time.
+    pub block_repeats: bool,
+    // Messages that are at least this much emoji and links get nothing; 0 is off.
+    pub max_junk_ratio: f64,
+    // Sending this many messages within burst_seconds discounts the rest by burst_multiplier.
+    pub burst_count: i32,
+    pub burst_seconds: i32,
+    pub burst_multiplier: f64
+}
+
+impl SpamSettings {
+    pub fn new(server_id: u64) -> Self {
+        SpamSettings {
+            server_id,
+            min_length: 0,
+            block_repeats: false,
+            max_junk_ratio: 0.0,
+            burst_count: 0,
+            burst_seconds: 10,
+            burst_multiplier: 0.5
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SpamReason {
+    Short,
+    Repeat,
+    Junk,
+    Burst
+}
+
+impl Display for SpamReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpamReason::Short => write!(f, "Too short"),
+            SpamReason::Repeat => write!(f, "Repeated"),
+            SpamReason::Junk => write!(f, "Mostly emoji or links"),
+            SpamReason::Burst => write!(f, "Sent in a burst")
+        }
+    }
+}
+
+// How many of someone's messages were ignored (or discounted, for bursts) by the anti-spam checks.
+#[derive(Default)]
+pub struct SpamStats {
+    pub short: i64,
+    pub repeat: i64,
+    pub junk: i64,
+    pub burst: i64,
+    pub last_flagged: Option<DateTime<Utc>>
+}
+
+impl SpamStats {
+    pub fn total(&self) -> i64 {
+        self.short + self.repeat + self.junk + self.burst
+    }
+}
+
+pub struct VoiceSettings {
+    pub server_id: u64,
+    // Zero turns voice XP off.
+    pub xp_per_minute: i32,
+    // Zero means there's no cap.
+    pub daily_cap: i32,
+    // Empty means every (non-AFK) voice channel counts.
+    pub channels: Vec<ChannelId>
+}
+
+impl VoiceSettings {
+    pub fn new(server_id: u64) -> Self {
+        VoiceSettings {
+            server_id,
+            xp_per_minute: 0,
+            daily_cap: 0,
+            channels: Vec::new()
+        }
+    }
+
+    // Takes the channel along with its category, so a whole category can be allowed at once.
+    pub fn counts(&self, channels: &[ChannelId]) -> bool {
+        self.channels.is_empty() || channels.iter().any(|o| self.channels.contains(o))
+    }
+}
+
+pub struct Season {
+    pub id: i32,
+    pub server_id: u64,
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    // None while the season is still running.
+    pub ended_at: Option<DateTime<Utc>>
+}
+
+impl Season {
+    pub fn window(&self) -> XpWindow {
+        match self.ended_at {
+            Some(_) => XpWindow::Archived(self.id),
+            None => XpWindow::Between(self.started_at, None)
+        }
+    }
+}
+
+// How long logged XP is kept for date ranges; anything in a season that's still running is kept regardless.
+pub const XP_LOG_DAYS: i64 = 400;
+
+// Which XP a leaderboard counts, on top of the lifetime totals in the level table.
+#[derive(Copy, Clone)]
+pub enum XpWindow {
+    // XP logged from the start up to (but not including) the end, or up to now.
+    Between(DateTime<Utc>, Option<DateTime<Utc>>),
+    // The standings saved when a season ended.
+    Archived(i32)
+}
+
+impl XpWindow {
+    // Since Monday, midnight UTC.
+    pub fn this_week() -> Self {
+        let today = Utc::now().naive_utc().date();
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        XpWindow::Between(Utc.from_utc_datetime(&monday.and_hms_opt(0, 0, 0).unwrap()), None)
+    }
+
+    // Since the 1st, midnight UTC.
+    pub fn this_month() -> Self {
+        let first = Utc::now().naive_utc().date().with_day(1).unwrap();
+        XpWindow::Between(Utc.from_utc_datetime(&first.and_hms_opt(0, 0, 0).unwrap()), None)
+    }
+
+    // Both days are included; dates are YYYY-MM-DD in UTC.
+    pub fn parse_range(from: &str, to: Option<&str>) -> Option<Self> {
+        let from = chrono::NaiveDate::parse_from_str(from.trim(), "%Y-%m-%d").ok()?;
+        let to = match to {
+            Some(to) => Some(chrono::NaiveDate::parse_from_str(to.trim(), "%Y-%m-%d").ok()?),
+            None => None
+        };
+
+        if to.map(|o| o < from).unwrap_or(false) {
+            return None;
+        }
+
+        Some(XpWindow::Between(
+            Utc.from_utc_datetime(&from.and_hms_opt(0, 0, 0)?),
+            to.and_then(|o| o.succ_opt()).and_then(|o| o.and_hms_opt(0, 0, 0)).map(|o| Utc.from_utc_datetime(&o))))
+    }
+
+    // The earliest XP that's still logged, outside of running seasons.
+    pub fn kept_since() -> DateTime<Utc> {
+        Utc::now() - Duration::days(XP_LOG_DAYS)
+    }
+
+    // Flattened for the storage queries: a season ID (negative for a date range), and the range itself.
+    pub fn bounds(&self) -> (i32, DateTime<Utc>, Option<DateTime<Utc>>) {
+        match *self {
+            XpWindow::Between(from, to) => (-1, from, to),
+            XpWindow::Archived(id) => (id, Utc.timestamp_opt(0, 0).unwrap(), None)
+        }
+    }
+}
+
+pub struct WindowMember {
+    pub id: UserId,
+    pub xp: i64
+}
+
+pub struct WindowPagination {
+    pub members: Vec<WindowMember>,
+    pub current_page: i32,
+    pub last_page: i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(curve: LevelCurve, table: &[i32]) -> XpSettings {
+        let mut settings = XpSettings::new(0);
+        settings.curve = curve;
+        settings.curve_table = table.to_vec();
+        settings
+    }
+
+    #[test]
+    fn quadratic_levels_match_the_old_procedure() {
+        let settings = XpSettings::new(0);
+        assert_eq!(settings.xp_for_level(0), 100);
+        assert_eq!(settings.xp_for_level(1), 155);
+        assert_eq!(settings.xp_for_level(2), 220);
+        assert_eq!(settings.level_up(0, 254), (1, 154));
+        assert_eq!(settings.level_up(0, 255), (2, 0));
+        assert_eq!(settings.level_up(1, 155), (2, 0));
+        assert_eq!(settings.total_xp(2, 10), 265);
+    }
+
+    #[test]
+    fn linear_ignores_growth() {
+        let mut settings = curve(LevelCurve::Linear, &[]);
+        settings.curve_growth = 1000;
+        assert_eq!(settings.xp_for_level(2), 200);
+        assert_eq!(settings.level_up(0, 460), (3, 10));
+    }
+
+    #[test]
+    fn custom_table_repeats_its_last_entry() {
+        let settings = curve(LevelCurve::Custom, &[100, 200]);
+        assert_eq!(settings.xp_for_level(5), 200);
+        assert_eq!(settings.level_up(0, 350), (2, 50));
+        assert_eq!(settings.total_xp(4, 0), 700);
+    }
+
+    #[test]
+    fn cheap_levels_are_worked_out_directly() {
+        let settings = curve(LevelCurve::Custom, &[1]);
+        assert_eq!(settings.level_up(0, i32::MAX), (i32::MAX, 0));
+        assert_eq!(settings.normalize(0, 5_000_000_000), (i32::MAX, i32::MAX));
+    }
+
+    #[test]
+    fn negative_xp_drops_levels_but_not_below_zero() {
+        let settings = XpSettings::new(0);
+        assert_eq!(settings.normalize(2, -1), (1, 154));
+        assert_eq!(settings.normalize(1, -1000), (0, 0));
+        assert_eq!(settings.normalize(-3, 50), (0, 50));
+    }
+
+    #[test]
+    fn totals_round_trip() {
+        for settings in [XpSettings::new(0), curve(LevelCurve::Linear, &[]), curve(LevelCurve::Custom, &[100, 250, 500])] {
+            for total in [0, 1, 99, 100, 12_345, 1_000_000, 1_900_000, 250_000_000, 3_000_000_000] {
+                let (level, xp) = settings.normalize(0, total);
+                assert_eq!(settings.total_xp(level, xp), total);
+                assert!(xp < settings.xp_for_level(level));
+            }
+        }
+    }
+
+    #[test]
+    fn per_level_costs_add_up_past_the_cap() {
+        let settings = XpSettings::new(0);
+        for level in (0..30).chain(20_700..20_750) {
+            let cost = settings.total_xp(level + 1, 0) - settings.total_xp(level, 0);
+            assert_eq!(cost, settings.xp_for_level(level) as i64);
+        }
+        assert_eq!(settings.xp_for_level(100_000), i32::MAX);
+    }
+
+    fn midnight(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(0, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn date_ranges_include_both_days() {
+        let (season, from, to) = XpWindow::parse_range(" 2024-01-31 ", Some("2024-02-29")).unwrap().bounds();
+        assert_eq!((season, from, to), (-1, midnight(2024, 1, 31), Some(midnight(2024, 3, 1))));
+        assert_eq!(XpWindow::parse_range("2024-01-31", None).unwrap().bounds(), (-1, midnight(2024, 1, 31), None));
+    }
+
+    #[test]
+    fn date_ranges_must_make_sense() {
+        assert!(XpWindow::parse_range("2024-02-01", Some("2024-01-31")).is_none());
+        assert!(XpWindow::parse_range("31/01/2024", None).is_none());
+        assert!(XpWindow::parse_range("2024-01-31", Some("soon")).is_none());
+        assert!(XpWindow::parse_range("2024-02-30", None).is_none());
+    }
+
+    #[test]
+    fn multipliers_take_the_closest_channel_and_stack_roles() {
+        let multipliers = vec![
+            XpMultiplier { target: MultiplierTarget::Channel(ChannelId(10)), multiplier: 2.0 },
+            XpMultiplier { target: MultiplierTarget::Channel(ChannelId(20)), multiplier: 3.0 },
+            XpMultiplier { target: MultiplierTarget::Role(RoleId(1)), multiplier: 1.5 },
+            XpMultiplier { target: MultiplierTarget::Role(RoleId(2)), multiplier: 2.0 }
+        ];
+
+        assert_eq!(XpMultiplier::combine(&multipliers, &[ChannelId(10), ChannelId(20)], &[RoleId(1), RoleId(2)]), 6.0);
+        assert_eq!(XpMultiplier::combine(&multipliers, &[ChannelId(11), ChannelId(20)], &[]), 3.0);
+        assert_eq!(XpMultiplier::combine(&multipliers, &[ChannelId(11)], &[RoleId(3)]), 1.0);
+    }
+
+    fn ranks() -> Vec<Rank> {
+        [(1, 5), (2, 10), (3, 20)].into_iter()
+            .map(|(id, min_level)| Rank { name: format!("rank {id}"), role_id: Some(RoleId(id)), min_level })
+            .collect()
+    }
+
+    fn roles(ids: &[u64]) -> Vec<RoleId> {
+        ids.iter().map(|o| RoleId(*o)).collect()
+    }
+
+    #[test]
+    fn replace_mode_keeps_only_the_highest_rank() {
+        let ranks = ranks();
+        assert_eq!(RoleMode::Replace.earned_roles(&ranks, 12), roles(&[2]));
+        assert_eq!(RoleMode::Replace.role_changes(&ranks, 12, &roles(&[1])), (roles(&[2]), roles(&[1])));
+        assert_eq!(RoleMode::Replace.role_changes(&ranks, 12, &roles(&[2, 99])), (roles(&[]), roles(&[])));
+    }
+
+    #[test]
+    fn stack_mode_keeps_every_rank_earned() {
+        let ranks = ranks();
+        assert_eq!(RoleMode::Stack.earned_roles(&ranks, 12), roles(&[1, 2]));
+        assert_eq!(RoleMode::Stack.role_changes(&ranks, 12, &roles(&[2, 3])), (roles(&[1]), roles(&[3])));
+        assert!(RoleMode::Stack.classify(&ranks, 12, &roles(&[1, 2])).is_none());
+    }
+
+    #[test]
+    fn fixes_are_classified_by_what_is_wrong() {
+        let ranks = ranks();
+        let classify = |mode: RoleMode, level: i32, held: &[u64]| mode.classify(&ranks, level, &roles(held));
+
+        assert!(classify(RoleMode::Replace, 12, &[2]).is_none());
+        assert!(classify(RoleMode::Replace, 12, &[1]) == Some(RoleFix::Trivial));
+        assert!(classify(RoleMode::Replace, 12, &[1, 2]) == Some(RoleFix::Multiple));
+        assert!(classify(RoleMode::Replace, 12, &[2, 3]) == Some(RoleFix::Demote));
+        assert!(classify(RoleMode::Replace, 2, &[1]) == Some(RoleFix::Remove));
+        assert!(classify(RoleMode::Stack, 12, &[]) == Some(RoleFix::Trivial));
+        assert!(classify(RoleMode::Stack, 12, &[1, 2, 3]) == Some(RoleFix::Demote));
+    }
+
+    fn level_up(old_level: i32, level: i32, new_rank: Option<u64>) -> LevelUp {
+        LevelUp { level, old_level, old_rank: None, new_rank }
+    }
+
+    #[test]
+    fn announcements_fill_in_the_template() {
+        let mut settings = AnnounceSettings::new(0);
+        assert_eq!(settings.render(UserId(7), &level_up(4, 5, None)), "<@7> leveled up from 4 to 5.");
+        assert_eq!(settings.render(UserId(7), &level_up(4, 5, Some(9))), "<@7> leveled up from 4 to 5.\nYou are now a <@&9>.");
+
+        settings.template = Some("{user} hit {level}! ({role})".to_string());
+        assert_eq!(settings.render(UserId(7), &level_up(4, 5, None)), "<@7> hit 5! (no new rank)");
+    }
+
+    #[test]
+    fn announcements_skip_levels_in_between() {
+        let mut settings = AnnounceSettings::new(0);
+        settings.every = 5;
+        assert!(settings.should_announce(&level_up(4, 5, None)));
+        assert!(settings.should_announce(&level_up(3, 11, None)));
+        assert!(!settings.should_announce(&level_up(5, 6, None)));
+        assert!(settings.should_announce(&level_up(5, 6, Some(9))));
+
+        settings.roles_only = true;
+        assert!(!settings.should_announce(&level_up(4, 5, None)));
+
+        settings.target = AnnounceTarget::Off;
+        assert!(!settings.should_announce(&level_up(5, 6, Some(9))));
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::{
+    CacheAndHttp,
+    http::CacheHttp,
+    model::id::{GuildId, RoleId, UserId},
+    prelude::TypeMap
+};
+use tokio::sync::RwLock;
+use tokio::time;
+use tracing::error;
+use crate::{Database, Error};
+use crate::models::db_models::{AutoFixSettings, FixChangeSet, FixOptions, RoleChange, RoleFix};
+use crate::services::message_handler::apply_rank_roles;
+
+// Scheduled runs stop for a breather after this many members, so a big server doesn't hog the rate limit.
+const BATCH_SIZE: i32 = 10;
+const BATCH_PAUSE: Duration = Duration::from_secs(10);
+// How long a fix can still be undone for.
+pub const FIX_RETENTION_DAYS: i64 = 7;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum FixRun {
+    // Work out what would change, but leave everyone's roles alone.
+    DryRun,
+    Now,
+    // Takes a break every BATCH_SIZE members; for scheduled runs, where nobody's waiting.
+    Paced
+}
+
+#[derive(Default)]
+pub struct FixReport {
+    pub total: i32,
+    pub found: i32,
+    pub trivial: i32,
+    pub multiple: i32,
+    pub remove: i32,
+    pub demote: i32,
+    pub errors: i32,
+    // Everything changed (or that would have been, for a dry run).
+    pub changes: Vec<RoleChange>,
+    // The change set to undo it all with; None for a dry run, or if nothing changed.
+    pub fix_id: Option<i32>
+}
+
+impl FixReport {
+    fn count(&mut self, fix: RoleFix) {
+        match fix {
+            RoleFix::Trivial => self.trivial += 1,
+            RoleFix::Multiple => self.multiple += 1,
+            RoleFix::Remove => self.remove += 1,
+            RoleFix::Demote => self.demote += 1
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!("Processed {} members in the database with {} errors found:\n\
+            - Trivial fixes: {}\n\
+            - Fixes for multiple roles: {}\n\
+            - Members with their roles fully revoked: {}\n\
+            - Members demoted: {}\n\
+            - Errors adding/removing roles: {}",
+            self.total, self.found, self.trivial, self.multiple, self.remove, self.demote, self.errors)
+    }
+
+    // Every role added or removed, one member per line, cut off before it gets longer than max_length.
+    pub fn diff(&self, max_length: usize) -> String {
+        let mut members: Vec<(UserId, Vec<String>)> = Vec::new();
+        for change in &self.changes {
+            let line = format!("{}<@&{}>", if change.added { "+" } else { "-" }, change.role_id);
+            match members.iter_mut().find(|(user_id, _)| *user_id == change.user_id) {
+                Some((_, roles)) => roles.push(line),
+                None => members.push((change.user_id, vec![line]))
+            }
+        }
+
+        let mut content = String::new();
+        for (i, (user_id, roles)) in members.iter().enumerate() {
+            let line = format!("<@{}>: {}\n", user_id, roles.join(" "));
+            // Leave room for the note about the rest.
+            if content.len() + line.len() > max_length.saturating_sub(40) {
+                content.push_str(&format!("...and {} more members.", members.len() - i));
+                break;
+            }
+            content.push_str(&line);
+        }
+
+        content
+    }
+}
+
+// Brings everyone in the database who's still on the server in line with their level, as far as `options` allows.
+// Whatever gets changed is saved as a change set, so undo_fix can put it back.
+pub async fn fix_roles(cache_http: &impl CacheHttp, db: &Database, guild_id: GuildId, options: FixOptions, run: FixRun) -> Result<FixReport, Error> {
+    let mut report = FixReport::default();
+    let ranks = db.get_roles(guild_id).await?;
+    let mode = db.get_role_mode(guild_id).await?;
+    let users = db.get_users(guild_id).await?;
+    let mut batch = 0;
+
+    for u in users {
+        if let Ok(mut member) = guild_id.member(cache_http, u.user).await {
+            report.total += 1;
+
+            let fix = match mode.classify(&ranks, u.exp.level, &member.roles) {
+                Some(fix) => fix,
+                None => continue // Correct: exactly the roles the mode says they've earned
+            };
+            report.found += 1;
+
+            if !options.allows(fix) {
+                continue;
+            }
+
+            let (missing, excess) = mode.role_changes(&ranks, u.exp.level, &member.roles);
+            if run != FixRun::DryRun {
+                if let Err(ex) = apply_rank_roles(cache_http.http(), &mut member, &ranks, mode, u.exp.level).await {
+                    error!("Failed to fix rank roles: {}", ex);
+                    report.errors += 1;
+                    continue;
+                }
+            }
+
+            report.count(fix);
+            report.changes.extend(missing.into_iter().map(|role_id| RoleChange { user_id: u.user, role_id, added: true }));
+            report.changes.extend(excess.into_iter().map(|role_id| RoleChange { user_id: u.user, role_id, added: false }));
+
+            batch += 1;
+            if run == FixRun::Paced && batch >= BATCH_SIZE {
+                batch = 0;
+                time::sleep(BATCH_PAUSE).await;
+            }
+        }
+    }
+
+    if run != FixRun::DryRun && !report.changes.is_empty() {
+        let keep_since = chrono::Utc::now() - chrono::Duration::days(FIX_RETENTION_DAYS);
+        report.fix_id = Some(db.save_fix(guild_id, &report.changes, keep_since).await?);
+    }
+
+    Ok(report)
+}
+
+// Puts back everything a fix changed, for whoever's still on the server; gives back how many members were put back, and how many failed.
+pub async fn undo_fix(cache_http: &impl CacheHttp, db: &Database, guild_id: GuildId, fix: &FixChangeSet) -> Result<(i32, i32), Error> {
+    let mut members: HashMap<UserId, (Vec<RoleId>, Vec<RoleId>)> = HashMap::new();
+    for change in &fix.changes {
+        let (add, remove) = members.entry(change.user_id).or_default();
+        // Whatever the fix added gets taken away again, and the other way around.
+        if change.added { remove.push(change.role_id) } else { add.push(change.role_id) }
+    }
+
+    let (mut reverted, mut errors) = (0, 0);
+    for (user_id, (add, remove)) in members {
+        let mut member = match guild_id.member(cache_http, user_id).await {
+            Ok(member) => member,
+            Err(_) => continue
+        };
+
+        let res = async {
+            if !remove.is_empty() {
+                member.remove_roles(cache_http.http(), &remove).await?;
+            }
+            if !add.is_empty() {
+                member.add_roles(cache_http.http(), &add).await?;
+            }
+            Ok::<_, Error>(())
+        }.await;
+
+        match res {
+            Ok(_) => reverted += 1,
+            Err(ex) => {
+                error!("Failed to undo rank role fix for user {}: {}", user_id, ex);
+                errors += 1;
+            }
+        }
+    }
+
+    db.set_fix_reverted(guild_id, fix.id).await?;
+
+    Ok((reverted, errors))
+}
+
+// Servers are done one after another, so a slow one only ever holds up the others, never itself.
+pub async fn check_auto_fix(data: Arc<RwLock<TypeMap>>, ctx: Arc<CacheAndHttp>) {
+    let mut interval = time::interval(Duration::from_secs(5 * 60));
+    loop {
+        interval.tick().await;
+        let db = {
+            let ctx_global = data.read().await;
+            ctx_global.get::<Database>().expect("Couldn't find database").clone()
+        };
+
+        let servers = match db.get_auto_fix_servers().await {
+            Ok(servers) => servers,
+            Err(ex) => {
+                error!("Failed to get servers with scheduled role fixes: {}", ex);
+                continue;
+            }
+        };
+
+        let now = chrono::Utc::now();
+        for settings in servers.into_iter().filter(|o| o.is_due(now)) {
+            auto_fix_server(&ctx, &db, &settings).await;
+        }
+    }
+}
+
+async fn auto_fix_server(ctx: &CacheAndHttp, db: &Database, settings: &AutoFixSettings) {
+    let guild_id = GuildId::from(settings.server_id);
+
+    // Counted from the start, so an hourly run doesn't slowly slip by however long each one takes.
+    if let Err(ex) = db.set_auto_fix_run(guild_id, chrono::Utc::now()).await {
+        error!("Failed to note scheduled role fix for server {}: {}", guild_id, ex);
+        return;
+    }
+
+    let report = match fix_roles(&ctx, db, guild_id, settings.options, FixRun::Paced).await {
+        Ok(report) => report,
+        Err(ex) => {
+            error!("Failed scheduled role fix for server {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    // Nobody needs to hear that nothing was wrong every few hours.
+    if report.found == 0 {
+        return;
+    }
+
+    if let Some(channel_id) = settings.log_channel {
+        if let Err(ex) = channel_id.send_message(&ctx.http, |m| m.embed(|e| e
+            .title("Scheduled Role Fix")
+            .description(match report.fix_id {
+                Some(fix_id) => format!("{}\n\nUndo this with `rankconfig undofix {fix_id}` within {FIX_RETENTION_DAYS} days.", report.summary()),
+                None => report.summary()
+            })
+        )).await {
+            error!("Failed to send scheduled role fix report to {}: {}", channel_id, ex);
+        }
+    }
+}
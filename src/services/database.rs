@@ -1,6 +1,8 @@
-use bb8::Pool;
-use bb8_tiberius::ConnectionManager;
+use std::collections::HashSet;
+use std::ops::Deref;
 use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serenity::{
     model::id::{
         UserId,
@@ -9,404 +11,204 @@ use serenity::{
     },
     prelude::TypeMapKey
 };
-use tiberius::{AuthMethod, Config};
-use rust_decimal::{
-    Decimal,
-    prelude::FromPrimitive
-};
-use rust_decimal::prelude::ToPrimitive;
+use crate::Error;
+use crate::models::config::{Config, DatabaseBackend};
 use crate::models::db_models::*;
+use crate::services::sql_server::SqlServerDatabase;
+use crate::services::sqlite::SqliteDatabase;
+use crate::services::minecraft_db::MinecraftStorage;
+use crate::commands::cowboard::cowboard_db::CowboardStorage;
+use crate::commands::ucm::courses_db::CourseStorage;
 
+// Every feature talks to the database through this, so swapping backends doesn't touch the commands.
 pub struct Database {
-    pub(crate) pool: Pool<ConnectionManager>
+    storage: Box<dyn Storage>
 }
 
 impl TypeMapKey for Database {
     type Value = Arc<Database>;
 }
 
-impl Database {
-    pub async fn new(ip: &str, port: u16, usr: &str, pwd: &str) -> Result<Self, bb8_tiberius::Error> {
-        // The password is stored in a file; using secure strings is probably not going to make much of a difference.
-        let mut config = Config::new();
+impl Deref for Database {
+    type Target = dyn Storage;
 
-        config.host(ip);
-        config.port(port);
-        config.authentication(AuthMethod::sql_server(usr, pwd));
-        // Default schema needs to be Cow
-        config.database("Cow");
-        config.trust_cert();
+    fn deref(&self) -> &Self::Target {
+        self.storage.as_ref()
+    }
+}
 
-        let manager = ConnectionManager::build(config)?;
-        let pool = Pool::builder().max_size(8).build(manager).await?;
+impl Database {
+    pub async fn new(config: &Config) -> Result<Self, Error> {
+        // Migrations run before anything else touches the database, and bail if the schema is newer than us.
+        let storage: Box<dyn Storage> = match config.database {
+            DatabaseBackend::SqlServer => {
+                let database = SqlServerDatabase::new(
+                    &config.sql_server_ip,
+                    config.sql_server_port,
+                    &config.sql_server_username,
+                    &config.sql_server_password
+                ).await?;
+                database.migrate().await?;
+                Box::new(database)
+            },
+            DatabaseBackend::Sqlite => {
+                let database = SqliteDatabase::new(&config.sqlite_path).await?;
+                database.migrate().await?;
+                Box::new(database)
+            }
+        };
 
-        Ok(Database { pool })
+        Ok(Database { storage })
     }
+}
 
-    pub async fn provide_exp(&self, server_id: GuildId, user_id: UserId) -> Result<LevelUp, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
-        let res = conn.query(
-            "EXEC Ranking.ProvideExp @serverid = @P1, @userid = @P2",
-            &[&server, &user])
-            .await?
-            .into_row()
-            .await?;
-
-        let mut out = LevelUp::new();
-
-        if let Some(row) = res {
-            let mut old_rank_id: Option<u64> = None;
-            let mut new_rank_id: Option<u64> = None;
-
-            if let Some(old_rank_id_row) = row.get(1) {
-                let old_rank_id_dec: rust_decimal::Decimal = old_rank_id_row;
-                old_rank_id = old_rank_id_dec.to_u64();
-            }
-            if let Some(new_rank_id_row) = row.get(2) {
-                let new_rank_id_dec: rust_decimal::Decimal = new_rank_id_row;
-                new_rank_id = new_rank_id_dec.to_u64();
-            }
+pub trait Storage: RankingStorage + CowboardStorage + CourseStorage + MinecraftStorage {}
 
-            out = LevelUp {
-                level: row.get(0).unwrap(),
-                old_rank: old_rank_id,
-                new_rank: new_rank_id
-            };
-        }
+impl<T: RankingStorage + CowboardStorage + CourseStorage + MinecraftStorage> Storage for T {}
 
-        Ok(out)
-    }
+#[async_trait]
+pub trait RankingStorage: Send + Sync {
+    // Adds the XP (unless the user is on cooldown) and levels them up using the server's curve.
+    async fn provide_exp(&self, server_id: GuildId, user_id: UserId, xp: i32, settings: &XpSettings) -> Result<LevelUp, Error>;
 
-    pub async fn get_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Experience, Box<dyn std::error::Error>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
-        let res = conn.query(
-            "SELECT xp, level FROM [Ranking].[Level] WHERE server_id = @P1 AND [user_id] = @P2",
-            &[&server, &user])
-            .await?
-            .into_row()
-            .await?;
-
-        let mut out = Experience::new();
-
-        if let Some(item) = res {
-            out = Experience {
-                xp: item.get(0).unwrap(),
-                level: item.get(1).unwrap()
-            };
-        }
-
-        Ok(out)
-    }
+    // Whether provide_exp would turn away a message from them right now.
+    async fn is_on_cooldown(&self, server_id: GuildId, user_id: UserId) -> Result<bool, Error>;
 
-    pub async fn get_highest_role(&self, server_id: GuildId, level: i32) -> Result<Option<RoleId>, Box<dyn std::error::Error>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let res = conn.query(
-            "SELECT TOP 1 role_id FROM [Ranking].[Role] WHERE server_id = @P1 AND min_level <= @P2 ORDER BY min_level DESC",
-            &[&server, &level])
-            .await?
-            .into_row()
-            .await?;
+    // Like provide_exp, but ignores the message cooldown and stops at the daily cap instead.
+    async fn provide_voice_exp(&self, server_id: GuildId, user_id: UserId, xp: i32, daily_cap: i32, settings: &XpSettings) -> Result<LevelUp, Error>;
 
-        let mut out: Option<RoleId> = None;
+    async fn get_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Experience, Error>;
 
-        if let Some(item) = res {
-            let id: rust_decimal::Decimal = item.get(0).unwrap();
-            out = id.to_u64().and_then(|u| Option::from(RoleId::from(u)));
-        }
+    // Overwrites the level and XP outright; doesn't touch the message cooldown.
+    async fn set_xp(&self, server_id: GuildId, user_id: UserId, level: i32, xp: i32) -> Result<(), Error>;
 
-        Ok(out)
-    }
+    // Same as set_xp for every member, all or nothing. Gives back how many were written.
+    async fn import_levels(&self, server_id: GuildId, members: &[Member]) -> Result<usize, Error>;
 
-    pub async fn calculate_level(&self, level: i32) -> Result<i32, Box<dyn std::error::Error>> {
-        let mut conn = self.pool.get().await?;
-        let res = conn.query(
-            "EXEC [Ranking].[CalculateLevel] @level = @P1",
-            &[&level])
-            .await?
-            .into_row()
-            .await?;
+    async fn get_highest_role(&self, server_id: GuildId, level: i32) -> Result<Option<RoleId>, Error>;
 
-        let mut out: i32 = 0;
+    async fn get_xp_settings(&self, server_id: GuildId) -> Result<XpSettings, Error>;
 
-        if let Some(item) = res {
-            out = item.get(0).unwrap();
-        }
+    async fn set_xp_settings(&self, settings: &XpSettings) -> Result<(), Error>;
 
-        Ok(out)
-    }
+    async fn get_multipliers(&self, server_id: GuildId) -> Result<Vec<XpMultiplier>, Error>;
+
+    // Replaces whatever multiplier the channel or role had before.
+    async fn set_multiplier(&self, server_id: GuildId, target: MultiplierTarget, multiplier: f64) -> Result<(), Error>;
+
+    // None clears every multiplier on the server; gives back how many were removed.
+    async fn clear_multipliers(&self, server_id: GuildId, target: Option<MultiplierTarget>) -> Result<usize, Error>;
 
     // True: disabled False: enabled
     // Because by default a channel should be enabled, right?
-    pub async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, Box<dyn std::error::Error>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let channel = Decimal::from_u64(*channel_id.as_u64()).unwrap();
-        let res = conn.query(
-            "EXEC [Ranking].[ToggleChannel] @serverid = @P1, @channelid = @P2",
-            &[&server, &channel])
-            .await?
-            .into_row()
-            .await?;
-
-        let mut out: bool = false;
-
-        if let Some(item) = res {
-            out = item.get(0).unwrap();
-        }
-
-        Ok(out)
-    }
+    async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, Error>;
 
     // True: disabled False: enabled
-    pub async fn toggle_server_ranking(&self, server_id: GuildId) -> Result<bool, Box<dyn std::error::Error>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let res = conn.query(
-            "UPDATE [Ranking].[Server] SET ranking_disabled = ~ranking_disabled WHERE id = @P1;\
-            SELECT ranking_disabled FROM [Ranking].[Server] WHERE id = @P1;",
-            &[&server])
-            .await?
-            .into_row()
-            .await?;
-
-        let mut out: bool = false;
-
-        if let Some(item) = res {
-            out = item.get(0).unwrap();
-        }
-
-        Ok(out)
-    }
+    async fn toggle_server_ranking(&self, server_id: GuildId) -> Result<bool, Error>;
 
-    pub async fn get_disablements(&self, server_id: GuildId, channel_id: ChannelId) -> Result<Disablements, Box<dyn std::error::Error>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let channel = Decimal::from_u64(*channel_id.as_u64()).unwrap();
-
-        let res = conn.query(
-            "SELECT \
-                (SELECT CAST(1 AS BIT) FROM [Ranking].[DisabledChannel] WHERE server_id = @P1 AND channel_id = @P2), \
-                (SELECT ranking_disabled FROM [Ranking].[Server] WHERE id = @P1);",
-            &[&server, &channel])
-            .await?
-            .into_row()
-            .await?;
-
-        let mut out: Disablements = Disablements {
-            channel: false,
-            guild: false
-        };
+    async fn get_disablements(&self, server_id: GuildId, channel_id: ChannelId) -> Result<Disablements, Error>;
 
-        if let Some(item) = res {
-            let channel_disabled: Option<bool> = item.get(0);
-            let guild_disabled: Option<bool> = item.get(1);
+    async fn get_spam_settings(&self, server_id: GuildId) -> Result<SpamSettings, Error>;
 
-            out = Disablements {
-                channel: channel_disabled.unwrap_or_default(),
-                guild: guild_disabled.unwrap_or_default()
-            };
-        }
+    async fn set_spam_settings(&self, settings: &SpamSettings) -> Result<(), Error>;
 
-        Ok(out)
-    }
+    // Bumps the count for whichever check caught the message.
+    async fn record_spam(&self, server_id: GuildId, user_id: UserId, reason: SpamReason) -> Result<(), Error>;
+
+    async fn get_spam_stats(&self, server_id: GuildId, user_id: UserId) -> Result<SpamStats, Error>;
+
+    async fn get_decay_settings(&self, server_id: GuildId) -> Result<DecaySettings, Error>;
+
+    async fn set_decay_settings(&self, settings: &DecaySettings) -> Result<(), Error>;
+
+    // Every server with decay turned on.
+    async fn get_decaying_servers(&self) -> Result<Vec<DecaySettings>, Error>;
+
+    // Members who last earned XP before inactive_since, and haven't decayed since decayed_since.
+    async fn get_decay_candidates(&self, server_id: GuildId, inactive_since: DateTime<Utc>, decayed_since: DateTime<Utc>) -> Result<Vec<Member>, Error>;
+
+    // Same as import_levels, but also notes that they've just decayed.
+    async fn apply_decay(&self, server_id: GuildId, members: &[Member]) -> Result<usize, Error>;
+
+    // Departed members stay in the database (so their roles come back if they rejoin), but can be hidden from leaderboards.
+    async fn set_departed(&self, server_id: GuildId, user_id: UserId, departed: bool) -> Result<(), Error>;
+
+    // Every server hiding departed members from its leaderboards.
+    async fn get_departed_hiding_servers(&self) -> Result<Vec<GuildId>, Error>;
+
+    // Marks everyone with levels on the server as departed or not, going by who's there right now; catches up on
+    // anyone who left or came back while we weren't listening. Gives back how many changed.
+    async fn reconcile_departed(&self, server_id: GuildId, present: &HashSet<UserId>) -> Result<usize, Error>;
+
+    async fn get_auto_fix_settings(&self, server_id: GuildId) -> Result<AutoFixSettings, Error>;
+
+    async fn set_auto_fix_settings(&self, settings: &AutoFixSettings) -> Result<(), Error>;
+
+    // Every server with scheduled role fixes turned on, due or not.
+    async fn get_auto_fix_servers(&self) -> Result<Vec<AutoFixSettings>, Error>;
+
+    async fn set_auto_fix_run(&self, server_id: GuildId, ran_at: DateTime<Utc>) -> Result<(), Error>;
+
+    // Saves the changes as a new change set, and forgets any created before keep_since.
+    async fn save_fix(&self, server_id: GuildId, changes: &[RoleChange], keep_since: DateTime<Utc>) -> Result<i32, Error>;
+
+    // A change set that hasn't been undone yet and was made after `since`; the latest one if no ID is given.
+    async fn get_fix(&self, server_id: GuildId, fix_id: Option<i32>, since: DateTime<Utc>) -> Result<Option<FixChangeSet>, Error>;
+
+    async fn set_fix_reverted(&self, server_id: GuildId, fix_id: i32) -> Result<(), Error>;
+
+    async fn get_announce_settings(&self, server_id: GuildId) -> Result<AnnounceSettings, Error>;
+
+    async fn set_announce_settings(&self, settings: &AnnounceSettings) -> Result<(), Error>;
+
+    // True: rank shows the plain embed False: rank renders a card
+    async fn toggle_rank_embed(&self, server_id: GuildId) -> Result<bool, Error>;
+
+    async fn get_rank_embed(&self, server_id: GuildId) -> Result<bool, Error>;
 
     // Page number is zero-indexed.
-    pub async fn top_members(&self, server_id: GuildId, page: i32) -> Result<MemberPagination, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        const ROWS_FETCHED: i32 = 10;
-        let mut offset = page * ROWS_FETCHED;
-        offset = offset.max(0);
-        let res = conn.query(
-            "SELECT user_id, level, xp FROM [Ranking].[Level] WHERE server_id = @P1 ORDER BY level DESC, xp DESC OFFSET @P2 ROWS FETCH NEXT @P3 ROWS ONLY; SELECT COUNT(1) FROM [Ranking].[Level] WHERE server_id = @P1",
-            &[&server, &offset, &ROWS_FETCHED])
-            .await?
-            .into_results()
-            .await?;
-
-        let count: i32 = res.get(1).unwrap().get(0).unwrap().get(0).unwrap();
-
-        let members = res.get(0).unwrap().iter()
-            .map(|row| {
-                let id: rust_decimal::Decimal = row.get(0).unwrap();
-                Member {
-                    id: UserId::from(id.to_u64().unwrap()),
-                    exp: Experience {
-                        level: row.get(1).unwrap(),
-                        xp: row.get(2).unwrap()
-                    }
-                }
-            })
-            .collect::<Vec<_>>();
-
-        let pages = (count / ROWS_FETCHED) + ((count % ROWS_FETCHED != 0) as i32); // Divide, then round if not perfect division
-
-        Ok(MemberPagination {
-            members,
-            current_page: page,
-            last_page: pages
-        })
-    }
+    async fn top_members(&self, server_id: GuildId, page: i32) -> Result<MemberPagination, Error>;
 
-    pub async fn rank_within_members(&self, server_id: GuildId, user_id: UserId) -> Result<Option<i64>, Box<dyn std::error::Error>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
-        let res = conn.query(
-            "SELECT row_number FROM (SELECT user_id, ROW_NUMBER() OVER (ORDER BY level DESC, xp DESC) AS row_number FROM [Ranking].[Level] WHERE server_id = @P1) mukyu WHERE user_id = @P2",
-            &[&server, &user])
-            .await?
-            .into_row()
-            .await?;
-
-        let mut out: Option<i64> = None;
-
-        if let Some(item) = res {
-            // Apparently it's an i64. Cool.
-            out = item.get(0);
-        }
-
-        Ok(out)
-    }
+    async fn rank_within_members(&self, server_id: GuildId, user_id: UserId) -> Result<Option<i64>, Error>;
 
-    pub async fn get_roles(&self, server_id: GuildId) -> Result<Vec<Rank>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let res = conn.query(
-            "SELECT role_name, role_id, min_level FROM [Ranking].[Role] WHERE server_id = @P1 ORDER BY min_level ASC",
-            &[&server])
-            .await?
-            .into_first_result()
-            .await?
-            .into_iter()
-            .map(|row| {
-                let name: &str = row.get(0).unwrap();
-                let mut id: Option<RoleId> = None;
-                if let Some(row) = row.get(1) {
-                    let id_dec: rust_decimal::Decimal = row;
-                    id = id_dec.to_u64().and_then(|u| Option::from(RoleId::from(u)));
-                }
-
-                Rank {
-                    name: name.to_string(),
-                    role_id: id,
-                    min_level: row.get(2).unwrap()
-                }
-            })
-            .collect::<Vec<_>>();
-
-        Ok(res)
-    }
+    // Same as top_members, but only counting the XP logged within the window.
+    async fn top_members_in(&self, server_id: GuildId, window: XpWindow, page: i32) -> Result<WindowPagination, Error>;
 
-    // will also set role
-    pub async fn add_role(&self, server_id: GuildId, role_name: &str, role_id: RoleId, min_level: i32) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let role = Decimal::from_u64(*role_id.as_u64()).unwrap();
-        let res = conn.query(
-            "EXEC [Ranking].[AddRole] @server_id = @P1, @role_name = @P2, @role_id = @P3, @min_level = @P4",
-            &[&server, &role_name, &role, &Decimal::from_i32(min_level).unwrap()])
-            .await?
-            .into_row()
-            .await?;
-
-        let mut out: bool = false;
-
-        if let Some(item) = res {
-            out = item.get(0).unwrap();
-        }
-
-        Ok(out)
-    }
+    async fn rank_within_window(&self, server_id: GuildId, user_id: UserId, window: XpWindow) -> Result<Option<i64>, Error>;
 
-    pub async fn remove_role(&self, server_id: GuildId, role_id: RoleId) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let role = Decimal::from_u64(*role_id.as_u64()).unwrap();
-        let res = conn.query(
-            "EXEC [Ranking].[RemoveRole] @serverid = @P1, @roleid = @P2",
-            &[&server, &role])
-            .await?
-            .into_row()
-            .await?;
+    // Newest first.
+    async fn get_seasons(&self, server_id: GuildId) -> Result<Vec<Season>, Error>;
 
-        let mut out: bool = false;
+    async fn start_season(&self, server_id: GuildId, name: &str) -> Result<Season, Error>;
 
-        if let Some(item) = res {
-            out = item.get(0).unwrap();
-        }
+    // Ends whichever season is running and saves its standings; None if there wasn't one.
+    async fn end_season(&self, server_id: GuildId) -> Result<Option<Season>, Error>;
 
-        Ok(out)
-    }
+    // Forgets XP logged before this on every server, except what's inside a season that's still running.
+    // Gives back how many grants were forgotten.
+    async fn prune_xp_log(&self, before: DateTime<Utc>) -> Result<u64, Error>;
 
-    pub async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let timeout = Decimal::from_i32(timeout).unwrap();
-        let res = conn.query(
-            "EXEC [Ranking].[SetServerTimeout] @serverid = @P1, @timeout = @P2",
-            &[&server, &timeout])
-            .await?
-            .into_row()
-            .await?;
+    async fn get_roles(&self, server_id: GuildId) -> Result<Vec<Rank>, Error>;
 
-        let mut out: bool = false;
+    async fn get_role_mode(&self, server_id: GuildId) -> Result<RoleMode, Error>;
 
-        if let Some(item) = res {
-            out = item.get(0).unwrap();
-        }
+    async fn set_role_mode(&self, server_id: GuildId, mode: RoleMode) -> Result<(), Error>;
 
-        Ok(out)
-    }
+    // will also set role
+    async fn add_role(&self, server_id: GuildId, role_name: &str, role_id: RoleId, min_level: i32) -> Result<bool, Error>;
 
-    pub async fn get_timeout(&self, server_id: GuildId) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let res = conn.query(
-            "SELECT TOP 1 timeout FROM [Ranking].[Server] WHERE id=@P1",
-            &[&server])
-            .await?
-            .into_row()
-            .await?;
+    async fn remove_role(&self, server_id: GuildId, role_id: RoleId) -> Result<bool, Error>;
 
-        let mut out: i32 = -1;
+    async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, Error>;
 
-        if let Some(item) = res {
-            out = item.get(0).unwrap();
-        }
+    async fn get_timeout(&self, server_id: GuildId) -> Result<i32, Error>;
 
-        Ok(out)
-    }
+    async fn get_voice_settings(&self, server_id: GuildId) -> Result<VoiceSettings, Error>;
 
-    pub async fn get_users(&self, server_id: GuildId) -> Result<Vec<FullMember>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
-        let res = conn.query(
-            "EXEC [Ranking].[GetAllUsers] @serverid = @P1",
-            &[&server])
-            .await?
-            .into_first_result()
-            .await?
-            .into_iter()
-            .map(|row| {
-                let id: UserId = row.get(0).and_then(|u: rust_decimal::Decimal| u.to_u64()).map(UserId::from).unwrap();
-                let role_id: Option<RoleId> = row.get(3).and_then(|u: rust_decimal::Decimal| u.to_u64()).map(RoleId::from);
-                FullMember {
-                    user: id,
-                    exp: Experience {
-                        level: row.get(1).unwrap(),
-                        xp: row.get(2).unwrap()
-                    },
-                    role_id
-                }
-            })
-            .collect::<Vec<_>>();
-
-        Ok(res)
-    }
-}
\ No newline at end of file
+    async fn set_voice_settings(&self, server_id: GuildId, xp_per_minute: i32, daily_cap: i32) -> Result<(), Error>;
+
+    // True: the channel now counts for voice XP False: it doesn't anymore
+    async fn toggle_voice_channel(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, Error>;
+
+    async fn get_users(&self, server_id: GuildId) -> Result<Vec<FullMember>, Error>;
+}
@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::{
+    CacheAndHttp,
+    http::Http,
+    model::id::{GuildId, UserId},
+    prelude::TypeMap
+};
+use tokio::sync::RwLock;
+use tokio::time;
+use tracing::{error, info};
+use crate::Database;
+use crate::models::db_models::{DecaySettings, Experience, Member, XpWindow};
+use crate::services::message_handler::apply_rank_roles;
+
+// Everyone decays at most once a week; checking hourly just spreads the work out.
+const DECAY_EVERY_DAYS: i64 = 7;
+
+// Old XP only has to go before the log gets big, so once a day is plenty.
+const PRUNE_EVERY_HOURS: u32 = 24;
+
+// Discord lists members 1000 at a time.
+const MEMBER_PAGE: u64 = 1000;
+
+pub async fn check_decay(data: Arc<RwLock<TypeMap>>, ctx: Arc<CacheAndHttp>) {
+    // Leaving is noticed as it happens after this, so anyone who left before we started listening (or while we were
+    // down) only needs catching up on once; `rankconfig decay set` does the same when a server turns hiding on.
+    {
+        let db = {
+            let ctx_global = data.read().await;
+            ctx_global.get::<Database>().expect("Couldn't find database").clone()
+        };
+
+        match db.get_departed_hiding_servers().await {
+            Ok(servers) => {
+                for guild_id in servers {
+                    reconcile_departed(&ctx.http, &db, guild_id).await;
+                }
+            }
+            Err(ex) => {
+                error!("Failed to get servers hiding departed members: {}", ex);
+            }
+        }
+    }
+
+    let mut interval_hour = time::interval(Duration::from_secs(60 * 60));
+    let mut since_pruned = PRUNE_EVERY_HOURS;
+    loop {
+        interval_hour.tick().await;
+        let db = {
+            let ctx_global = data.read().await;
+            ctx_global.get::<Database>().expect("Couldn't find database").clone()
+        };
+
+        since_pruned += 1;
+        if since_pruned >= PRUNE_EVERY_HOURS {
+            since_pruned = 0;
+            match db.prune_xp_log(XpWindow::kept_since()).await {
+                Ok(0) => {}
+                Ok(count) => info!("Forgot {} old XP grants", count),
+                Err(ex) => error!("Failed to prune the XP log: {}", ex)
+            }
+        }
+
+        let servers = match db.get_decaying_servers().await {
+            Ok(servers) => servers,
+            Err(ex) => {
+                error!("Failed to get servers with XP decay: {}", ex);
+                continue;
+            }
+        };
+
+        for settings in servers {
+            decay_server(&ctx, &db, &settings).await;
+        }
+    }
+}
+
+pub async fn reconcile_departed(http: &Http, db: &Database, guild_id: GuildId) {
+    // Only go by a complete list; a partial one would mark everyone we didn't get to as gone.
+    let mut present: HashSet<UserId> = HashSet::new();
+    let mut after: Option<UserId> = None;
+    loop {
+        let page = match guild_id.members(http, Some(MEMBER_PAGE), after).await {
+            Ok(page) => page,
+            Err(ex) => {
+                error!("Failed to list members of server {}: {}", guild_id, ex);
+                return;
+            }
+        };
+
+        after = page.last().map(|o| o.user.id);
+        present.extend(page.iter().map(|o| o.user.id));
+        if (page.len() as u64) < MEMBER_PAGE {
+            break;
+        }
+    }
+
+    match db.reconcile_departed(guild_id, &present).await {
+        Ok(0) => {}
+        Ok(count) => info!("Updated whether {} members are still on server {}", count, guild_id),
+        Err(ex) => {
+            error!("Failed to update departed members for server {}: {}", guild_id, ex);
+        }
+    }
+}
+
+async fn decay_server(ctx: &CacheAndHttp, db: &Database, settings: &DecaySettings) {
+    let guild_id = GuildId::from(settings.server_id);
+    let now = chrono::Utc::now();
+    let inactive_since = now - chrono::Duration::days(settings.after_days as i64);
+    let decayed_since = now - chrono::Duration::days(DECAY_EVERY_DAYS);
+
+    let candidates = match db.get_decay_candidates(guild_id, inactive_since, decayed_since).await {
+        Ok(candidates) => candidates,
+        Err(ex) => {
+            error!("Failed to get decay candidates for server {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    if candidates.is_empty() {
+        return;
+    }
+
+    let xp_settings = match db.get_xp_settings(guild_id).await {
+        Ok(xp_settings) => xp_settings,
+        Err(ex) => {
+            error!("Failed getting xp settings for the server: {}", ex);
+            return;
+        }
+    };
+
+    let decayed = candidates.iter()
+        .map(|o| {
+            let (level, xp) = xp_settings.decay(o.exp.level, o.exp.xp, settings.percent);
+            Member { id: o.id, exp: Experience { level, xp } }
+        })
+        .collect::<Vec<_>>();
+
+    match db.apply_decay(guild_id, &decayed).await {
+        Ok(count) => info!("Decayed {} inactive members on server {}", count, guild_id),
+        Err(ex) => {
+            error!("Failed to apply XP decay for server {}: {}", guild_id, ex);
+            return;
+        }
+    }
+
+    if settings.demote {
+        demote(ctx, db, guild_id, &candidates, &decayed).await;
+    }
+}
+
+// Takes away whatever rank roles they no longer have the level for, the same way `rankconfig fix` would.
+async fn demote(ctx: &CacheAndHttp, db: &Database, guild_id: GuildId, before: &[Member], after: &[Member]) {
+    let ranks = match db.get_roles(guild_id).await {
+        Ok(ranks) => ranks,
+        Err(ex) => {
+            error!("Failed to get rank roles: {}", ex);
+            return;
+        }
+    };
+
+    let mode = match db.get_role_mode(guild_id).await {
+        Ok(mode) => mode,
+        Err(ex) => {
+            error!("Failed to get role mode: {}", ex);
+            return;
+        }
+    };
+
+    for (old, new) in before.iter().zip(after) {
+        if new.exp.level >= old.exp.level {
+            continue;
+        }
+
+        // Anyone who's left has nothing to take away; their roles are worked out again if they come back.
+        let mut member = match guild_id.member(ctx, new.id).await {
+            Ok(member) => member,
+            Err(_) => continue
+        };
+
+        if let Err(ex) = apply_rank_roles(&ctx.http, &mut member, &ranks, mode, new.exp.level).await {
+            error!("Failed to update rank roles for decayed user {}: {}", new.id, ex);
+        }
+    }
+}
@@ -1,10 +1,16 @@
+use std::sync::Arc;
+use std::time::Instant;
 use serenity::{
+    cache::Cache,
     client::Context,
-    model::{id::{RoleId}, guild::Member}
+    http::Http,
+    model::{id::{ChannelId, GuildId, UserId}, guild::Member, user::User}
 };
 use tracing::error;
 use serenity::model::channel::Message;
 use crate::{Database, db, Error};
+use crate::models::db_models::{AnnounceSettings, AnnounceTarget, LevelUp, Rank, RoleMode, SpamSettings, XpMultiplier};
+use crate::services::spam_filter::SpamFilter;
 use crate::models::minecraft_db_models::*;
 use proto_mc::rcon::RCONClient;
 use crate::models::minecraft_db_models::Message as MCMessage;
@@ -169,7 +175,58 @@ async fn ranking_check(ctx: &Context, msg: &Message) {
             }
         }
 
-        match db.provide_exp(guild.id, author.id).await {
+        // Messages on cooldown won't earn anything, so they aren't judged or counted as spam either.
+        match db.is_on_cooldown(guild.id, author.id).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(ex) => error!("Failed checking the xp cooldown for user: {}", ex)
+        }
+
+        let settings = match db.get_xp_settings(guild.id).await {
+            Ok(settings) => settings,
+            Err(ex) => {
+                error!("Failed getting xp settings for the server: {}", ex);
+                return;
+            }
+        };
+
+        let multipliers = match db.get_multipliers(guild.id).await {
+            Ok(multipliers) => multipliers,
+            Err(ex) => {
+                error!("Failed getting xp multipliers for the server: {}", ex);
+                Vec::new()
+            }
+        };
+
+        let channels = channel_lineage(&ctx.cache, msg.channel_id);
+        let roles = msg.member.as_ref().map(|o| o.roles.clone()).unwrap_or_default();
+        let multiplier = XpMultiplier::combine(&multipliers, &channels, &roles);
+        let mut xp = (settings.roll_xp() as f64 * multiplier).round().max(0.0) as i32;
+
+        let spam = db.get_spam_settings(guild.id).await.unwrap_or_else(|ex| {
+            error!("Failed getting anti-spam settings for the server: {}", ex);
+            SpamSettings::new(guild.id.0)
+        });
+        let filter = {
+            let ctx_global = ctx.data.read().await;
+            ctx_global.get::<SpamFilter>().expect("Couldn't find spam filter").clone()
+        };
+        let verdict = filter.lock().expect("Spam filter was poisoned").judge(guild.id, author.id, &msg.content, &spam, Instant::now());
+
+        if let Some(reason) = verdict.reason {
+            if let Err(ex) = db.record_spam(guild.id, author.id, reason).await {
+                error!("Failed recording spam for user: {}", ex);
+            }
+
+            // Ignored messages don't touch the cooldown either, so the next real message still counts.
+            if verdict.multiplier <= 0.0 {
+                return;
+            }
+
+            xp = (xp as f64 * verdict.multiplier).round() as i32;
+        }
+
+        match db.provide_exp(guild.id, author.id, xp, &settings).await {
             Err(ex) => {
                 error!("Failed providing exp to user: {}", ex)
             },
@@ -178,51 +235,114 @@ async fn ranking_check(ctx: &Context, msg: &Message) {
                     return;
                 }
 
-                let mut content = format!("<@{}> leveled up from {} to {}.", author.id.as_u64(), data.level - 1, data.level);
-                if let Some(new_rank_id) = data.new_rank {
-                    content += &format!("\nYou are now a <@&{new_rank_id}>.");
-
-                    let mut error = false;
-
-                    match msg.member(&ctx.http).await {
-                        Ok(mut member) => {
-                            if let Some(old_rank_id) = data.old_rank {
-                                let old_rank = RoleId::from(old_rank_id);
-                                if member.roles.contains(&old_rank) {
-                                    // We know we're in a guild, so an error is probably an API issue.
-                                    if let Err(ex) = member.remove_role(&ctx.http, old_rank).await {
-                                        error = true;
-                                        content += "\n(We failed to update your roles; maybe we don't have permission?)";
-                                        error!("Failed to remove role from user: {}", ex);
-                                    }
-                                }
-                            }
-
-                            if let Err(ex) = member.add_role(&ctx.http, RoleId::from(new_rank_id)).await {
-                                if !error {
-                                    content += "\n(We failed to update your roles; maybe we don't have permission?)";
-                                }
-                                error!("Failed to add role to user: {}", ex);
-                            }
-                        }
-                        Err(ex) => {
-                            error!("Failed to get member from message: {}", ex);
-                        }
-                    }
-                }
+                level_up(&ctx.http, &db, guild.id, author.id, msg.channel_id, &data).await;
+            }
+        }
+    }
+}
+
+// Most specific first: the channel (or thread), then whatever it's nested under.
+pub fn channel_lineage(cache: &Arc<Cache>, channel_id: ChannelId) -> Vec<ChannelId> {
+    let mut channels = vec![channel_id];
+    while channels.len() < 3 {
+        match channels.last().and_then(|o| o.to_channel_cached(cache)).and_then(|o| o.guild()).and_then(|o| o.parent_id) {
+            Some(parent) => channels.push(parent),
+            None => break
+        }
+    }
+
+    channels
+}
+
+// Puts on the rank roles they've earned and takes off the ones they shouldn't have, going by the server's role mode.
+pub async fn apply_rank_roles(http: &Http, member: &mut Member, ranks: &[Rank], mode: RoleMode, level: i32) -> Result<(), Error> {
+    let (missing, excess) = mode.role_changes(ranks, level, &member.roles);
+
+    if !excess.is_empty() {
+        member.remove_roles(http, &excess).await?;
+    }
 
-                if let Err(ex2) =
-                    msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| e
-                        .title("Level Up!")
-                        .description(content)
-                    )).await {
-                    error!("Error sending level-up message: {}", ex2)
-                };
+    if !missing.is_empty() {
+        member.add_roles(http, &missing).await?;
+    }
+
+    Ok(())
+}
+
+// apply_rank_roles for someone we only have the ID of; false if Discord didn't let us.
+pub async fn sync_rank_roles(http: &Http, db: &Database, guild_id: GuildId, user_id: UserId, level: i32) -> bool {
+    let ranks = match db.get_roles(guild_id).await {
+        Ok(ranks) => ranks,
+        Err(ex) => {
+            error!("Failed to get rank roles: {}", ex);
+            return false;
+        }
+    };
+
+    let mode = match db.get_role_mode(guild_id).await {
+        Ok(mode) => mode,
+        Err(ex) => {
+            error!("Failed to get role mode: {}", ex);
+            return false;
+        }
+    };
+
+    match guild_id.member(http, user_id).await {
+        Ok(mut member) => {
+            // We know we're in a guild, so an error is probably an API issue.
+            if let Err(ex) = apply_rank_roles(http, &mut member, &ranks, mode, level).await {
+                error!("Failed to update rank roles for user: {}", ex);
+                return false;
             }
+
+            true
+        }
+        Err(ex) => {
+            error!("Failed to get member for rank update: {}", ex);
+            false
         }
     }
 }
 
+// Updates rank roles and announces the level-up wherever the server wants it; text and voice XP both end up here.
+pub async fn level_up(http: &Http, db: &Database, guild_id: GuildId, user_id: UserId, channel_id: ChannelId, data: &LevelUp) {
+    let roles_failed = data.new_rank.is_some() && !sync_rank_roles(http, db, guild_id, user_id, data.level).await;
+
+    let settings = db.get_announce_settings(guild_id).await.unwrap_or_else(|ex| {
+        error!("Failed to get level-up announcement settings: {}", ex);
+        AnnounceSettings::new(guild_id.0)
+    });
+
+    if !settings.should_announce(data) {
+        return;
+    }
+
+    let mut content = settings.render(user_id, data);
+    if roles_failed {
+        content += "\n(We failed to update your roles; maybe we don't have permission?)";
+    }
+
+    let target = match (settings.target, settings.channel_id) {
+        (AnnounceTarget::Channel, Some(announce_channel)) => announce_channel,
+        (AnnounceTarget::Dm, _) => match user_id.create_dm_channel(http).await {
+            Ok(dm) => dm.id,
+            Err(ex) => {
+                error!("Failed to open DMs for level-up message: {}", ex);
+                return;
+            }
+        },
+        _ => channel_id
+    };
+
+    if let Err(ex2) =
+        target.send_message(http, |m| m.embed(|e| e
+            .title("Level Up!")
+            .description(content)
+        )).await {
+        error!("Error sending level-up message: {}", ex2)
+    };
+}
+
 pub async fn on_join(ctx: &Context, new_member: &Member) {
     if new_member.user.bot {
         return;
@@ -232,15 +352,31 @@ pub async fn on_join(ctx: &Context, new_member: &Member) {
     let mut member = new_member.clone();
     let guild_id = new_member.guild_id;
 
+    if let Err(ex) = db.set_departed(guild_id, member.user.id, false).await {
+        error!("Failed to mark user {} as back on server {}: {}", member.user.id, guild_id, ex);
+    }
+
+    // Everything they'd earned before leaving comes back, following the server's role mode.
     let experience = db.get_xp(guild_id, member.user.id).await.unwrap();
-    let current_role = db.get_highest_role(guild_id, experience.level).await.unwrap();
-    if let Some(current_role_id) = current_role {
-        if let Err(ex) = member.add_role(&ctx.http, current_role_id).await {
-            error!("Failed to add role for server {}: {}", guild_id, ex);
-            if let Err(ex2) = member.user.direct_message(&ctx.http, |m|
-                m.content("I tried to re-add your roles, but the server didn't let me. Sorry~")).await {
-                error!("Failed to send error message to user {}: {}", member.user.id, ex2);
-            }
+    let ranks = db.get_roles(guild_id).await.unwrap();
+    let mode = db.get_role_mode(guild_id).await.unwrap();
+    if let Err(ex) = apply_rank_roles(&ctx.http, &mut member, &ranks, mode, experience.level).await {
+        error!("Failed to add role for server {}: {}", guild_id, ex);
+        if let Err(ex2) = member.user.direct_message(&ctx.http, |m|
+            m.content("I tried to re-add your roles, but the server didn't let me. Sorry~")).await {
+            error!("Failed to send error message to user {}: {}", member.user.id, ex2);
         }
     }
-}
\ No newline at end of file
+}
+
+// Their levels stay put in case they come back; this only matters for hiding them from leaderboards.
+pub async fn on_leave(ctx: &Context, guild_id: GuildId, user: &User) {
+    if user.bot {
+        return;
+    }
+
+    let db = db!(ctx);
+    if let Err(ex) = db.set_departed(guild_id, user.id, true).await {
+        error!("Failed to mark user {} as gone from server {}: {}", user.id, guild_id, ex);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::Error;
+
+pub struct Migration {
+    pub name: &'static str,
+    pub sql: &'static str
+}
+
+impl Migration {
+    // GO isn't actually T-SQL, it's a client-side batch separator, so we have to split on it ourselves.
+    pub fn batches(&self) -> Vec<String> {
+        let mut batches = Vec::new();
+        let mut current = String::new();
+
+        for line in self.sql.lines() {
+            if line.trim().eq_ignore_ascii_case("GO") {
+                batches.push(std::mem::take(&mut current));
+            } else {
+                current.push_str(line);
+                current.push('\n');
+            }
+        }
+
+        batches.push(current);
+        batches.into_iter().filter(|o| !o.trim().is_empty()).collect()
+    }
+}
+
+// The schema version is how many of these have been applied, so never reorder or remove one; only append.
+pub const SQL_SERVER_MIGRATIONS: &[Migration] = &[
+    Migration { name: "ranking", sql: include_str!("../../migrations/sql_server/0001_ranking.sql") },
+    Migration { name: "cowboard", sql: include_str!("../../migrations/sql_server/0002_cowboard.sql") },
+    Migration { name: "minecraft", sql: include_str!("../../migrations/sql_server/0003_minecraft.sql") },
+    Migration { name: "ucm", sql: include_str!("../../migrations/sql_server/0004_ucm.sql") },
+    Migration { name: "xp_settings", sql: include_str!("../../migrations/sql_server/0005_xp_settings.sql") },
+    Migration { name: "xp_multipliers", sql: include_str!("../../migrations/sql_server/0006_xp_multipliers.sql") },
+    Migration { name: "voice_xp", sql: include_str!("../../migrations/sql_server/0007_voice_xp.sql") },
+    Migration { name: "rank_card", sql: include_str!("../../migrations/sql_server/0008_rank_card.sql") },
+    Migration { name: "seasons", sql: include_str!("../../migrations/sql_server/0009_seasons.sql") },
+    Migration { name: "role_mode", sql: include_str!("../../migrations/sql_server/0010_role_mode.sql") },
+    Migration { name: "announcements", sql: include_str!("../../migrations/sql_server/0011_announcements.sql") },
+    Migration { name: "anti_spam", sql: include_str!("../../migrations/sql_server/0012_anti_spam.sql") },
+    Migration { name: "xp_decay", sql: include_str!("../../migrations/sql_server/0013_xp_decay.sql") },
+    Migration { name: "auto_fix", sql: include_str!("../../migrations/sql_server/0014_auto_fix.sql") },
+    Migration { name: "fix_history", sql: include_str!("../../migrations/sql_server/0015_fix_history.sql") },
+    Migration { name: "cowboard_boards", sql: include_str!("../../migrations/sql_server/0016_cowboard_boards.sql") },
+    Migration { name: "cowboard_filters", sql: include_str!("../../migrations/sql_server/0017_cowboard_filters.sql") },
+    Migration { name: "cowboard_stats", sql: include_str!("../../migrations/sql_server/0018_cowboard_stats.sql") },
+    Migration { name: "cowboard_rules", sql: include_str!("../../migrations/sql_server/0019_cowboard_rules.sql") },
+    Migration { name: "cowboard_moderation", sql: include_str!("../../migrations/sql_server/0020_cowboard_moderation.sql") },
+    Migration { name: "cowboard_deletes", sql: include_str!("../../migrations/sql_server/0021_cowboard_deletes.sql") },
+    Migration { name: "reminder_conditions", sql: include_str!("../../migrations/sql_server/0022_reminder_conditions.sql") },
+    Migration { name: "reminder_delivery", sql: include_str!("../../migrations/sql_server/0023_reminder_delivery.sql") }
+];
+
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    Migration { name: "ranking", sql: include_str!("../../migrations/sqlite/0001_ranking.sql") },
+    Migration { name: "cowboard", sql: include_str!("../../migrations/sqlite/0002_cowboard.sql") },
+    Migration { name: "minecraft", sql: include_str!("../../migrations/sqlite/0003_minecraft.sql") },
+    Migration { name: "ucm", sql: include_str!("../../migrations/sqlite/0004_ucm.sql") },
+    Migration { name: "xp_settings", sql: include_str!("../../migrations/sqlite/0005_xp_settings.sql") },
+    Migration { name: "xp_multipliers", sql: include_str!("../../migrations/sqlite/0006_xp_multipliers.sql") },
+    Migration { name: "voice_xp", sql: include_str!("../../migrations/sqlite/0007_voice_xp.sql") },
+    Migration { name: "rank_card", sql: include_str!("../../migrations/sqlite/0008_rank_card.sql") },
+    Migration { name: "seasons", sql: include_str!("../../migrations/sqlite/0009_seasons.sql") },
+    Migration { name: "role_mode", sql: include_str!("../../migrations/sqlite/0010_role_mode.sql") },
+    Migration { name: "announcements", sql: include_str!("../../migrations/sqlite/0011_announcements.sql") },
+    Migration { name: "anti_spam", sql: include_str!("../../migrations/sqlite/0012_anti_spam.sql") },
+    Migration { name: "xp_decay", sql: include_str!("../../migrations/sqlite/0013_xp_decay.sql") },
+    Migration { name: "auto_fix", sql: include_str!("../../migrations/sqlite/0014_auto_fix.sql") },
+    Migration { name: "fix_history", sql: include_str!("../../migrations/sqlite/0015_fix_history.sql") },
+    Migration { name: "cowboard_boards", sql: include_str!("../../migrations/sqlite/0016_cowboard_boards.sql") },
+    Migration { name: "cowboard_filters", sql: include_str!("../../migrations/sqlite/0017_cowboard_filters.sql") },
+    Migration { name: "cowboard_stats", sql: include_str!("../../migrations/sqlite/0018_cowboard_stats.sql") },
+    Migration { name: "cowboard_rules", sql: include_str!("../../migrations/sqlite/0019_cowboard_rules.sql") },
+    Migration { name: "cowboard_moderation", sql: include_str!("../../migrations/sqlite/0020_cowboard_moderation.sql") },
+    Migration { name: "cowboard_deletes", sql: include_str!("../../migrations/sqlite/0021_cowboard_deletes.sql") },
+    Migration { name: "reminder_conditions", sql: include_str!("../../migrations/sqlite/0022_reminder_conditions.sql") },
+    Migration { name: "reminder_delivery", sql: include_str!("../../migrations/sqlite/0023_reminder_delivery.sql") }
+];
+
+// Gives back the migrations that still need to run, along with the version each one brings us to.
+pub fn pending(current: usize, migrations: &'static [Migration]) -> Result<Vec<(usize, &'static Migration)>, Error> {
+    if current > migrations.len() {
+        return Err(format!("The database is at schema version {current}, but this build only knows up to version {}. \
+            Refusing to start; update the bot instead.", migrations.len()).into());
+    }
+
+    Ok(migrations.iter().enumerate().skip(current).map(|(index, migration)| (index + 1, migration)).collect())
+}
@@ -1,14 +1,24 @@
+use async_trait::async_trait;
+use rusqlite::{OptionalExtension, params};
 use serenity::model::id::ChannelId;
 use rust_decimal::{
     Decimal,
     prelude::FromPrimitive
 };
 
-use crate::Database;
+use crate::Error;
 use crate::models::minecraft_db_models::*;
+use crate::services::sql_server::SqlServerDatabase;
+use crate::services::sqlite::{SqliteDatabase, to_sql_id};
 
-impl Database {
-    pub async fn get_minecraft_channel(&self, channel_id: ChannelId) -> Result<Option<Feed>, Box<dyn std::error::Error + Send + Sync>> {
+#[async_trait]
+pub trait MinecraftStorage: Send + Sync {
+    async fn get_minecraft_channel(&self, channel_id: ChannelId) -> Result<Option<Feed>, Error>;
+}
+
+#[async_trait]
+impl MinecraftStorage for SqlServerDatabase {
+    async fn get_minecraft_channel(&self, channel_id: ChannelId) -> Result<Option<Feed>, Error> {
         let mut conn = self.pool.get().await?;
         let server = Decimal::from_u64(*channel_id.as_u64()).unwrap();
         let res = conn.query(
@@ -29,4 +39,23 @@ impl Database {
             }
         }))
     }
-}
\ No newline at end of file
+}
+
+#[async_trait]
+impl MinecraftStorage for SqliteDatabase {
+    async fn get_minecraft_channel(&self, channel_id: ChannelId) -> Result<Option<Feed>, Error> {
+        let channel = to_sql_id(channel_id.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                "SELECT host, password FROM minecraft_feed WHERE channel_id = ?1",
+                params![channel],
+                |row| Ok(Feed {
+                    channel: channel_id,
+                    host: row.get(0)?,
+                    password: row.get(1)?
+                }))
+                .optional()
+        }).await
+    }
+}
@@ -1,4 +1,11 @@
 pub mod message_handler;
+pub mod voice_handler;
+pub mod decay_handler;
+pub mod auto_fix_handler;
+pub mod spam_filter;
 pub mod bot_init;
 pub mod database;
-mod minecraft_db;
\ No newline at end of file
+pub mod sql_server;
+pub mod sqlite;
+pub mod migrations;
+mod minecraft_db;
@@ -0,0 +1,207 @@
+use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use regex::Regex;
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::TypeMapKey;
+use crate::models::db_models::{SpamReason, SpamSettings};
+
+// Anyone quiet for this long is forgotten, so the map doesn't grow forever.
+const FORGET_AFTER: Duration = Duration::from_secs(60 * 60);
+const PRUNE_EVERY: usize = 1000;
+
+static CUSTOM_EMOJI: OnceLock<Regex> = OnceLock::new();
+
+struct Recent {
+    // Hash of their last message, for spotting repeats.
+    last: u64,
+    last_at: Instant,
+    // When each of their messages inside the burst window was sent.
+    sent: VecDeque<Instant>
+}
+
+// What the checks made of a message; a multiplier of 0 means it gets no XP at all.
+pub struct Verdict {
+    pub reason: Option<SpamReason>,
+    pub multiplier: f64
+}
+
+// Remembers just enough about everyone's last few messages to catch repeats and bursts.
+// Nothing here is saved; a restart only means the first message afterwards can't be a repeat.
+#[derive(Default)]
+pub struct SpamFilter {
+    recent: HashMap<(GuildId, UserId), Recent>,
+    seen: usize
+}
+
+impl TypeMapKey for SpamFilter {
+    type Value = Arc<Mutex<SpamFilter>>;
+}
+
+impl SpamFilter {
+    pub fn judge(&mut self, guild_id: GuildId, user_id: UserId, content: &str, settings: &SpamSettings, now: Instant) -> Verdict {
+        self.seen += 1;
+        if self.seen >= PRUNE_EVERY {
+            self.seen = 0;
+            self.recent.retain(|_, o| now.duration_since(o.last_at) < FORGET_AFTER);
+        }
+
+        // Attachments and stickers on their own have no text to judge, so they're neither short nor repeats.
+        let empty = content.trim().is_empty();
+        let hash = normalized_hash(content);
+        let window = Duration::from_secs(settings.burst_seconds.max(0) as u64);
+        let (repeat, sent) = match self.recent.get_mut(&(guild_id, user_id)) {
+            Some(recent) => {
+                let repeat = !empty && recent.last == hash;
+                if !empty {
+                    recent.last = hash;
+                }
+                recent.last_at = now;
+                recent.sent.push_back(now);
+                while recent.sent.front().map(|o| now.duration_since(*o) > window).unwrap_or(false) {
+                    recent.sent.pop_front();
+                }
+
+                (repeat, recent.sent.len())
+            }
+            None => {
+                self.recent.insert((guild_id, user_id), Recent { last: hash, last_at: now, sent: VecDeque::from([now]) });
+                (false, 1)
+            }
+        };
+
+        let (length, junk_ratio) = measure(content);
+        let reason = if settings.block_repeats && repeat {
+            Some(SpamReason::Repeat)
+        } else if !empty && settings.min_length > 0 && length < settings.min_length as usize {
+            Some(SpamReason::Short)
+        } else if settings.max_junk_ratio > 0.0 && junk_ratio >= settings.max_junk_ratio {
+            Some(SpamReason::Junk)
+        } else if settings.burst_count > 0 && sent > settings.burst_count as usize {
+            Some(SpamReason::Burst)
+        } else {
+            None
+        };
+
+        let multiplier = match reason {
+            None => 1.0,
+            Some(SpamReason::Burst) => settings.burst_multiplier.clamp(0.0, 1.0),
+            Some(_) => 0.0
+        };
+
+        Verdict { reason, multiplier }
+    }
+}
+
+// Case and spacing don't make a message any less of a repeat.
+fn normalized_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for word in content.split_whitespace() {
+        word.to_lowercase().hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF)
+}
+
+// Joiners and variation selectors glue emoji together; they aren't anything on their own.
+fn is_emoji_glue(c: char) -> bool {
+    matches!(c as u32, 0x200D | 0xFE0E | 0xFE0F)
+}
+
+// Gives back how many characters of actual text there are, and what fraction of the
+// message is emoji and links (counting each emoji, link, or word as one piece).
+fn measure(content: &str) -> (usize, f64) {
+    let custom_emoji = CUSTOM_EMOJI.get_or_init(|| Regex::new(r"<a?:\w+:\d+>").unwrap());
+    let (mut text, mut words, mut junk) = (0, 0, 0);
+
+    for word in content.split_whitespace() {
+        if word.starts_with("http://") || word.starts_with("https://") {
+            junk += 1;
+            continue;
+        }
+
+        junk += custom_emoji.find_iter(word).count();
+        let rest = custom_emoji.replace_all(word, "");
+        let mut letters = 0;
+        for c in rest.chars() {
+            if is_emoji(c) {
+                junk += 1;
+            } else if !is_emoji_glue(c) {
+                letters += 1;
+            }
+        }
+
+        if letters > 0 {
+            text += letters;
+            words += 1;
+        }
+    }
+
+    let pieces = words + junk;
+    (text, if pieces == 0 { 0.0 } else { junk as f64 / pieces as f64 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> SpamSettings {
+        SpamSettings::new(1)
+    }
+
+    fn judge_all(settings: &SpamSettings, messages: &[&str]) -> Vec<Option<SpamReason>> {
+        let mut filter = SpamFilter::default();
+        let start = Instant::now();
+        messages.iter().enumerate()
+            .map(|(i, o)| filter.judge(GuildId(1), UserId(2), o, settings, start + Duration::from_secs(i as u64 * 60)).reason)
+            .collect()
+    }
+
+    #[test]
+    fn repeats_ignore_case_and_spacing() {
+        let mut settings = settings();
+        settings.block_repeats = true;
+        assert_eq!(judge_all(&settings, &["hello there", "Hello   there", "something else"]), vec![None, Some(SpamReason::Repeat), None]);
+    }
+
+    #[test]
+    fn empty_messages_are_not_repeats_or_short() {
+        let mut settings = settings();
+        settings.block_repeats = true;
+        settings.min_length = 5;
+        assert_eq!(judge_all(&settings, &["hello there", "", "", "hello there"]), vec![None, None, None, Some(SpamReason::Repeat)]);
+        assert_eq!(judge_all(&settings, &["hey"]), vec![Some(SpamReason::Short)]);
+    }
+
+    #[test]
+    fn mostly_emoji_and_links_is_junk() {
+        let mut settings = settings();
+        settings.max_junk_ratio = 0.5;
+        assert_eq!(judge_all(&settings, &["😀 <:cow:123> https://example.com moo", "an actual sentence 😀"]), vec![Some(SpamReason::Junk), None]);
+        assert_eq!(measure("<:cow:123>moo 👍"), (3, 2.0 / 3.0));
+    }
+
+    #[test]
+    fn bursts_are_discounted() {
+        let mut settings = settings();
+        settings.burst_count = 2;
+        settings.burst_seconds = 10;
+        let mut filter = SpamFilter::default();
+        let start = Instant::now();
+        let verdicts = (0..4)
+            .map(|i| filter.judge(GuildId(1), UserId(2), &format!("message {i}"), &settings, start + Duration::from_secs(i * 2)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(verdicts.iter().map(|o| o.reason).collect::<Vec<_>>(), vec![None, None, Some(SpamReason::Burst), Some(SpamReason::Burst)]);
+        assert_eq!(verdicts[2].multiplier, 0.5);
+
+        let later = filter.judge(GuildId(1), UserId(2), "much later", &settings, start + Duration::from_secs(60));
+        assert!(later.reason.is_none());
+    }
+}
@@ -0,0 +1,1402 @@
+use std::collections::HashSet;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use tracing::info;
+use bb8::{Pool, PooledConnection};
+use bb8_tiberius::ConnectionManager;
+use serenity::model::id::{
+    UserId,
+    GuildId,
+    ChannelId, RoleId
+};
+use tiberius::{AuthMethod, Config, Row};
+use rust_decimal::{
+    Decimal,
+    prelude::FromPrimitive
+};
+use rust_decimal::prelude::ToPrimitive;
+use crate::Error;
+use crate::models::db_models::*;
+use crate::services::database::RankingStorage;
+use crate::services::migrations::{SQL_SERVER_MIGRATIONS, pending};
+
+pub struct SqlServerDatabase {
+    pub(crate) pool: Pool<ConnectionManager>
+}
+
+impl SqlServerDatabase {
+    pub async fn new(ip: &str, port: u16, usr: &str, pwd: &str) -> Result<Self, bb8_tiberius::Error> {
+        // The password is stored in a file; using secure strings is probably not going to make much of a difference.
+        let mut config = Config::new();
+
+        config.host(ip);
+        config.port(port);
+        config.authentication(AuthMethod::sql_server(usr, pwd));
+        // Default schema needs to be Cow
+        config.database("Cow");
+        config.trust_cert();
+
+        let manager = ConnectionManager::build(config)?;
+        let pool = Pool::builder().max_size(8).build(manager).await?;
+
+        Ok(SqlServerDatabase { pool })
+    }
+
+    pub async fn migrate(&self) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+
+        conn.simple_query(
+            "IF OBJECT_ID(N'[dbo].[SchemaVersion]', N'U') IS NULL \
+            CREATE TABLE [dbo].[SchemaVersion] (version INT NOT NULL PRIMARY KEY, name NVARCHAR(100) NOT NULL, applied_at DATETIME2 NOT NULL DEFAULT SYSUTCDATETIME());")
+            .await?
+            .into_results()
+            .await?;
+
+        let version: i32 = conn.simple_query(
+            "SELECT ISNULL(MAX(version), 0) FROM [dbo].[SchemaVersion];")
+            .await?
+            .into_row()
+            .await?
+            .and_then(|row| row.get(0))
+            .unwrap_or(0);
+
+        for (version, migration) in pending(version as usize, SQL_SERVER_MIGRATIONS)? {
+            info!("Applying SQL Server migration {}: {}", version, migration.name);
+
+            // No transaction here; some batches (like CREATE DATABASE) aren't allowed in one, so the scripts guard themselves instead.
+            for batch in migration.batches() {
+                conn.simple_query(batch)
+                    .await?
+                    .into_results()
+                    .await?;
+            }
+
+            conn.execute(
+                "INSERT INTO [dbo].[SchemaVersion] (version, name) VALUES (@P1, @P2)",
+                &[&(version as i32), &migration.name])
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+// The Grant procedures only add the XP; the level curve lives in Rust, so the level up happens here.
+async fn finish_grant(conn: &mut PooledConnection<'_, ConnectionManager>, server: Decimal, user: Decimal, res: Option<Row>, settings: &XpSettings) -> Result<LevelUp, Error> {
+    let mut out = LevelUp { level: -1, old_level: 0, old_rank: None, new_rank: None };
+
+    if let Some(row) = res {
+        let granted: bool = row.get(0).unwrap();
+        let old_level: i32 = row.get(1).unwrap();
+        let xp: i32 = row.get(2).unwrap();
+        out.old_level = old_level;
+
+        if !granted {
+            return Ok(out);
+        }
+
+        let (level, leftover) = settings.level_up(old_level, xp);
+        if level == old_level {
+            return Ok(out);
+        }
+
+        // Only take away what was spent, so a message that sneaks in between doesn't lose its XP.
+        let updated = conn.execute(
+            "UPDATE [Ranking].[Level] SET level = @P3, xp = xp - @P4 WHERE server_id = @P1 AND [user_id] = @P2 AND level = @P5",
+            &[&server, &user, &level, &(xp - leftover), &old_level])
+            .await?;
+
+        if updated.total() == 0 {
+            return Ok(out);
+        }
+
+        out.level = level;
+
+        let ranks = conn.query(
+            "SELECT \
+                (SELECT TOP 1 role_id FROM [Ranking].[Role] WHERE server_id = @P1 AND min_level <= @P2 ORDER BY min_level DESC), \
+                (SELECT TOP 1 role_id FROM [Ranking].[Role] WHERE server_id = @P1 AND min_level <= @P3 ORDER BY min_level DESC)",
+            &[&server, &old_level, &level])
+            .await?
+            .into_row()
+            .await?;
+
+        if let Some(row) = ranks {
+            let old_rank_id = row.get(0).and_then(|u: rust_decimal::Decimal| u.to_u64());
+            let new_rank_id = row.get(1).and_then(|u: rust_decimal::Decimal| u.to_u64());
+
+            if old_rank_id != new_rank_id {
+                out.old_rank = old_rank_id;
+                out.new_rank = new_rank_id;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+async fn set_level(conn: &mut PooledConnection<'_, ConnectionManager>, server: Decimal, user: Decimal, level: i32, xp: i32) -> Result<(), Error> {
+    conn.execute(
+        "IF EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @P1 AND [user_id] = @P2) \
+            UPDATE [Ranking].[Level] SET level = @P3, xp = @P4 WHERE server_id = @P1 AND [user_id] = @P2 \
+        ELSE \
+            INSERT INTO [Ranking].[Level] (server_id, [user_id], level, xp) VALUES (@P1, @P2, @P3, @P4)",
+        &[&server, &user, &level, &xp])
+        .await?;
+
+    Ok(())
+}
+
+// The body of save_fix, so it can roll back whatever part of this fails.
+async fn save_fix_changes(conn: &mut PooledConnection<'_, ConnectionManager>, server: Decimal, changes: &[RoleChange], keep_since: NaiveDateTime) -> Result<i32, Error> {
+    conn.execute(
+        "DELETE FROM [Ranking].[FixChange] WHERE fix_id IN (SELECT id FROM [Ranking].[Fix] WHERE server_id = @P1 AND created_at < @P2); \
+        DELETE FROM [Ranking].[Fix] WHERE server_id = @P1 AND created_at < @P2",
+        &[&server, &keep_since])
+        .await?;
+
+    let id: i32 = conn.query(
+        "INSERT INTO [Ranking].[Fix] (server_id) OUTPUT inserted.id VALUES (@P1)",
+        &[&server])
+        .await?
+        .into_row()
+        .await?
+        .and_then(|row| row.get(0))
+        .unwrap();
+
+    for change in changes {
+        let user = Decimal::from_u64(*change.user_id.as_u64()).unwrap();
+        let role = Decimal::from_u64(*change.role_id.as_u64()).unwrap();
+        conn.execute(
+            "INSERT INTO [Ranking].[FixChange] (fix_id, [user_id], role_id, added) VALUES (@P1, @P2, @P3, @P4)",
+            &[&id, &user, &role, &change.added])
+            .await?;
+    }
+
+    Ok(id)
+}
+
+// Members who left are kept off the leaderboards if the server says so; @P1 is always the server.
+const HIDDEN_MEMBERS: &str = "SELECT [user_id] FROM [Ranking].[Level] WHERE server_id = @P1 AND departed = 1 \
+    AND EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1 AND decay_hide_departed = 1)";
+
+// Per-user totals for an XpWindow: @P1 is the server, then whatever window_params gives back.
+// `hidden` is HIDDEN_MEMBERS again; it has to be spelled out to live in the same WITH.
+const WINDOW_TOTALS: &str = "WITH hidden AS (SELECT [user_id] FROM [Ranking].[Level] WHERE server_id = @P1 AND departed = 1 \
+    AND EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1 AND decay_hide_departed = 1)), \
+    totals AS ( \
+    SELECT [user_id], SUM(CAST(xp AS BIGINT)) AS xp FROM [Ranking].[XpLog] \
+    WHERE @P2 < 0 AND server_id = @P1 AND gained_at >= @P3 AND gained_at < @P4 AND [user_id] NOT IN (SELECT [user_id] FROM hidden) \
+    GROUP BY [user_id] HAVING SUM(xp) > 0 \
+    UNION ALL \
+    SELECT [user_id], xp FROM [Ranking].[SeasonStanding] WHERE season_id = @P2 AND [user_id] NOT IN (SELECT [user_id] FROM hidden)) ";
+
+// Open-ended windows run until the end of DATETIME2, more or less.
+fn window_params(window: XpWindow) -> (i32, NaiveDateTime, NaiveDateTime) {
+    let (season, from, to) = window.bounds();
+    let end = NaiveDate::from_ymd_opt(9999, 12, 31).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    (season, from.naive_utc(), to.map(|o| o.naive_utc()).unwrap_or(end))
+}
+
+// Only ever one of our own column names, so it's fine to format into the query.
+fn spam_column(reason: SpamReason) -> &'static str {
+    match reason {
+        SpamReason::Short => "short_count",
+        SpamReason::Repeat => "repeat_count",
+        SpamReason::Junk => "junk_count",
+        SpamReason::Burst => "burst_count"
+    }
+}
+
+const AUTO_FIX_COLUMNS: &str = "id, autofix_hours, autofix_channel, autofix_multiple, autofix_remove, autofix_demote, autofix_last_run";
+
+fn auto_fix_from_row(row: &Row) -> AutoFixSettings {
+    let id: Decimal = row.get(0).unwrap();
+    let channel: Option<Decimal> = row.get(2);
+    let last_run: Option<NaiveDateTime> = row.get(6);
+
+    AutoFixSettings {
+        server_id: id.to_u64().unwrap(),
+        every_hours: row.get(1).unwrap(),
+        log_channel: channel.and_then(|o| o.to_u64()).map(ChannelId::from),
+        options: FixOptions {
+            multiple: row.get(3).unwrap(),
+            remove: row.get(4).unwrap(),
+            demote: row.get(5).unwrap()
+        },
+        last_run: last_run.map(|o| Utc.from_utc_datetime(&o))
+    }
+}
+
+fn season_from_row(row: &Row) -> Season {
+    let server_id: Decimal = row.get(1).unwrap();
+    let started_at: NaiveDateTime = row.get(3).unwrap();
+    let ended_at: Option<NaiveDateTime> = row.get(4);
+
+    Season {
+        id: row.get(0).unwrap(),
+        server_id: server_id.to_u64().unwrap(),
+        name: row.get::<&str, _>(2).unwrap().to_string(),
+        started_at: Utc.from_utc_datetime(&started_at),
+        ended_at: ended_at.map(|o| Utc.from_utc_datetime(&o))
+    }
+}
+
+#[async_trait]
+impl RankingStorage for SqlServerDatabase {
+    async fn provide_exp(&self, server_id: GuildId, user_id: UserId, gained: i32, settings: &XpSettings) -> Result<LevelUp, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let res = conn.query(
+            "EXEC [Ranking].[GrantExp] @serverid = @P1, @userid = @P2, @xp = @P3",
+            &[&server, &user, &gained])
+            .await?
+            .into_row()
+            .await?;
+
+        finish_grant(&mut conn, server, user, res, settings).await
+    }
+
+    async fn is_on_cooldown(&self, server_id: GuildId, user_id: UserId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT CAST(CASE WHEN l.last_message IS NOT NULL AND DATEDIFF_BIG(MILLISECOND, l.last_message, SYSUTCDATETIME()) < s.timeout THEN 1 ELSE 0 END AS BIT) \
+            FROM [Ranking].[Level] l JOIN [Ranking].[Server] s ON s.id = l.server_id WHERE l.server_id = @P1 AND l.[user_id] = @P2",
+            &[&server, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.and_then(|o| o.get(0)).unwrap_or(false))
+    }
+
+    async fn provide_voice_exp(&self, server_id: GuildId, user_id: UserId, gained: i32, daily_cap: i32, settings: &XpSettings) -> Result<LevelUp, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let res = conn.query(
+            "EXEC [Ranking].[GrantVoiceExp] @serverid = @P1, @userid = @P2, @xp = @P3, @cap = @P4",
+            &[&server, &user, &gained, &daily_cap])
+            .await?
+            .into_row()
+            .await?;
+
+        finish_grant(&mut conn, server, user, res, settings).await
+    }
+
+    async fn get_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Experience, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT xp, level FROM [Ranking].[Level] WHERE server_id = @P1 AND [user_id] = @P2",
+            &[&server, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out = Experience::new();
+
+        if let Some(item) = res {
+            out = Experience {
+                xp: item.get(0).unwrap(),
+                level: item.get(1).unwrap()
+            };
+        }
+
+        Ok(out)
+    }
+
+    async fn set_xp(&self, server_id: GuildId, user_id: UserId, level: i32, xp: i32) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        set_level(&mut conn, server, user, level, xp).await
+    }
+
+    async fn import_levels(&self, server_id: GuildId, members: &[Member]) -> Result<usize, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+
+        conn.simple_query("BEGIN TRANSACTION").await?.into_results().await?;
+
+        for member in members {
+            let user = Decimal::from_u64(*member.id.as_u64()).unwrap();
+            if let Err(ex) = set_level(&mut conn, server, user, member.exp.level, member.exp.xp).await {
+                // Don't leave half an import behind, or hand a connection stuck in a transaction back to the pool.
+                conn.simple_query("ROLLBACK TRANSACTION").await?.into_results().await?;
+                return Err(ex);
+            }
+        }
+
+        conn.simple_query("COMMIT TRANSACTION").await?.into_results().await?;
+
+        Ok(members.len())
+    }
+
+    async fn get_highest_role(&self, server_id: GuildId, level: i32) -> Result<Option<RoleId>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT TOP 1 role_id FROM [Ranking].[Role] WHERE server_id = @P1 AND min_level <= @P2 ORDER BY min_level DESC",
+            &[&server, &level])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: Option<RoleId> = None;
+
+        if let Some(item) = res {
+            let id: rust_decimal::Decimal = item.get(0).unwrap();
+            out = id.to_u64().and_then(|u| Option::from(RoleId::from(u)));
+        }
+
+        Ok(out)
+    }
+
+    async fn get_xp_settings(&self, server_id: GuildId) -> Result<XpSettings, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT min_xp, max_xp, xp_multiplier, level_curve, curve_base, curve_step, curve_growth, curve_table FROM [Ranking].[Server] WHERE id = @P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out = XpSettings::new(*server_id.as_u64());
+
+        if let Some(row) = res {
+            let curve: u8 = row.get(3).unwrap();
+            let table: Option<&str> = row.get(7);
+
+            out = XpSettings {
+                server_id: *server_id.as_u64(),
+                min_xp: row.get(0).unwrap(),
+                max_xp: row.get(1).unwrap(),
+                multiplier: row.get(2).unwrap(),
+                curve: LevelCurve::try_from(curve).unwrap_or(LevelCurve::Quadratic),
+                curve_base: row.get(4).unwrap(),
+                curve_step: row.get(5).unwrap(),
+                curve_growth: row.get(6).unwrap(),
+                curve_table: table.map(XpSettings::table_from_string).unwrap_or_default()
+            };
+        }
+
+        Ok(out)
+    }
+
+    async fn set_xp_settings(&self, settings: &XpSettings) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(settings.server_id).unwrap();
+        let curve = settings.curve as u8;
+        let table = settings.table_to_string();
+        conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET min_xp = @P2, max_xp = @P3, xp_multiplier = @P4, level_curve = @P5, \
+            curve_base = @P6, curve_step = @P7, curve_growth = @P8, curve_table = @P9 WHERE id = @P1",
+            &[&server, &settings.min_xp, &settings.max_xp, &settings.multiplier, &curve,
+                &settings.curve_base, &settings.curve_step, &settings.curve_growth, &table])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_multipliers(&self, server_id: GuildId) -> Result<Vec<XpMultiplier>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT target_id, is_role, multiplier FROM [Ranking].[Multiplier] WHERE server_id = @P1 ORDER BY is_role, multiplier DESC",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| {
+                let id: rust_decimal::Decimal = row.get(0).unwrap();
+                XpMultiplier {
+                    target: MultiplierTarget::from_id(id.to_u64().unwrap(), row.get(1).unwrap()),
+                    multiplier: row.get(2).unwrap()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(res)
+    }
+
+    async fn set_multiplier(&self, server_id: GuildId, target: MultiplierTarget, multiplier: f64) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let target_id = Decimal::from_u64(target.id()).unwrap();
+        conn.execute(
+            "IF EXISTS (SELECT 1 FROM [Ranking].[Multiplier] WHERE server_id = @P1 AND target_id = @P2) \
+                UPDATE [Ranking].[Multiplier] SET is_role = @P3, multiplier = @P4 WHERE server_id = @P1 AND target_id = @P2 \
+            ELSE \
+                INSERT INTO [Ranking].[Multiplier] (server_id, target_id, is_role, multiplier) VALUES (@P1, @P2, @P3, @P4);",
+            &[&server, &target_id, &target.is_role(), &multiplier])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn clear_multipliers(&self, server_id: GuildId, target: Option<MultiplierTarget>) -> Result<usize, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let target_id = target.and_then(|o| Decimal::from_u64(o.id()));
+        let res = conn.execute(
+            "DELETE FROM [Ranking].[Multiplier] WHERE server_id = @P1 AND (@P2 IS NULL OR target_id = @P2)",
+            &[&server, &target_id])
+            .await?;
+
+        Ok(res.total() as usize)
+    }
+
+    async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let channel = Decimal::from_u64(*channel_id.as_u64()).unwrap();
+        let res = conn.query(
+            "EXEC [Ranking].[ToggleChannel] @serverid = @P1, @channelid = @P2",
+            &[&server, &channel])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn toggle_server_ranking(&self, server_id: GuildId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "UPDATE [Ranking].[Server] SET ranking_disabled = ~ranking_disabled WHERE id = @P1;\
+            SELECT ranking_disabled FROM [Ranking].[Server] WHERE id = @P1;",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn get_disablements(&self, server_id: GuildId, channel_id: ChannelId) -> Result<Disablements, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let channel = Decimal::from_u64(*channel_id.as_u64()).unwrap();
+
+        let res = conn.query(
+            "SELECT \
+                (SELECT CAST(1 AS BIT) FROM [Ranking].[DisabledChannel] WHERE server_id = @P1 AND channel_id = @P2), \
+                (SELECT ranking_disabled FROM [Ranking].[Server] WHERE id = @P1);",
+            &[&server, &channel])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: Disablements = Disablements {
+            channel: false,
+            guild: false
+        };
+
+        if let Some(item) = res {
+            let channel_disabled: Option<bool> = item.get(0);
+            let guild_disabled: Option<bool> = item.get(1);
+
+            out = Disablements {
+                channel: channel_disabled.unwrap_or_default(),
+                guild: guild_disabled.unwrap_or_default()
+            };
+        }
+
+        Ok(out)
+    }
+
+    async fn get_spam_settings(&self, server_id: GuildId) -> Result<SpamSettings, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT spam_min_length, spam_block_repeats, spam_junk_ratio, spam_burst_count, spam_burst_seconds, spam_burst_multiplier \
+            FROM [Ranking].[Server] WHERE id = @P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out = SpamSettings::new(*server_id.as_u64());
+
+        if let Some(row) = res {
+            out = SpamSettings {
+                server_id: *server_id.as_u64(),
+                min_length: row.get(0).unwrap(),
+                block_repeats: row.get(1).unwrap(),
+                max_junk_ratio: row.get(2).unwrap(),
+                burst_count: row.get(3).unwrap(),
+                burst_seconds: row.get(4).unwrap(),
+                burst_multiplier: row.get(5).unwrap()
+            };
+        }
+
+        Ok(out)
+    }
+
+    async fn set_spam_settings(&self, settings: &SpamSettings) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(settings.server_id).unwrap();
+        conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET spam_min_length = @P2, spam_block_repeats = @P3, spam_junk_ratio = @P4, \
+            spam_burst_count = @P5, spam_burst_seconds = @P6, spam_burst_multiplier = @P7 WHERE id = @P1",
+            &[&server, &settings.min_length, &settings.block_repeats, &settings.max_junk_ratio,
+                &settings.burst_count, &settings.burst_seconds, &settings.burst_multiplier])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn record_spam(&self, server_id: GuildId, user_id: UserId, reason: SpamReason) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let column = spam_column(reason);
+        conn.execute(
+            format!("IF NOT EXISTS (SELECT 1 FROM [Ranking].[Spam] WHERE server_id = @P1 AND [user_id] = @P2) \
+                INSERT INTO [Ranking].[Spam] (server_id, [user_id], last_flagged) VALUES (@P1, @P2, SYSUTCDATETIME()); \
+            UPDATE [Ranking].[Spam] SET {column} = {column} + 1, last_flagged = SYSUTCDATETIME() WHERE server_id = @P1 AND [user_id] = @P2"),
+            &[&server, &user])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_spam_stats(&self, server_id: GuildId, user_id: UserId) -> Result<SpamStats, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT short_count, repeat_count, junk_count, burst_count, last_flagged FROM [Ranking].[Spam] WHERE server_id = @P1 AND [user_id] = @P2",
+            &[&server, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.map(|row| {
+            let (short, repeat, junk, burst): (i32, i32, i32, i32) = (row.get(0).unwrap(), row.get(1).unwrap(), row.get(2).unwrap(), row.get(3).unwrap());
+            let last_flagged: NaiveDateTime = row.get(4).unwrap();
+            SpamStats {
+                short: short as i64,
+                repeat: repeat as i64,
+                junk: junk as i64,
+                burst: burst as i64,
+                last_flagged: Some(Utc.from_utc_datetime(&last_flagged))
+            }
+        }).unwrap_or_default())
+    }
+
+    async fn get_decay_settings(&self, server_id: GuildId) -> Result<DecaySettings, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT decay_after_days, decay_percent, decay_demote, decay_hide_departed FROM [Ranking].[Server] WHERE id = @P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out = DecaySettings::new(*server_id.as_u64());
+
+        if let Some(row) = res {
+            out = DecaySettings {
+                server_id: *server_id.as_u64(),
+                after_days: row.get(0).unwrap(),
+                percent: row.get(1).unwrap(),
+                demote: row.get(2).unwrap(),
+                hide_departed: row.get(3).unwrap()
+            };
+        }
+
+        Ok(out)
+    }
+
+    async fn set_decay_settings(&self, settings: &DecaySettings) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(settings.server_id).unwrap();
+        conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET decay_after_days = @P2, decay_percent = @P3, decay_demote = @P4, decay_hide_departed = @P5 WHERE id = @P1",
+            &[&server, &settings.after_days, &settings.percent, &settings.demote, &settings.hide_departed])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_decaying_servers(&self) -> Result<Vec<DecaySettings>, Error> {
+        let mut conn = self.pool.get().await?;
+        let res = conn.query(
+            "SELECT id, decay_after_days, decay_percent, decay_demote, decay_hide_departed FROM [Ranking].[Server] \
+            WHERE decay_after_days > 0 AND decay_percent > 0",
+            &[])
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.into_iter()
+            .map(|row| {
+                let id: Decimal = row.get(0).unwrap();
+                DecaySettings {
+                    server_id: id.to_u64().unwrap(),
+                    after_days: row.get(1).unwrap(),
+                    percent: row.get(2).unwrap(),
+                    demote: row.get(3).unwrap(),
+                    hide_departed: row.get(4).unwrap()
+                }
+            })
+            .collect())
+    }
+
+    async fn get_decay_candidates(&self, server_id: GuildId, inactive_since: DateTime<Utc>, decayed_since: DateTime<Utc>) -> Result<Vec<Member>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let (inactive_since, decayed_since) = (inactive_since.naive_utc(), decayed_since.naive_utc());
+        // Whichever's later out of their last message and their last logged gain (which covers voice XP).
+        let res = conn.query(
+            "SELECT [user_id], level, xp FROM ( \
+                SELECT l.[user_id], l.level, l.xp, l.decayed_at, (SELECT MAX(v) FROM (VALUES (l.last_message), \
+                    ((SELECT MAX(g.gained_at) FROM [Ranking].[XpLog] g WHERE g.server_id = l.server_id AND g.[user_id] = l.[user_id]))) t(v)) AS last_active \
+                FROM [Ranking].[Level] l WHERE l.server_id = @P1 AND (l.level > 0 OR l.xp > 0)) mukyu \
+            WHERE last_active < @P2 AND (decayed_at IS NULL OR decayed_at < @P3)",
+            &[&server, &inactive_since, &decayed_since])
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.into_iter()
+            .map(|row| {
+                let id: Decimal = row.get(0).unwrap();
+                Member {
+                    id: UserId::from(id.to_u64().unwrap()),
+                    exp: Experience {
+                        level: row.get(1).unwrap(),
+                        xp: row.get(2).unwrap()
+                    }
+                }
+            })
+            .collect())
+    }
+
+    async fn apply_decay(&self, server_id: GuildId, members: &[Member]) -> Result<usize, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+
+        conn.simple_query("BEGIN TRANSACTION").await?.into_results().await?;
+
+        for member in members {
+            let user = Decimal::from_u64(*member.id.as_u64()).unwrap();
+            let res = conn.execute(
+                "UPDATE [Ranking].[Level] SET level = @P3, xp = @P4, decayed_at = SYSUTCDATETIME() WHERE server_id = @P1 AND [user_id] = @P2",
+                &[&server, &user, &member.exp.level, &member.exp.xp])
+                .await;
+            if let Err(ex) = res {
+                conn.simple_query("ROLLBACK TRANSACTION").await?.into_results().await?;
+                return Err(ex.into());
+            }
+        }
+
+        conn.simple_query("COMMIT TRANSACTION").await?.into_results().await?;
+
+        Ok(members.len())
+    }
+
+    async fn set_departed(&self, server_id: GuildId, user_id: UserId, departed: bool) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        conn.execute(
+            "UPDATE [Ranking].[Level] SET departed = @P3 WHERE server_id = @P1 AND [user_id] = @P2",
+            &[&server, &user, &departed])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_departed_hiding_servers(&self) -> Result<Vec<GuildId>, Error> {
+        let mut conn = self.pool.get().await?;
+        let res = conn.simple_query("SELECT id FROM [Ranking].[Server] WHERE decay_hide_departed = 1")
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.into_iter()
+            .map(|row| {
+                let id: Decimal = row.get(0).unwrap();
+                GuildId::from(id.to_u64().unwrap())
+            })
+            .collect())
+    }
+
+    async fn reconcile_departed(&self, server_id: GuildId, present: &HashSet<UserId>) -> Result<usize, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let stored = conn.query(
+            "SELECT [user_id], departed FROM [Ranking].[Level] WHERE server_id = @P1",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?;
+
+        let changed = stored.into_iter()
+            .map(|row| {
+                let id: Decimal = row.get(0).unwrap();
+                let departed: bool = row.get(1).unwrap();
+                (UserId::from(id.to_u64().unwrap()), departed)
+            })
+            .filter(|(user, departed)| *departed == present.contains(user))
+            .collect::<Vec<_>>();
+
+        conn.simple_query("BEGIN TRANSACTION").await?.into_results().await?;
+
+        for (user, departed) in &changed {
+            let user = Decimal::from_u64(*user.as_u64()).unwrap();
+            let res = conn.execute(
+                "UPDATE [Ranking].[Level] SET departed = @P3 WHERE server_id = @P1 AND [user_id] = @P2",
+                &[&server, &user, &!departed])
+                .await;
+            if let Err(ex) = res {
+                conn.simple_query("ROLLBACK TRANSACTION").await?.into_results().await?;
+                return Err(ex.into());
+            }
+        }
+
+        conn.simple_query("COMMIT TRANSACTION").await?.into_results().await?;
+
+        Ok(changed.len())
+    }
+
+    async fn get_auto_fix_settings(&self, server_id: GuildId) -> Result<AutoFixSettings, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            format!("SELECT {AUTO_FIX_COLUMNS} FROM [Ranking].[Server] WHERE id = @P1"),
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.map(|row| auto_fix_from_row(&row)).unwrap_or_else(|| AutoFixSettings::new(*server_id.as_u64())))
+    }
+
+    async fn set_auto_fix_settings(&self, settings: &AutoFixSettings) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(settings.server_id).unwrap();
+        let channel = settings.log_channel.and_then(|o| Decimal::from_u64(o.0));
+        conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET autofix_hours = @P2, autofix_channel = @P3, autofix_multiple = @P4, \
+            autofix_remove = @P5, autofix_demote = @P6 WHERE id = @P1",
+            &[&server, &settings.every_hours, &channel, &settings.options.multiple, &settings.options.remove, &settings.options.demote])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_auto_fix_servers(&self) -> Result<Vec<AutoFixSettings>, Error> {
+        let mut conn = self.pool.get().await?;
+        let res = conn.query(
+            format!("SELECT {AUTO_FIX_COLUMNS} FROM [Ranking].[Server] WHERE autofix_hours > 0"),
+            &[])
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.iter().map(auto_fix_from_row).collect())
+    }
+
+    async fn set_auto_fix_run(&self, server_id: GuildId, ran_at: DateTime<Utc>) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let ran_at = ran_at.naive_utc();
+        conn.execute(
+            "UPDATE [Ranking].[Server] SET autofix_last_run = @P2 WHERE id = @P1",
+            &[&server, &ran_at])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_fix(&self, server_id: GuildId, changes: &[RoleChange], keep_since: DateTime<Utc>) -> Result<i32, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let keep_since = keep_since.naive_utc();
+
+        conn.simple_query("BEGIN TRANSACTION").await?.into_results().await?;
+
+        let res = save_fix_changes(&mut conn, server, changes, keep_since).await;
+        match res {
+            Ok(id) => {
+                conn.simple_query("COMMIT TRANSACTION").await?.into_results().await?;
+                Ok(id)
+            }
+            Err(ex) => {
+                // Same as import_levels: no half-saved change sets, and no connection stuck in a transaction.
+                conn.simple_query("ROLLBACK TRANSACTION").await?.into_results().await?;
+                Err(ex)
+            }
+        }
+    }
+
+    async fn get_fix(&self, server_id: GuildId, fix_id: Option<i32>, since: DateTime<Utc>) -> Result<Option<FixChangeSet>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let since = since.naive_utc();
+        let res = conn.query(
+            "SELECT TOP 1 id, created_at FROM [Ranking].[Fix] WHERE server_id = @P1 AND (@P2 IS NULL OR id = @P2) \
+            AND reverted_at IS NULL AND created_at >= @P3 ORDER BY created_at DESC, id DESC",
+            &[&server, &fix_id, &since])
+            .await?
+            .into_row()
+            .await?;
+
+        let (id, created_at): (i32, NaiveDateTime) = match res {
+            Some(row) => (row.get(0).unwrap(), row.get(1).unwrap()),
+            None => return Ok(None)
+        };
+
+        let changes = conn.query(
+            "SELECT [user_id], role_id, added FROM [Ranking].[FixChange] WHERE fix_id = @P1",
+            &[&id])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| {
+                let user: Decimal = row.get(0).unwrap();
+                let role: Decimal = row.get(1).unwrap();
+                RoleChange {
+                    user_id: UserId::from(user.to_u64().unwrap()),
+                    role_id: RoleId::from(role.to_u64().unwrap()),
+                    added: row.get(2).unwrap()
+                }
+            })
+            .collect();
+
+        Ok(Some(FixChangeSet {
+            id,
+            created_at: Utc.from_utc_datetime(&created_at),
+            changes
+        }))
+    }
+
+    async fn set_fix_reverted(&self, server_id: GuildId, fix_id: i32) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        conn.execute(
+            "UPDATE [Ranking].[Fix] SET reverted_at = SYSUTCDATETIME() WHERE server_id = @P1 AND id = @P2",
+            &[&server, &fix_id])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_announce_settings(&self, server_id: GuildId) -> Result<AnnounceSettings, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT announce_target, announce_channel, announce_template, announce_every, announce_roles_only FROM [Ranking].[Server] WHERE id = @P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out = AnnounceSettings::new(*server_id.as_u64());
+
+        if let Some(row) = res {
+            let target: u8 = row.get(0).unwrap();
+            let channel: Option<rust_decimal::Decimal> = row.get(1);
+            let template: Option<&str> = row.get(2);
+
+            out = AnnounceSettings {
+                server_id: *server_id.as_u64(),
+                target: AnnounceTarget::try_from(target).unwrap_or(AnnounceTarget::Here),
+                channel_id: channel.and_then(|o| o.to_u64()).map(ChannelId::from),
+                template: template.map(|o| o.to_string()),
+                every: row.get(3).unwrap(),
+                roles_only: row.get(4).unwrap()
+            };
+        }
+
+        Ok(out)
+    }
+
+    async fn set_announce_settings(&self, settings: &AnnounceSettings) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(settings.server_id).unwrap();
+        let target = settings.target as u8;
+        let channel = settings.channel_id.and_then(|o| Decimal::from_u64(o.0));
+        conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET announce_target = @P2, announce_channel = @P3, announce_template = @P4, \
+            announce_every = @P5, announce_roles_only = @P6 WHERE id = @P1",
+            &[&server, &target, &channel, &settings.template.as_deref(), &settings.every, &settings.roles_only])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn toggle_rank_embed(&self, server_id: GuildId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET rank_embed = ~rank_embed WHERE id = @P1; \
+            SELECT rank_embed FROM [Ranking].[Server] WHERE id = @P1;",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn get_rank_embed(&self, server_id: GuildId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT rank_embed FROM [Ranking].[Server] WHERE id = @P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn top_members(&self, server_id: GuildId, page: i32) -> Result<MemberPagination, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        const ROWS_FETCHED: i32 = 10;
+        let mut offset = page * ROWS_FETCHED;
+        offset = offset.max(0);
+        let res = conn.query(
+            format!("SELECT user_id, level, xp FROM [Ranking].[Level] WHERE server_id = @P1 AND user_id NOT IN ({HIDDEN_MEMBERS}) \
+                ORDER BY level DESC, xp DESC OFFSET @P2 ROWS FETCH NEXT @P3 ROWS ONLY; \
+            SELECT COUNT(1) FROM [Ranking].[Level] WHERE server_id = @P1 AND user_id NOT IN ({HIDDEN_MEMBERS})"),
+            &[&server, &offset, &ROWS_FETCHED])
+            .await?
+            .into_results()
+            .await?;
+
+        let count: i32 = res.get(1).unwrap().get(0).unwrap().get(0).unwrap();
+
+        let members = res.get(0).unwrap().iter()
+            .map(|row| {
+                let id: rust_decimal::Decimal = row.get(0).unwrap();
+                Member {
+                    id: UserId::from(id.to_u64().unwrap()),
+                    exp: Experience {
+                        level: row.get(1).unwrap(),
+                        xp: row.get(2).unwrap()
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let pages = (count / ROWS_FETCHED) + ((count % ROWS_FETCHED != 0) as i32); // Divide, then round if not perfect division
+
+        Ok(MemberPagination {
+            members,
+            current_page: page,
+            last_page: pages
+        })
+    }
+
+    async fn rank_within_members(&self, server_id: GuildId, user_id: UserId) -> Result<Option<i64>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let res = conn.query(
+            format!("SELECT row_number FROM (SELECT user_id, ROW_NUMBER() OVER (ORDER BY level DESC, xp DESC) AS row_number FROM [Ranking].[Level] \
+                WHERE server_id = @P1 AND user_id NOT IN ({HIDDEN_MEMBERS})) mukyu WHERE user_id = @P2"),
+            &[&server, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: Option<i64> = None;
+
+        if let Some(item) = res {
+            // Apparently it's an i64. Cool.
+            out = item.get(0);
+        }
+
+        Ok(out)
+    }
+
+    async fn top_members_in(&self, server_id: GuildId, window: XpWindow, page: i32) -> Result<WindowPagination, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let (season, from, to) = window_params(window);
+        const ROWS_FETCHED: i32 = 10;
+        let offset = (page * ROWS_FETCHED).max(0);
+        let res = conn.query(
+            format!("{WINDOW_TOTALS}SELECT [user_id], xp FROM totals ORDER BY xp DESC, [user_id] OFFSET @P5 ROWS FETCH NEXT @P6 ROWS ONLY; \
+            {WINDOW_TOTALS}SELECT COUNT(1) FROM totals"),
+            &[&server, &season, &from, &to, &offset, &ROWS_FETCHED])
+            .await?
+            .into_results()
+            .await?;
+
+        let count: i32 = res.get(1).unwrap().get(0).unwrap().get(0).unwrap();
+
+        let members = res.get(0).unwrap().iter()
+            .map(|row| {
+                let id: Decimal = row.get(0).unwrap();
+                WindowMember {
+                    id: UserId::from(id.to_u64().unwrap()),
+                    xp: row.get(1).unwrap()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let pages = (count / ROWS_FETCHED) + ((count % ROWS_FETCHED != 0) as i32);
+
+        Ok(WindowPagination {
+            members,
+            current_page: page,
+            last_page: pages
+        })
+    }
+
+    async fn rank_within_window(&self, server_id: GuildId, user_id: UserId, window: XpWindow) -> Result<Option<i64>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let user = Decimal::from_u64(*user_id.as_u64()).unwrap();
+        let (season, from, to) = window_params(window);
+        let res = conn.query(
+            format!("{WINDOW_TOTALS}SELECT row_number FROM (SELECT [user_id], ROW_NUMBER() OVER (ORDER BY xp DESC, [user_id]) AS row_number FROM totals) mukyu WHERE [user_id] = @P5"),
+            &[&server, &season, &from, &to, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.and_then(|row| row.get(0)))
+    }
+
+    async fn get_seasons(&self, server_id: GuildId) -> Result<Vec<Season>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT id, server_id, name, started_at, ended_at FROM [Ranking].[Season] WHERE server_id = @P1 ORDER BY started_at DESC",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?;
+
+        Ok(res.iter().map(season_from_row).collect())
+    }
+
+    async fn start_season(&self, server_id: GuildId, name: &str) -> Result<Season, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "INSERT INTO [Ranking].[Season] (server_id, name) \
+            OUTPUT inserted.id, inserted.server_id, inserted.name, inserted.started_at, inserted.ended_at \
+            VALUES (@P1, @P2)",
+            &[&server, &name])
+            .await?
+            .into_row()
+            .await?;
+
+        res.as_ref().map(season_from_row).ok_or_else(|| "The season wasn't created".into())
+    }
+
+    async fn end_season(&self, server_id: GuildId) -> Result<Option<Season>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SET XACT_ABORT ON; \
+            BEGIN TRANSACTION; \
+            DECLARE @ended TABLE (id INT, server_id DECIMAL(20, 0), name NVARCHAR(100), started_at DATETIME2, ended_at DATETIME2); \
+            UPDATE [Ranking].[Season] SET ended_at = SYSUTCDATETIME() \
+            OUTPUT inserted.id, inserted.server_id, inserted.name, inserted.started_at, inserted.ended_at INTO @ended \
+            WHERE server_id = @P1 AND ended_at IS NULL; \
+            INSERT INTO [Ranking].[SeasonStanding] (season_id, [user_id], xp) \
+            SELECT e.id, l.[user_id], SUM(CAST(l.xp AS BIGINT)) FROM @ended e \
+            JOIN [Ranking].[XpLog] l ON l.server_id = e.server_id AND l.gained_at >= e.started_at AND l.gained_at <= e.ended_at \
+            GROUP BY e.id, l.[user_id] HAVING SUM(l.xp) > 0; \
+            COMMIT TRANSACTION; \
+            SELECT id, server_id, name, started_at, ended_at FROM @ended;",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        Ok(res.as_ref().map(season_from_row))
+    }
+
+    async fn prune_xp_log(&self, before: DateTime<Utc>) -> Result<u64, Error> {
+        let mut conn = self.pool.get().await?;
+        let before = before.naive_utc();
+        let res = conn.execute(
+            "DELETE l FROM [Ranking].[XpLog] l WHERE l.gained_at < @P1 AND NOT EXISTS ( \
+                SELECT 1 FROM [Ranking].[Season] s WHERE s.server_id = l.server_id AND s.ended_at IS NULL AND l.gained_at >= s.started_at)",
+            &[&before])
+            .await?;
+
+        Ok(res.total())
+    }
+
+    async fn get_roles(&self, server_id: GuildId) -> Result<Vec<Rank>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT role_name, role_id, min_level FROM [Ranking].[Role] WHERE server_id = @P1 ORDER BY min_level ASC",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| {
+                let name: &str = row.get(0).unwrap();
+                let mut id: Option<RoleId> = None;
+                if let Some(row) = row.get(1) {
+                    let id_dec: rust_decimal::Decimal = row;
+                    id = id_dec.to_u64().and_then(|u| Option::from(RoleId::from(u)));
+                }
+
+                Rank {
+                    name: name.to_string(),
+                    role_id: id,
+                    min_level: row.get(2).unwrap()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(res)
+    }
+
+    async fn get_role_mode(&self, server_id: GuildId) -> Result<RoleMode, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT role_mode FROM [Ranking].[Server] WHERE id = @P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mode: Option<u8> = res.and_then(|row| row.get(0));
+        Ok(mode.and_then(|o| RoleMode::try_from(o).ok()).unwrap_or(RoleMode::Replace))
+    }
+
+    async fn set_role_mode(&self, server_id: GuildId, mode: RoleMode) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET role_mode = @P2 WHERE id = @P1",
+            &[&server, &(mode as u8)])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn add_role(&self, server_id: GuildId, role_name: &str, role_id: RoleId, min_level: i32) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let role = Decimal::from_u64(*role_id.as_u64()).unwrap();
+        let res = conn.query(
+            "EXEC [Ranking].[AddRole] @server_id = @P1, @role_name = @P2, @role_id = @P3, @min_level = @P4",
+            &[&server, &role_name, &role, &Decimal::from_i32(min_level).unwrap()])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn remove_role(&self, server_id: GuildId, role_id: RoleId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let role = Decimal::from_u64(*role_id.as_u64()).unwrap();
+        let res = conn.query(
+            "EXEC [Ranking].[RemoveRole] @serverid = @P1, @roleid = @P2",
+            &[&server, &role])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let timeout = Decimal::from_i32(timeout).unwrap();
+        let res = conn.query(
+            "EXEC [Ranking].[SetServerTimeout] @serverid = @P1, @timeout = @P2",
+            &[&server, &timeout])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn get_timeout(&self, server_id: GuildId) -> Result<i32, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT TOP 1 timeout FROM [Ranking].[Server] WHERE id=@P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: i32 = -1;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn get_voice_settings(&self, server_id: GuildId) -> Result<VoiceSettings, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "SELECT voice_xp, voice_daily_cap FROM [Ranking].[Server] WHERE id = @P1; \
+            SELECT channel_id FROM [Ranking].[VoiceChannel] WHERE server_id = @P1",
+            &[&server])
+            .await?
+            .into_results()
+            .await?;
+
+        let mut out = VoiceSettings::new(*server_id.as_u64());
+
+        if let Some(row) = res.first().and_then(|o| o.first()) {
+            out.xp_per_minute = row.get(0).unwrap();
+            out.daily_cap = row.get(1).unwrap();
+        }
+
+        if let Some(rows) = res.get(1) {
+            out.channels = rows.iter()
+                .filter_map(|row| row.get(0).and_then(|u: rust_decimal::Decimal| u.to_u64()).map(ChannelId::from))
+                .collect();
+        }
+
+        Ok(out)
+    }
+
+    async fn set_voice_settings(&self, server_id: GuildId, xp_per_minute: i32, daily_cap: i32) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        conn.execute(
+            "IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @P1) INSERT INTO [Ranking].[Server] (id) VALUES (@P1); \
+            UPDATE [Ranking].[Server] SET voice_xp = @P2, voice_daily_cap = @P3 WHERE id = @P1",
+            &[&server, &xp_per_minute, &daily_cap])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn toggle_voice_channel(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let channel = Decimal::from_u64(*channel_id.as_u64()).unwrap();
+        let res = conn.query(
+            "IF EXISTS (SELECT 1 FROM [Ranking].[VoiceChannel] WHERE server_id = @P1 AND channel_id = @P2) \
+            BEGIN \
+                DELETE FROM [Ranking].[VoiceChannel] WHERE server_id = @P1 AND channel_id = @P2; \
+                SELECT CAST(0 AS BIT); \
+            END \
+            ELSE \
+            BEGIN \
+                INSERT INTO [Ranking].[VoiceChannel] (server_id, channel_id) VALUES (@P1, @P2); \
+                SELECT CAST(1 AS BIT); \
+            END",
+            &[&server, &channel])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = item.get(0).unwrap();
+        }
+
+        Ok(out)
+    }
+
+    async fn get_users(&self, server_id: GuildId) -> Result<Vec<FullMember>, Error> {
+        let mut conn = self.pool.get().await?;
+        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let res = conn.query(
+            "EXEC [Ranking].[GetAllUsers] @serverid = @P1",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| {
+                let id: UserId = row.get(0).and_then(|u: rust_decimal::Decimal| u.to_u64()).map(UserId::from).unwrap();
+                let role_id: Option<RoleId> = row.get(3).and_then(|u: rust_decimal::Decimal| u.to_u64()).map(RoleId::from);
+                FullMember {
+                    user: id,
+                    exp: Experience {
+                        level: row.get(1).unwrap(),
+                        xp: row.get(2).unwrap()
+                    },
+                    role_id
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(res)
+    }
+}
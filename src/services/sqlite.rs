@@ -0,0 +1,1208 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use tracing::info;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serenity::model::id::{
+    UserId,
+    GuildId,
+    ChannelId, RoleId
+};
+use crate::Error;
+use crate::models::db_models::*;
+use crate::services::database::RankingStorage;
+use crate::services::migrations::{SQLITE_MIGRATIONS, pending};
+
+pub struct SqliteDatabase {
+    conn: Arc<Mutex<Connection>>
+}
+
+impl SqliteDatabase {
+    pub async fn new(path: &str) -> Result<Self, Error> {
+        let path = path.to_string();
+        let conn = tokio::task::spawn_blocking(move || Connection::open(path)).await??;
+
+        Ok(SqliteDatabase { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    // PRAGMA user_version keeps track of how many migrations we've run.
+    pub async fn migrate(&self) -> Result<(), Error> {
+        let version: usize = self.call(|conn| conn.query_row("PRAGMA user_version", [], |row| row.get(0))).await?;
+
+        for (version, migration) in pending(version, SQLITE_MIGRATIONS)? {
+            info!("Applying SQLite migration {}: {}", version, migration.name);
+            self.call(move |conn| {
+                let tx = conn.transaction()?;
+                tx.execute_batch(migration.sql)?;
+                // PRAGMA doesn't take parameters, but this is our own number anyways.
+                tx.execute_batch(&format!("PRAGMA user_version = {version}"))?;
+                tx.commit()
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    // rusqlite is blocking, so every query gets shoved onto Tokio's blocking pool.
+    pub(crate) async fn call<T, F>(&self, f: F) -> Result<T, Error>
+        where T: Send + 'static, F: FnOnce(&mut Connection) -> Result<T, rusqlite::Error> + Send + 'static {
+        let conn = self.conn.clone();
+        let out = tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().expect("SQLite connection was poisoned");
+            f(&mut conn)
+        }).await??;
+
+        Ok(out)
+    }
+}
+
+// SQLite only has signed 64-bit integers, but snowflakes never use the top bit.
+pub(crate) fn to_sql_id(id: u64) -> i64 {
+    id as i64
+}
+
+pub(crate) fn from_sql_id(id: i64) -> u64 {
+    id as u64
+}
+
+fn highest_role(conn: &Connection, server: i64, level: i32) -> Result<Option<u64>, rusqlite::Error> {
+    let role: Option<Option<i64>> = conn.query_row(
+        "SELECT role_id FROM ranking_role WHERE server_id = ?1 AND min_level <= ?2 ORDER BY min_level DESC LIMIT 1",
+        params![server, level],
+        |row| row.get(0))
+        .optional()?;
+
+    Ok(role.flatten().map(from_sql_id))
+}
+
+// Same shape as what Ranking.ProvideExp used to hand back: a level of -1 if nothing happened, and ranks only if they changed.
+fn level_result(conn: &Connection, server: i64, old_level: i32, level: i32) -> Result<LevelUp, rusqlite::Error> {
+    let mut out = LevelUp { level: -1, old_level, old_rank: None, new_rank: None };
+
+    if level != old_level {
+        let old_rank = highest_role(conn, server, old_level)?;
+        let new_rank = highest_role(conn, server, level)?;
+        out.level = level;
+        if old_rank != new_rank {
+            out.old_rank = old_rank;
+            out.new_rank = new_rank;
+        }
+    }
+
+    Ok(out)
+}
+
+fn log_xp(conn: &Connection, server: i64, user: i64, xp: i32, now: i64) -> Result<(), rusqlite::Error> {
+    if xp > 0 {
+        conn.execute(
+            "INSERT INTO ranking_xp_log (server_id, user_id, xp, gained_at) VALUES (?1, ?2, ?3, ?4)",
+            params![server, user, xp, now])?;
+    }
+
+    Ok(())
+}
+
+// Members who left are kept off the leaderboards if the server says so; ?1 is always the server.
+const HIDDEN_MEMBERS: &str = "SELECT user_id FROM ranking_level WHERE server_id = ?1 AND departed = 1 \
+    AND EXISTS (SELECT 1 FROM ranking_server WHERE id = ?1 AND decay_hide_departed = 1)";
+
+// Per-user totals for an XpWindow: ?1 is the server, then whatever XpWindow::bounds gives back (with an end of i64::MAX if there isn't one).
+// `hidden` is HIDDEN_MEMBERS again; it has to be spelled out to live in the same WITH.
+const WINDOW_TOTALS: &str = "WITH hidden AS (SELECT user_id FROM ranking_level WHERE server_id = ?1 AND departed = 1 \
+    AND EXISTS (SELECT 1 FROM ranking_server WHERE id = ?1 AND decay_hide_departed = 1)), \
+    totals AS ( \
+    SELECT user_id, SUM(xp) AS xp FROM ranking_xp_log \
+    WHERE ?2 < 0 AND server_id = ?1 AND gained_at >= ?3 AND gained_at < ?4 AND user_id NOT IN hidden \
+    GROUP BY user_id HAVING SUM(xp) > 0 \
+    UNION ALL \
+    SELECT user_id, xp FROM ranking_season_standing WHERE season_id = ?2 AND user_id NOT IN hidden) ";
+
+// Only ever one of our own column names, so it's fine to format into the query.
+fn spam_column(reason: SpamReason) -> &'static str {
+    match reason {
+        SpamReason::Short => "short_count",
+        SpamReason::Repeat => "repeat_count",
+        SpamReason::Junk => "junk_count",
+        SpamReason::Burst => "burst_count"
+    }
+}
+
+fn window_params(window: XpWindow) -> (i32, i64, i64) {
+    let (season, from, to) = window.bounds();
+    (season, from.timestamp_millis(), to.map(|o| o.timestamp_millis()).unwrap_or(i64::MAX))
+}
+
+fn season_from_row(row: &rusqlite::Row) -> Result<Season, rusqlite::Error> {
+    let started_at: i64 = row.get(3)?;
+    let ended_at: Option<i64> = row.get(4)?;
+
+    Ok(Season {
+        id: row.get(0)?,
+        server_id: from_sql_id(row.get(1)?),
+        name: row.get(2)?,
+        started_at: chrono::Utc.timestamp_millis_opt(started_at).unwrap(),
+        ended_at: ended_at.map(|o| chrono::Utc.timestamp_millis_opt(o).unwrap())
+    })
+}
+
+const AUTO_FIX_COLUMNS: &str = "id, autofix_hours, autofix_channel, autofix_multiple, autofix_remove, autofix_demote, autofix_last_run";
+
+fn auto_fix_from_row(row: &rusqlite::Row) -> Result<AutoFixSettings, rusqlite::Error> {
+    let channel: Option<i64> = row.get(2)?;
+    let last_run: Option<i64> = row.get(6)?;
+
+    Ok(AutoFixSettings {
+        server_id: from_sql_id(row.get(0)?),
+        every_hours: row.get(1)?,
+        log_channel: channel.map(|o| ChannelId::from(from_sql_id(o))),
+        options: FixOptions {
+            multiple: row.get(3)?,
+            remove: row.get(4)?,
+            demote: row.get(5)?
+        },
+        last_run: last_run.and_then(|o| chrono::Utc.timestamp_millis_opt(o).single())
+    })
+}
+
+#[async_trait]
+impl RankingStorage for SqliteDatabase {
+    async fn provide_exp(&self, server_id: GuildId, user_id: UserId, gained: i32, settings: &XpSettings) -> Result<LevelUp, Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+        let now = chrono::Utc::now().timestamp_millis();
+        let settings = settings.clone();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("INSERT OR IGNORE INTO ranking_server (id) VALUES (?1)", params![server])?;
+            let timeout: i64 = tx.query_row("SELECT timeout FROM ranking_server WHERE id = ?1", params![server], |row| row.get(0))?;
+
+            let existing: Option<(i32, i32, Option<i64>)> = tx.query_row(
+                "SELECT level, xp, last_message FROM ranking_level WHERE server_id = ?1 AND user_id = ?2",
+                params![server, user],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .optional()?;
+            let (old_level, xp, last_message) = existing.unwrap_or((0, 0, None));
+
+            if let Some(last_message) = last_message {
+                if now - last_message < timeout {
+                    return Ok(LevelUp { level: -1, old_level, old_rank: None, new_rank: None });
+                }
+            }
+
+            let (level, xp) = settings.level_up(old_level, xp.saturating_add(gained));
+            log_xp(&tx, server, user, gained, now)?;
+
+            tx.execute(
+                "INSERT INTO ranking_level (server_id, user_id, level, xp, last_message) VALUES (?1, ?2, ?3, ?4, ?5) \
+                ON CONFLICT (server_id, user_id) DO UPDATE SET level = excluded.level, xp = excluded.xp, last_message = excluded.last_message",
+                params![server, user, level, xp, now])?;
+
+            let out = level_result(&tx, server, old_level, level)?;
+            tx.commit()?;
+            Ok(out)
+        }).await
+    }
+
+    async fn is_on_cooldown(&self, server_id: GuildId, user_id: UserId) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        self.call(move |conn| {
+            let res: Option<(Option<i64>, i64)> = conn.query_row(
+                "SELECT l.last_message, s.timeout FROM ranking_level l JOIN ranking_server s ON s.id = l.server_id \
+                WHERE l.server_id = ?1 AND l.user_id = ?2",
+                params![server, user],
+                |row| Ok((row.get(0)?, row.get(1)?)))
+                .optional()?;
+
+            Ok(matches!(res, Some((Some(last_message), timeout)) if now - last_message < timeout))
+        }).await
+    }
+
+    async fn provide_voice_exp(&self, server_id: GuildId, user_id: UserId, gained: i32, daily_cap: i32, settings: &XpSettings) -> Result<LevelUp, Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let settings = settings.clone();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("INSERT OR IGNORE INTO ranking_server (id) VALUES (?1)", params![server])?;
+            tx.execute("INSERT OR IGNORE INTO ranking_level (server_id, user_id) VALUES (?1, ?2)", params![server, user])?;
+
+            let (old_level, xp, earned): (i32, i32, i32) = tx.query_row(
+                "SELECT level, xp, CASE WHEN voice_day = ?3 THEN voice_xp ELSE 0 END FROM ranking_level WHERE server_id = ?1 AND user_id = ?2",
+                params![server, user, today],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+            let gained = if daily_cap > 0 { gained.min(daily_cap - earned) } else { gained };
+            if gained <= 0 {
+                return Ok(LevelUp { level: -1, old_level, old_rank: None, new_rank: None });
+            }
+
+            let (level, xp) = settings.level_up(old_level, xp.saturating_add(gained));
+            log_xp(&tx, server, user, gained, chrono::Utc::now().timestamp_millis())?;
+
+            tx.execute(
+                "UPDATE ranking_level SET level = ?3, xp = ?4, voice_day = ?5, voice_xp = ?6 WHERE server_id = ?1 AND user_id = ?2",
+                params![server, user, level, xp, today, earned + gained])?;
+
+            let out = level_result(&tx, server, old_level, level)?;
+            tx.commit()?;
+            Ok(out)
+        }).await
+    }
+
+    async fn get_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Experience, Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                "SELECT xp, level FROM ranking_level WHERE server_id = ?1 AND user_id = ?2",
+                params![server, user],
+                |row| Ok(Experience { xp: row.get(0)?, level: row.get(1)? }))
+                .optional()?;
+
+            Ok(res.unwrap_or_else(Experience::new))
+        }).await
+    }
+
+    async fn set_xp(&self, server_id: GuildId, user_id: UserId, level: i32, xp: i32) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_level (server_id, user_id, level, xp) VALUES (?1, ?2, ?3, ?4) \
+                ON CONFLICT (server_id, user_id) DO UPDATE SET level = excluded.level, xp = excluded.xp",
+                params![server, user, level, xp])?;
+            Ok(())
+        }).await
+    }
+
+    async fn import_levels(&self, server_id: GuildId, members: &[Member]) -> Result<usize, Error> {
+        let server = to_sql_id(server_id.0);
+        let members = members.iter().map(|o| (to_sql_id(o.id.0), o.exp.level, o.exp.xp)).collect::<Vec<_>>();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("INSERT OR IGNORE INTO ranking_server (id) VALUES (?1)", params![server])?;
+            {
+                let mut statement = tx.prepare(
+                    "INSERT INTO ranking_level (server_id, user_id, level, xp) VALUES (?1, ?2, ?3, ?4) \
+                    ON CONFLICT (server_id, user_id) DO UPDATE SET level = excluded.level, xp = excluded.xp")?;
+                for (user, level, xp) in &members {
+                    statement.execute(params![server, user, level, xp])?;
+                }
+            }
+            tx.commit()?;
+
+            Ok(members.len())
+        }).await
+    }
+
+    async fn get_highest_role(&self, server_id: GuildId, level: i32) -> Result<Option<RoleId>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            Ok(highest_role(conn, server, level)?.map(RoleId::from))
+        }).await
+    }
+
+    async fn get_xp_settings(&self, server_id: GuildId) -> Result<XpSettings, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                "SELECT min_xp, max_xp, xp_multiplier, level_curve, curve_base, curve_step, curve_growth, curve_table FROM ranking_server WHERE id = ?1",
+                params![server],
+                |row| {
+                    let curve: u8 = row.get(3)?;
+                    let table: Option<String> = row.get(7)?;
+                    Ok(XpSettings {
+                        server_id: server_id.0,
+                        min_xp: row.get(0)?,
+                        max_xp: row.get(1)?,
+                        multiplier: row.get(2)?,
+                        curve: LevelCurve::try_from(curve).unwrap_or(LevelCurve::Quadratic),
+                        curve_base: row.get(4)?,
+                        curve_step: row.get(5)?,
+                        curve_growth: row.get(6)?,
+                        curve_table: table.map(|o| XpSettings::table_from_string(&o)).unwrap_or_default()
+                    })
+                })
+                .optional()?;
+
+            Ok(res.unwrap_or_else(|| XpSettings::new(server_id.0)))
+        }).await
+    }
+
+    async fn set_xp_settings(&self, settings: &XpSettings) -> Result<(), Error> {
+        let server = to_sql_id(settings.server_id);
+        let min_xp = settings.min_xp;
+        let max_xp = settings.max_xp;
+        let multiplier = settings.multiplier;
+        let curve = settings.curve as u8;
+        let curve_base = settings.curve_base;
+        let curve_step = settings.curve_step;
+        let curve_growth = settings.curve_growth;
+        let curve_table = settings.table_to_string();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_server (id, min_xp, max_xp, xp_multiplier, level_curve, curve_base, curve_step, curve_growth, curve_table) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9) \
+                ON CONFLICT (id) DO UPDATE SET min_xp = excluded.min_xp, max_xp = excluded.max_xp, xp_multiplier = excluded.xp_multiplier, \
+                level_curve = excluded.level_curve, curve_base = excluded.curve_base, curve_step = excluded.curve_step, \
+                curve_growth = excluded.curve_growth, curve_table = excluded.curve_table",
+                params![server, min_xp, max_xp, multiplier, curve, curve_base, curve_step, curve_growth, curve_table])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_multipliers(&self, server_id: GuildId) -> Result<Vec<XpMultiplier>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare("SELECT target_id, is_role, multiplier FROM ranking_multiplier WHERE server_id = ?1 ORDER BY is_role, multiplier DESC")?;
+            let multipliers = statement
+                .query_map(params![server], |row| Ok(XpMultiplier {
+                    target: MultiplierTarget::from_id(from_sql_id(row.get(0)?), row.get(1)?),
+                    multiplier: row.get(2)?
+                }))?
+                .collect::<Result<Vec<_>, _>>();
+            multipliers
+        }).await
+    }
+
+    async fn set_multiplier(&self, server_id: GuildId, target: MultiplierTarget, multiplier: f64) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+        let target_id = to_sql_id(target.id());
+        let is_role = target.is_role();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_multiplier (server_id, target_id, is_role, multiplier) VALUES (?1, ?2, ?3, ?4) \
+                ON CONFLICT (server_id, target_id) DO UPDATE SET is_role = excluded.is_role, multiplier = excluded.multiplier",
+                params![server, target_id, is_role, multiplier])?;
+            Ok(())
+        }).await
+    }
+
+    async fn clear_multipliers(&self, server_id: GuildId, target: Option<MultiplierTarget>) -> Result<usize, Error> {
+        let server = to_sql_id(server_id.0);
+        let target_id = target.map(|o| to_sql_id(o.id()));
+
+        self.call(move |conn| {
+            conn.execute(
+                "DELETE FROM ranking_multiplier WHERE server_id = ?1 AND (?2 IS NULL OR target_id = ?2)",
+                params![server, target_id])
+        }).await
+    }
+
+    async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+        let channel = to_sql_id(channel_id.0);
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            let removed = tx.execute(
+                "DELETE FROM ranking_disabled_channel WHERE server_id = ?1 AND channel_id = ?2",
+                params![server, channel])?;
+
+            if removed == 0 {
+                tx.execute(
+                    "INSERT INTO ranking_disabled_channel (server_id, channel_id) VALUES (?1, ?2)",
+                    params![server, channel])?;
+            }
+
+            tx.commit()?;
+            Ok(removed == 0)
+        }).await
+    }
+
+    async fn toggle_server_ranking(&self, server_id: GuildId) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                "INSERT INTO ranking_server (id, ranking_disabled) VALUES (?1, 1) \
+                ON CONFLICT (id) DO UPDATE SET ranking_disabled = NOT ranking_disabled \
+                RETURNING ranking_disabled",
+                params![server],
+                |row| row.get(0))
+        }).await
+    }
+
+    async fn get_disablements(&self, server_id: GuildId, channel_id: ChannelId) -> Result<Disablements, Error> {
+        let server = to_sql_id(server_id.0);
+        let channel = to_sql_id(channel_id.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                "SELECT \
+                    EXISTS (SELECT 1 FROM ranking_disabled_channel WHERE server_id = ?1 AND channel_id = ?2), \
+                    COALESCE((SELECT ranking_disabled FROM ranking_server WHERE id = ?1), 0)",
+                params![server, channel],
+                |row| Ok(Disablements { channel: row.get(0)?, guild: row.get(1)? }))
+        }).await
+    }
+
+    async fn get_spam_settings(&self, server_id: GuildId) -> Result<SpamSettings, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                "SELECT spam_min_length, spam_block_repeats, spam_junk_ratio, spam_burst_count, spam_burst_seconds, spam_burst_multiplier \
+                FROM ranking_server WHERE id = ?1",
+                params![server],
+                |row| Ok(SpamSettings {
+                    server_id: server_id.0,
+                    min_length: row.get(0)?,
+                    block_repeats: row.get(1)?,
+                    max_junk_ratio: row.get(2)?,
+                    burst_count: row.get(3)?,
+                    burst_seconds: row.get(4)?,
+                    burst_multiplier: row.get(5)?
+                }))
+                .optional()?;
+
+            Ok(res.unwrap_or_else(|| SpamSettings::new(server_id.0)))
+        }).await
+    }
+
+    async fn set_spam_settings(&self, settings: &SpamSettings) -> Result<(), Error> {
+        let server = to_sql_id(settings.server_id);
+        let settings = settings.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_server (id, spam_min_length, spam_block_repeats, spam_junk_ratio, spam_burst_count, spam_burst_seconds, spam_burst_multiplier) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+                ON CONFLICT (id) DO UPDATE SET spam_min_length = excluded.spam_min_length, spam_block_repeats = excluded.spam_block_repeats, \
+                spam_junk_ratio = excluded.spam_junk_ratio, spam_burst_count = excluded.spam_burst_count, \
+                spam_burst_seconds = excluded.spam_burst_seconds, spam_burst_multiplier = excluded.spam_burst_multiplier",
+                params![server, settings.min_length, settings.block_repeats, settings.max_junk_ratio,
+                    settings.burst_count, settings.burst_seconds, settings.burst_multiplier])?;
+            Ok(())
+        }).await
+    }
+
+    async fn record_spam(&self, server_id: GuildId, user_id: UserId, reason: SpamReason) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+        let now = chrono::Utc::now().timestamp_millis();
+        let column = spam_column(reason);
+
+        self.call(move |conn| {
+            conn.execute(
+                &format!("INSERT INTO ranking_spam (server_id, user_id, {column}, last_flagged) VALUES (?1, ?2, 1, ?3) \
+                ON CONFLICT (server_id, user_id) DO UPDATE SET {column} = {column} + 1, last_flagged = excluded.last_flagged"),
+                params![server, user, now])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_spam_stats(&self, server_id: GuildId, user_id: UserId) -> Result<SpamStats, Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                "SELECT short_count, repeat_count, junk_count, burst_count, last_flagged FROM ranking_spam WHERE server_id = ?1 AND user_id = ?2",
+                params![server, user],
+                |row| {
+                    let last_flagged: i64 = row.get(4)?;
+                    Ok(SpamStats {
+                        short: row.get(0)?,
+                        repeat: row.get(1)?,
+                        junk: row.get(2)?,
+                        burst: row.get(3)?,
+                        last_flagged: chrono::Utc.timestamp_millis_opt(last_flagged).single()
+                    })
+                })
+                .optional()?;
+
+            Ok(res.unwrap_or_default())
+        }).await
+    }
+
+    async fn get_decay_settings(&self, server_id: GuildId) -> Result<DecaySettings, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                "SELECT decay_after_days, decay_percent, decay_demote, decay_hide_departed FROM ranking_server WHERE id = ?1",
+                params![server],
+                |row| Ok(DecaySettings {
+                    server_id: server_id.0,
+                    after_days: row.get(0)?,
+                    percent: row.get(1)?,
+                    demote: row.get(2)?,
+                    hide_departed: row.get(3)?
+                }))
+                .optional()?;
+
+            Ok(res.unwrap_or_else(|| DecaySettings::new(server_id.0)))
+        }).await
+    }
+
+    async fn set_decay_settings(&self, settings: &DecaySettings) -> Result<(), Error> {
+        let server = to_sql_id(settings.server_id);
+        let settings = settings.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_server (id, decay_after_days, decay_percent, decay_demote, decay_hide_departed) VALUES (?1, ?2, ?3, ?4, ?5) \
+                ON CONFLICT (id) DO UPDATE SET decay_after_days = excluded.decay_after_days, decay_percent = excluded.decay_percent, \
+                decay_demote = excluded.decay_demote, decay_hide_departed = excluded.decay_hide_departed",
+                params![server, settings.after_days, settings.percent, settings.demote, settings.hide_departed])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_decaying_servers(&self) -> Result<Vec<DecaySettings>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, decay_after_days, decay_percent, decay_demote, decay_hide_departed FROM ranking_server \
+                WHERE decay_after_days > 0 AND decay_percent > 0")?;
+            let servers = statement
+                .query_map([], |row| Ok(DecaySettings {
+                    server_id: from_sql_id(row.get(0)?),
+                    after_days: row.get(1)?,
+                    percent: row.get(2)?,
+                    demote: row.get(3)?,
+                    hide_departed: row.get(4)?
+                }))?
+                .collect::<Result<Vec<_>, _>>();
+            servers
+        }).await
+    }
+
+    async fn get_decay_candidates(&self, server_id: GuildId, inactive_since: DateTime<Utc>, decayed_since: DateTime<Utc>) -> Result<Vec<Member>, Error> {
+        let server = to_sql_id(server_id.0);
+        let (inactive_since, decayed_since) = (inactive_since.timestamp_millis(), decayed_since.timestamp_millis());
+
+        self.call(move |conn| {
+            // Whichever's later out of their last message and their last logged gain (which covers voice XP).
+            let mut statement = conn.prepare(
+                "SELECT user_id, level, xp FROM ( \
+                    SELECT l.user_id, l.level, l.xp, l.decayed_at, max(COALESCE(l.last_message, 0), \
+                        COALESCE((SELECT MAX(g.gained_at) FROM ranking_xp_log g WHERE g.server_id = l.server_id AND g.user_id = l.user_id), 0)) AS last_active \
+                    FROM ranking_level l WHERE l.server_id = ?1 AND (l.level > 0 OR l.xp > 0)) \
+                WHERE last_active > 0 AND last_active < ?2 AND (decayed_at IS NULL OR decayed_at < ?3)")?;
+            let members = statement
+                .query_map(params![server, inactive_since, decayed_since], |row| Ok(Member {
+                    id: UserId::from(from_sql_id(row.get(0)?)),
+                    exp: Experience {
+                        level: row.get(1)?,
+                        xp: row.get(2)?
+                    }
+                }))?
+                .collect::<Result<Vec<_>, _>>();
+            members
+        }).await
+    }
+
+    async fn apply_decay(&self, server_id: GuildId, members: &[Member]) -> Result<usize, Error> {
+        let server = to_sql_id(server_id.0);
+        let now = chrono::Utc::now().timestamp_millis();
+        let members = members.iter().map(|o| (to_sql_id(o.id.0), o.exp.level, o.exp.xp)).collect::<Vec<_>>();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut statement = tx.prepare(
+                    "UPDATE ranking_level SET level = ?3, xp = ?4, decayed_at = ?5 WHERE server_id = ?1 AND user_id = ?2")?;
+                for (user, level, xp) in &members {
+                    statement.execute(params![server, user, level, xp, now])?;
+                }
+            }
+            tx.commit()?;
+
+            Ok(members.len())
+        }).await
+    }
+
+    async fn set_departed(&self, server_id: GuildId, user_id: UserId, departed: bool) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+
+        self.call(move |conn| {
+            conn.execute(
+                "UPDATE ranking_level SET departed = ?3 WHERE server_id = ?1 AND user_id = ?2",
+                params![server, user, departed])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_departed_hiding_servers(&self) -> Result<Vec<GuildId>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare("SELECT id FROM ranking_server WHERE decay_hide_departed = 1")?;
+            let servers = statement
+                .query_map([], |row| Ok(GuildId::from(from_sql_id(row.get(0)?))))?
+                .collect::<Result<Vec<_>, _>>();
+            servers
+        }).await
+    }
+
+    async fn reconcile_departed(&self, server_id: GuildId, present: &HashSet<UserId>) -> Result<usize, Error> {
+        let server = to_sql_id(server_id.0);
+        let present = present.iter().map(|o| to_sql_id(o.0)).collect::<HashSet<_>>();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            let changed = {
+                let stored = tx.prepare("SELECT user_id, departed FROM ranking_level WHERE server_id = ?1")?
+                    .query_map(params![server], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, bool>(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let changed = stored.into_iter()
+                    .filter(|(user, departed)| *departed == present.contains(user))
+                    .collect::<Vec<_>>();
+
+                let mut statement = tx.prepare("UPDATE ranking_level SET departed = ?3 WHERE server_id = ?1 AND user_id = ?2")?;
+                for (user, departed) in &changed {
+                    statement.execute(params![server, user, !departed])?;
+                }
+                changed.len()
+            };
+            tx.commit()?;
+
+            Ok(changed)
+        }).await
+    }
+
+    async fn get_auto_fix_settings(&self, server_id: GuildId) -> Result<AutoFixSettings, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                &format!("SELECT {AUTO_FIX_COLUMNS} FROM ranking_server WHERE id = ?1"),
+                params![server],
+                auto_fix_from_row)
+                .optional()?;
+
+            Ok(res.unwrap_or_else(|| AutoFixSettings::new(server_id.0)))
+        }).await
+    }
+
+    async fn set_auto_fix_settings(&self, settings: &AutoFixSettings) -> Result<(), Error> {
+        let server = to_sql_id(settings.server_id);
+        let channel = settings.log_channel.map(|o| to_sql_id(o.0));
+        let (hours, options) = (settings.every_hours, settings.options);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_server (id, autofix_hours, autofix_channel, autofix_multiple, autofix_remove, autofix_demote) VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+                ON CONFLICT (id) DO UPDATE SET autofix_hours = excluded.autofix_hours, autofix_channel = excluded.autofix_channel, \
+                autofix_multiple = excluded.autofix_multiple, autofix_remove = excluded.autofix_remove, autofix_demote = excluded.autofix_demote",
+                params![server, hours, channel, options.multiple, options.remove, options.demote])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_auto_fix_servers(&self) -> Result<Vec<AutoFixSettings>, Error> {
+        self.call(move |conn| {
+            let mut statement = conn.prepare(&format!("SELECT {AUTO_FIX_COLUMNS} FROM ranking_server WHERE autofix_hours > 0"))?;
+            let servers = statement
+                .query_map([], auto_fix_from_row)?
+                .collect::<Result<Vec<_>, _>>();
+            servers
+        }).await
+    }
+
+    async fn set_auto_fix_run(&self, server_id: GuildId, ran_at: DateTime<Utc>) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+        let ran_at = ran_at.timestamp_millis();
+
+        self.call(move |conn| {
+            conn.execute("UPDATE ranking_server SET autofix_last_run = ?2 WHERE id = ?1", params![server, ran_at])?;
+            Ok(())
+        }).await
+    }
+
+    async fn save_fix(&self, server_id: GuildId, changes: &[RoleChange], keep_since: DateTime<Utc>) -> Result<i32, Error> {
+        let server = to_sql_id(server_id.0);
+        let now = chrono::Utc::now().timestamp_millis();
+        let keep_since = keep_since.timestamp_millis();
+        let changes = changes.iter().map(|o| (to_sql_id(o.user_id.0), to_sql_id(o.role_id.0), o.added)).collect::<Vec<_>>();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM ranking_fix_change WHERE fix_id IN (SELECT id FROM ranking_fix WHERE server_id = ?1 AND created_at < ?2)",
+                params![server, keep_since])?;
+            tx.execute("DELETE FROM ranking_fix WHERE server_id = ?1 AND created_at < ?2", params![server, keep_since])?;
+
+            let id: i32 = tx.query_row(
+                "INSERT INTO ranking_fix (server_id, created_at) VALUES (?1, ?2) RETURNING id",
+                params![server, now],
+                |row| row.get(0))?;
+            {
+                let mut statement = tx.prepare(
+                    "INSERT OR REPLACE INTO ranking_fix_change (fix_id, user_id, role_id, added) VALUES (?1, ?2, ?3, ?4)")?;
+                for (user, role, added) in &changes {
+                    statement.execute(params![id, user, role, added])?;
+                }
+            }
+            tx.commit()?;
+
+            Ok(id)
+        }).await
+    }
+
+    async fn get_fix(&self, server_id: GuildId, fix_id: Option<i32>, since: DateTime<Utc>) -> Result<Option<FixChangeSet>, Error> {
+        let server = to_sql_id(server_id.0);
+        let since = since.timestamp_millis();
+
+        self.call(move |conn| {
+            let fix = conn.query_row(
+                "SELECT id, created_at FROM ranking_fix WHERE server_id = ?1 AND (?2 IS NULL OR id = ?2) \
+                AND reverted_at IS NULL AND created_at >= ?3 ORDER BY created_at DESC, id DESC LIMIT 1",
+                params![server, fix_id, since],
+                |row| Ok((row.get::<_, i32>(0)?, row.get::<_, i64>(1)?)))
+                .optional()?;
+
+            let (id, created_at) = match fix {
+                Some(fix) => fix,
+                None => return Ok(None)
+            };
+
+            let mut statement = conn.prepare("SELECT user_id, role_id, added FROM ranking_fix_change WHERE fix_id = ?1")?;
+            let changes = statement
+                .query_map(params![id], |row| Ok(RoleChange {
+                    user_id: UserId::from(from_sql_id(row.get(0)?)),
+                    role_id: RoleId::from(from_sql_id(row.get(1)?)),
+                    added: row.get(2)?
+                }))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Some(FixChangeSet {
+                id,
+                created_at: chrono::Utc.timestamp_millis_opt(created_at).unwrap(),
+                changes
+            }))
+        }).await
+    }
+
+    async fn set_fix_reverted(&self, server_id: GuildId, fix_id: i32) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        self.call(move |conn| {
+            conn.execute("UPDATE ranking_fix SET reverted_at = ?3 WHERE server_id = ?1 AND id = ?2", params![server, fix_id, now])?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_announce_settings(&self, server_id: GuildId) -> Result<AnnounceSettings, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let res = conn.query_row(
+                "SELECT announce_target, announce_channel, announce_template, announce_every, announce_roles_only FROM ranking_server WHERE id = ?1",
+                params![server],
+                |row| {
+                    let target: u8 = row.get(0)?;
+                    let channel: Option<i64> = row.get(1)?;
+                    Ok(AnnounceSettings {
+                        server_id: server_id.0,
+                        target: AnnounceTarget::try_from(target).unwrap_or(AnnounceTarget::Here),
+                        channel_id: channel.map(|o| ChannelId::from(from_sql_id(o))),
+                        template: row.get(2)?,
+                        every: row.get(3)?,
+                        roles_only: row.get(4)?
+                    })
+                })
+                .optional()?;
+
+            Ok(res.unwrap_or_else(|| AnnounceSettings::new(server_id.0)))
+        }).await
+    }
+
+    async fn set_announce_settings(&self, settings: &AnnounceSettings) -> Result<(), Error> {
+        let server = to_sql_id(settings.server_id);
+        let target = settings.target as u8;
+        let channel = settings.channel_id.map(|o| to_sql_id(o.0));
+        let template = settings.template.clone();
+        let (every, roles_only) = (settings.every, settings.roles_only);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_server (id, announce_target, announce_channel, announce_template, announce_every, announce_roles_only) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+                ON CONFLICT (id) DO UPDATE SET announce_target = excluded.announce_target, announce_channel = excluded.announce_channel, \
+                announce_template = excluded.announce_template, announce_every = excluded.announce_every, announce_roles_only = excluded.announce_roles_only",
+                params![server, target, channel, template, every, roles_only])?;
+            Ok(())
+        }).await
+    }
+
+    async fn toggle_rank_embed(&self, server_id: GuildId) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                "INSERT INTO ranking_server (id, rank_embed) VALUES (?1, 1) \
+                ON CONFLICT (id) DO UPDATE SET rank_embed = NOT rank_embed \
+                RETURNING rank_embed",
+                params![server],
+                |row| row.get(0))
+        }).await
+    }
+
+    async fn get_rank_embed(&self, server_id: GuildId) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let embed: Option<bool> = conn.query_row("SELECT rank_embed FROM ranking_server WHERE id = ?1", params![server], |row| row.get(0))
+                .optional()?;
+            Ok(embed.unwrap_or_default())
+        }).await
+    }
+
+    async fn top_members(&self, server_id: GuildId, page: i32) -> Result<MemberPagination, Error> {
+        let server = to_sql_id(server_id.0);
+        const ROWS_FETCHED: i32 = 10;
+        let offset = (page * ROWS_FETCHED).max(0);
+
+        self.call(move |conn| {
+            let count: i32 = conn.query_row(
+                &format!("SELECT COUNT(1) FROM ranking_level WHERE server_id = ?1 AND user_id NOT IN ({HIDDEN_MEMBERS})"),
+                params![server],
+                |row| row.get(0))?;
+
+            let mut statement = conn.prepare(&format!(
+                "SELECT user_id, level, xp FROM ranking_level WHERE server_id = ?1 AND user_id NOT IN ({HIDDEN_MEMBERS}) \
+                ORDER BY level DESC, xp DESC LIMIT ?2 OFFSET ?3"))?;
+            let members = statement
+                .query_map(params![server, ROWS_FETCHED, offset], |row| Ok(Member {
+                    id: UserId::from(from_sql_id(row.get(0)?)),
+                    exp: Experience {
+                        level: row.get(1)?,
+                        xp: row.get(2)?
+                    }
+                }))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let pages = (count / ROWS_FETCHED) + ((count % ROWS_FETCHED != 0) as i32); // Divide, then round if not perfect division
+
+            Ok(MemberPagination {
+                members,
+                current_page: page,
+                last_page: pages
+            })
+        }).await
+    }
+
+    async fn rank_within_members(&self, server_id: GuildId, user_id: UserId) -> Result<Option<i64>, Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+
+        self.call(move |conn| {
+            conn.query_row(
+                &format!("SELECT row_number FROM (SELECT user_id, ROW_NUMBER() OVER (ORDER BY level DESC, xp DESC) AS row_number FROM ranking_level \
+                WHERE server_id = ?1 AND user_id NOT IN ({HIDDEN_MEMBERS})) WHERE user_id = ?2"),
+                params![server, user],
+                |row| row.get(0))
+                .optional()
+        }).await
+    }
+
+    async fn top_members_in(&self, server_id: GuildId, window: XpWindow, page: i32) -> Result<WindowPagination, Error> {
+        let server = to_sql_id(server_id.0);
+        let (season, from, to) = window_params(window);
+        const ROWS_FETCHED: i32 = 10;
+        let offset = (page * ROWS_FETCHED).max(0);
+
+        self.call(move |conn| {
+            let count: i32 = conn.query_row(
+                &format!("{WINDOW_TOTALS}SELECT COUNT(1) FROM totals"),
+                params![server, season, from, to],
+                |row| row.get(0))?;
+
+            let mut statement = conn.prepare(&format!("{WINDOW_TOTALS}SELECT user_id, xp FROM totals ORDER BY xp DESC, user_id LIMIT ?5 OFFSET ?6"))?;
+            let members = statement
+                .query_map(params![server, season, from, to, ROWS_FETCHED, offset], |row| Ok(WindowMember {
+                    id: UserId::from(from_sql_id(row.get(0)?)),
+                    xp: row.get(1)?
+                }))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let pages = (count / ROWS_FETCHED) + ((count % ROWS_FETCHED != 0) as i32);
+
+            Ok(WindowPagination {
+                members,
+                current_page: page,
+                last_page: pages
+            })
+        }).await
+    }
+
+    async fn rank_within_window(&self, server_id: GuildId, user_id: UserId, window: XpWindow) -> Result<Option<i64>, Error> {
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+        let (season, from, to) = window_params(window);
+
+        self.call(move |conn| {
+            conn.query_row(
+                &format!("{WINDOW_TOTALS}SELECT row_number FROM (SELECT user_id, ROW_NUMBER() OVER (ORDER BY xp DESC, user_id) AS row_number FROM totals) WHERE user_id = ?5"),
+                params![server, season, from, to, user],
+                |row| row.get(0))
+                .optional()
+        }).await
+    }
+
+    async fn get_seasons(&self, server_id: GuildId) -> Result<Vec<Season>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, server_id, name, started_at, ended_at FROM ranking_season WHERE server_id = ?1 ORDER BY started_at DESC")?;
+            let seasons = statement
+                .query_map(params![server], season_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(seasons)
+        }).await
+    }
+
+    async fn start_season(&self, server_id: GuildId, name: &str) -> Result<Season, Error> {
+        let server = to_sql_id(server_id.0);
+        let name = name.to_string();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        self.call(move |conn| {
+            conn.query_row(
+                "INSERT INTO ranking_season (server_id, name, started_at) VALUES (?1, ?2, ?3) \
+                RETURNING id, server_id, name, started_at, ended_at",
+                params![server, name, now],
+                season_from_row)
+        }).await
+    }
+
+    async fn end_season(&self, server_id: GuildId) -> Result<Option<Season>, Error> {
+        let server = to_sql_id(server_id.0);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            let season = tx.query_row(
+                "UPDATE ranking_season SET ended_at = ?2 WHERE server_id = ?1 AND ended_at IS NULL \
+                RETURNING id, server_id, name, started_at, ended_at",
+                params![server, now],
+                season_from_row)
+                .optional()?;
+
+            if let Some(season) = &season {
+                tx.execute(
+                    "INSERT INTO ranking_season_standing (season_id, user_id, xp) \
+                    SELECT ?2, user_id, SUM(xp) FROM ranking_xp_log \
+                    WHERE server_id = ?1 AND gained_at >= ?3 AND gained_at <= ?4 \
+                    GROUP BY user_id HAVING SUM(xp) > 0",
+                    params![server, season.id, season.started_at.timestamp_millis(), now])?;
+            }
+
+            tx.commit()?;
+            Ok(season)
+        }).await
+    }
+
+    async fn prune_xp_log(&self, before: DateTime<Utc>) -> Result<u64, Error> {
+        let before = before.timestamp_millis();
+
+        self.call(move |conn| {
+            let count = conn.execute(
+                "DELETE FROM ranking_xp_log WHERE gained_at < ?1 AND NOT EXISTS ( \
+                    SELECT 1 FROM ranking_season s WHERE s.server_id = ranking_xp_log.server_id AND s.ended_at IS NULL \
+                    AND ranking_xp_log.gained_at >= s.started_at)",
+                params![before])?;
+            Ok(count as u64)
+        }).await
+    }
+
+    async fn get_roles(&self, server_id: GuildId) -> Result<Vec<Rank>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare("SELECT role_name, role_id, min_level FROM ranking_role WHERE server_id = ?1 ORDER BY min_level ASC")?;
+            let roles = statement
+                .query_map(params![server], |row| {
+                    let role_id: Option<i64> = row.get(1)?;
+                    Ok(Rank {
+                        name: row.get(0)?,
+                        role_id: role_id.map(|o| RoleId::from(from_sql_id(o))),
+                        min_level: row.get(2)?
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>();
+            roles
+        }).await
+    }
+
+    async fn get_role_mode(&self, server_id: GuildId) -> Result<RoleMode, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mode: Option<u8> = conn.query_row("SELECT role_mode FROM ranking_server WHERE id = ?1", params![server], |row| row.get(0))
+                .optional()?;
+            Ok(mode.and_then(|o| RoleMode::try_from(o).ok()).unwrap_or(RoleMode::Replace))
+        }).await
+    }
+
+    async fn set_role_mode(&self, server_id: GuildId, mode: RoleMode) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_server (id, role_mode) VALUES (?1, ?2) \
+                ON CONFLICT (id) DO UPDATE SET role_mode = excluded.role_mode",
+                params![server, mode as u8])?;
+            Ok(())
+        }).await
+    }
+
+    async fn add_role(&self, server_id: GuildId, role_name: &str, role_id: RoleId, min_level: i32) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+        let role = to_sql_id(role_id.0);
+        let role_name = role_name.to_string();
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            let duplicate: bool = tx.query_row(
+                "SELECT EXISTS (SELECT 1 FROM ranking_role WHERE server_id = ?1 AND min_level = ?2 AND role_id IS NOT ?3)",
+                params![server, min_level, role],
+                |row| row.get(0))?;
+
+            if duplicate {
+                return Ok(false);
+            }
+
+            // A role can only be tied to one level, so re-adding it moves it.
+            tx.execute("DELETE FROM ranking_role WHERE server_id = ?1 AND role_id = ?2", params![server, role])?;
+            tx.execute(
+                "INSERT INTO ranking_role (server_id, role_name, role_id, min_level) VALUES (?1, ?2, ?3, ?4)",
+                params![server, role_name, role, min_level])?;
+            tx.commit()?;
+
+            Ok(true)
+        }).await
+    }
+
+    async fn remove_role(&self, server_id: GuildId, role_id: RoleId) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+        let role = to_sql_id(role_id.0);
+
+        self.call(move |conn| {
+            let removed = conn.execute("DELETE FROM ranking_role WHERE server_id = ?1 AND role_id = ?2", params![server, role])?;
+            Ok(removed > 0)
+        }).await
+    }
+
+    async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let updated = conn.execute(
+                "INSERT INTO ranking_server (id, timeout) VALUES (?1, ?2) ON CONFLICT (id) DO UPDATE SET timeout = excluded.timeout",
+                params![server, timeout])?;
+            Ok(updated > 0)
+        }).await
+    }
+
+    async fn get_timeout(&self, server_id: GuildId) -> Result<i32, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let timeout: Option<i32> = conn.query_row("SELECT timeout FROM ranking_server WHERE id = ?1", params![server], |row| row.get(0))
+                .optional()?;
+            Ok(timeout.unwrap_or(-1))
+        }).await
+    }
+
+    async fn get_voice_settings(&self, server_id: GuildId) -> Result<VoiceSettings, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut out = conn.query_row(
+                "SELECT voice_xp, voice_daily_cap FROM ranking_server WHERE id = ?1",
+                params![server],
+                |row| Ok(VoiceSettings {
+                    server_id: server_id.0,
+                    xp_per_minute: row.get(0)?,
+                    daily_cap: row.get(1)?,
+                    channels: Vec::new()
+                }))
+                .optional()?
+                .unwrap_or_else(|| VoiceSettings::new(server_id.0));
+
+            let mut statement = conn.prepare("SELECT channel_id FROM ranking_voice_channel WHERE server_id = ?1")?;
+            out.channels = statement
+                .query_map(params![server], |row| Ok(ChannelId::from(from_sql_id(row.get(0)?))))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(out)
+        }).await
+    }
+
+    async fn set_voice_settings(&self, server_id: GuildId, xp_per_minute: i32, daily_cap: i32) -> Result<(), Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO ranking_server (id, voice_xp, voice_daily_cap) VALUES (?1, ?2, ?3) \
+                ON CONFLICT (id) DO UPDATE SET voice_xp = excluded.voice_xp, voice_daily_cap = excluded.voice_daily_cap",
+                params![server, xp_per_minute, daily_cap])?;
+            Ok(())
+        }).await
+    }
+
+    async fn toggle_voice_channel(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, Error> {
+        let server = to_sql_id(server_id.0);
+        let channel = to_sql_id(channel_id.0);
+
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            let removed = tx.execute(
+                "DELETE FROM ranking_voice_channel WHERE server_id = ?1 AND channel_id = ?2",
+                params![server, channel])?;
+
+            if removed == 0 {
+                tx.execute(
+                    "INSERT INTO ranking_voice_channel (server_id, channel_id) VALUES (?1, ?2)",
+                    params![server, channel])?;
+            }
+
+            tx.commit()?;
+            Ok(removed == 0)
+        }).await
+    }
+
+    async fn get_users(&self, server_id: GuildId) -> Result<Vec<FullMember>, Error> {
+        let server = to_sql_id(server_id.0);
+
+        self.call(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT user_id, level, xp, \
+                    (SELECT role_id FROM ranking_role WHERE ranking_role.server_id = ranking_level.server_id AND min_level <= level ORDER BY min_level DESC LIMIT 1) \
+                FROM ranking_level WHERE server_id = ?1")?;
+            let users = statement
+                .query_map(params![server], |row| {
+                    let role_id: Option<i64> = row.get(3)?;
+                    Ok(FullMember {
+                        user: UserId::from(from_sql_id(row.get(0)?)),
+                        exp: Experience {
+                            level: row.get(1)?,
+                            xp: row.get(2)?
+                        },
+                        role_id: role_id.map(|o| RoleId::from(from_sql_id(o)))
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>();
+            users
+        }).await
+    }
+}
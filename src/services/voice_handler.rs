@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::{
+    CacheAndHttp,
+    model::id::{ChannelId, GuildId, RoleId, UserId},
+    prelude::TypeMap
+};
+use tokio::sync::RwLock;
+use tokio::time;
+use tracing::error;
+use crate::Database;
+use crate::models::db_models::XpMultiplier;
+use crate::services::message_handler::{channel_lineage, level_up};
+
+struct Listener {
+    user_id: UserId,
+    roles: Vec<RoleId>
+}
+
+// The cache keeps every guild's voice states up to date from voice state updates, so we just look at it once a minute.
+pub async fn check_voice(data: Arc<RwLock<TypeMap>>, ctx: Arc<CacheAndHttp>) {
+    let mut interval_min = time::interval(Duration::from_secs(60));
+    loop {
+        interval_min.tick().await;
+        let db = {
+            let ctx_global = data.read().await;
+            ctx_global.get::<Database>().expect("Couldn't find database").clone()
+        };
+
+        for guild_id in ctx.cache.guilds() {
+            let channels = active_listeners(&ctx, guild_id);
+            if !channels.is_empty() {
+                award_voice_exp(&ctx, &db, guild_id, channels).await;
+            }
+        }
+    }
+}
+
+// Everyone who could earn XP this minute, grouped by voice channel.
+fn active_listeners(ctx: &CacheAndHttp, guild_id: GuildId) -> HashMap<ChannelId, Vec<Listener>> {
+    ctx.cache.guild_field(guild_id, |guild| {
+        let mut humans: HashMap<ChannelId, Vec<(UserId, bool)>> = HashMap::new();
+
+        for state in guild.voice_states.values() {
+            let channel_id = match state.channel_id {
+                Some(channel_id) => channel_id,
+                None => continue
+            };
+
+            // Sitting in the AFK channel doesn't count.
+            if guild.afk_channel_id == Some(channel_id) {
+                continue;
+            }
+
+            let member = guild.members.get(&state.user_id).or(state.member.as_ref());
+            if member.map(|o| o.user.bot).unwrap_or(false) {
+                continue;
+            }
+
+            // Deafened people still keep someone else company, but don't earn anything themselves.
+            humans.entry(channel_id).or_default().push((state.user_id, !(state.deaf || state.self_deaf)));
+        }
+
+        humans.into_iter()
+            // At least one other human has to be there; talking to yourself doesn't count.
+            .filter(|(_, users)| users.len() >= 2)
+            .map(|(channel_id, users)| {
+                let listeners = users.into_iter()
+                    .filter(|(_, listening)| *listening)
+                    .map(|(user_id, _)| Listener {
+                        user_id,
+                        roles: guild.members.get(&user_id).map(|o| o.roles.clone()).unwrap_or_default()
+                    })
+                    .collect::<Vec<_>>();
+                (channel_id, listeners)
+            })
+            .collect()
+    }).unwrap_or_default()
+}
+
+async fn award_voice_exp(ctx: &CacheAndHttp, db: &Database, guild_id: GuildId, channels: HashMap<ChannelId, Vec<Listener>>) {
+    let voice = match db.get_voice_settings(guild_id).await {
+        Ok(voice) => voice,
+        Err(ex) => {
+            error!("Failed getting voice settings for the server: {}", ex);
+            return;
+        }
+    };
+
+    if voice.xp_per_minute <= 0 {
+        return;
+    }
+
+    let settings = match db.get_xp_settings(guild_id).await {
+        Ok(settings) => settings,
+        Err(ex) => {
+            error!("Failed getting xp settings for the server: {}", ex);
+            return;
+        }
+    };
+
+    let multipliers = match db.get_multipliers(guild_id).await {
+        Ok(multipliers) => multipliers,
+        Err(ex) => {
+            error!("Failed getting xp multipliers for the server: {}", ex);
+            Vec::new()
+        }
+    };
+
+    for (channel_id, listeners) in channels {
+        let lineage = channel_lineage(&ctx.cache, channel_id);
+        if !voice.counts(&lineage) {
+            continue;
+        }
+
+        match db.get_disablements(guild_id, channel_id).await {
+            Err(ex) => {
+                error!("Failed checking if the voice channel or guild was disabled: {}", ex);
+                continue;
+            },
+            Ok(result) => {
+                if result.channel || result.guild {
+                    continue;
+                }
+            }
+        }
+
+        for listener in listeners {
+            let multiplier = XpMultiplier::combine(&multipliers, &lineage, &listener.roles);
+            let xp = (voice.xp_per_minute as f64 * multiplier).round().max(0.0) as i32;
+
+            match db.provide_voice_exp(guild_id, listener.user_id, xp, voice.daily_cap, &settings).await {
+                Err(ex) => {
+                    error!("Failed providing voice exp to user: {}", ex)
+                },
+                Ok(data) => {
+                    if data.level >= 0 {
+                        // Voice channels have their own text chat, so the level-up message goes there.
+                        level_up(&ctx.http, db, guild_id, listener.user_id, channel_id, &data).await;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 mod duration;
+pub mod rank_card;
+pub mod paginator;
 
 pub use duration::to_ms;
 pub use duration::from_ms;
\ No newline at end of file
@@ -0,0 +1,129 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::collector::CollectComponentInteraction;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::InteractionResponseType;
+use tracing::error;
+use crate::{CowContext, Error};
+
+// How long the buttons stay around after the last press.
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+#[async_trait]
+pub trait PageSource: Send + Sync {
+    // Fills in the embed for a (zero-indexed) page, and gives back how many pages there are.
+    async fn render(&self, page: usize, embed: &mut CreateEmbed) -> Result<usize, Error>;
+
+    // The page the person who ran the command is on; None hides the "jump to me" button.
+    async fn author_page(&self) -> Result<Option<usize>, Error> {
+        Ok(None)
+    }
+}
+
+fn buttons(components: &mut CreateComponents, page: usize, pages: usize, author_page: Option<usize>, disabled: bool) -> &mut CreateComponents {
+    let last = pages.saturating_sub(1);
+
+    components.create_action_row(|r| {
+        r
+            .create_button(|b| b.custom_id("first").emoji('⏮').style(ButtonStyle::Secondary).disabled(disabled || page == 0))
+            .create_button(|b| b.custom_id("prev").emoji('◀').style(ButtonStyle::Secondary).disabled(disabled || page == 0))
+            .create_button(|b| b.custom_id("next").emoji('▶').style(ButtonStyle::Secondary).disabled(disabled || page >= last))
+            .create_button(|b| b.custom_id("last").emoji('⏭').style(ButtonStyle::Secondary).disabled(disabled || page >= last));
+
+        if let Some(author_page) = author_page {
+            r.create_button(|b| b.custom_id("me").label("Jump to me").style(ButtonStyle::Primary).disabled(disabled || page == author_page));
+        }
+
+        r
+    })
+}
+
+// Renders a page, stepping back to the last page if we asked for one past the end.
+async fn render<S: PageSource>(source: &S, page: usize) -> Result<(usize, usize, CreateEmbed), Error> {
+    let mut embed = CreateEmbed::default();
+    let pages = source.render(page, &mut embed).await?;
+
+    if pages > 0 && page >= pages {
+        let mut embed = CreateEmbed::default();
+        source.render(pages - 1, &mut embed).await?;
+        return Ok((pages - 1, pages, embed));
+    }
+
+    Ok((page, pages, embed))
+}
+
+// Sends the given (zero-indexed) page, with first/prev/next/last buttons if there's more than one.
+// Only whoever ran the command can use them, and they're disabled once they time out.
+pub async fn paginate<S: PageSource>(ctx: CowContext<'_>, source: S, page: usize) -> Result<(), Error> {
+    let author_page = source.author_page().await.unwrap_or_else(|ex| {
+        error!("Failed to find the author's page: {}", ex);
+        None
+    });
+    let (mut page, mut pages, mut embed) = render(&source, page).await?;
+
+    if pages <= 1 && author_page.is_none() {
+        ctx.send(|m| {
+            m.embeds.clear();
+            m.embeds.push(embed);
+            m
+        }).await?;
+        return Ok(());
+    }
+
+    let reply = ctx.send(|m| {
+        m.embeds.clear();
+        m.embeds.push(embed.clone());
+        m.components(|c| buttons(c, page, pages, author_page, false))
+    }).await?;
+    let message_id = reply.message().await?.id;
+
+    while let Some(press) = CollectComponentInteraction::new(ctx.serenity_context())
+        .message_id(message_id)
+        .timeout(TIMEOUT)
+        .await
+    {
+        if press.user.id != ctx.author().id {
+            if let Err(ex) = press.create_interaction_response(ctx.serenity_context(), |r| r
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d
+                    .content("Only the person who ran the command can use these buttons.")
+                    .ephemeral(true))
+            ).await {
+                error!("Failed to respond to button press: {}", ex);
+            }
+            continue;
+        }
+
+        let target = match press.data.custom_id.as_str() {
+            "first" => 0,
+            "prev" => page.saturating_sub(1),
+            "next" => page + 1,
+            "last" => pages.saturating_sub(1),
+            "me" => author_page.unwrap_or(page),
+            _ => continue
+        };
+
+        match render(&source, target).await {
+            Ok(out) => (page, pages, embed) = out,
+            Err(ex) => error!("Failed to render page: {}", ex)
+        }
+
+        if let Err(ex) = press.create_interaction_response(ctx.serenity_context(), |r| r
+            .kind(InteractionResponseType::UpdateMessage)
+            .interaction_response_data(|d| d
+                .set_embed(embed.clone())
+                .components(|c| buttons(c, page, pages, author_page, false)))
+        ).await {
+            error!("Failed to update page: {}", ex);
+        }
+    }
+
+    reply.edit(ctx, |m| {
+        m.embeds.clear();
+        m.embeds.push(embed);
+        m.components(|c| buttons(c, page, pages, author_page, true))
+    }).await?;
+
+    Ok(())
+}
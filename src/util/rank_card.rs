@@ -0,0 +1,202 @@
+use std::io::Cursor;
+use image::{imageops, ImageOutputFormat, Rgba, RgbaImage};
+use rusttype::{point, Font, Scale};
+use crate::Error;
+
+// Everything is drawn in-process, so the fonts ship with the bot.
+static REGULAR_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+static BOLD_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+const WIDTH: u32 = 934;
+const HEIGHT: u32 = 282;
+const AVATAR_SIZE: u32 = 200;
+const AVATAR_X: i32 = 40;
+const AVATAR_Y: i32 = 41;
+const TEXT_X: i32 = 280;
+const BAR_Y: i32 = 190;
+const BAR_HEIGHT: i32 = 40;
+const MARGIN: i32 = 40;
+
+const BACKGROUND: Rgba<u8> = Rgba([35, 39, 42, 255]);
+const PANEL: Rgba<u8> = Rgba([44, 47, 51, 255]);
+const BAR_EMPTY: Rgba<u8> = Rgba([72, 75, 78, 255]);
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const GREY: Rgba<u8> = Rgba([153, 170, 181, 255]);
+// Discord's blurple, for members without a coloured rank role.
+pub const DEFAULT_COLOUR: (u8, u8, u8) = (88, 101, 242);
+
+pub struct RankCard {
+    pub name: String,
+    pub discriminator: u16,
+    pub level: i32,
+    pub xp: i32,
+    pub next_level_xp: i32,
+    pub rank: Option<i64>,
+    pub role_name: Option<String>,
+    pub colour: (u8, u8, u8),
+    // PNG/JPEG bytes; a plain circle is drawn if this is missing or can't be decoded.
+    pub avatar: Option<Vec<u8>>
+}
+
+impl RankCard {
+    // Fraction of the way to the next level, for the progress bar.
+    pub fn progress(&self) -> f32 {
+        if self.next_level_xp <= 0 {
+            return 1.0;
+        }
+
+        (self.xp as f32 / self.next_level_xp as f32).clamp(0.0, 1.0)
+    }
+
+    pub fn render(&self) -> Result<Vec<u8>, Error> {
+        let regular = Font::try_from_bytes(REGULAR_FONT).ok_or("Failed to load the regular font")?;
+        let bold = Font::try_from_bytes(BOLD_FONT).ok_or("Failed to load the bold font")?;
+        let colour = Rgba([self.colour.0, self.colour.1, self.colour.2, 255]);
+
+        let mut canvas = RgbaImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+        fill_rounded_rect(&mut canvas, 16, 16, WIDTH as i32 - 32, HEIGHT as i32 - 32, 20, PANEL);
+
+        // Avatar, with a ring in the role colour.
+        let ring = 6;
+        fill_circle(&mut canvas, AVATAR_X + AVATAR_SIZE as i32 / 2, AVATAR_Y + AVATAR_SIZE as i32 / 2, AVATAR_SIZE as i32 / 2 + ring, colour);
+        match self.avatar.as_ref().and_then(|o| image::load_from_memory(o).ok()) {
+            Some(avatar) => {
+                let avatar = imageops::resize(&avatar.to_rgba8(), AVATAR_SIZE, AVATAR_SIZE, imageops::FilterType::Triangle);
+                draw_circular(&mut canvas, &avatar, AVATAR_X, AVATAR_Y);
+            }
+            None => fill_circle(&mut canvas, AVATAR_X + AVATAR_SIZE as i32 / 2, AVATAR_Y + AVATAR_SIZE as i32 / 2, AVATAR_SIZE as i32 / 2, BAR_EMPTY)
+        }
+
+        // Rank and level in the top right, right-aligned against each other.
+        let right = WIDTH as i32 - MARGIN - 16;
+        let big = Scale::uniform(52.0);
+        let small = Scale::uniform(24.0);
+        let level_value = self.level.to_string();
+        let rank_value = self.rank.map(|o| format!("#{o}")).unwrap_or_else(|| "-".to_string());
+
+        let mut x = right - text_width(&bold, big, &level_value);
+        draw_text(&mut canvas, &bold, big, x, 50, &level_value, colour);
+        x -= text_width(&regular, small, "LEVEL") + 8;
+        draw_text(&mut canvas, &regular, small, x, 72, "LEVEL", colour);
+        x -= text_width(&bold, big, &rank_value) + 24;
+        draw_text(&mut canvas, &bold, big, x, 50, &rank_value, WHITE);
+        x -= text_width(&regular, small, "RANK") + 8;
+        draw_text(&mut canvas, &regular, small, x, 72, "RANK", WHITE);
+
+        // Name (and role) above the bar, cut short so they don't run into the XP count.
+        let xp_text = format!("{} / {} XP", format_xp(self.xp), format_xp(self.next_level_xp));
+        let xp_scale = Scale::uniform(26.0);
+        let xp_width = text_width(&regular, xp_scale, &xp_text);
+        let name_scale = Scale::uniform(38.0);
+        let discriminator = format!("#{:04}", self.discriminator);
+        let name_space = right - TEXT_X - xp_width - 24;
+        let name = truncate(&bold, name_scale, &self.name, name_space - text_width(&regular, Scale::uniform(26.0), &discriminator));
+        draw_text(&mut canvas, &bold, name_scale, TEXT_X, BAR_Y - 56, &name, WHITE);
+        draw_text(&mut canvas, &regular, Scale::uniform(26.0), TEXT_X + text_width(&bold, name_scale, &name) + 6, BAR_Y - 46, &discriminator, GREY);
+        draw_text(&mut canvas, &regular, xp_scale, right - xp_width, BAR_Y - 46, &xp_text, GREY);
+
+        if let Some(role_name) = &self.role_name {
+            let role_name = truncate(&regular, small, role_name, right - TEXT_X);
+            draw_text(&mut canvas, &regular, small, TEXT_X, BAR_Y + BAR_HEIGHT + 10, &role_name, colour);
+        }
+
+        // Progress bar toward the next level.
+        let bar_width = right - TEXT_X;
+        fill_rounded_rect(&mut canvas, TEXT_X, BAR_Y, bar_width, BAR_HEIGHT, BAR_HEIGHT / 2, BAR_EMPTY);
+        let filled = (bar_width as f32 * self.progress()).round() as i32;
+        if filled > 0 {
+            // Never narrower than the bar is tall, so the rounded ends don't overlap.
+            fill_rounded_rect(&mut canvas, TEXT_X, BAR_Y, filled.max(BAR_HEIGHT), BAR_HEIGHT, BAR_HEIGHT / 2, colour);
+        }
+
+        let mut out = Cursor::new(Vec::new());
+        canvas.write_to(&mut out, ImageOutputFormat::Png)?;
+        Ok(out.into_inner())
+    }
+}
+
+// 12345 -> 12.3K, to keep the numbers from eating the whole card.
+fn format_xp(xp: i32) -> String {
+    match xp.abs() {
+        0..=9_999 => xp.to_string(),
+        10_000..=999_999 => format!("{:.1}K", xp as f64 / 1_000.0),
+        _ => format!("{:.1}M", xp as f64 / 1_000_000.0)
+    }
+}
+
+fn blend(canvas: &mut RgbaImage, x: i32, y: i32, colour: Rgba<u8>, coverage: f32) {
+    if x < 0 || y < 0 || x >= canvas.width() as i32 || y >= canvas.height() as i32 {
+        return;
+    }
+
+    let alpha = coverage.clamp(0.0, 1.0) * (colour[3] as f32 / 255.0);
+    let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+    for i in 0..3 {
+        pixel[i] = (colour[i] as f32 * alpha + pixel[i] as f32 * (1.0 - alpha)).round() as u8;
+    }
+}
+
+fn fill_circle(canvas: &mut RgbaImage, cx: i32, cy: i32, radius: i32, colour: Rgba<u8>) {
+    for y in (cy - radius - 1)..=(cy + radius + 1) {
+        for x in (cx - radius - 1)..=(cx + radius + 1) {
+            let distance = (((x - cx) as f32 + 0.5).powi(2) + ((y - cy) as f32 + 0.5).powi(2)).sqrt();
+            // Soften the last pixel of the edge a little.
+            blend(canvas, x, y, colour, radius as f32 - distance + 0.5);
+        }
+    }
+}
+
+fn fill_rounded_rect(canvas: &mut RgbaImage, left: i32, top: i32, width: i32, height: i32, radius: i32, colour: Rgba<u8>) {
+    let radius = radius.min(width / 2).min(height / 2) as f32;
+    let (inner_left, inner_right) = (left as f32 + radius, (left + width) as f32 - radius);
+    let (inner_top, inner_bottom) = (top as f32 + radius, (top + height) as f32 - radius);
+
+    for y in top..(top + height) {
+        for x in left..(left + width) {
+            // Distance from the rectangle the corners are rounded around; only non-zero in the corners.
+            let dx = (x as f32 + 0.5) - (x as f32 + 0.5).clamp(inner_left, inner_right);
+            let dy = (y as f32 + 0.5) - (y as f32 + 0.5).clamp(inner_top, inner_bottom);
+            blend(canvas, x, y, colour, radius - (dx * dx + dy * dy).sqrt() + 0.5);
+        }
+    }
+}
+
+fn draw_circular(canvas: &mut RgbaImage, image: &RgbaImage, left: i32, top: i32) {
+    let radius = image.width() as f32 / 2.0;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let distance = ((x as f32 + 0.5 - radius).powi(2) + (y as f32 + 0.5 - radius).powi(2)).sqrt();
+        blend(canvas, left + x as i32, top + y as i32, *pixel, radius - distance + 0.5);
+    }
+}
+
+fn text_width(font: &Font, scale: Scale, text: &str) -> i32 {
+    font.layout(text, scale, point(0.0, 0.0))
+        .last()
+        .map(|o| (o.position().x + o.unpositioned().h_metrics().advance_width).ceil() as i32)
+        .unwrap_or(0)
+}
+
+fn truncate(font: &Font, scale: Scale, text: &str, max_width: i32) -> String {
+    if text_width(font, scale, text) <= max_width {
+        return text.to_string();
+    }
+
+    let mut out: String = text.to_string();
+    while !out.is_empty() && text_width(font, scale, &format!("{out}…")) > max_width {
+        out.pop();
+    }
+
+    format!("{out}…")
+}
+
+// y is the top of the line, not the baseline.
+fn draw_text(canvas: &mut RgbaImage, font: &Font, scale: Scale, x: i32, y: i32, text: &str, colour: Rgba<u8>) {
+    let ascent = font.v_metrics(scale).ascent;
+    for glyph in font.layout(text, scale, point(x as f32, y as f32 + ascent)) {
+        if let Some(bounds) = glyph.pixel_bounding_box() {
+            glyph.draw(|gx, gy, coverage| {
+                blend(canvas, bounds.min.x + gx as i32, bounds.min.y + gy as i32, colour, coverage);
+            });
+        }
+    }
+}